@@ -1,9 +1,9 @@
 use crate::LocalSpawner;
 use fumio_utils::current::Current;
-use futures_executor::Enter;
+use fumio_utils::enter::Enter;
 
 thread_local! {
-	static CURRENT: Current<LocalSpawner> = Current::new();
+	static CURRENT: Current<LocalSpawner> = const { Current::new() };
 }
 
 pub(crate) fn enter_local<F, T>(spawner: LocalSpawner, enter: &mut Enter, f: F) -> T
@@ -13,6 +13,13 @@ where
 	Current::enter(&CURRENT, enter, spawner, f)
 }
 
+pub(crate) fn enter_local_nonblocking<F, T>(spawner: LocalSpawner, f: F) -> T
+where
+	F: FnOnce() -> T
+{
+	Current::enter_nonblocking(&CURRENT, spawner, f)
+}
+
 /// Retrieve the current handle.
 pub fn current_local() -> Option<LocalSpawner> {
 	#[allow(clippy::redundant_closure_for_method_calls)] // sadly the suggestion doesn't compile
@@ -14,10 +14,16 @@
 	clippy::module_name_repetitions, // often hidden modules and reexported
 	clippy::if_not_else, // `... != 0` is a positive condition
 	clippy::multiple_crate_versions, // not useful
+	clippy::must_use_candidate, // would mark nearly every getter
+	clippy::missing_const_for_fn, // `const` is an API promise; only added where it is needed
+	clippy::redundant_pub_crate, // stays crate-internal even if the module becomes public
 )]
 
 mod pool;
-pub use pool::{LocalPool, LocalSpawner};
+pub use pool::{LocalPool, LocalSpawner, PanicPolicy, PoolMetrics, Priority, TaskId, TaskInfo, TaskPanic};
 
 mod current;
 pub use current::{current_local};
+
+mod yield_now;
+pub use yield_now::{yield_now, YieldNow};
@@ -1,13 +1,25 @@
 
+mod metrics;
+mod panic_policy;
+mod priority;
+mod shuffle;
 mod task;
+mod task_info;
 
+pub use self::metrics::PoolMetrics;
+pub use self::panic_policy::{PanicPolicy, TaskId, TaskPanic};
+pub use self::priority::Priority;
+pub use self::task_info::TaskInfo;
+
+use fumio_utils::enter::Enter;
 use fumio_utils::park::Park;
-use futures_core::future::{Future, FutureObj, LocalFutureObj};
-use futures_core::task::{Spawn, LocalSpawn, SpawnError};
-use futures_executor::Enter;
+use futures_core::future::Future;
+use futures_util::future::{FutureObj, LocalFutureObj};
 use futures_util::pin_mut;
+use futures_util::task::{Spawn, LocalSpawn, SpawnError};
 use std::rc::{Rc, Weak};
 use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
 
 // Set up and run a basic single-threaded spawner loop, invoking `f` on each
 // turn.
@@ -30,10 +42,10 @@ fn run_executor<P: Park, T, F: FnMut(&mut Context<'_>) -> Poll<T>>(park: &mut P,
 /// little work in between I/O actions.
 ///
 /// To get a handle to the pool that implements
-/// [`Spawn`](futures_core::task::Spawn), use the
+/// [`Spawn`](futures_util::task::Spawn), use the
 /// [`spawner()`](LocalPool::spawner) method. Because the executor is
 /// single-threaded, it supports a special form of task spawning for non-`Send`
-/// futures, via [`spawn_local_obj`](futures_core::task::LocalSpawn::spawn_local_obj).
+/// futures, via [`spawn_local_obj`](futures_util::task::LocalSpawn::spawn_local_obj).
 #[derive(Debug)]
 pub struct LocalPool {
 	task_list: Rc<task::LocalTaskList>,
@@ -59,7 +71,58 @@ impl LocalPool {
 	/// The function will block the calling thread until *all* tasks in the pool
 	/// completed, including any spawned while running existing tasks.
 	pub fn run<P: Park>(&mut self, park: &mut P, enter: &mut Enter) {
-		run_executor(park, enter, |cx| self.poll_pool(cx))
+		run_executor(park, enter, |cx| self.poll_pool(cx));
+	}
+
+	/// Run all tasks in the pool to completion, but only up to `timeout`.
+	///
+	/// Returns whether all tasks completed; remaining tasks stay in the pool.
+	pub fn run_timeout<P: Park>(&mut self, park: &mut P, enter: &mut Enter, timeout: Duration) -> bool {
+		let deadline = Instant::now() + timeout;
+		let waker = park.waker();
+		let mut cx = Context::from_waker(&waker);
+
+		loop {
+			if self.poll_pool(&mut cx).is_ready() {
+				return true;
+			}
+			let now = Instant::now();
+			if now >= deadline {
+				return false;
+			}
+			park.park(enter, Some(deadline - now));
+		}
+	}
+
+	/// Run tasks and park only until `budget` is spent, e.g. once per frame of a game loop.
+	///
+	/// Unlike [`run_timeout`](#method.run_timeout) the budget is checked after each polled task;
+	/// a single task taking too long can still exceed it.  Parks (i.e. waits for IO or timers) only
+	/// while no task is pending.  Returns whether all tasks completed.
+	pub fn run_for<P: Park>(&mut self, park: &mut P, enter: &mut Enter, budget: Duration) -> bool {
+		let deadline = Instant::now() + budget;
+		let waker = park.waker();
+		let cx = Context::from_waker(&waker);
+
+		loop {
+			// poll tasks pending at the start of the round, then let the reactor catch up
+			let mut round = self.pending_len();
+			while round > 0 && Instant::now() < deadline && self.task_list.poll_one(&cx) {
+				round -= 1;
+			}
+			if self.is_empty() {
+				return true;
+			}
+			let now = Instant::now();
+			if now >= deadline {
+				return false;
+			}
+			if self.is_idle() {
+				park.park(enter, Some(deadline - now));
+			} else {
+				park.park(enter, Some(Duration::from_secs(0)));
+			}
+		}
 	}
 
 	/// Runs all the tasks in the pool until the given future completes.
@@ -90,6 +153,28 @@ impl LocalPool {
 		})
 	}
 
+	/// Run a single pending task, blocking the thread until a task is pending.
+	///
+	/// Returns `false` (without blocking) if there are no tasks left in the pool.
+	pub fn run_one<P: Park>(&mut self, park: &mut P, enter: &mut Enter) -> bool {
+		run_executor(park, enter, |cx| {
+			if self.task_list.poll_one(cx) {
+				Poll::Ready(true)
+			} else if self.task_list.is_empty() {
+				Poll::Ready(false)
+			} else {
+				Poll::Pending
+			}
+		})
+	}
+
+	/// Poll a single pending task without blocking.
+	///
+	/// Returns whether a task was polled; `cx` gets notified when a task becomes pending.
+	pub fn try_run_one(&mut self, cx: &mut Context<'_>) -> bool {
+		self.task_list.poll_one(cx)
+	}
+
 	/// Make progress on entire pool, polling each spawend task at most once.
 	///
 	/// Becomes `Ready` when all tasks are completed.
@@ -97,10 +182,144 @@ impl LocalPool {
 		self.task_list.poll(cx)
 	}
 
+	/// Number of tasks in the pool.
+	pub fn len(&self) -> usize {
+		self.task_list.len()
+	}
+
+	/// Whether there are no tasks in the pool.
+	pub fn is_empty(&self) -> bool {
+		self.task_list.is_empty()
+	}
+
+	/// Snapshot of all tasks in the pool (in spawn order), e.g. to debug stuck tasks.
+	///
+	/// Walks the list of tasks; don't call it too often with many tasks.
+	pub fn tasks(&self) -> Vec<TaskInfo> {
+		self.task_list.tasks()
+	}
+
+	/// Ids of all tasks in the pool (in spawn order).
+	///
+	/// Walks the list of tasks; don't call it too often with many tasks.
+	pub fn task_ids(&self) -> Vec<TaskId> {
+		self.task_list.task_ids()
+	}
+
+	/// Number of tasks waiting to be polled.
+	///
+	/// When called from a task running in this pool, tasks pending in the current round that
+	/// haven't been polled yet are not included.
+	pub fn pending_len(&self) -> usize {
+		self.task_list.pending_len()
+	}
+
+	/// Whether there are no tasks waiting to be polled (i.e. the pool waits for IO, timers or
+	/// other threads).
+	pub fn is_idle(&self) -> bool {
+		self.task_list.is_idle()
+	}
+
+	/// Snapshot of the pool's counters
+	pub fn metrics(&self) -> PoolMetrics {
+		self.task_list.metrics()
+	}
+
 	/// Spawn future on pool
 	pub fn spawn(&self, future: LocalFutureObj<'static, ()>) {
 		self.task_list.add_task(future);
 	}
+
+	/// Spawn future on pool with the given priority (`spawn` uses `Priority::Normal`)
+	pub fn spawn_with_priority(&self, priority: Priority, future: LocalFutureObj<'static, ()>) {
+		self.task_list.add_task_with(future, priority, None);
+	}
+
+	/// Spawn future on pool with a name (shown in [`tasks`](#method.tasks))
+	pub fn spawn_named<N>(&self, name: N, future: LocalFutureObj<'static, ()>)
+	where
+		N: Into<String>,
+	{
+		self.task_list.add_task_with(future, Priority::default(), Some(name.into().into_boxed_str()));
+	}
+
+	/// Set how panics in tasks are handled.
+	///
+	/// Defaults to [`PanicPolicy::Propagate`](enum.PanicPolicy.html#variant.Propagate).
+	pub fn set_panic_policy(&mut self, policy: PanicPolicy) {
+		self.task_list.set_panic_policy(policy);
+	}
+
+	/// Set a hook called for each panicking task (before the panic policy is applied).
+	pub fn set_panic_hook<F>(&mut self, hook: F)
+	where
+		F: Fn(&TaskPanic<'_>) + 'static,
+	{
+		self.task_list.set_panic_hook(Some(Rc::new(hook)));
+	}
+
+	/// Remove the panic hook.
+	pub fn clear_panic_hook(&mut self) {
+		self.task_list.set_panic_hook(None);
+	}
+
+	/// Set the cooperative budget for a single poll of a task.
+	///
+	/// IO resources consume the budget; once exhausted they return `Poll::Pending` (and wake the
+	/// task) to give other tasks a chance to run.  `None` disables the budget.
+	///
+	/// Defaults to [`coop::DEFAULT_BUDGET`](../fumio_utils/coop/constant.DEFAULT_BUDGET.html).
+	pub fn set_coop_budget(&mut self, budget: Option<u32>) {
+		self.task_list.set_coop_budget(budget);
+	}
+
+	/// Enable or disable the LIFO slot (enabled by default).
+	///
+	/// With the LIFO slot a task woken by the currently running task is polled right after it
+	/// (limited to a few tasks in a row), instead of being queued at the end; this keeps
+	/// message-passing between tasks hot in cache, but is less fair.  A task waking itself (e.g.
+	/// through [`yield_now`](fn.yield_now.html)) is always queued at the end.
+	pub fn set_lifo_slot(&mut self, enabled: bool) {
+		self.task_list.set_lifo_slot(enabled);
+	}
+
+	/// Limit how many tasks are polled per [`poll_pool`](#method.poll_pool) call (`None`: no limit,
+	/// the default).
+	///
+	/// Without a limit all pending tasks are polled before the reactor gets a chance to run again;
+	/// tasks that continuously wake each other can delay IO and timers that way.  Tasks not polled
+	/// due to the limit stay pending (in order).
+	///
+	/// # Panics
+	///
+	/// Panics if `max` is `Some(0)`.
+	pub fn set_max_tasks_per_tick(&mut self, max: Option<usize>) {
+		assert_ne!(max, Some(0), "need to poll at least one task per tick");
+		self.task_list.set_max_tasks_per_tick(max);
+	}
+
+	/// Poll pending tasks in a random order derived from `seed` (`None`: disabled, the default).
+	///
+	/// Meant for tests: code relying on the order in which local tasks run fails with some seeds,
+	/// and the same seed (with the same sequence of spawns and wakeups) reproduces the failure.
+	/// The [panic hook](#method.set_panic_hook) gets the seed through
+	/// [`TaskPanic::shuffle_seed`](struct.TaskPanic.html#method.shuffle_seed), so it can be reported
+	/// with the failure.
+	///
+	/// Priorities and the [LIFO slot](#method.set_lifo_slot) are ignored while shuffling.
+	pub fn set_shuffle_seed(&mut self, seed: Option<u64>) {
+		self.task_list.set_shuffle_seed(seed);
+	}
+
+	/// Seed set with [`set_shuffle_seed`](#method.set_shuffle_seed)
+	pub fn shuffle_seed(&self) -> Option<u64> {
+		self.task_list.shuffle_seed()
+	}
+
+	/// Drop all tasks in the pool without completing them.
+	pub fn clear(&mut self) {
+		self.task_list.clear();
+	}
 }
 
 impl Default for LocalPool {
@@ -111,7 +330,7 @@ impl Default for LocalPool {
 
 impl Spawn for LocalPool {
 	fn spawn_obj(
-		&mut self,
+		&self,
 		future: FutureObj<'static, ()>,
 	) -> Result<(), SpawnError> {
 		self.spawn_local_obj(future.into())
@@ -124,7 +343,7 @@ impl Spawn for LocalPool {
 
 impl LocalSpawn for LocalPool {
 	fn spawn_local_obj(
-		&mut self,
+		&self,
 		future: LocalFutureObj<'static, ()>,
 	) -> Result<(), SpawnError> {
 		self.spawn(future);
@@ -136,8 +355,8 @@ impl LocalSpawn for LocalPool {
 	}
 }
 
-/// A handle to a [`LocalPool`](LocalPool) that implements [`Spawn`](futures_core::task::Spawn) and
-/// [`LocalSpawn`](futures_core::task::LocalSpawn).
+/// A handle to a [`LocalPool`](LocalPool) that implements [`Spawn`](futures_util::task::Spawn) and
+/// [`LocalSpawn`](futures_util::task::LocalSpawn).
 #[derive(Clone, Debug)]
 pub struct LocalSpawner {
 	task_list: Weak<task::LocalTaskList>,
@@ -163,11 +382,98 @@ impl LocalSpawner {
 	{
 		crate::current::enter_local(self, enter, f)
 	}
+
+	/// Enter a spawner without an `Enter` guard.
+	///
+	/// Like [`enter`](#method.enter), but meant for executors driven from within another executor;
+	/// `f` must not block the thread.
+	///
+	/// # Panics
+	///
+	/// Panics if a spawner is already entered.
+	pub fn enter_nonblocking<F, T>(self, f: F) -> T
+	where
+		F: FnOnce() -> T
+	{
+		crate::current::enter_local_nonblocking(self, f)
+	}
+
+	/// Snapshot of the pool's counters; `None` if the pool is gone.
+	pub fn metrics(&self) -> Option<PoolMetrics> {
+		Some(self.task_list.upgrade()?.metrics())
+	}
+
+	/// Whether both spawners refer to the same pool
+	pub fn same_pool(&self, other: &Self) -> bool {
+		Weak::ptr_eq(&self.task_list, &other.task_list)
+	}
+
+	/// Spawn future on the pool
+	///
+	/// Like `LocalSpawn::spawn_local_obj`, but boxes the future itself.
+	///
+	/// # Errors
+	///
+	/// Fails if the pool is gone.
+	pub fn spawn<F>(&self, future: F) -> Result<(), SpawnError>
+	where
+		F: Future<Output=()> + 'static,
+	{
+		let task_list = self.task_list.upgrade().ok_or_else(SpawnError::shutdown)?;
+		task_list.add_task(Box::pin(future).into());
+		Ok(())
+	}
+
+	/// Spawn future on the pool with the given priority
+	///
+	/// See [`Priority`](enum.Priority.html); [`spawn`](#method.spawn) uses `Priority::Normal`.
+	///
+	/// # Errors
+	///
+	/// Fails if the pool is gone.
+	pub fn spawn_with_priority<F>(&self, priority: Priority, future: F) -> Result<(), SpawnError>
+	where
+		F: Future<Output=()> + 'static,
+	{
+		let task_list = self.task_list.upgrade().ok_or_else(SpawnError::shutdown)?;
+		task_list.add_task_with(Box::pin(future).into(), priority, None);
+		Ok(())
+	}
+
+	/// Spawn future on the pool with a name (shown in
+	/// [`LocalPool::tasks`](struct.LocalPool.html#method.tasks))
+	///
+	/// # Errors
+	///
+	/// Fails if the pool is gone.
+	pub fn spawn_named<N, F>(&self, name: N, future: F) -> Result<(), SpawnError>
+	where
+		N: Into<String>,
+		F: Future<Output=()> + 'static,
+	{
+		let task_list = self.task_list.upgrade().ok_or_else(SpawnError::shutdown)?;
+		task_list.add_task_with(Box::pin(future).into(), Priority::default(), Some(name.into().into_boxed_str()));
+		Ok(())
+	}
+
+	/// Snapshot of all tasks in the pool; `None` if the pool is gone.
+	///
+	/// See [`LocalPool::tasks`](struct.LocalPool.html#method.tasks).
+	pub fn tasks(&self) -> Option<Vec<TaskInfo>> {
+		Some(self.task_list.upgrade()?.tasks())
+	}
+
+	/// Ids of all tasks in the pool; `None` if the pool is gone.
+	///
+	/// See [`LocalPool::task_ids`](struct.LocalPool.html#method.task_ids).
+	pub fn task_ids(&self) -> Option<Vec<TaskId>> {
+		Some(self.task_list.upgrade()?.task_ids())
+	}
 }
 
 impl Spawn for LocalSpawner {
 	fn spawn_obj(
-		&mut self,
+		&self,
 		future: FutureObj<'static, ()>,
 	) -> Result<(), SpawnError> {
 		self.spawn_local_obj(future.into())
@@ -180,15 +486,12 @@ impl Spawn for LocalSpawner {
 
 impl LocalSpawn for LocalSpawner {
 	fn spawn_local_obj(
-		&mut self,
+		&self,
 		future: LocalFutureObj<'static, ()>,
 	) -> Result<(), SpawnError> {
-		if let Some(task_list) = self.task_list.upgrade() {
-			task_list.add_task(future);
-			Ok(())
-		} else {
-			Err(SpawnError::shutdown())
-		}
+		let task_list = self.task_list.upgrade().ok_or_else(SpawnError::shutdown)?;
+		task_list.add_task(future);
+		Ok(())
 	}
 
 	fn status_local(&self) -> Result<(), SpawnError> {
@@ -0,0 +1,53 @@
+use std::cell::Cell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Snapshot of the counters of a [`LocalPool`](struct.LocalPool.html).
+///
+/// All counters start at zero when the pool is created and wrap around on overflow.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolMetrics {
+	/// Number of tasks spawned
+	pub tasks_spawned: u64,
+	/// Number of tasks that ran to completion
+	pub tasks_completed: u64,
+	/// Number of spawned tasks that reused the allocation of a finished task
+	pub tasks_reused: u64,
+	/// Number of times a task was polled
+	pub task_polls: u64,
+	/// Number of task wakeups from the thread running the pool
+	pub local_wakeups: u64,
+	/// Number of task wakeups from other threads
+	pub remote_wakeups: u64,
+}
+
+// only `remote_wakeups` is thread-safe; the rest is only used from the thread owning the pool
+#[derive(Debug, Default)]
+pub(super) struct Counters {
+	pub(super) tasks_spawned: Cell<u64>,
+	pub(super) tasks_completed: Cell<u64>,
+	pub(super) tasks_reused: Cell<u64>,
+	pub(super) task_polls: Cell<u64>,
+	pub(super) local_wakeups: Cell<u64>,
+	pub(super) remote_wakeups: AtomicU64,
+}
+
+impl Counters {
+	pub(super) fn inc(counter: &Cell<u64>) {
+		counter.set(counter.get().wrapping_add(1));
+	}
+
+	pub(super) fn inc_remote_wakeups(&self) {
+		self.remote_wakeups.fetch_add(1, Ordering::Relaxed);
+	}
+
+	pub(super) fn snapshot(&self) -> PoolMetrics {
+		PoolMetrics {
+			tasks_spawned: self.tasks_spawned.get(),
+			tasks_completed: self.tasks_completed.get(),
+			tasks_reused: self.tasks_reused.get(),
+			task_polls: self.task_polls.get(),
+			local_wakeups: self.local_wakeups.get(),
+			remote_wakeups: self.remote_wakeups.load(Ordering::Relaxed),
+		}
+	}
+}
@@ -0,0 +1,65 @@
+use std::any::Any;
+use std::fmt;
+
+/// Identifies a task within its pool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaskId(pub(crate) u64);
+
+impl fmt::Display for TaskId {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "task#{}", self.0)
+	}
+}
+
+/// What to do when polling a task panics.
+///
+/// The panicking task is always dropped; the [panic hook](struct.LocalPool.html#method.set_panic_hook)
+/// is called before the policy is applied.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PanicPolicy {
+	/// Propagate the panic to the caller of `run`, `run_until`, `poll_pool`, ... (the default).
+	///
+	/// Other tasks stay in the pool and can continue with further use of one of the pool's run or
+	/// poll methods.
+	#[default]
+	Propagate,
+	/// Continue with the other tasks.
+	///
+	/// Combine with a panic hook to report panics.
+	DropTask,
+	/// Abort the process.
+	Abort,
+}
+
+/// Information about a panicking task, passed to the panic hook.
+#[derive(Debug)]
+pub struct TaskPanic<'a> {
+	pub(crate) task_id: TaskId,
+	pub(crate) payload: &'a (dyn Any + Send),
+	pub(crate) shuffle_seed: Option<u64>,
+}
+
+impl TaskPanic<'_> {
+	/// Task that panicked
+	pub fn task_id(&self) -> TaskId {
+		self.task_id
+	}
+
+	/// The panic payload (usually `&'static str` or `String`)
+	pub fn payload(&self) -> &(dyn Any + Send) {
+		self.payload
+	}
+
+	/// The [shuffle seed](struct.LocalPool.html#method.set_shuffle_seed) of the pool, if enabled
+	///
+	/// Report it to reproduce the polling order that led to the panic.
+	pub fn shuffle_seed(&self) -> Option<u64> {
+		self.shuffle_seed
+	}
+
+	/// The panic message if the payload was a string
+	pub fn message(&self) -> Option<&str> {
+		self.payload.downcast_ref::<&'static str>().copied()
+			.or_else(|| self.payload.downcast_ref::<String>().map(String::as_str))
+	}
+}
@@ -0,0 +1,30 @@
+/// Scheduling priority of a task.
+///
+/// Pending tasks are queued per priority; a round of polling interleaves the queues weighted by
+/// priority (up to 4 high, 2 normal and 1 low priority task in turn), so lower priorities are
+/// delayed but not starved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+	/// Latency sensitive tasks (e.g. heartbeats, control connections)
+	High,
+	/// Default priority
+	#[default]
+	Normal,
+	/// Bulk work (e.g. background data processing)
+	Low,
+}
+
+impl Priority {
+	pub(crate) const COUNT: usize = 3;
+
+	// tasks polled in a row before switching to the next priority
+	pub(crate) const WEIGHTS: [usize; Self::COUNT] = [4, 2, 1];
+
+	pub(crate) fn index(self) -> usize {
+		match self {
+			Self::High => 0,
+			Self::Normal => 1,
+			Self::Low => 2,
+		}
+	}
+}
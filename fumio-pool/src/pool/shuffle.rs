@@ -0,0 +1,39 @@
+use std::cell::Cell;
+
+// small deterministic PRNG (splitmix64) for shuffling the polling order; not for cryptography
+#[derive(Debug)]
+pub(super) struct Rng {
+	state: Cell<u64>,
+}
+
+impl Rng {
+	pub(super) fn new(seed: u64) -> Self {
+		Self { state: Cell::new(seed) }
+	}
+
+	pub(super) fn reseed(&self, seed: u64) {
+		self.state.set(seed);
+	}
+
+	fn next_u64(&self) -> u64 {
+		let state = self.state.get().wrapping_add(0x9e37_79b9_7f4a_7c15);
+		self.state.set(state);
+		let mut z = state;
+		z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+		z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+		z ^ (z >> 31)
+	}
+
+	// uniform in `0..n` (`n > 0`); the modulo bias is irrelevant for shuffling tasks
+	#[allow(clippy::cast_possible_truncation)]
+	pub(super) fn below(&self, n: usize) -> usize {
+		(self.next_u64() % n as u64) as usize
+	}
+
+	// Fisher-Yates
+	pub(super) fn shuffle<T>(&self, items: &mut [T]) {
+		for i in (1..items.len()).rev() {
+			items.swap(i, self.below(i + 1));
+		}
+	}
+}
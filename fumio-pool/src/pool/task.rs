@@ -1,18 +1,32 @@
-use futures_core::future::{Future, LocalFutureObj};
+use super::metrics::{Counters, PoolMetrics};
+use super::panic_policy::{PanicPolicy, TaskId, TaskPanic};
+use super::priority::Priority;
+use super::shuffle::Rng;
+use super::task_info::TaskInfo;
+use fumio_utils::coop;
+use futures_core::future::Future;
+use futures_util::future::LocalFutureObj;
 use futures_util::task::AtomicWaker;
-use std::cell::{Cell, UnsafeCell};
+use std::any::Any;
+use std::cell::{Cell, RefCell, UnsafeCell};
+use std::fmt;
 use std::marker::PhantomData;
 use std::mem::ManuallyDrop;
+use std::panic::{self, AssertUnwindSafe};
 use std::pin::Pin;
+use std::ptr::NonNull;
+use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::task::{Context, Poll};
 use std::thread::{self, ThreadId};
+#[cfg(feature = "task-dump")]
+use std::time::Instant;
 
 fumio_utils::local_dl_list! {
 	mod loc_pending_list {
 		link TaskPendingLink;
-		head TaskPendingHead;
+		counted head TaskPendingHead;
 		member local_pending_link of Task;
 	}
 }
@@ -20,7 +34,7 @@ fumio_utils::local_dl_list! {
 fumio_utils::local_dl_list! {
 	mod loc_list {
 		link TaskLink;
-		head TaskHead;
+		counted head TaskHead;
 		member local_link of Task;
 	}
 }
@@ -33,6 +47,113 @@ fumio_utils::mpsc! {
 	}
 }
 
+// how many tasks can be polled in a row through the LIFO slot
+const MAX_LIFO_POLLS: usize = 3;
+
+// how many finished task blocks are kept for reuse
+const MAX_FREE_TASKS: usize = 256;
+
+// one pending queue per priority; doesn't own a refcount on the tasks
+#[derive(Debug)]
+struct PendingQueues {
+	queues: [TaskPendingHead; Priority::COUNT],
+}
+
+impl PendingQueues {
+	fn new() -> Self {
+		Self {
+			queues: [TaskPendingHead::new(), TaskPendingHead::new(), TaskPendingHead::new()],
+		}
+	}
+
+	fn queue(&self, priority: Priority) -> &TaskPendingHead {
+		&self.queues[priority.index()]
+	}
+
+	fn is_empty(&self) -> bool {
+		self.queues.iter().all(TaskPendingHead::is_empty)
+	}
+
+	fn len(&self) -> usize {
+		self.queues.iter().map(TaskPendingHead::len).sum()
+	}
+
+	unsafe fn take_from(&mut self, other: &Self) {
+		for (queue, other) in self.queues.iter_mut().zip(other.queues.iter()) {
+			queue.take_from(other);
+		}
+	}
+
+	// highest priority first
+	unsafe fn pop_front(&self) -> Option<*const Task> {
+		self.queues.iter().find_map(|queue| queue.pop_front())
+	}
+
+	// any order
+	unsafe fn pop_back(&self) -> Option<*const Task> {
+		self.queues.iter().find_map(|queue| queue.pop_back())
+	}
+
+	// remove a random task (priorities are ignored); the other tasks stay in their queues (in
+	// the same order), as tasks are removed from the queue of their priority
+	unsafe fn pop_random(&self, rng: &Rng) -> Option<*const Task> {
+		let len = self.len();
+		if 0 == len {
+			return None;
+		}
+		let mut tasks = Vec::with_capacity(len);
+		while let Some(task) = self.pop_front() {
+			tasks.push(task);
+		}
+		let task = tasks.remove(rng.below(len));
+		for other in tasks {
+			self.queue((*other).priority).append(&*other);
+		}
+		Some(task)
+	}
+
+	// move all tasks in random order to the normal priority queue (priorities are ignored); only
+	// for the queues of a polling round, which put tasks back by their priority
+	unsafe fn shuffle(&self, rng: &Rng) {
+		let mut tasks = Vec::new();
+		while let Some(task) = self.pop_front() {
+			tasks.push(task);
+		}
+		rng.shuffle(&mut tasks);
+		let queue = self.queue(Priority::Normal);
+		for task in tasks {
+			queue.append(&*task);
+		}
+	}
+
+	// take up to `Priority::WEIGHTS[i]` tasks from queue `i` in turn; `cursor` is
+	// `(queue, remaining)`
+	unsafe fn pop_weighted(&self, cursor: &mut (usize, usize)) -> Option<*const Task> {
+		// enough steps to visit each queue with fresh weight
+		for _ in 0..=Priority::COUNT {
+			let (index, remaining) = *cursor;
+			if remaining > 0 {
+				if let Some(task) = self.queues[index].pop_front() {
+					cursor.1 -= 1;
+					return Some(task);
+				}
+			}
+			let next = (index + 1) % Priority::COUNT;
+			*cursor = (next, Priority::WEIGHTS[next]);
+		}
+		None
+	}
+}
+
+#[derive(Clone, Copy, Debug)]
+struct PollConfig {
+	coop_budget: Option<u32>,
+	lifo_slot: bool,
+	max_tasks_per_tick: Option<usize>,
+	// poll pending tasks in random order (seeded `shuffle_rng`)
+	shuffle_seed: Option<u64>,
+}
+
 #[derive(Debug)]
 struct TaskList {
 	// local state:
@@ -40,8 +161,8 @@ struct TaskList {
 	// list of all alive tasks, owns a refcount on each task
 	// all alive tasks need to be on this list
 	local_all: TaskHead,
-	// list of pending (and alive!) tasks, doesn't own a refcount
-	local_pending: TaskPendingHead,
+	// lists of pending (and alive!) tasks, don't own a refcount
+	local_pending: PendingQueues,
 	// head of the single-linked global pending task queue; only the
 	// owning thread advances the head, therefore local state.
 	//
@@ -54,6 +175,18 @@ struct TaskList {
 	//
 	// this queue keeps a refcount on each task (but not for the stub task).
 	global_pending: GlobalTaskListHead, // local state!
+	// whether woken tasks are put into `lifo_slot`; only enabled while polling
+	lifo_active: Cell<bool>,
+	// last task woken (locally) while polling, owns a refcount
+	lifo_slot: Cell<Option<NonNull<Task>>>,
+	// task currently polled (null if none); tasks waking themselves don't use the LIFO slot
+	polling: Cell<*const Task>,
+	// finished tasks (future already dropped); reused by `add_task` once no waker refers to them
+	// anymore.  Owns a refcount on each task.
+	free_tasks: RefCell<Vec<Arc<Task>>>,
+	// only used with `PollConfig::shuffle_seed`
+	shuffle_rng: Rng,
+	counters: Counters,
 
 	// thread-safe:
 	local_thread: ThreadId,
@@ -61,6 +194,8 @@ struct TaskList {
 	waker: AtomicWaker,
 }
 
+// the local fields are only used on `local_thread`
+#[allow(clippy::non_send_fields_in_send_ty)]
 unsafe impl Send for TaskList {}
 unsafe impl Sync for TaskList {}
 
@@ -68,8 +203,14 @@ impl TaskList {
 	fn new() -> Self {
 		Self {
 			local_all: TaskHead::new(),
-			local_pending: TaskPendingHead::new(),
+			local_pending: PendingQueues::new(),
 			global_pending: GlobalTaskListHead::new(),
+			lifo_active: Cell::new(false),
+			lifo_slot: Cell::new(None),
+			polling: Cell::new(std::ptr::null()),
+			free_tasks: RefCell::new(Vec::new()),
+			shuffle_rng: Rng::new(0),
+			counters: Counters::default(),
 			local_thread: thread::current().id(),
 			waker: AtomicWaker::new(),
 		}
@@ -79,8 +220,38 @@ impl TaskList {
 		// local_pending doesn't keep a reference, but only still active tasks
 		// are allowed (as they are kept on local_all too)
 		if task.alive.get() && task.local_pending_link.is_unlinked() {
-			unsafe { self.local_pending.append(task); }
+			unsafe { self.local_pending.queue(task.priority).append(task); }
 			self.waker.wake();
+			// only newly queued tasks: a task in the LIFO slot must be on `local_pending` (and not
+			// in the queues of the current round), so `poll` knows where to remove it from
+			let self_wake = std::ptr::eq(self.polling.get(), Arc::as_ptr(task));
+			if self.lifo_active.get() && !self_wake {
+				self.replace_lifo_slot(Some(task.clone()));
+			}
+		}
+	}
+
+	fn replace_lifo_slot(&self, task: Option<Arc<Task>>) -> Option<Arc<Task>> {
+		let task = task.map(|task| unsafe { NonNull::new_unchecked(Arc::into_raw(task).cast_mut()) });
+		self.lifo_slot.replace(task).map(|task| unsafe { Arc::from_raw(task.as_ptr()) })
+	}
+
+	// keep finished task for reuse
+	fn recycle(&self, task: Arc<Task>) {
+		let mut free_tasks = self.free_tasks.borrow_mut();
+		if free_tasks.len() < MAX_FREE_TASKS {
+			free_tasks.push(task);
+		}
+	}
+
+	// take a finished task nobody else refers to; tasks still referenced (e.g. by wakers) are
+	// given up, the last reference frees them
+	fn take_free_task(&self) -> Option<Arc<Task>> {
+		loop {
+			let mut task = self.free_tasks.borrow_mut().pop()?;
+			if Arc::get_mut(&mut task).is_some() {
+				return Some(task);
+			}
 		}
 	}
 
@@ -99,35 +270,87 @@ impl TaskList {
 			task.queued.swap(false, Ordering::Acquire); // sync with Release in global_notify
 			// move to local queue
 			if task.alive.get() && task.local_pending_link.is_unlinked() {
-				unsafe { self.local_pending.append(&task); }
+				unsafe { self.local_pending.queue(task.priority).append(&task); }
 			}
 		}
 	}
 
-	fn poll(&self) -> Poll<()> {
-		struct PollList {
-			pending: TaskPendingHead,
+	// `on_panic` is called after a panicking task was dropped
+	fn poll<F>(&self, config: PollConfig, mut on_panic: F) -> Poll<()>
+	where
+		F: FnMut(TaskId, Box<dyn Any + Send>),
+	{
+		struct PollList<'a> {
+			task_list: &'a TaskList,
+			pending: PendingQueues,
 		}
-		impl Drop for PollList {
+		impl Drop for PollList<'_> {
 			fn drop(&mut self) {
+				self.task_list.lifo_active.set(false);
+				self.task_list.replace_lifo_slot(None);
 				// pop all to readd them on panic
 				while let Some(task) = unsafe { self.pending.pop_back() } {
 					let task = unsafe { &*task };
 					if task.alive.get() && task.local_pending_link.is_unlinked() {
-						unsafe { task.task_list().local_pending.prepend(task); }
+						unsafe { task.task_list().local_pending.queue(task.priority).prepend(task); }
 					}
 				}
 			}
 		}
 
 		let mut poll_list = PollList {
-			pending: TaskPendingHead::new(),
+			task_list: self,
+			pending: PendingQueues::new(),
+		};
+		let mut cursor = (0, Priority::WEIGHTS[0]);
+		let polled = Cell::new(0);
+		let mut poll_task = |task: &Task| {
+			polled.set(polled.get() + 1);
+			let task_id = task.id; // task might be gone after a panic
+			if let Err(payload) = task.local_poll() {
+				on_panic(task_id, payload);
+			}
 		};
 
 		unsafe {
 			poll_list.pending.take_from(&self.local_pending);
-			while let Some(task) = poll_list.pending.pop_front() {
-				/* unsafe */ { &*task }.local_poll();
+			if config.shuffle_seed.is_some() {
+				poll_list.pending.shuffle(&self.shuffle_rng);
+			}
+			// the LIFO slot would make the order predictable again
+			self.lifo_active.set(config.lifo_slot && config.shuffle_seed.is_none());
+			loop {
+				if config.max_tasks_per_tick.is_some_and(|max| polled.get() >= max) {
+					if !poll_list.pending.is_empty() {
+						// remaining tasks are put back by `PollList::drop`; make sure we get polled again
+						self.waker.wake();
+					}
+					break;
+				}
+				let Some(task) = poll_list.pending.pop_weighted(&mut cursor) else {
+					break;
+				};
+				let task = /* unsafe */ { &*task };
+				// tasks polled through the LIFO slot share the budget
+				coop::with_budget(config.coop_budget, || {
+					poll_task(task);
+					// run tasks woken by the previous task right away (while their data is still
+					// hot), but not forever
+					for _ in 0..MAX_LIFO_POLLS {
+						if !coop::has_budget_remaining() {
+							break;
+						}
+						let Some(task) = self.replace_lifo_slot(None) else {
+							break;
+						};
+						if task.alive.get() && !task.local_pending_link.is_unlinked() {
+							self.local_pending.queue(task.priority).remove(&task);
+							poll_task(&task);
+						}
+					}
+				});
+				// a task left in the slot is still pending in one of the lists
+				self.replace_lifo_slot(None);
 			}
 		}
 		if self.local_all.is_empty() {
@@ -136,6 +359,28 @@ impl TaskList {
 			Poll::Pending
 		}
 	}
+
+	// poll the first pending task of the highest priority (a random one when shuffling; if there
+	// is one); returns whether a task was polled
+	fn poll_one<F>(&self, config: PollConfig, on_panic: F) -> bool
+	where
+		F: FnOnce(TaskId, Box<dyn Any + Send>),
+	{
+		let task = if config.shuffle_seed.is_some() {
+			unsafe { self.local_pending.pop_random(&self.shuffle_rng) }
+		} else {
+			unsafe { self.local_pending.pop_front() }
+		};
+		let task = match task {
+			Some(task) => unsafe { &*task },
+			None => return false,
+		};
+		let task_id = task.id; // task might be gone after a panic
+		if let Err(payload) = coop::with_budget(config.coop_budget, || task.local_poll()) {
+			on_panic(task_id, payload);
+		}
+		true
+	}
 }
 
 impl Drop for TaskList {
@@ -145,38 +390,180 @@ impl Drop for TaskList {
 	}
 }
 
-#[derive(Debug)]
+pub(super) type PanicHook = Rc<dyn Fn(&TaskPanic<'_>)>;
+
 pub(super) struct LocalTaskList {
 	task_list: Arc<TaskList>,
+	next_task_id: Cell<u64>,
+	panic_policy: Cell<PanicPolicy>,
+	panic_hook: RefCell<Option<PanicHook>>,
+	poll_config: Cell<PollConfig>,
 	_marker: PhantomData<*mut ()>, // don't send
 }
 
+impl fmt::Debug for LocalTaskList {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("LocalTaskList")
+			.field("task_list", &self.task_list)
+			.field("next_task_id", &self.next_task_id)
+			.field("panic_policy", &self.panic_policy)
+			.field("panic_hook", &self.panic_hook.borrow().as_ref().map(|_| ()))
+			.field("poll_config", &self.poll_config)
+			.finish()
+	}
+}
+
 impl LocalTaskList {
 	pub fn new() -> Self {
 		Self {
 			task_list: Arc::new(TaskList::new()),
+			next_task_id: Cell::new(0),
+			panic_policy: Cell::new(PanicPolicy::default()),
+			panic_hook: RefCell::new(None),
+			poll_config: Cell::new(PollConfig {
+				coop_budget: Some(coop::DEFAULT_BUDGET),
+				lifo_slot: true,
+				max_tasks_per_tick: None,
+				shuffle_seed: None,
+			}),
 			_marker: PhantomData,
 		}
 	}
 
+	pub fn set_panic_policy(&self, policy: PanicPolicy) {
+		self.panic_policy.set(policy);
+	}
+
+	pub fn set_panic_hook(&self, hook: Option<PanicHook>) {
+		*self.panic_hook.borrow_mut() = hook;
+	}
+
+	fn update_poll_config<F: FnOnce(&mut PollConfig)>(&self, f: F) {
+		let mut config = self.poll_config.get();
+		f(&mut config);
+		self.poll_config.set(config);
+	}
+
+	pub fn set_coop_budget(&self, budget: Option<u32>) {
+		self.update_poll_config(|c| c.coop_budget = budget);
+	}
+
+	pub fn set_lifo_slot(&self, enabled: bool) {
+		self.update_poll_config(|c| c.lifo_slot = enabled);
+	}
+
+	pub fn set_max_tasks_per_tick(&self, max: Option<usize>) {
+		self.update_poll_config(|c| c.max_tasks_per_tick = max);
+	}
+
+	pub fn set_shuffle_seed(&self, seed: Option<u64>) {
+		if let Some(seed) = seed {
+			self.task_list.shuffle_rng.reseed(seed);
+		}
+		self.update_poll_config(|c| c.shuffle_seed = seed);
+	}
+
+	pub fn shuffle_seed(&self) -> Option<u64> {
+		self.poll_config.get().shuffle_seed
+	}
+
+	fn handle_panic(&self, task_id: TaskId, payload: Box<dyn Any + Send>) {
+		let hook = self.panic_hook.borrow().clone();
+		if let Some(hook) = hook {
+			hook(&TaskPanic { task_id, payload: &*payload, shuffle_seed: self.shuffle_seed() });
+		}
+		match self.panic_policy.get() {
+			PanicPolicy::Propagate => panic::resume_unwind(payload),
+			PanicPolicy::DropTask => (),
+			PanicPolicy::Abort => std::process::abort(),
+		}
+	}
+
 	// poll one round; completes when all tasks completed
-	pub fn poll(&self, cx: &mut Context<'_>) -> Poll<()> {
+	pub fn poll(&self, cx: &Context<'_>) -> Poll<()> {
+		self.task_list.waker.register(cx.waker());
+		self.task_list.fetch_global_notifies();
+		self.task_list.poll(self.poll_config.get(), |task_id, payload| self.handle_panic(task_id, payload))
+	}
+
+	// poll a single pending task; returns whether a task was polled
+	pub fn poll_one(&self, cx: &Context<'_>) -> bool {
 		self.task_list.waker.register(cx.waker());
 		self.task_list.fetch_global_notifies();
-		self.task_list.poll()
+		self.task_list.poll_one(self.poll_config.get(), |task_id, payload| self.handle_panic(task_id, payload))
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.task_list.local_all.is_empty()
+	}
+
+	pub fn len(&self) -> usize {
+		self.task_list.local_all.len()
+	}
+
+	pub fn tasks(&self) -> Vec<TaskInfo> {
+		self.task_list.fetch_global_notifies();
+		let mut tasks = Vec::new();
+		// tasks on `local_all` are alive (the list owns a refcount)
+		unsafe {
+			self.task_list.local_all.for_each(|task| tasks.push(TaskInfo {
+				id: task.id,
+				name: task.name.as_ref().map(|name| String::from(&**name)),
+				priority: task.priority,
+				#[cfg(feature = "task-dump")]
+				last_poll: task.last_poll.get(),
+				pending: !task.local_pending_link.is_unlinked(),
+			}));
+		}
+		tasks
+	}
+
+	pub fn task_ids(&self) -> Vec<TaskId> {
+		let mut ids = Vec::new();
+		// tasks on `local_all` are alive (the list owns a refcount)
+		unsafe { self.task_list.local_all.for_each(|task| ids.push(task.id)); }
+		ids
+	}
+
+	pub fn pending_len(&self) -> usize {
+		self.task_list.fetch_global_notifies();
+		self.task_list.local_pending.len()
+	}
+
+	pub fn is_idle(&self) -> bool {
+		self.task_list.fetch_global_notifies();
+		self.task_list.local_pending.is_empty()
+	}
+
+	pub fn metrics(&self) -> PoolMetrics {
+		self.task_list.counters.snapshot()
 	}
 
 	pub fn add_task(&self, future: LocalFutureObj<'static, ()>) {
-		let task = Arc::new(Task::new(self.task_list.clone(), future));
+		self.add_task_with(future, Priority::default(), None);
+	}
+
+	pub fn add_task_with(&self, future: LocalFutureObj<'static, ()>, priority: Priority, name: Option<Box<str>>) {
+		Counters::inc(&self.task_list.counters.tasks_spawned);
+		let id = TaskId(self.next_task_id.get());
+		self.next_task_id.set(id.0 + 1);
+		let task = Task::new(self.task_list.clone(), id, priority, name, future);
+		let task = match self.task_list.take_free_task() {
+			Some(mut free) => {
+				Counters::inc(&self.task_list.counters.tasks_reused);
+				*Arc::get_mut(&mut free).expect("free task not shared") = task;
+				free
+			},
+			None => Arc::new(task),
+		};
 		unsafe { self.task_list.local_all.append(&task); }
 		let task = ManuallyDrop::new(task); // now owned by `local_all`
 		// trigger initial poll
 		self.task_list.local_notify(&task);
 	}
-}
 
-impl Drop for LocalTaskList {
-	fn drop(&mut self) {
+	// drop all tasks (dropping a future might spawn new tasks, they get dropped too)
+	pub fn clear(&self) {
 		while let Some(task) = unsafe { self.task_list.local_all.pop_front() } {
 			// local_clear will drop the refcount from local_all
 			unsafe { &*task }.local_clear();
@@ -184,11 +571,26 @@ impl Drop for LocalTaskList {
 	}
 }
 
+impl Drop for LocalTaskList {
+	fn drop(&mut self) {
+		self.clear();
+		// free tasks refer to the task list
+		let free_tasks = std::mem::take(&mut *self.task_list.free_tasks.borrow_mut());
+		drop(free_tasks);
+	}
+}
+
 #[derive(Debug)]
 // unless marked fields are not thread-safe and only for the thread owning the
 // corresponding `LocalTaskList`
+#[allow(clippy::struct_field_names)] // `task_list` is the list owning the task
 pub(super) struct Task {
 	task_list: Option<Arc<TaskList>>, // thread-safe
+	id: TaskId, // thread-safe
+	priority: Priority, // thread-safe
+	name: Option<Box<str>>, // thread-safe
+	#[cfg(feature = "task-dump")]
+	last_poll: Cell<Option<Instant>>,
 	local_link: TaskLink,
 	local_pending_link: TaskPendingLink,
 	global_pending_next: GlobalTaskListLink, // thread-safe
@@ -197,13 +599,20 @@ pub(super) struct Task {
 	future: ManuallyDrop<UnsafeCell<Option<LocalFutureObj<'static, ()>>>>,
 }
 
+// the fields not marked thread-safe are only used by the thread owning the task list
+#[allow(clippy::non_send_fields_in_send_ty)]
 unsafe impl Send for Task {}
 unsafe impl Sync for Task {}
 
 impl Task {
-	fn new(task_list: Arc<TaskList>, future: LocalFutureObj<'static, ()>) -> Self {
+	fn new(task_list: Arc<TaskList>, id: TaskId, priority: Priority, name: Option<Box<str>>, future: LocalFutureObj<'static, ()>) -> Self {
 		Self {
 			task_list: Some(task_list),
+			id,
+			priority,
+			name,
+			#[cfg(feature = "task-dump")]
+			last_poll: Cell::new(None),
 			local_link: TaskLink::new(),
 			local_pending_link: TaskPendingLink::new(),
 			global_pending_next: GlobalTaskListLink::new(),
@@ -223,20 +632,8 @@ impl Task {
 		&mut *self.future.get()
 	}
 
-	fn local_poll(&self) {
-		struct ClearOnPanic<'a> {
-			task: Option<&'a Task>,
-		}
-		impl Drop for ClearOnPanic<'_> {
-			fn drop(&mut self) {
-				if let Some(t) = self.task.take() {
-					if t.alive.get() {
-						t.local_clear();
-					}
-				}
-			}
-		}
-
+	// on panic the task is cleared and the panic payload returned
+	fn local_poll(&self) -> std::thread::Result<()> {
 		debug_assert!(self.alive.get());
 		let arc_self = ManuallyDrop::new(unsafe { Arc::from_raw(self) }); // no refcount
 		let waker = futures_util::task::waker_ref(&arc_self);
@@ -244,25 +641,50 @@ impl Task {
 
 		let fut = unsafe { self.local_future() }.as_mut().expect("pending futures must be alive");
 		let fut = unsafe { Pin::new_unchecked(fut) };
-
-		let mut cop = ClearOnPanic { task: Some(self) };
-		if let Poll::Ready(()) = fut.poll(&mut cx) {
-			self.local_clear();
+		#[cfg(feature = "task-dump")]
+		self.last_poll.set(Some(Instant::now()));
+
+		Counters::inc(&self.task_list().counters.task_polls);
+		let prev_polling = self.task_list().polling.replace(self);
+		let result = panic::catch_unwind(AssertUnwindSafe(|| fut.poll(&mut cx)));
+		self.task_list().polling.set(prev_polling);
+		match result {
+			Ok(Poll::Ready(())) => {
+				Counters::inc(&self.task_list().counters.tasks_completed);
+				self.local_clear();
+				Ok(())
+			},
+			Ok(Poll::Pending) => Ok(()),
+			Err(payload) => {
+				if self.alive.get() {
+					self.local_clear();
+				}
+				Err(payload)
+			},
 		}
-		cop.task.take(); // no panic, undo clear on panic
 	}
 
-	// consumes one reference (for the one kept by `local_link`)
+	// consumes one reference (for the one kept by `local_link`), which is passed on to the free
+	// list
 	fn local_clear(&self) {
 		let this = unsafe { Arc::from_raw(self) };
 		// mark as queued: won't poll ever again though, no need to queue anymore
 		this.queued.store(true, Ordering::Relaxed);
 		this.alive.set(false);
+		let task_list = self.task_list();
 		unsafe {
-			this.local_pending_link.unlink();
-			this.local_link.unlink();
+			// a task is only cleared while not in the queues of a polling round: either it is
+			// the task being polled (which can only be queued again on `local_pending`) or the
+			// pool isn't polling
+			if !this.local_pending_link.is_unlinked() {
+				task_list.local_pending.queue(this.priority).remove(&this);
+			}
+			if !this.local_link.is_unlinked() {
+				task_list.local_all.remove(&this);
+			}
 			*this.local_future() = None;
 		}
+		task_list.recycle(this);
 	}
 }
 
@@ -271,8 +693,10 @@ impl futures_util::task::ArcWake for Task {
 		let id = thread::current().id();
 		let task_list = arc_self.task_list();
 		if id == task_list.local_thread {
+			Counters::inc(&task_list.counters.local_wakeups);
 			task_list.local_notify(arc_self);
 		} else {
+			task_list.counters.inc_remote_wakeups();
 			task_list.global_notify(arc_self);
 		}
 	}
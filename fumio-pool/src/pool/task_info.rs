@@ -0,0 +1,41 @@
+use super::panic_policy::TaskId;
+use super::priority::Priority;
+use std::fmt;
+#[cfg(feature = "task-dump")]
+use std::time::Instant;
+
+/// Snapshot of a task in a [`LocalPool`](struct.LocalPool.html), e.g. for debugging stuck tasks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskInfo {
+	/// Task id
+	pub id: TaskId,
+	/// Name given when spawned (see [`LocalSpawner::spawn_named`](struct.LocalSpawner.html#method.spawn_named))
+	pub name: Option<String>,
+	/// Scheduling priority
+	pub priority: Priority,
+	/// When the task was polled last (`None` if not polled yet)
+	///
+	/// Only recorded with the `task-dump` feature.
+	#[cfg(feature = "task-dump")]
+	pub last_poll: Option<Instant>,
+	/// Whether the task is waiting to be polled
+	pub pending: bool,
+}
+
+/// One line per task: id, name, priority, state and (with the `task-dump` feature) time since the
+/// last poll
+impl fmt::Display for TaskInfo {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.id)?;
+		if let Some(name) = &self.name {
+			write!(f, " {name:?}")?;
+		}
+		write!(f, " {:?} {}", self.priority, if self.pending { "pending" } else { "waiting" })?;
+		#[cfg(feature = "task-dump")]
+		match self.last_poll {
+			Some(last_poll) => write!(f, ", last poll {:?} ago", last_poll.elapsed())?,
+			None => write!(f, ", never polled")?,
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,31 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Yield to other tasks: the current task is rescheduled at the back of the pending queue.
+///
+/// Useful in long-running (compute-heavy) loops to keep the pool responsive.
+pub fn yield_now() -> YieldNow {
+	YieldNow { yielded: false }
+}
+
+/// Future returned by [`yield_now`](fn.yield_now.html)
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct YieldNow {
+	yielded: bool,
+}
+
+impl Future for YieldNow {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		if self.yielded {
+			return Poll::Ready(());
+		}
+		self.yielded = true;
+		// a task waking itself is queued at the back (not in the LIFO slot)
+		cx.waker().wake_by_ref();
+		Poll::Pending
+	}
+}
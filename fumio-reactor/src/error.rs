@@ -0,0 +1,44 @@
+use std::fmt;
+use std::io;
+
+/// Errors caused by the reactor state (instead of the OS).
+///
+/// Converted into `io::Error` (of kind `Other`) by the IO APIs; use
+/// [`from_io`](#method.from_io) to get it back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+	/// No reactor handle was bound or entered.
+	NoReactor,
+	/// The reactor was dropped.
+	ReactorGone,
+	/// The IO object isn't registered with a reactor.
+	NotRegistered,
+	/// The reactor can't take more registrations.
+	TooManyRegistrations,
+}
+
+impl Error {
+	/// Extract reactor error from an `io::Error` (if it was created from one).
+	pub fn from_io(e: &io::Error) -> Option<Self> {
+		e.get_ref()?.downcast_ref::<Self>().copied()
+	}
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Self::NoReactor => "no reactor present",
+			Self::ReactorGone => "reactor not running anymore",
+			Self::NotRegistered => "not registered",
+			Self::TooManyRegistrations => "too many registrations",
+		})
+	}
+}
+
+impl std::error::Error for Error {}
+
+impl From<Error> for io::Error {
+	fn from(e: Error) -> Self {
+		Self::other(e)
+	}
+}
@@ -8,9 +8,58 @@ where
 	loop {
 		match op() {
 			Ok(v) => return Poll::Ready(Ok(v)),
-			Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+			Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
 			Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => return Poll::Pending,
 			Err(e) => return Poll::Ready(Err(e)),
 		}
 	}
 }
+
+// mio doesn't provide `try_clone` or conversions into `std` types; go through the raw file
+// descriptor (socket on windows) instead.
+
+/// Convert between types owning the same kind of socket.
+#[cfg(unix)]
+pub(crate) fn convert_raw<S, T>(source: S) -> T
+where
+	S: std::os::unix::io::IntoRawFd,
+	T: std::os::unix::io::FromRawFd,
+{
+	unsafe { T::from_raw_fd(source.into_raw_fd()) }
+}
+
+/// Run `op` with a temporary `T` sharing the socket of `source`.
+#[cfg(unix)]
+pub(crate) fn with_raw_as<S, T, F, R>(source: &S, op: F) -> R
+where
+	S: std::os::unix::io::AsRawFd,
+	T: std::os::unix::io::FromRawFd,
+	F: FnOnce(&T) -> R,
+{
+	// must not close the socket
+	let borrowed = std::mem::ManuallyDrop::new(unsafe { T::from_raw_fd(source.as_raw_fd()) });
+	op(&borrowed)
+}
+
+/// Convert between types owning the same kind of socket.
+#[cfg(windows)]
+pub(crate) fn convert_raw<S, T>(source: S) -> T
+where
+	S: std::os::windows::io::IntoRawSocket,
+	T: std::os::windows::io::FromRawSocket,
+{
+	unsafe { T::from_raw_socket(source.into_raw_socket()) }
+}
+
+/// Run `op` with a temporary `T` sharing the socket of `source`.
+#[cfg(windows)]
+pub(crate) fn with_raw_as<S, T, F, R>(source: &S, op: F) -> R
+where
+	S: std::os::windows::io::AsRawSocket,
+	T: std::os::windows::io::FromRawSocket,
+	F: FnOnce(&T) -> R,
+{
+	// must not close the socket
+	let borrowed = std::mem::ManuallyDrop::new(unsafe { T::from_raw_socket(source.as_raw_socket()) });
+	op(&borrowed)
+}
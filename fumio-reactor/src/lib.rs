@@ -23,8 +23,16 @@
 	clippy::module_name_repetitions, // often hidden modules and reexported
 	clippy::if_not_else, // `... != 0` is a positive condition
 	clippy::multiple_crate_versions, // not useful
+	clippy::must_use_candidate, // would mark nearly every getter
+	clippy::missing_const_for_fn, // `const` is an API promise; only added where it is needed
+	clippy::redundant_pub_crate, // stays crate-internal even if the module becomes public
+	clippy::missing_errors_doc, // IO wrappers: errors come from the underlying OS calls
 )]
 
+mod error;
 mod helper;
 pub mod net;
 pub mod reactor;
+mod sync;
+
+pub use self::error::Error;
@@ -2,12 +2,35 @@
 //!
 //! Based on [`PollEvented`](../reactor/struct.PollEvented.html).
 
+mod keepalive;
+mod lookup_host;
+#[cfg(all(unix, feature = "raw-socket"))]
+mod raw_socket;
 mod tcp_connect;
+mod tcp_limited;
 mod tcp_listen;
+mod tcp_split;
 mod tcp_stream;
+mod tclass;
+mod udp_batch;
+mod udp_framed;
 mod udp_socket;
+#[cfg(target_os = "linux")]
+mod zero_copy;
 
-pub use self::tcp_connect::TcpConnectFuture;
-pub use self::tcp_listen::{TcpListener, TcpIncoming};
-pub use self::tcp_stream::TcpStream;
+pub use self::keepalive::KeepAlive;
+pub use self::lookup_host::{lookup_host, LookupHost, ToSocketAddrs};
+#[cfg(all(unix, feature = "raw-socket"))]
+pub use self::raw_socket::{RawSocket, RawRecvFrom, RawSendTo};
+/// `socket2` for [`RawSocket`](struct.RawSocket.html) construction and options
+#[cfg(all(unix, feature = "raw-socket"))]
+pub use socket2;
+pub use self::tcp_connect::{TcpConnect, TcpConnectFuture, TcpConnectDeadline};
+pub use self::tcp_limited::{ConnectionPermit, TcpIncomingLimited};
+pub use self::tcp_listen::{is_accept_resource_error, TcpListener, TcpIncoming};
+pub use self::tcp_split::{ReadHalf, WriteHalf, OwnedReadHalf, OwnedWriteHalf};
+pub use self::tcp_stream::{TcpStream, TcpPeek};
+pub use self::udp_framed::{UdpCodec, UdpFramed};
 pub use self::udp_socket::{UdpSocket, UdpRecvFrom, UdpSendTo};
+#[cfg(target_os = "linux")]
+pub use self::zero_copy::{send_file, splice, SendFile, Splice};
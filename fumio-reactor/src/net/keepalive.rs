@@ -0,0 +1,81 @@
+use crate::helper::with_raw_as;
+use std::io;
+use std::time::Duration;
+
+/// TCP keepalive parameters.
+///
+/// Fields not set use the OS defaults; `interval` and `retries` are ignored on platforms not
+/// supporting them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KeepAlive {
+	/// Idle time before the first probe is sent (`TCP_KEEPIDLE`).
+	pub time: Option<Duration>,
+	/// Time between probes (`TCP_KEEPINTVL`).
+	pub interval: Option<Duration>,
+	/// Number of unanswered probes before the connection is dropped (`TCP_KEEPCNT`).
+	pub retries: Option<u32>,
+}
+
+impl KeepAlive {
+	#[allow(unused_mut)] // not all platforms support all parameters
+	fn to_mio(self) -> mio::net::TcpKeepalive {
+		let mut params = mio::net::TcpKeepalive::new();
+		if let Some(time) = self.time {
+			params = params.with_time(time);
+		}
+		#[cfg(any(
+			target_os = "linux",
+			target_os = "macos",
+			target_os = "ios",
+			target_os = "freebsd",
+			target_os = "netbsd",
+			target_os = "windows",
+		))]
+		{
+			if let Some(interval) = self.interval {
+				params = params.with_interval(interval);
+			}
+		}
+		#[cfg(any(
+			target_os = "linux",
+			target_os = "macos",
+			target_os = "ios",
+			target_os = "freebsd",
+			target_os = "netbsd",
+		))]
+		{
+			if let Some(retries) = self.retries {
+				params = params.with_retries(retries);
+			}
+		}
+		params
+	}
+}
+
+// enable (with given parameters) or disable (`None`) keepalive on any TCP socket
+#[cfg(unix)]
+pub(super) fn set_keepalive<S>(socket: &S, keepalive: Option<KeepAlive>) -> io::Result<()>
+where
+	S: std::os::unix::io::AsRawFd,
+{
+	with_raw_as(socket, |socket: &mio::net::TcpSocket| {
+		keepalive.map_or_else(
+			|| socket.set_keepalive(false),
+			|keepalive| socket.set_keepalive_params(keepalive.to_mio()),
+		)
+	})
+}
+
+// enable (with given parameters) or disable (`None`) keepalive on any TCP socket
+#[cfg(windows)]
+pub(super) fn set_keepalive<S>(socket: &S, keepalive: Option<KeepAlive>) -> io::Result<()>
+where
+	S: std::os::windows::io::AsRawSocket,
+{
+	with_raw_as(socket, |socket: &mio::net::TcpSocket| {
+		keepalive.map_or_else(
+			|| socket.set_keepalive(false),
+			|keepalive| socket.set_keepalive_params(keepalive.to_mio()),
+		)
+	})
+}
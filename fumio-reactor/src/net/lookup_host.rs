@@ -0,0 +1,262 @@
+use crate::sync::AtomicWaker;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6, ToSocketAddrs as _};
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+// resolver threads running at most (further lookups are queued)
+const MAX_RESOLVER_THREADS: usize = 4;
+
+// resolver threads exit after being idle this long
+const RESOLVER_IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+mod sealed {
+	use std::net::SocketAddr;
+
+	// owned lookup target, can be passed to a resolver thread
+	#[derive(Debug)]
+	pub enum Target {
+		// no lookup needed
+		Addrs(Vec<SocketAddr>),
+		// "host:port"
+		HostPort(String),
+		Host(String, u16),
+	}
+
+	pub trait Sealed {
+		fn to_target(&self) -> Target;
+	}
+}
+
+use self::sealed::{Sealed, Target};
+
+/// Targets accepted by [`lookup_host`](fn.lookup_host.html) and
+/// [`TcpStream::connect`](struct.TcpStream.html#method.connect)
+///
+/// Implemented for the same types as [`std::net::ToSocketAddrs`] (and `Vec<SocketAddr>`),
+/// including references to them; host names are copied, so they can be resolved in a helper
+/// thread.  Socket addresses and IP addresses don't need a lookup.
+pub trait ToSocketAddrs: Sealed {}
+
+impl<T: ToSocketAddrs + ?Sized> Sealed for &T {
+	fn to_target(&self) -> Target {
+		(**self).to_target()
+	}
+}
+
+impl<T: ToSocketAddrs + ?Sized> ToSocketAddrs for &T {}
+
+macro_rules! addr_targets {
+	($($ty:ty),*) => {$(
+		impl Sealed for $ty {
+			fn to_target(&self) -> Target {
+				Target::Addrs(vec![SocketAddr::from(*self)])
+			}
+		}
+
+		impl ToSocketAddrs for $ty {}
+	)*};
+}
+
+addr_targets!(SocketAddr, SocketAddrV4, SocketAddrV6, (IpAddr, u16), (Ipv4Addr, u16), (Ipv6Addr, u16));
+
+impl Sealed for [SocketAddr] {
+	fn to_target(&self) -> Target {
+		Target::Addrs(self.to_vec())
+	}
+}
+
+impl ToSocketAddrs for [SocketAddr] {}
+
+impl Sealed for Vec<SocketAddr> {
+	fn to_target(&self) -> Target {
+		Target::Addrs(self.clone())
+	}
+}
+
+impl ToSocketAddrs for Vec<SocketAddr> {}
+
+impl Sealed for str {
+	fn to_target(&self) -> Target {
+		self.parse::<SocketAddr>().map_or_else(|_| Target::HostPort(self.to_owned()), |addr| Target::Addrs(vec![addr]))
+	}
+}
+
+impl ToSocketAddrs for str {}
+
+impl Sealed for String {
+	fn to_target(&self) -> Target {
+		self.as_str().to_target()
+	}
+}
+
+impl ToSocketAddrs for String {}
+
+impl Sealed for (&str, u16) {
+	fn to_target(&self) -> Target {
+		let (host, port) = *self;
+		host.parse::<IpAddr>().map_or_else(
+			|_| Target::Host(host.to_owned(), port),
+			|addr| Target::Addrs(vec![SocketAddr::new(addr, port)]),
+		)
+	}
+}
+
+impl ToSocketAddrs for (&str, u16) {}
+
+impl Sealed for (String, u16) {
+	fn to_target(&self) -> Target {
+		(self.0.as_str(), self.1).to_target()
+	}
+}
+
+impl ToSocketAddrs for (String, u16) {}
+
+impl Target {
+	// blocking
+	fn resolve(&self) -> io::Result<Vec<SocketAddr>> {
+		match self {
+			Self::Addrs(addrs) => Ok(addrs.clone()),
+			Self::HostPort(host_port) => host_port.as_str().to_socket_addrs().map(Iterator::collect),
+			Self::Host(host, port) => (host.as_str(), *port).to_socket_addrs().map(Iterator::collect),
+		}
+	}
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+	result: Mutex<Option<io::Result<Vec<SocketAddr>>>>,
+	waker: AtomicWaker,
+}
+
+impl Shared {
+	fn complete(&self, result: io::Result<Vec<SocketAddr>>) {
+		*self.result.lock().unwrap() = Some(result);
+		self.waker.wake();
+	}
+}
+
+struct Job {
+	target: Target,
+	shared: Arc<Shared>,
+}
+
+struct ResolverState {
+	jobs: VecDeque<Job>,
+	threads: usize,
+	idle: usize,
+}
+
+// threads running the blocking std resolver; started on demand
+struct Resolver {
+	state: Mutex<ResolverState>,
+	condvar: Condvar,
+}
+
+static RESOLVER: Resolver = Resolver {
+	state: Mutex::new(ResolverState {
+		jobs: VecDeque::new(),
+		threads: 0,
+		idle: 0,
+	}),
+	condvar: Condvar::new(),
+};
+
+impl Resolver {
+	fn submit(&'static self, job: Job) {
+		let spawn = {
+			let mut state = self.state.lock().unwrap();
+			state.jobs.push_back(job);
+			let spawn = state.jobs.len() > state.idle && state.threads < MAX_RESOLVER_THREADS;
+			if spawn {
+				// counted right away, so concurrent lookups don't start too many threads
+				state.threads += 1;
+			}
+			spawn
+		};
+		self.condvar.notify_one();
+		if !spawn {
+			return;
+		}
+		let spawned = std::thread::Builder::new()
+			.name("fumio-lookup-host".into())
+			.spawn(move || self.run());
+		if let Err(e) = spawned {
+			let jobs = {
+				let mut state = self.state.lock().unwrap();
+				state.threads -= 1;
+				// running threads will handle the jobs eventually
+				if state.threads > 0 {
+					return;
+				}
+				std::mem::take(&mut state.jobs)
+			};
+			for job in jobs {
+				job.shared.complete(Err(io::Error::new(e.kind(), e.to_string())));
+			}
+		}
+	}
+
+	fn next_job(&self) -> Option<Job> {
+		let mut state = self.state.lock().unwrap();
+		loop {
+			if let Some(job) = state.jobs.pop_front() {
+				drop(state);
+				return Some(job);
+			}
+			state.idle += 1;
+			let (new_state, timeout) = self.condvar.wait_timeout(state, RESOLVER_IDLE_TIMEOUT).unwrap();
+			state = new_state;
+			state.idle -= 1;
+			if timeout.timed_out() && state.jobs.is_empty() {
+				state.threads -= 1;
+				drop(state);
+				return None;
+			}
+		}
+	}
+
+	fn run(&self) {
+		while let Some(job) = self.next_job() {
+			job.shared.complete(job.target.resolve());
+		}
+	}
+}
+
+/// Resolve host name (and port) in a background thread.
+///
+/// Accepts the same targets as [`std::net::ToSocketAddrs`] (see
+/// [`ToSocketAddrs`](trait.ToSocketAddrs.html)), like `"example.com:443"`,
+/// `("example.com", 443)` or `&String`; the blocking resolver runs in a small pool of shared
+/// helper threads (at most 4, further lookups wait for a free thread), so the reactor thread isn't
+/// blocked.  Socket addresses (and IP address / port pairs) complete right away without a helper
+/// thread.
+pub fn lookup_host<A: ToSocketAddrs>(host: A) -> LookupHost {
+	let shared = Arc::new(Shared::default());
+	match host.to_target() {
+		Target::Addrs(addrs) => shared.complete(Ok(addrs)),
+		target => RESOLVER.submit(Job { target, shared: shared.clone() }),
+	}
+	LookupHost { shared }
+}
+
+/// Pending [`lookup_host`](fn.lookup_host.html) operation
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct LookupHost {
+	shared: Arc<Shared>,
+}
+
+impl Future for LookupHost {
+	type Output = io::Result<std::vec::IntoIter<SocketAddr>>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		self.shared.waker.register(cx.waker());
+		let result = self.shared.result.lock().unwrap().take();
+		result.map_or(Poll::Pending, |result| Poll::Ready(result.map(Vec::into_iter)))
+	}
+}
@@ -0,0 +1,163 @@
+use crate::helper::async_io;
+use crate::reactor::{FdSource, LazyHandle, PollEvented};
+use socket2::{Domain, Protocol, SockAddr, Socket, Type};
+use std::future::Future;
+use std::io;
+use std::mem::MaybeUninit;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+fn to_socket_addr(addr: &SockAddr) -> io::Result<SocketAddr> {
+	addr.as_socket().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "unsupported address family"))
+}
+
+/// A raw (or ICMP) datagram socket
+///
+/// Requires the `raw-socket` feature.  Raw sockets usually need elevated privileges
+/// (`CAP_NET_RAW` on linux); on linux [`icmp_v4`](#method.icmp_v4) and
+/// [`icmp_v6`](#method.icmp_v6) use unprivileged ICMP sockets instead (if allowed by
+/// `net.ipv4.ping_group_range`), where the kernel manages the ICMP identifier.
+#[derive(Debug)]
+#[must_use = "A raw socket does nothing if not actually used"]
+pub struct RawSocket {
+	socket: PollEvented<FdSource<Socket>>,
+}
+
+impl RawSocket {
+	/// Create a new socket of type `ty` (usually `Type::RAW`)
+	pub fn new(domain: Domain, ty: Type, protocol: Option<Protocol>) -> io::Result<Self> {
+		Self::new_with(domain, ty, protocol, LazyHandle::new())
+	}
+
+	/// Create a new socket of type `ty` (usually `Type::RAW`)
+	pub fn new_with(domain: Domain, ty: Type, protocol: Option<Protocol>, handle: LazyHandle) -> io::Result<Self> {
+		Self::from_socket(Socket::new(domain, ty, protocol)?, handle)
+	}
+
+	/// Create an ICMP socket for IPv4
+	///
+	/// Received packets include the IPv4 header, unless it is an unprivileged ICMP socket (linux).
+	pub fn icmp_v4() -> io::Result<Self> {
+		Self::new(Domain::IPV4, Self::icmp_type(), Some(Protocol::ICMPV4))
+	}
+
+	/// Create an `ICMPv6` socket
+	pub fn icmp_v6() -> io::Result<Self> {
+		Self::new(Domain::IPV6, Self::icmp_type(), Some(Protocol::ICMPV6))
+	}
+
+	#[cfg(target_os = "linux")]
+	fn icmp_type() -> Type {
+		Type::DGRAM
+	}
+
+	#[cfg(not(target_os = "linux"))]
+	fn icmp_type() -> Type {
+		Type::RAW
+	}
+
+	/// Wraps a socket (configured with `socket2`)
+	pub fn from_socket(socket: Socket, handle: LazyHandle) -> io::Result<Self> {
+		socket.set_nonblocking(true)?;
+		Ok(Self {
+			socket: PollEvented::new(FdSource(socket), handle),
+		})
+	}
+
+	/// Access the socket to query or modify options
+	pub fn get_ref(&self) -> &Socket {
+		&self.socket.io_ref().0
+	}
+
+	/// Deregisters from the reactor and returns the socket (still in non-blocking mode).
+	pub fn into_inner(self) -> Socket {
+		self.socket.into_inner().0
+	}
+
+	/// Binds the socket to a local address (e.g. to select the source address)
+	pub fn bind(&self, local: SocketAddr) -> io::Result<()> {
+		self.get_ref().bind(&local.into())
+	}
+
+	/// Returns the local socket address of this socket.
+	pub fn local_addr(&self) -> io::Result<SocketAddr> {
+		to_socket_addr(&self.get_ref().local_addr()?)
+	}
+
+	/// Receives a packet from the socket. On success, returns the number of bytes read and the
+	/// address from whence the packet came.
+	pub fn poll_recv_from(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<(usize, SocketAddr)>> {
+		// `recv_from` only writes initialized bytes
+		let buf = unsafe { &mut *(std::ptr::from_mut(buf) as *mut [MaybeUninit<u8>]) };
+		self.socket.try_mut_read(cx, |io| {
+			async_io(|| {
+				let (n, addr) = io.0.recv_from(buf)?;
+				Ok((n, to_socket_addr(&addr)?))
+			})
+		})
+	}
+
+	/// Receives a packet from the socket. On success, completes with the number of bytes read and
+	/// the address from whence the packet came.
+	pub fn recv_from<'a>(&'a mut self, buf: &'a mut [u8]) -> RawRecvFrom<'a> {
+		RawRecvFrom {
+			socket: self,
+			buf,
+		}
+	}
+
+	/// Sends a packet on the socket to the given address. On success, returns the number of bytes
+	/// written.
+	pub fn poll_send_to(&mut self, cx: &mut Context<'_>, buf: &[u8], target: &SocketAddr) -> Poll<io::Result<usize>> {
+		let target = SockAddr::from(*target);
+		self.socket.try_mut_write(cx, |io| {
+			async_io(|| io.0.send_to(buf, &target))
+		})
+	}
+
+	/// Sends a packet on the socket to the given address. On success, completes with the number
+	/// of bytes written.
+	pub fn send_to<'a>(&'a mut self, buf: &'a [u8], target: &'a SocketAddr) -> RawSendTo<'a> {
+		RawSendTo {
+			socket: self,
+			buf,
+			target,
+		}
+	}
+}
+
+/// Pending `recv_from` operation
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct RawRecvFrom<'a> {
+	socket: &'a mut RawSocket,
+	buf: &'a mut [u8],
+}
+
+impl Future for RawRecvFrom<'_> {
+	type Output = io::Result<(usize, SocketAddr)>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.get_mut();
+		this.socket.poll_recv_from(cx, this.buf)
+	}
+}
+
+/// Pending `send_to` operation
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct RawSendTo<'a> {
+	socket: &'a mut RawSocket,
+	buf: &'a [u8],
+	target: &'a SocketAddr,
+}
+
+impl Future for RawSendTo<'_> {
+	type Output = io::Result<usize>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.get_mut();
+		this.socket.poll_send_to(cx, this.buf, this.target)
+	}
+}
@@ -0,0 +1,58 @@
+// `IPV6_TCLASS` socket option; not supported by socket2
+
+use std::io;
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)] // size of c_int; traffic class fits
+pub(super) fn set_tclass_v6<S>(socket: &S, tclass: u32) -> io::Result<()>
+where
+	S: std::os::unix::io::AsRawFd,
+{
+	let value = tclass as libc::c_int;
+	let r = unsafe {
+		libc::setsockopt(
+			socket.as_raw_fd(),
+			libc::IPPROTO_IPV6,
+			libc::IPV6_TCLASS,
+			std::ptr::addr_of!(value).cast(),
+			std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+		)
+	};
+	if r != 0 {
+		return Err(io::Error::last_os_error());
+	}
+	Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // size of c_int; traffic class is not negative
+pub(super) fn tclass_v6<S>(socket: &S) -> io::Result<u32>
+where
+	S: std::os::unix::io::AsRawFd,
+{
+	let mut value: libc::c_int = 0;
+	let mut len = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+	let r = unsafe {
+		libc::getsockopt(
+			socket.as_raw_fd(),
+			libc::IPPROTO_IPV6,
+			libc::IPV6_TCLASS,
+			std::ptr::addr_of_mut!(value).cast(),
+			std::ptr::addr_of_mut!(len),
+		)
+	};
+	if r != 0 {
+		return Err(io::Error::last_os_error());
+	}
+	Ok(value as u32)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd")))]
+pub(super) fn set_tclass_v6<S>(_socket: &S, _tclass: u32) -> io::Result<()> {
+	Err(io::Error::other("IPV6_TCLASS not supported on this platform"))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd")))]
+pub(super) fn tclass_v6<S>(_socket: &S) -> io::Result<u32> {
+	Err(io::Error::other("IPV6_TCLASS not supported on this platform"))
+}
@@ -1,10 +1,14 @@
-use crate::net::TcpStream;
+use crate::net::{LookupHost, TcpStream};
+use crate::reactor::LazyHandle;
 use std::future::Future;
 use std::io;
+use std::net::SocketAddr;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 
-/// A future completing when a stream is ready to use (or failed).
+/// A future completing when a stream connecting to a single address is ready to use (or failed).
+///
+/// See [`TcpStream::connect_addr`](struct.TcpStream.html#method.connect_addr).
 #[must_use = "futures do nothing unless you `.await` or poll them"]
 #[derive(Debug)]
 pub struct TcpConnectFuture {
@@ -17,17 +21,156 @@ impl TcpConnectFuture {
 			stream: Some(stream),
 		}
 	}
+
+	/// Fail with `TimedOut` (and abort the connection attempt) if `deadline` completes before the
+	/// connection is established.
+	///
+	/// See [`TcpConnect::with_deadline`](struct.TcpConnect.html#method.with_deadline).
+	pub fn with_deadline<D>(self, deadline: D) -> TcpConnectDeadline<D>
+	where
+		D: Future<Output = ()> + Unpin,
+	{
+		TcpConnect::from_future(self).with_deadline(deadline)
+	}
 }
 
 impl Future for TcpConnectFuture {
 	type Output = io::Result<TcpStream>;
 
 	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-		futures_core::ready!(self.stream.as_mut().expect("can't poll TcpConnectFuture twice").mio_stream.poll_write_ready(cx))?;
-		let stream = self.stream.take().unwrap();
-		if let Some(e) = stream.mio_stream.io_ref().take_error()? {
-			return Poll::Ready(Err(e));
+		let stream = self.stream.as_mut().expect("can't poll TcpConnectFuture twice");
+		loop {
+			futures_core::ready!(stream.mio_stream.poll_write_ready(cx))?;
+			if let Some(e) = stream.mio_stream.io_ref().take_error()? {
+				return Poll::Ready(Err(e));
+			}
+			match stream.mio_stream.io_ref().peer_addr() {
+				Ok(_) => break,
+				// not connected yet; wait for next write event
+				Err(ref e) if e.kind() == io::ErrorKind::NotConnected => (),
+				Err(e) => return Poll::Ready(Err(e)),
+			}
+		}
+		Poll::Ready(Ok(self.stream.take().unwrap()))
+	}
+}
+
+/// A [`TcpConnect`](struct.TcpConnect.html) (or [`TcpConnectFuture`](struct.TcpConnectFuture.html))
+/// with a deadline.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct TcpConnectDeadline<D> {
+	connect: TcpConnect,
+	deadline: D,
+}
+
+impl<D> Future for TcpConnectDeadline<D>
+where
+	D: Future<Output = ()> + Unpin,
+{
+	type Output = io::Result<TcpStream>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		if let Poll::Ready(r) = Pin::new(&mut self.connect).poll(cx) {
+			return Poll::Ready(r);
+		}
+		futures_core::ready!(Pin::new(&mut self.deadline).poll(cx));
+		// closing the socket aborts the connection attempt
+		self.connect.abort();
+		Poll::Ready(Err(io::Error::new(io::ErrorKind::TimedOut, "connect timed out")))
+	}
+}
+
+/// A future resolving the target and connecting to the first address accepting the connection.
+///
+/// See [`TcpStream::connect`](struct.TcpStream.html#method.connect).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct TcpConnect {
+	lookup: Option<LookupHost>,
+	addrs: std::vec::IntoIter<SocketAddr>,
+	connect: Option<TcpConnectFuture>,
+	handle: LazyHandle,
+	last_error: Option<io::Error>,
+}
+
+impl TcpConnect {
+	pub(super) fn new(lookup: LookupHost, handle: LazyHandle) -> Self {
+		Self {
+			lookup: Some(lookup),
+			addrs: Vec::new().into_iter(),
+			connect: None,
+			handle,
+			last_error: None,
+		}
+	}
+
+	fn from_future(connect: TcpConnectFuture) -> Self {
+		Self {
+			lookup: None,
+			addrs: Vec::new().into_iter(),
+			connect: Some(connect),
+			handle: LazyHandle::new(),
+			last_error: None,
+		}
+	}
+
+	// stop resolving and close the socket of the current attempt
+	fn abort(&mut self) {
+		self.lookup = None;
+		self.addrs = Vec::new().into_iter();
+		self.connect = None;
+	}
+
+	/// Fail with `TimedOut` (and abort the connection attempt) if `deadline` completes before the
+	/// connection is established.
+	///
+	/// The deadline covers resolving the target and all connection attempts.  The reactor
+	/// doesn't provide timers itself; use for example a `Delay` from the runtime:
+	///
+	/// ```ignore
+	/// let timer = fumio::current().unwrap().timer();
+	/// let deadline = timer.delay(Instant::now() + Duration::from_secs(5));
+	/// let stream = TcpStream::connect(addr).with_deadline(deadline).await?;
+	/// ```
+	pub fn with_deadline<D>(self, deadline: D) -> TcpConnectDeadline<D>
+	where
+		D: Future<Output = ()> + Unpin,
+	{
+		TcpConnectDeadline {
+			connect: self,
+			deadline,
+		}
+	}
+}
+
+impl Future for TcpConnect {
+	type Output = io::Result<TcpStream>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.get_mut();
+		if let Some(lookup) = &mut this.lookup {
+			this.addrs = futures_core::ready!(Pin::new(lookup).poll(cx))?;
+			this.lookup = None;
+		}
+		loop {
+			if let Some(connect) = &mut this.connect {
+				match futures_core::ready!(Pin::new(connect).poll(cx)) {
+					Ok(stream) => return Poll::Ready(Ok(stream)),
+					Err(e) => this.last_error = Some(e),
+				}
+				this.connect = None;
+			}
+			// try next address
+			let Some(addr) = this.addrs.next() else {
+				return Poll::Ready(Err(this.last_error.take().unwrap_or_else(|| {
+					io::Error::new(io::ErrorKind::InvalidInput, "could not resolve to any address")
+				})));
+			};
+			match TcpStream::connect_addr(addr, this.handle.clone()) {
+				Ok(connect) => this.connect = Some(connect),
+				Err(e) => this.last_error = Some(e),
+			}
 		}
-		Poll::Ready(Ok(stream))
 	}
 }
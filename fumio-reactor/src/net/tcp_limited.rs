@@ -0,0 +1,91 @@
+use crate::net::{TcpListener, TcpStream};
+use crate::sync::AtomicWaker;
+use crate::sync::atomic::{AtomicUsize, Ordering};
+use futures_core::Stream;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+#[derive(Debug, Default)]
+struct Limit {
+	outstanding: AtomicUsize,
+	// waiting `TcpIncomingLimited`
+	waker: AtomicWaker,
+}
+
+/// Stream of incoming connections, accepting only while less than `max` connections are
+/// outstanding.
+///
+/// Can also be polled as single future to get the next connection, as the stream never ends.
+/// Each connection comes with a [`ConnectionPermit`](struct.ConnectionPermit.html); the
+/// connection counts as outstanding until the permit is dropped.
+#[must_use = "futures and streams do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct TcpIncomingLimited<'a> {
+	listener: &'a mut TcpListener,
+	max: usize,
+	limit: Arc<Limit>,
+}
+
+impl<'a> TcpIncomingLimited<'a> {
+	pub(super) fn new(listener: &'a mut TcpListener, max: usize) -> Self {
+		Self {
+			listener,
+			max,
+			limit: Arc::new(Limit::default()),
+		}
+	}
+
+	/// Number of currently outstanding connections (permits not dropped yet).
+	pub fn outstanding(&self) -> usize {
+		self.limit.outstanding.load(Ordering::Acquire)
+	}
+
+	fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<(TcpStream, SocketAddr, ConnectionPermit)>> {
+		if self.outstanding() >= self.max {
+			self.limit.waker.register(cx.waker());
+			// check again: a permit might have been dropped before the waker was registered
+			if self.outstanding() >= self.max {
+				return Poll::Pending;
+			}
+		}
+		let (stream, addr) = futures_core::ready!(self.listener.poll_accept(cx))?;
+		self.limit.outstanding.fetch_add(1, Ordering::AcqRel);
+		let permit = ConnectionPermit { limit: self.limit.clone() };
+		Poll::Ready(Ok((stream, addr, permit)))
+	}
+}
+
+impl Future for TcpIncomingLimited<'_> {
+	type Output = io::Result<(TcpStream, SocketAddr, ConnectionPermit)>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		self.poll_accept(cx)
+	}
+}
+
+impl Stream for TcpIncomingLimited<'_> {
+	type Item = io::Result<(TcpStream, SocketAddr, ConnectionPermit)>;
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		self.poll_accept(cx).map(Some)
+	}
+}
+
+/// Marks a connection accepted by [`TcpIncomingLimited`](struct.TcpIncomingLimited.html) as
+/// outstanding; drop it when the connection is done.
+#[derive(Debug)]
+#[must_use = "dropping the permit releases the connection slot"]
+pub struct ConnectionPermit {
+	limit: Arc<Limit>,
+}
+
+impl Drop for ConnectionPermit {
+	fn drop(&mut self) {
+		self.limit.outstanding.fetch_sub(1, Ordering::AcqRel);
+		self.limit.waker.wake();
+	}
+}
@@ -1,11 +1,13 @@
-use crate::helper::async_io;
-use crate::net::TcpStream;
+use crate::helper::{async_io, convert_raw, with_raw_as};
+use crate::net::keepalive::set_keepalive;
+use crate::net::{KeepAlive, TcpIncomingLimited, TcpStream};
 use crate::reactor::{LazyHandle, PollEvented};
 use futures_core::Stream;
 use std::future::Future;
 use std::io;
 use std::net::SocketAddr;
 use std::pin::Pin;
+use std::time::Duration;
 use std::task::{Context, Poll};
 
 /// A TCP listening socket.
@@ -16,29 +18,33 @@ pub struct TcpListener {
 }
 
 impl TcpListener {
-	/// Create builder with default options, but doesn't bind yet.
-	///
-	/// To create a `TcpListener` from a builder go through the `std::net::TcpListener` created by
-	/// `builder.listen(...)?`.
-	pub fn default_builder_for(local: &SocketAddr) -> io::Result<net2::TcpBuilder> {
-		let builder;
-		match local {
-			SocketAddr::V4(_) => {
-				builder = net2::TcpBuilder::new_v4()?;
-			}
-			SocketAddr::V6(a) => {
-				builder = net2::TcpBuilder::new_v6()?;
-				if a.ip().is_unspecified() {
-					// always try to disable only_v6
-					let _ = builder.only_v6(false);
-				}
+	/// Create socket with default options, but doesn't bind yet.
+	///
+	/// To create a `TcpListener` from the socket bind it, start listening and convert it into a
+	/// `std::net::TcpListener` (`socket.into()`) for [`from_std`](#method.from_std).
+	pub fn default_builder_for(local: &SocketAddr) -> io::Result<socket2::Socket> {
+		let socket = socket2::Socket::new(socket2::Domain::for_address(*local), socket2::Type::STREAM, Some(socket2::Protocol::TCP))?;
+		if let SocketAddr::V6(a) = local {
+			if a.ip().is_unspecified() {
+				// always try to disable only_v6
+				let _ = socket.set_only_v6(false);
 			}
 		}
-		builder.reuse_address(true)?;
-		Ok(builder)
+		socket.set_reuse_address(true)?;
+		Ok(socket)
+	}
+
+	/// Like [`default_builder_for`](#method.default_builder_for), but also configures keepalive
+	/// (`None` disables it).
+	///
+	/// Accepted connections inherit the keepalive settings on most platforms.
+	pub fn default_builder_with_keepalive(local: &SocketAddr, keepalive: Option<KeepAlive>) -> io::Result<socket2::Socket> {
+		let socket = Self::default_builder_for(local)?;
+		set_keepalive(&socket, keepalive)?;
+		Ok(socket)
 	}
 
-	/// Binds a new listener to IPv6 `[::]` with V6_ONLY=false (i.e. also listen on IPv4) to the
+	/// Binds a new listener to IPv6 `[::]` with `V6_ONLY=false` (i.e. also listen on IPv4) to the
 	/// given port.
 	///
 	/// If the port is 0 the OS will select a random port.
@@ -66,15 +72,39 @@ impl TcpListener {
 	/// Uses `default_builder_for(addr)` to construct a builder, binds the address and listens with
 	/// a backlog of up to 1024 connections.
 	pub fn bind_with(local: SocketAddr, handle: LazyHandle) -> io::Result<Self> {
-		let builder = Self::default_builder_for(&local)?;
-		builder.bind(&local)?;
-		Self::from_std(builder.listen(1024)?, handle)
+		Self::bind_with_backlog(local, 1024, handle)
+	}
+
+	/// Bind a new listener to the specified address
+	///
+	/// Uses `default_builder_for(addr)` to construct a builder, binds the address and listens with
+	/// a backlog of up to `backlog` connections (the OS might limit it further).
+	pub fn bind_with_backlog(local: SocketAddr, backlog: i32, handle: LazyHandle) -> io::Result<Self> {
+		let socket = Self::default_builder_for(&local)?;
+		socket.bind(&local.into())?;
+		socket.listen(backlog)?;
+		Self::from_std(socket.into(), handle)
+	}
+
+	/// Bind a new listener with `SO_REUSEPORT` to the specified address
+	///
+	/// Multiple listeners (e.g. one per thread) can bind the same address this way; the OS
+	/// distributes incoming connections between them.  Listens with a backlog of up to 1024
+	/// connections.
+	#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+	pub fn bind_reuse_port(local: SocketAddr, handle: LazyHandle) -> io::Result<Self> {
+		let socket = Self::default_builder_for(&local)?;
+		socket.set_reuse_port(true)?;
+		socket.bind(&local.into())?;
+		socket.listen(1024)?;
+		Self::from_std(socket.into(), handle)
 	}
 
 	/// Wraps a `std` listener
 	pub fn from_std(listener: std::net::TcpListener, handle: LazyHandle) -> io::Result<Self> {
+		listener.set_nonblocking(true)?;
 		Ok(Self {
-			mio_listener: PollEvented::new(mio::net::TcpListener::from_std(listener)?, handle),
+			mio_listener: PollEvented::new(mio::net::TcpListener::from_std(listener), handle),
 		})
 	}
 
@@ -85,11 +115,88 @@ impl TcpListener {
 		})
 	}
 
+	/// Deregisters from the reactor and returns the `std` listener.
+	///
+	/// The socket stays in non-blocking mode if `nonblocking` is true, otherwise it is switched
+	/// back to blocking mode.
+	pub fn into_std(self, nonblocking: bool) -> io::Result<std::net::TcpListener> {
+		let socket: std::net::TcpListener = convert_raw(self.mio_listener.into_inner());
+		socket.set_nonblocking(nonblocking)?;
+		Ok(socket)
+	}
+
 	/// Returns the local socket address of this listener.
 	pub fn local_addr(&self) -> io::Result<SocketAddr> {
 		self.mio_listener.io_ref().local_addr()
 	}
 
+	/// Sets the value of the `SO_RCVBUF` option on this socket.
+	///
+	/// The OS might adjust (e.g. double) the value.
+	pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+		with_raw_as(self.mio_listener.io_ref(), |s: &socket2::Socket| s.set_recv_buffer_size(size))
+	}
+
+	/// Gets the value of the `SO_RCVBUF` option on this socket.
+	pub fn recv_buffer_size(&self) -> io::Result<usize> {
+		with_raw_as(self.mio_listener.io_ref(), socket2::Socket::recv_buffer_size)
+	}
+
+	/// Sets the value of the `SO_SNDBUF` option on this socket.
+	///
+	/// The OS might adjust (e.g. double) the value.
+	pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+		with_raw_as(self.mio_listener.io_ref(), |s: &socket2::Socket| s.set_send_buffer_size(size))
+	}
+
+	/// Gets the value of the `SO_SNDBUF` option on this socket.
+	pub fn send_buffer_size(&self) -> io::Result<usize> {
+		with_raw_as(self.mio_listener.io_ref(), socket2::Socket::send_buffer_size)
+	}
+
+	/// Sets the value of the `SO_LINGER` option on this socket.
+	///
+	/// With `Some(Duration::from_secs(0))` closing the socket drops unsent data and resets the
+	/// connection (RST) instead of a graceful shutdown; `None` disables lingering.
+	///
+	/// Accepted connections inherit the setting on most platforms.
+	pub fn set_linger(&self, linger: Option<Duration>) -> io::Result<()> {
+		with_raw_as(self.mio_listener.io_ref(), |s: &socket2::Socket| s.set_linger(linger))
+	}
+
+	/// Gets the value of the `SO_LINGER` option on this socket.
+	pub fn linger(&self) -> io::Result<Option<Duration>> {
+		with_raw_as(self.mio_listener.io_ref(), socket2::Socket::linger)
+	}
+
+	/// Sets the value of the `IP_TOS` option on this socket (IPv4 only).
+	#[cfg(not(any(target_os = "fuchsia", target_os = "redox", target_os = "solaris", target_os = "illumos")))]
+	pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+		with_raw_as(self.mio_listener.io_ref(), |s: &socket2::Socket| s.set_tos(tos))
+	}
+
+	/// Gets the value of the `IP_TOS` option on this socket.
+	#[cfg(not(any(target_os = "fuchsia", target_os = "redox", target_os = "solaris", target_os = "illumos")))]
+	pub fn tos(&self) -> io::Result<u32> {
+		with_raw_as(self.mio_listener.io_ref(), socket2::Socket::tos)
+	}
+
+	/// Sets the value of the `SO_REUSEPORT` option on this socket.
+	///
+	/// Only useful before binding, i.e. usually set on the socket from
+	/// [`default_builder_for`](#method.default_builder_for) instead; allows multiple listeners to
+	/// bind the same address.
+	#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+	pub fn set_reuse_port(&self, reuse: bool) -> io::Result<()> {
+		with_raw_as(self.mio_listener.io_ref(), |s: &socket2::Socket| s.set_reuse_port(reuse))
+	}
+
+	/// Gets the value of the `SO_REUSEPORT` option on this socket.
+	#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+	pub fn reuse_port(&self) -> io::Result<bool> {
+		with_raw_as(self.mio_listener.io_ref(), socket2::Socket::reuse_port)
+	}
+
 	/// Creates a new independently owned handle to the underlying socket.
 	///
 	/// The new listener isn't registered to a reactor yet.
@@ -99,14 +206,21 @@ impl TcpListener {
 
 	/// Creates a new independently owned handle to the underlying socket.
 	pub fn try_clone_with(&self, handle: LazyHandle) -> io::Result<Self> {
+		let listener = with_raw_as(self.mio_listener.io_ref(), std::net::TcpListener::try_clone)?;
 		Ok(Self {
-			mio_listener: PollEvented::new(self.mio_listener.io_ref().try_clone()?, handle),
+			mio_listener: PollEvented::new(mio::net::TcpListener::from_std(listener), handle),
 		})
 	}
 
 	/// Stream of incoming `(TcpStream, SocketAddr)` connections.
 	pub fn incoming(&mut self) -> TcpIncoming<'_> {
-		TcpIncoming { listener: self }
+		TcpIncoming { listener: self, backoff: None }
+	}
+
+	/// Stream of incoming `(TcpStream, SocketAddr, ConnectionPermit)` connections; stops accepting
+	/// while `max` connections (permits) are outstanding.
+	pub fn incoming_limited(&mut self, max: usize) -> TcpIncomingLimited<'_> {
+		TcpIncomingLimited::new(self, max)
 	}
 
 	/// Stream of incoming `(std::net::TcpStream, SocketAddr)` connections.
@@ -126,7 +240,12 @@ impl TcpListener {
 	/// Accept a new `std` connection or register context.
 	pub fn poll_accept_std(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<(std::net::TcpStream, SocketAddr)>> {
 		self.mio_listener.try_mut_read(cx, |io| {
-				async_io(|| io.accept_std())
+				async_io(|| {
+					let (stream, addr) = io.accept()?;
+					let stream: std::net::TcpStream = convert_raw(stream);
+					stream.set_nonblocking(false)?;
+					Ok((stream, addr))
+				})
 		})
 	}
 }
@@ -147,19 +266,101 @@ impl std::convert::TryFrom<mio::net::TcpListener> for TcpListener {
 	}
 }
 
+/// Whether an `accept` error is caused by running out of resources (like file descriptors), i.e.
+/// the listener itself is fine and accepting should be retried later.
+pub fn is_accept_resource_error(e: &io::Error) -> bool {
+	#[cfg(unix)]
+	const CODES: &[i32] = &[libc::EMFILE, libc::ENFILE, libc::ENOBUFS, libc::ENOMEM];
+	// WSAEMFILE, WSAENOBUFS
+	#[cfg(windows)]
+	const CODES: &[i32] = &[10024, 10055];
+	#[cfg(not(any(unix, windows)))]
+	const CODES: &[i32] = &[];
+
+	e.raw_os_error().is_some_and(|code| CODES.contains(&code))
+}
+
+type SleepFn<'a> = Box<dyn FnMut(Duration) -> Pin<Box<dyn Future<Output = ()> + 'a>> + 'a>;
+
+struct Backoff<'a> {
+	initial: Duration,
+	max: Duration,
+	current: Duration,
+	sleep_fn: SleepFn<'a>,
+	sleeping: Option<Pin<Box<dyn Future<Output = ()> + 'a>>>,
+}
+
+impl std::fmt::Debug for Backoff<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("Backoff")
+			.field("initial", &self.initial)
+			.field("max", &self.max)
+			.field("current", &self.current)
+			.field("sleeping", &self.sleeping.is_some())
+			.finish()
+	}
+}
+
 /// Stream of incoming connections (can also be polled as single future to get the next connection,
 /// as the stream never ends).
 #[must_use = "futures and streams do nothing unless you `.await` or poll them"]
 #[derive(Debug)]
 pub struct TcpIncoming<'a> {
 	listener: &'a mut TcpListener,
+	backoff: Option<Backoff<'a>>,
+}
+
+impl<'a> TcpIncoming<'a> {
+	/// Back off (exponentially, from `initial` up to `max`) instead of returning errors when
+	/// accepting fails due to exhausted resources (see
+	/// [`is_accept_resource_error`](fn.is_accept_resource_error.html)).
+	///
+	/// The reactor has no timers; `sleep` has to create a future completing after the given
+	/// duration, for example a `Delay` from the runtime's timer.
+	pub fn with_backoff<S, F>(mut self, initial: Duration, max: Duration, mut sleep: S) -> Self
+	where
+		S: FnMut(Duration) -> F + 'a,
+		F: Future<Output = ()> + 'a,
+	{
+		self.backoff = Some(Backoff {
+			initial,
+			max,
+			current: initial,
+			sleep_fn: Box::new(move |duration| Box::pin(sleep(duration))),
+			sleeping: None,
+		});
+		self
+	}
+
+	fn poll_accept(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<(TcpStream, SocketAddr)>> {
+		let Some(backoff) = &mut self.backoff else {
+			return self.listener.poll_accept(cx);
+		};
+		loop {
+			if let Some(sleeping) = &mut backoff.sleeping {
+				futures_core::ready!(sleeping.as_mut().poll(cx));
+				backoff.sleeping = None;
+			}
+			match futures_core::ready!(self.listener.poll_accept(cx)) {
+				Ok(conn) => {
+					backoff.current = backoff.initial;
+					return Poll::Ready(Ok(conn));
+				},
+				Err(ref e) if is_accept_resource_error(e) => {
+					backoff.sleeping = Some((backoff.sleep_fn)(backoff.current));
+					backoff.current = std::cmp::min(backoff.current * 2, backoff.max);
+				},
+				Err(e) => return Poll::Ready(Err(e)),
+			}
+		}
+	}
 }
 
 impl Future for TcpIncoming<'_> {
 	type Output = io::Result<(TcpStream, SocketAddr)>;
 
 	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-		self.listener.poll_accept(cx)
+		self.poll_accept(cx)
 	}
 }
 
@@ -167,7 +368,7 @@ impl Stream for TcpIncoming<'_> {
 	type Item = io::Result<(TcpStream, SocketAddr)>;
 
 	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-		self.listener.poll_accept(cx).map(Some)
+		self.poll_accept(cx).map(Some)
 	}
 }
 
@@ -0,0 +1,168 @@
+use crate::helper::async_io;
+use crate::net::TcpStream;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+fn poll_read(stream: &TcpStream, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+	stream.mio_stream.try_read(cx, |mut io| {
+		async_io(|| io.read(buf))
+	})
+}
+
+fn poll_write(stream: &TcpStream, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+	stream.mio_stream.try_write(cx, |mut io| {
+		async_io(|| io.write(buf))
+	})
+}
+
+fn poll_close(stream: &TcpStream) -> Poll<io::Result<()>> {
+	// nothing to flush
+	Poll::Ready(stream.shutdown(Shutdown::Write))
+}
+
+/// Borrowed read half of a [`TcpStream`](struct.TcpStream.html), created by
+/// [`split`](struct.TcpStream.html#method.split).
+#[derive(Debug)]
+pub struct ReadHalf<'a> {
+	stream: &'a TcpStream,
+}
+
+/// Borrowed write half of a [`TcpStream`](struct.TcpStream.html), created by
+/// [`split`](struct.TcpStream.html#method.split).
+///
+/// Closing it shuts down the write direction of the connection.
+#[derive(Debug)]
+pub struct WriteHalf<'a> {
+	stream: &'a TcpStream,
+}
+
+pub(super) fn split(stream: &mut TcpStream) -> (ReadHalf<'_>, WriteHalf<'_>) {
+	stream.mio_stream.register();
+	let stream = &*stream;
+	(ReadHalf { stream }, WriteHalf { stream })
+}
+
+impl ReadHalf<'_> {
+	/// Returns the socket address of the remote peer of this connection.
+	pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+		self.stream.peer_addr()
+	}
+
+	/// Returns the local socket address of this connection.
+	pub fn local_addr(&self) -> io::Result<SocketAddr> {
+		self.stream.local_addr()
+	}
+}
+
+impl WriteHalf<'_> {
+	/// Returns the socket address of the remote peer of this connection.
+	pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+		self.stream.peer_addr()
+	}
+
+	/// Returns the local socket address of this connection.
+	pub fn local_addr(&self) -> io::Result<SocketAddr> {
+		self.stream.local_addr()
+	}
+}
+
+impl futures_io::AsyncRead for ReadHalf<'_> {
+	fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+		poll_read(self.stream, cx, buf)
+	}
+}
+
+impl futures_io::AsyncWrite for WriteHalf<'_> {
+	fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+		poll_write(self.stream, cx, buf)
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		poll_close(self.stream)
+	}
+}
+
+/// Owned read half of a [`TcpStream`](struct.TcpStream.html), created by
+/// [`into_split`](struct.TcpStream.html#method.into_split).
+#[derive(Debug)]
+pub struct OwnedReadHalf {
+	stream: Arc<TcpStream>,
+}
+
+/// Owned write half of a [`TcpStream`](struct.TcpStream.html), created by
+/// [`into_split`](struct.TcpStream.html#method.into_split).
+///
+/// Closing it shuts down the write direction of the connection.
+#[derive(Debug)]
+pub struct OwnedWriteHalf {
+	stream: Arc<TcpStream>,
+}
+
+pub(super) fn into_split(mut stream: TcpStream) -> (OwnedReadHalf, OwnedWriteHalf) {
+	stream.mio_stream.register();
+	let stream = Arc::new(stream);
+	(OwnedReadHalf { stream: stream.clone() }, OwnedWriteHalf { stream })
+}
+
+impl OwnedReadHalf {
+	/// Returns the socket address of the remote peer of this connection.
+	pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+		self.stream.peer_addr()
+	}
+
+	/// Returns the local socket address of this connection.
+	pub fn local_addr(&self) -> io::Result<SocketAddr> {
+		self.stream.local_addr()
+	}
+
+	/// Put the stream back together.
+	///
+	/// Fails (returning both halves) if the halves came from different streams.
+	#[allow(clippy::missing_panics_doc)] // only the two halves own the stream
+	pub fn reunite(self, other: OwnedWriteHalf) -> Result<TcpStream, (Self, OwnedWriteHalf)> {
+		if !Arc::ptr_eq(&self.stream, &other.stream) {
+			return Err((self, other));
+		}
+		drop(other);
+		Ok(Arc::try_unwrap(self.stream).expect("only the two halves own the stream"))
+	}
+}
+
+impl OwnedWriteHalf {
+	/// Returns the socket address of the remote peer of this connection.
+	pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+		self.stream.peer_addr()
+	}
+
+	/// Returns the local socket address of this connection.
+	pub fn local_addr(&self) -> io::Result<SocketAddr> {
+		self.stream.local_addr()
+	}
+}
+
+impl futures_io::AsyncRead for OwnedReadHalf {
+	fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+		poll_read(&self.stream, cx, buf)
+	}
+}
+
+impl futures_io::AsyncWrite for OwnedWriteHalf {
+	fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+		poll_write(&self.stream, cx, buf)
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		poll_close(&self.stream)
+	}
+}
@@ -1,10 +1,16 @@
-use crate::net::TcpConnectFuture;
-use crate::reactor::{LazyHandle, PollEvented};
+use crate::helper::{async_io, convert_raw, with_raw_as};
+use crate::net::keepalive::set_keepalive;
+use crate::net::tclass;
+use crate::net::{lookup_host, KeepAlive, TcpConnect, TcpConnectFuture, ToSocketAddrs};
+use crate::net::{ReadHalf, WriteHalf, OwnedReadHalf, OwnedWriteHalf};
+use crate::reactor::{CloseOffloader, LazyHandle, PollEvented};
 use mio::net::TcpStream as MioTcpStream;
+use std::future::Future;
 use std::io;
 use std::net::{Shutdown, SocketAddr};
 use std::pin::Pin;
 use std::task::{Context, Poll};
+use std::time::Duration;
 
 /// A TCP connection
 #[derive(Debug)]
@@ -16,8 +22,9 @@ pub struct TcpStream {
 impl TcpStream {
 	/// Wraps an already connected tcp stream
 	pub fn from_std(stream: std::net::TcpStream, handle: LazyHandle) -> io::Result<Self> {
+		stream.set_nonblocking(true)?;
 		Ok(Self {
-			mio_stream: PollEvented::new(MioTcpStream::from_stream(stream)?, handle),
+			mio_stream: PollEvented::new(MioTcpStream::from_std(stream), handle),
 		})
 	}
 
@@ -28,37 +35,225 @@ impl TcpStream {
 		})
 	}
 
+	/// Deregisters from the reactor and returns the `std` stream.
+	///
+	/// The socket stays in non-blocking mode if `nonblocking` is true, otherwise it is switched
+	/// back to blocking mode.
+	pub fn into_std(self, nonblocking: bool) -> io::Result<std::net::TcpStream> {
+		let socket: std::net::TcpStream = convert_raw(self.mio_stream.into_inner());
+		socket.set_nonblocking(nonblocking)?;
+		Ok(socket)
+	}
+
+	/// Close the socket in the helper thread of `offloader` when dropped (`None`: close right
+	/// away, the default).
+	///
+	/// Useful if closing can block or take a while (e.g. with [`set_linger`](#method.set_linger)
+	/// or large kernel buffers), so the reactor thread isn't stalled.
+	pub fn set_close_offloader(&mut self, offloader: Option<CloseOffloader>) {
+		self.mio_stream.set_close_offloader(offloader);
+	}
+
 	/// Create a new TCP connection to the given target.
-	pub fn connect(target: SocketAddr) -> io::Result<TcpConnectFuture> {
+	///
+	/// Accepts everything [`std::net::ToSocketAddrs`] does (see
+	/// [`ToSocketAddrs`](trait.ToSocketAddrs.html)); host names are resolved with
+	/// [`lookup_host`](fn.lookup_host.html) (not blocking the reactor thread), and the resolved
+	/// addresses are tried in order until one accepts the connection.
+	///
+	/// Errors (including creating the socket) are returned by the future; this used to return
+	/// `io::Result<TcpConnectFuture>`, which is what [`connect_addr`](#method.connect_addr) does
+	/// now.
+	pub fn connect<A: ToSocketAddrs>(target: A) -> TcpConnect {
 		Self::connect_with(target, LazyHandle::new())
 	}
 
 	/// Create a new TCP connection to the given target.
-	pub fn connect_with(target: SocketAddr, handle: LazyHandle) -> io::Result<TcpConnectFuture> {
-		let builder;
-		match target {
-			SocketAddr::V4(_) => {
-				builder = net2::TcpBuilder::new_v4()?;
-				#[cfg(windows)]
-				builder.bind(std::net::SocketAddrV4::new(std::net::Ipv4Addr::UNSPECIFIED, 0))?;
-			}
-			SocketAddr::V6(_) => {
-				builder = net2::TcpBuilder::new_v6()?;
-				#[cfg(windows)]
-				builder.bind(std::net::SocketAddrV6::new(std::net::Ipv6Addr::UNSPECIFIED, 0, 0, 0))?;
-			},
-		};
-		Self::connect_builder(builder, target, handle)
+	///
+	/// See [`connect`](#method.connect).
+	pub fn connect_with<A: ToSocketAddrs>(target: A, handle: LazyHandle) -> TcpConnect {
+		TcpConnect::new(lookup_host(target), handle)
 	}
 
-	/// Create a new TCP connection to the given target using a prepared socket.
-	#[allow(clippy::needless_pass_by_value)] // builders should actually be consumed, even if net2 screwed this up
-	pub fn connect_builder(builder: net2::TcpBuilder, target: SocketAddr, handle: LazyHandle) -> io::Result<TcpConnectFuture> {
+	/// Start a new TCP connection to a single address.
+	///
+	/// Unlike [`connect`](#method.connect) this creates the socket and starts connecting right
+	/// away, returning errors from that synchronously.
+	pub fn connect_addr(target: SocketAddr, handle: LazyHandle) -> io::Result<TcpConnectFuture> {
+		let socket = socket2::Socket::new(socket2::Domain::for_address(target), socket2::Type::STREAM, Some(socket2::Protocol::TCP))?;
+		#[cfg(windows)]
+		{
+			let unspecified: SocketAddr = match target {
+				SocketAddr::V4(_) => std::net::SocketAddrV4::new(std::net::Ipv4Addr::UNSPECIFIED, 0).into(),
+				SocketAddr::V6(_) => std::net::SocketAddrV6::new(std::net::Ipv6Addr::UNSPECIFIED, 0, 0, 0).into(),
+			};
+			socket.bind(&unspecified.into())?;
+		}
+		Self::connect_builder(socket, target, handle)
+	}
+
+	/// Create a new TCP connection to the given target using a prepared (not connected yet)
+	/// socket.
+	pub fn connect_builder(socket: socket2::Socket, target: SocketAddr, handle: LazyHandle) -> io::Result<TcpConnectFuture> {
+		socket.set_nonblocking(true)?;
+		let socket: mio::net::TcpSocket = convert_raw(socket);
 		let stream = Self {
-			mio_stream: PollEvented::new(MioTcpStream::connect_stream(builder.to_tcp_stream()?, &target)?, handle),
+			mio_stream: PollEvented::new(socket.connect(target)?, handle),
 		};
 		Ok(TcpConnectFuture::new(stream))
 	}
+
+	/// Receives data without removing it from the queue. On success, returns the number of bytes
+	/// peeked.
+	pub fn poll_peek(&mut self, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+		self.mio_stream.try_mut_read(cx, |io| {
+			async_io(|| io.peek(buf))
+		})
+	}
+
+	/// Receives data without removing it from the queue. On success, completes with the number of
+	/// bytes peeked.
+	pub fn peek<'a>(&'a mut self, buf: &'a mut [u8]) -> TcpPeek<'a> {
+		TcpPeek {
+			stream: self,
+			buf,
+		}
+	}
+
+	/// Split into borrowed read and write halves, which can be used from different tasks.
+	///
+	/// With `futures::io::AsyncReadExt` in scope `stream.split()` resolves to its (generic)
+	/// `split`; use `TcpStream::split(&mut stream)` instead.
+	///
+	/// Binds the stream to the reactor if that didn't happen yet; if that fails (e.g. no reactor
+	/// is present) the halves return errors.
+	pub fn split(&mut self) -> (ReadHalf<'_>, WriteHalf<'_>) {
+		super::tcp_split::split(self)
+	}
+
+	/// Split into owned read and write halves, which can be used from different tasks.
+	///
+	/// Binds the stream to the reactor if that didn't happen yet; if that fails (e.g. no reactor
+	/// is present) the halves return errors.
+	pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+		super::tcp_split::into_split(self)
+	}
+
+	/// Returns the socket address of the remote peer of this connection.
+	pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+		self.mio_stream.io_ref().peer_addr()
+	}
+
+	/// Returns the local socket address of this connection.
+	pub fn local_addr(&self) -> io::Result<SocketAddr> {
+		self.mio_stream.io_ref().local_addr()
+	}
+
+	/// Creates a new independently owned handle to the underlying socket.
+	///
+	/// The new stream isn't registered to a reactor yet.
+	pub fn try_clone(&self) -> io::Result<Self> {
+		self.try_clone_with(LazyHandle::new())
+	}
+
+	/// Creates a new independently owned handle to the underlying socket.
+	pub fn try_clone_with(&self, handle: LazyHandle) -> io::Result<Self> {
+		let stream = with_raw_as(self.mio_stream.io_ref(), std::net::TcpStream::try_clone)?;
+		Ok(Self {
+			mio_stream: PollEvented::new(MioTcpStream::from_std(stream), handle),
+		})
+	}
+
+	/// Shuts down the read, write, or both halves of this connection.
+	pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+		self.mio_stream.io_ref().shutdown(how)
+	}
+
+	/// Sets the value of the `TCP_NODELAY` option on this socket.
+	pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+		self.mio_stream.io_ref().set_nodelay(nodelay)
+	}
+
+	/// Gets the value of the `TCP_NODELAY` option on this socket.
+	pub fn nodelay(&self) -> io::Result<bool> {
+		self.mio_stream.io_ref().nodelay()
+	}
+
+	/// Enable keepalive probes with the given parameters, or disable them (`None`).
+	pub fn set_keepalive(&self, keepalive: Option<KeepAlive>) -> io::Result<()> {
+		set_keepalive(self.mio_stream.io_ref(), keepalive)
+	}
+
+	/// Sets the value for the `IP_TTL` option on this socket.
+	pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+		self.mio_stream.io_ref().set_ttl(ttl)
+	}
+
+	/// Gets the value of the `IP_TTL` option for this socket.
+	pub fn ttl(&self) -> io::Result<u32> {
+		self.mio_stream.io_ref().ttl()
+	}
+
+	/// Sets the value of the `SO_RCVBUF` option on this socket.
+	///
+	/// The OS might adjust (e.g. double) the value.
+	pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+		with_raw_as(self.mio_stream.io_ref(), |s: &socket2::Socket| s.set_recv_buffer_size(size))
+	}
+
+	/// Gets the value of the `SO_RCVBUF` option on this socket.
+	pub fn recv_buffer_size(&self) -> io::Result<usize> {
+		with_raw_as(self.mio_stream.io_ref(), socket2::Socket::recv_buffer_size)
+	}
+
+	/// Sets the value of the `SO_SNDBUF` option on this socket.
+	///
+	/// The OS might adjust (e.g. double) the value.
+	pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+		with_raw_as(self.mio_stream.io_ref(), |s: &socket2::Socket| s.set_send_buffer_size(size))
+	}
+
+	/// Gets the value of the `SO_SNDBUF` option on this socket.
+	pub fn send_buffer_size(&self) -> io::Result<usize> {
+		with_raw_as(self.mio_stream.io_ref(), socket2::Socket::send_buffer_size)
+	}
+
+	/// Sets the value of the `SO_LINGER` option on this socket.
+	///
+	/// With `Some(Duration::from_secs(0))` closing the socket drops unsent data and resets the
+	/// connection (RST) instead of a graceful shutdown; `None` disables lingering.
+	pub fn set_linger(&self, linger: Option<Duration>) -> io::Result<()> {
+		with_raw_as(self.mio_stream.io_ref(), |s: &socket2::Socket| s.set_linger(linger))
+	}
+
+	/// Gets the value of the `SO_LINGER` option on this socket.
+	pub fn linger(&self) -> io::Result<Option<Duration>> {
+		with_raw_as(self.mio_stream.io_ref(), socket2::Socket::linger)
+	}
+
+	/// Sets the value of the `IP_TOS` option on this socket (IPv4 only).
+	#[cfg(not(any(target_os = "fuchsia", target_os = "redox", target_os = "solaris", target_os = "illumos")))]
+	pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+		with_raw_as(self.mio_stream.io_ref(), |s: &socket2::Socket| s.set_tos(tos))
+	}
+
+	/// Gets the value of the `IP_TOS` option on this socket.
+	#[cfg(not(any(target_os = "fuchsia", target_os = "redox", target_os = "solaris", target_os = "illumos")))]
+	pub fn tos(&self) -> io::Result<u32> {
+		with_raw_as(self.mio_stream.io_ref(), socket2::Socket::tos)
+	}
+
+	/// Sets the value of the `IPV6_TCLASS` option on this socket (IPv6 only).
+	///
+	/// The traffic class is the IPv6 equivalent of the `IP_TOS` value (DSCP and ECN bits).
+	pub fn set_tclass_v6(&self, tclass: u32) -> io::Result<()> {
+		tclass::set_tclass_v6(self.mio_stream.io_ref(), tclass)
+	}
+
+	/// Gets the value of the `IPV6_TCLASS` option on this socket.
+	pub fn tclass_v6(&self) -> io::Result<u32> {
+		tclass::tclass_v6(self.mio_stream.io_ref())
+	}
 }
 
 impl std::convert::TryFrom<std::net::TcpStream> for TcpStream {
@@ -81,6 +276,10 @@ impl futures_io::AsyncRead for TcpStream {
 	fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
 		Pin::new(&mut self.mio_stream).poll_read(cx, buf)
 	}
+
+	fn poll_read_vectored(mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &mut [io::IoSliceMut<'_>]) -> Poll<io::Result<usize>> {
+		Pin::new(&mut self.mio_stream).poll_read_vectored(cx, bufs)
+	}
 }
 
 impl futures_io::AsyncWrite for TcpStream {
@@ -88,6 +287,10 @@ impl futures_io::AsyncWrite for TcpStream {
 		Pin::new(&mut self.mio_stream).poll_write(cx, buf)
 	}
 
+	fn poll_write_vectored(mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[io::IoSlice<'_>]) -> Poll<io::Result<usize>> {
+		Pin::new(&mut self.mio_stream).poll_write_vectored(cx, bufs)
+	}
+
 	fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
 		Pin::new(&mut self.mio_stream).poll_flush(cx)
 	}
@@ -98,3 +301,20 @@ impl futures_io::AsyncWrite for TcpStream {
 		Poll::Ready(Ok(()))
 	}
 }
+
+/// Pending `peek` operation
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct TcpPeek<'a> {
+	stream: &'a mut TcpStream,
+	buf: &'a mut [u8],
+}
+
+impl Future for TcpPeek<'_> {
+	type Output = io::Result<usize>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.get_mut();
+		this.stream.poll_peek(cx, this.buf)
+	}
+}
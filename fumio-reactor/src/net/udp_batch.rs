@@ -0,0 +1,154 @@
+//! Batched `recv_from` / `send_to`; uses `recvmmsg` / `sendmmsg` on linux.
+
+use mio::net::UdpSocket as MioUdpSocket;
+use std::io;
+use std::net::SocketAddr;
+
+#[cfg(target_os = "linux")]
+mod sys {
+	use mio::net::UdpSocket as MioUdpSocket;
+	use std::io;
+	use std::mem;
+	use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+	use std::os::unix::io::AsRawFd;
+
+	// datagrams per syscall
+	const BATCH_MAX: usize = 32;
+
+	#[allow(clippy::cast_possible_truncation)] // socket address sizes fit
+	fn to_sockaddr(addr: &SocketAddr, storage: &mut libc::sockaddr_storage) -> libc::socklen_t {
+		match addr {
+			SocketAddr::V4(a) => {
+				let sin = unsafe { &mut *std::ptr::from_mut(storage).cast::<libc::sockaddr_in>() };
+				sin.sin_family = libc::AF_INET as libc::sa_family_t;
+				sin.sin_port = a.port().to_be();
+				sin.sin_addr.s_addr = u32::from_ne_bytes(a.ip().octets());
+				mem::size_of::<libc::sockaddr_in>() as libc::socklen_t
+			},
+			SocketAddr::V6(a) => {
+				let sin6 = unsafe { &mut *std::ptr::from_mut(storage).cast::<libc::sockaddr_in6>() };
+				sin6.sin6_family = libc::AF_INET6 as libc::sa_family_t;
+				sin6.sin6_port = a.port().to_be();
+				sin6.sin6_flowinfo = a.flowinfo();
+				sin6.sin6_addr.s6_addr = a.ip().octets();
+				sin6.sin6_scope_id = a.scope_id();
+				mem::size_of::<libc::sockaddr_in6>() as libc::socklen_t
+			},
+		}
+	}
+
+	fn from_sockaddr(storage: &libc::sockaddr_storage) -> io::Result<SocketAddr> {
+		match libc::c_int::from(storage.ss_family) {
+			libc::AF_INET => {
+				let sin = unsafe { &*std::ptr::from_ref(storage).cast::<libc::sockaddr_in>() };
+				let ip = Ipv4Addr::from(sin.sin_addr.s_addr.to_ne_bytes());
+				Ok(SocketAddrV4::new(ip, u16::from_be(sin.sin_port)).into())
+			},
+			libc::AF_INET6 => {
+				let sin6 = unsafe { &*std::ptr::from_ref(storage).cast::<libc::sockaddr_in6>() };
+				let ip = Ipv6Addr::from(sin6.sin6_addr.s6_addr);
+				Ok(SocketAddrV6::new(ip, u16::from_be(sin6.sin6_port), sin6.sin6_flowinfo, sin6.sin6_scope_id).into())
+			},
+			_ => Err(io::Error::new(io::ErrorKind::InvalidData, "unsupported address family")),
+		}
+	}
+
+	#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // count <= BATCH_MAX; n >= 0
+	pub(super) fn recv_batch(io: &MioUdpSocket, bufs: &mut [&mut [u8]], received: &mut Vec<(usize, SocketAddr)>) -> io::Result<usize> {
+		let mut iovecs: [libc::iovec; BATCH_MAX] = unsafe { mem::zeroed() };
+		let mut addrs: [libc::sockaddr_storage; BATCH_MAX] = unsafe { mem::zeroed() };
+		let mut msgs: [libc::mmsghdr; BATCH_MAX] = unsafe { mem::zeroed() };
+		let count = std::cmp::min(bufs.len(), BATCH_MAX);
+		for (((buf, iovec), addr), msg) in bufs.iter_mut().zip(&mut iovecs).zip(&mut addrs).zip(&mut msgs) {
+			iovec.iov_base = buf.as_mut_ptr().cast();
+			iovec.iov_len = buf.len();
+			msg.msg_hdr.msg_name = std::ptr::from_mut(addr).cast();
+			msg.msg_hdr.msg_namelen = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+			msg.msg_hdr.msg_iov = iovec;
+			msg.msg_hdr.msg_iovlen = 1;
+		}
+		let n = unsafe {
+			libc::recvmmsg(io.as_raw_fd(), msgs.as_mut_ptr(), count as libc::c_uint, 0, std::ptr::null_mut())
+		};
+		if n < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		// the datagrams are already taken from the socket: don't fail the batch because of a
+		// single bad source address, only drop that datagram (moving the buffers of the
+		// following datagrams down, so `received` and `bufs` still match up)
+		for (index, (msg, addr)) in msgs[..n as usize].iter().zip(&addrs).enumerate() {
+			if let Ok(addr) = from_sockaddr(addr) {
+				bufs.swap(received.len(), index);
+				received.push((msg.msg_len as usize, addr));
+			}
+		}
+		Ok(received.len())
+	}
+
+	#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // count <= BATCH_MAX; n >= 0
+	pub(super) fn send_batch(io: &MioUdpSocket, datagrams: &[(&[u8], SocketAddr)]) -> io::Result<usize> {
+		let mut iovecs: [libc::iovec; BATCH_MAX] = unsafe { mem::zeroed() };
+		let mut addrs: [libc::sockaddr_storage; BATCH_MAX] = unsafe { mem::zeroed() };
+		let mut msgs: [libc::mmsghdr; BATCH_MAX] = unsafe { mem::zeroed() };
+		let count = std::cmp::min(datagrams.len(), BATCH_MAX);
+		for ((((buf, target), iovec), addr), msg) in datagrams.iter().zip(&mut iovecs).zip(&mut addrs).zip(&mut msgs) {
+			// sendmsg doesn't write to the buffer
+			iovec.iov_base = buf.as_ptr().cast::<libc::c_void>().cast_mut();
+			iovec.iov_len = buf.len();
+			msg.msg_hdr.msg_namelen = to_sockaddr(target, addr);
+			msg.msg_hdr.msg_name = std::ptr::from_mut(addr).cast();
+			msg.msg_hdr.msg_iov = iovec;
+			msg.msg_hdr.msg_iovlen = 1;
+		}
+		let n = unsafe {
+			libc::sendmmsg(io.as_raw_fd(), msgs.as_mut_ptr(), count as libc::c_uint, 0)
+		};
+		if n < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		Ok(n as usize)
+	}
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+	use mio::net::UdpSocket as MioUdpSocket;
+	use std::io;
+	use std::net::SocketAddr;
+
+	// errors after the first datagram are dropped; they show up again in the next call
+	pub(super) fn recv_batch(io: &MioUdpSocket, bufs: &mut [&mut [u8]], received: &mut Vec<(usize, SocketAddr)>) -> io::Result<usize> {
+		let mut n = 0;
+		for buf in bufs {
+			match io.recv_from(buf) {
+				Ok(r) => received.push(r),
+				Err(_) if n > 0 => break,
+				Err(e) => return Err(e),
+			}
+			n += 1;
+		}
+		Ok(n)
+	}
+
+	pub(super) fn send_batch(io: &MioUdpSocket, datagrams: &[(&[u8], SocketAddr)]) -> io::Result<usize> {
+		let mut n = 0;
+		for (buf, target) in datagrams {
+			match io.send_to(buf, *target) {
+				Ok(_) => (),
+				Err(_) if n > 0 => break,
+				Err(e) => return Err(e),
+			}
+			n += 1;
+		}
+		Ok(n)
+	}
+}
+
+pub(super) fn recv_batch(io: &MioUdpSocket, bufs: &mut [&mut [u8]], received: &mut Vec<(usize, SocketAddr)>) -> io::Result<usize> {
+	received.clear();
+	sys::recv_batch(io, bufs, received)
+}
+
+pub(super) fn send_batch(io: &MioUdpSocket, datagrams: &[(&[u8], SocketAddr)]) -> io::Result<usize> {
+	sys::send_batch(io, datagrams)
+}
@@ -0,0 +1,139 @@
+use crate::net::UdpSocket;
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// large enough for any UDP payload
+const RECV_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Converts between datagrams and messages for [`UdpFramed`](struct.UdpFramed.html).
+pub trait UdpCodec {
+	/// Decoded type of received datagrams
+	type In;
+	/// Type of messages to encode into datagrams
+	type Out;
+	/// Error type for decoding and encoding; IO errors are converted into it too.
+	type Error: From<io::Error>;
+
+	/// Decode a received datagram.
+	///
+	/// A decode error is yielded by the stream, but doesn't end it.
+	fn decode(&mut self, buf: &[u8], from: &SocketAddr) -> Result<Self::In, Self::Error>;
+
+	/// Encode a message into a datagram; `buf` is empty when called.
+	fn encode(&mut self, msg: Self::Out, buf: &mut Vec<u8>) -> Result<(), Self::Error>;
+}
+
+/// A [`UdpSocket`](struct.UdpSocket.html) with a [`UdpCodec`](trait.UdpCodec.html), as `Stream`
+/// of received messages and `Sink` for messages to send.
+///
+/// The sink buffers a single datagram; it needs to be flushed before the next message is
+/// accepted.
+#[must_use = "streams and sinks do nothing unless polled"]
+#[derive(Debug)]
+pub struct UdpFramed<C> {
+	socket: UdpSocket,
+	codec: C,
+	rd: Vec<u8>,
+	wr: Vec<u8>,
+	// target of the buffered datagram in `wr`
+	wr_target: Option<SocketAddr>,
+}
+
+impl<C> UdpFramed<C>
+where
+	C: UdpCodec,
+{
+	/// Wrap socket with codec.
+	pub fn new(socket: UdpSocket, codec: C) -> Self {
+		Self {
+			socket,
+			codec,
+			rd: vec![0; RECV_BUFFER_SIZE],
+			wr: Vec::new(),
+			wr_target: None,
+		}
+	}
+
+	/// Retrieve reference to the socket
+	pub fn get_ref(&self) -> &UdpSocket {
+		&self.socket
+	}
+
+	/// Retrieve mutable reference to the socket
+	pub fn get_mut(&mut self) -> &mut UdpSocket {
+		&mut self.socket
+	}
+
+	/// Retrieve reference to the codec
+	pub fn codec(&self) -> &C {
+		&self.codec
+	}
+
+	/// Retrieve mutable reference to the codec
+	pub fn codec_mut(&mut self) -> &mut C {
+		&mut self.codec
+	}
+
+	/// Extract socket and codec; a buffered datagram not flushed yet is lost.
+	pub fn into_inner(self) -> (UdpSocket, C) {
+		(self.socket, self.codec)
+	}
+
+	fn poll_send_buffered(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), C::Error>> {
+		if let Some(target) = self.wr_target {
+			let len = futures_core::ready!(self.socket.poll_send_to(cx, &self.wr, &target))?;
+			let complete = len == self.wr.len();
+			self.wr_target = None;
+			self.wr.clear();
+			if !complete {
+				return Poll::Ready(Err(io::Error::other("failed to send entire datagram").into()));
+			}
+		}
+		Poll::Ready(Ok(()))
+	}
+}
+
+impl<C> Stream for UdpFramed<C>
+where
+	C: UdpCodec + Unpin,
+{
+	type Item = Result<(C::In, SocketAddr), C::Error>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.get_mut();
+		let (len, from) = futures_core::ready!(this.socket.poll_recv_from(cx, &mut this.rd))?;
+		let msg = this.codec.decode(&this.rd[..len], &from)?;
+		Poll::Ready(Some(Ok((msg, from))))
+	}
+}
+
+impl<C> Sink<(C::Out, SocketAddr)> for UdpFramed<C>
+where
+	C: UdpCodec + Unpin,
+{
+	type Error = C::Error;
+
+	fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.get_mut().poll_send_buffered(cx)
+	}
+
+	fn start_send(self: Pin<&mut Self>, (msg, target): (C::Out, SocketAddr)) -> Result<(), Self::Error> {
+		let this = self.get_mut();
+		assert!(this.wr_target.is_none(), "start_send called without poll_ready");
+		this.codec.encode(msg, &mut this.wr)?;
+		this.wr_target = Some(target);
+		Ok(())
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.get_mut().poll_send_buffered(cx)
+	}
+
+	fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		self.get_mut().poll_send_buffered(cx)
+	}
+}
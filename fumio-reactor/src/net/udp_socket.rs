@@ -1,4 +1,6 @@
-use crate::helper::async_io;
+use crate::helper::{async_io, convert_raw, with_raw_as};
+use crate::net::tclass;
+use crate::net::udp_batch;
 use crate::reactor::{LazyHandle, PollEvented};
 use mio::net::UdpSocket as MioUdpSocket;
 use std::future::Future;
@@ -15,32 +17,27 @@ pub struct UdpSocket {
 }
 
 impl UdpSocket {
-	/// Create builder with default options, but doesn't bind yet.
-	///
-	/// To create a `UdpSocket` from a builder go through the `std::net::UdpSocket` created by
-	/// `builder.bind(...)?`.
-	pub fn default_builder_for(local: &SocketAddr) -> io::Result<net2::UdpBuilder> {
-		let builder;
-		match local {
-			SocketAddr::V4(_) => {
-				builder = net2::UdpBuilder::new_v4()?;
-			}
-			SocketAddr::V6(a) => {
-				builder = net2::UdpBuilder::new_v6()?;
-				if a.ip().is_unspecified() {
-					// always try to disable only_v6
-					let _ = builder.only_v6(false);
-				}
+	/// Create socket with default options, but doesn't bind yet.
+	///
+	/// To create a `UdpSocket` from the socket bind it and convert it into a
+	/// `std::net::UdpSocket` (`socket.into()`) for [`from_std`](#method.from_std).
+	pub fn default_builder_for(local: &SocketAddr) -> io::Result<socket2::Socket> {
+		let socket = socket2::Socket::new(socket2::Domain::for_address(*local), socket2::Type::DGRAM, Some(socket2::Protocol::UDP))?;
+		if let SocketAddr::V6(a) = local {
+			if a.ip().is_unspecified() {
+				// always try to disable only_v6
+				let _ = socket.set_only_v6(false);
 			}
 		}
-		builder.reuse_address(true)?;
-		Ok(builder)
+		socket.set_reuse_address(true)?;
+		Ok(socket)
 	}
 
 	/// Wraps an already bound tcp stream
 	pub fn from_std(stream: std::net::UdpSocket, handle: LazyHandle) -> io::Result<Self> {
+		stream.set_nonblocking(true)?;
 		Ok(Self {
-			mio_socket: PollEvented::new(MioUdpSocket::from_socket(stream)?, handle),
+			mio_socket: PollEvented::new(MioUdpSocket::from_std(stream), handle),
 		})
 	}
 
@@ -51,7 +48,17 @@ impl UdpSocket {
 		})
 	}
 
-	/// Binds a new UDP socket to IPv6 `[::]` with V6_ONLY=false (i.e. also listen on IPv4) and the
+	/// Deregisters from the reactor and returns the `std` socket.
+	///
+	/// The socket stays in non-blocking mode if `nonblocking` is true, otherwise it is switched
+	/// back to blocking mode.
+	pub fn into_std(self, nonblocking: bool) -> io::Result<std::net::UdpSocket> {
+		let socket: std::net::UdpSocket = convert_raw(self.mio_socket.into_inner());
+		socket.set_nonblocking(nonblocking)?;
+		Ok(socket)
+	}
+
+	/// Binds a new UDP socket to IPv6 `[::]` with `V6_ONLY=false` (i.e. also listen on IPv4) and the
 	/// given port.
 	///
 	/// If the port is 0 the OS will select a random port.
@@ -73,8 +80,9 @@ impl UdpSocket {
 
 	/// Bind a new UDP socket  to the specified address.
 	pub fn bind_with(local: SocketAddr, handle: LazyHandle) -> io::Result<Self> {
-		let builder = Self::default_builder_for(&local)?;
-		Self::from_std(builder.bind(&local)?, handle)
+		let socket = Self::default_builder_for(&local)?;
+		socket.bind(&local.into())?;
+		Self::from_std(socket.into(), handle)
 	}
 
 	/// Returns the local socket address of this socket.
@@ -91,8 +99,9 @@ impl UdpSocket {
 
 	/// Creates a new independently owned handle to the underlying socket.
 	pub fn try_clone_with(&self, handle: LazyHandle) -> io::Result<Self> {
+		let socket = with_raw_as(self.mio_socket.io_ref(), std::net::UdpSocket::try_clone)?;
 		Ok(Self {
-			mio_socket: PollEvented::new(self.mio_socket.io_ref().try_clone()?, handle),
+			mio_socket: PollEvented::new(MioUdpSocket::from_std(socket), handle),
 		})
 	}
 
@@ -119,7 +128,7 @@ impl UdpSocket {
 		// use mutable (although io.send_to doesn't need it), because only one context can get registered;
 		// shared ownership isn't useful.
 		self.mio_socket.try_mut_write(cx, |io| {
-			async_io(|| io.send_to(buf, target))
+			async_io(|| io.send_to(buf, *target))
 		})
 	}
 
@@ -133,6 +142,28 @@ impl UdpSocket {
 		}
 	}
 
+	/// Receives multiple datagrams from the socket (using `recvmmsg` on linux).
+	///
+	/// Replaces the content of `received` with length and source address of each datagram
+	/// received into the corresponding buffer in `bufs`. On success, returns the number of
+	/// datagrams received (at least one unless `bufs` is empty or all datagrams were dropped).
+	///
+	/// Datagrams with a source address of an unsupported family are dropped; the buffers in `bufs`
+	/// get reordered so the entries in `received` still match the first buffers.
+	pub fn poll_recv_batch(&mut self, cx: &mut Context<'_>, bufs: &mut [&mut [u8]], received: &mut Vec<(usize, SocketAddr)>) -> Poll<io::Result<usize>> {
+		self.mio_socket.try_mut_read(cx, |io| {
+			async_io(|| udp_batch::recv_batch(io, bufs, received))
+		})
+	}
+
+	/// Sends multiple datagrams on the socket (using `sendmmsg` on linux). On success, returns the
+	/// number of datagrams sent (at least one unless `datagrams` is empty).
+	pub fn poll_send_batch(&mut self, cx: &mut Context<'_>, datagrams: &[(&[u8], SocketAddr)]) -> Poll<io::Result<usize>> {
+		self.mio_socket.try_mut_write(cx, |io| {
+			async_io(|| udp_batch::send_batch(io, datagrams))
+		})
+	}
+
 /*
 	// connected UDP sockets should get a separate type?
 
@@ -166,9 +197,9 @@ impl UdpSocket {
 		self.mio_socket.io_ref().set_broadcast(on)
 	}
 
-	/// Gets the value of the SO_BROADCAST option for this socket.
+	/// Gets the value of the `SO_BROADCAST` option for this socket.
 	///
-	/// For more information about this option, see set_broadcast.
+	/// For more information about this option, see `set_broadcast`.
 	pub fn broadcast(&self) -> io::Result<bool> {
 		self.mio_socket.io_ref().broadcast()
 	}
@@ -247,6 +278,142 @@ impl UdpSocket {
 		self.mio_socket.io_ref().ttl()
 	}
 
+	/// Sets the value for the `IPV6_UNICAST_HOPS` option on this socket.
+	///
+	/// This value sets the hop limit field that is used in every unicast packet sent from this
+	/// socket (the IPv6 equivalent of `IP_TTL`).
+	pub fn set_unicast_hops_v6(&self, hops: u32) -> io::Result<()> {
+		with_raw_as(self.mio_socket.io_ref(), |s: &socket2::Socket| s.set_unicast_hops_v6(hops))
+	}
+
+	/// Gets the value of the `IPV6_UNICAST_HOPS` option for this socket.
+	///
+	/// For more information about this option, see
+	/// [`set_unicast_hops_v6`][link].
+	///
+	/// [link]: #method.set_unicast_hops_v6
+	pub fn unicast_hops_v6(&self) -> io::Result<u32> {
+		with_raw_as(self.mio_socket.io_ref(), socket2::Socket::unicast_hops_v6)
+	}
+
+	/// Sets the value for the `IPV6_MULTICAST_HOPS` option on this socket.
+	///
+	/// Indicates the hop limit of outgoing multicast packets for this socket (the IPv6
+	/// equivalent of `IP_MULTICAST_TTL`).
+	pub fn set_multicast_hops_v6(&self, hops: u32) -> io::Result<()> {
+		with_raw_as(self.mio_socket.io_ref(), |s: &socket2::Socket| s.set_multicast_hops_v6(hops))
+	}
+
+	/// Gets the value of the `IPV6_MULTICAST_HOPS` option for this socket.
+	///
+	/// For more information about this option, see
+	/// [`set_multicast_hops_v6`][link].
+	///
+	/// [link]: #method.set_multicast_hops_v6
+	pub fn multicast_hops_v6(&self) -> io::Result<u32> {
+		with_raw_as(self.mio_socket.io_ref(), |s: &socket2::Socket| s.multicast_hops_v6())
+	}
+
+	/// Sets the value of the `IPV6_MULTICAST_IF` option for this socket.
+	///
+	/// Specifies the interface (by index) to send outgoing multicast packets on; 0 lets the
+	/// system choose.
+	pub fn set_multicast_if_v6(&self, interface: u32) -> io::Result<()> {
+		with_raw_as(self.mio_socket.io_ref(), |s: &socket2::Socket| s.set_multicast_if_v6(interface))
+	}
+
+	/// Gets the value of the `IPV6_MULTICAST_IF` option for this socket.
+	///
+	/// For more information about this option, see
+	/// [`set_multicast_if_v6`][link].
+	///
+	/// [link]: #method.set_multicast_if_v6
+	pub fn multicast_if_v6(&self) -> io::Result<u32> {
+		with_raw_as(self.mio_socket.io_ref(), |s: &socket2::Socket| s.multicast_if_v6())
+	}
+
+	/// Sets the value of the `IPV6_V6ONLY` option for this socket.
+	///
+	/// If enabled an IPv6 socket only handles IPv6 traffic; otherwise it also handles IPv4
+	/// traffic through IPv4-mapped addresses. Most systems only allow changing this before the
+	/// socket is bound (see [`default_builder_for`](#method.default_builder_for)).
+	pub fn set_only_v6(&self, only_v6: bool) -> io::Result<()> {
+		with_raw_as(self.mio_socket.io_ref(), |s: &socket2::Socket| s.set_only_v6(only_v6))
+	}
+
+	/// Gets the value of the `IPV6_V6ONLY` option for this socket.
+	///
+	/// For more information about this option, see [`set_only_v6`][link].
+	///
+	/// [link]: #method.set_only_v6
+	pub fn only_v6(&self) -> io::Result<bool> {
+		self.mio_socket.io_ref().only_v6()
+	}
+
+	/// Sets the value of the `SO_RCVBUF` option on this socket.
+	///
+	/// The OS might adjust (e.g. double) the value.
+	pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+		with_raw_as(self.mio_socket.io_ref(), |s: &socket2::Socket| s.set_recv_buffer_size(size))
+	}
+
+	/// Gets the value of the `SO_RCVBUF` option on this socket.
+	pub fn recv_buffer_size(&self) -> io::Result<usize> {
+		with_raw_as(self.mio_socket.io_ref(), socket2::Socket::recv_buffer_size)
+	}
+
+	/// Sets the value of the `SO_SNDBUF` option on this socket.
+	///
+	/// The OS might adjust (e.g. double) the value.
+	pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+		with_raw_as(self.mio_socket.io_ref(), |s: &socket2::Socket| s.set_send_buffer_size(size))
+	}
+
+	/// Gets the value of the `SO_SNDBUF` option on this socket.
+	pub fn send_buffer_size(&self) -> io::Result<usize> {
+		with_raw_as(self.mio_socket.io_ref(), socket2::Socket::send_buffer_size)
+	}
+
+	/// Sets the value of the `IP_TOS` option on this socket (IPv4 only).
+	#[cfg(not(any(target_os = "fuchsia", target_os = "redox", target_os = "solaris", target_os = "illumos")))]
+	pub fn set_tos(&self, tos: u32) -> io::Result<()> {
+		with_raw_as(self.mio_socket.io_ref(), |s: &socket2::Socket| s.set_tos(tos))
+	}
+
+	/// Gets the value of the `IP_TOS` option on this socket.
+	#[cfg(not(any(target_os = "fuchsia", target_os = "redox", target_os = "solaris", target_os = "illumos")))]
+	pub fn tos(&self) -> io::Result<u32> {
+		with_raw_as(self.mio_socket.io_ref(), socket2::Socket::tos)
+	}
+
+	/// Sets the value of the `IPV6_TCLASS` option on this socket (IPv6 only).
+	///
+	/// The traffic class is the IPv6 equivalent of the `IP_TOS` value (DSCP and ECN bits).
+	pub fn set_tclass_v6(&self, tclass: u32) -> io::Result<()> {
+		tclass::set_tclass_v6(self.mio_socket.io_ref(), tclass)
+	}
+
+	/// Gets the value of the `IPV6_TCLASS` option on this socket.
+	pub fn tclass_v6(&self) -> io::Result<u32> {
+		tclass::tclass_v6(self.mio_socket.io_ref())
+	}
+
+	/// Sets the value of the `SO_REUSEPORT` option on this socket.
+	///
+	/// Only useful before binding, i.e. usually set on the socket from
+	/// [`default_builder_for`](#method.default_builder_for) instead; allows multiple sockets to
+	/// bind the same address.
+	#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+	pub fn set_reuse_port(&self, reuse: bool) -> io::Result<()> {
+		with_raw_as(self.mio_socket.io_ref(), |s: &socket2::Socket| s.set_reuse_port(reuse))
+	}
+
+	/// Gets the value of the `SO_REUSEPORT` option on this socket.
+	#[cfg(all(unix, not(any(target_os = "solaris", target_os = "illumos"))))]
+	pub fn reuse_port(&self) -> io::Result<bool> {
+		with_raw_as(self.mio_socket.io_ref(), socket2::Socket::reuse_port)
+	}
+
 	/// Executes an operation of the `IP_ADD_MEMBERSHIP` type.
 	///
 	/// This function specifies a new multicast group for this socket to join.
@@ -287,6 +454,63 @@ impl UdpSocket {
 		self.mio_socket.io_ref().leave_multicast_v6(&multiaddr, interface)
 	}
 
+	/// Executes an operation of the `IP_ADD_SOURCE_MEMBERSHIP` type.
+	///
+	/// Joins the multicast group `multiaddr`, but only receives packets sent by `source`
+	/// (source-specific multicast). `interface` is the address of the local interface (or
+	/// `INADDR_ANY` to let the system choose).
+	pub fn join_ssm_v4(&self, multiaddr: Ipv4Addr, source: Ipv4Addr, interface: Ipv4Addr) -> io::Result<()> {
+		self.ssm_v4(SsmOp::Join, multiaddr, source, interface)
+	}
+
+	/// Executes an operation of the `IP_DROP_SOURCE_MEMBERSHIP` type.
+	///
+	/// For more information about this option, see
+	/// [`join_ssm_v4`][link].
+	///
+	/// [link]: #method.join_ssm_v4
+	pub fn leave_ssm_v4(&self, multiaddr: Ipv4Addr, source: Ipv4Addr, interface: Ipv4Addr) -> io::Result<()> {
+		self.ssm_v4(SsmOp::Leave, multiaddr, source, interface)
+	}
+
+	#[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+	#[allow(clippy::cast_possible_truncation)] // size of ip_mreq_source
+	fn ssm_v4(&self, op: SsmOp, multiaddr: Ipv4Addr, source: Ipv4Addr, interface: Ipv4Addr) -> io::Result<()> {
+		use std::os::unix::io::AsRawFd;
+
+		fn in_addr(addr: Ipv4Addr) -> libc::in_addr {
+			libc::in_addr { s_addr: u32::from_ne_bytes(addr.octets()) }
+		}
+
+		let option = match op {
+			SsmOp::Join => libc::IP_ADD_SOURCE_MEMBERSHIP,
+			SsmOp::Leave => libc::IP_DROP_SOURCE_MEMBERSHIP,
+		};
+		let mreq = libc::ip_mreq_source {
+			imr_multiaddr: in_addr(multiaddr),
+			imr_interface: in_addr(interface),
+			imr_sourceaddr: in_addr(source),
+		};
+		let r = unsafe {
+			libc::setsockopt(
+				self.mio_socket.io_ref().as_raw_fd(),
+				libc::IPPROTO_IP,
+				option,
+				std::ptr::addr_of!(mreq).cast(),
+				std::mem::size_of::<libc::ip_mreq_source>() as libc::socklen_t,
+			)
+		};
+		if r != 0 {
+			return Err(io::Error::last_os_error());
+		}
+		Ok(())
+	}
+
+	#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios", target_os = "freebsd")))]
+	fn ssm_v4(&self, _op: SsmOp, _multiaddr: Ipv4Addr, _source: Ipv4Addr, _interface: Ipv4Addr) -> io::Result<()> {
+		Err(io::Error::other("source-specific multicast not supported on this platform"))
+	}
+
 	/// Get the value of the `SO_ERROR` option on this socket.
 	///
 	/// This will retrieve the stored error in the underlying socket, clearing
@@ -297,6 +521,12 @@ impl UdpSocket {
 	}
 }
 
+#[derive(Clone, Copy, Debug)]
+enum SsmOp {
+	Join,
+	Leave,
+}
+
 impl std::convert::TryFrom<std::net::UdpSocket> for UdpSocket {
 	type Error = io::Error;
 
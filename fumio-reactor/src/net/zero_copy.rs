@@ -0,0 +1,167 @@
+//! Zero-copy transfers using `sendfile` and `splice` (linux only).
+
+use crate::helper::async_io;
+use crate::net::TcpStream;
+use std::fs::File;
+use std::future::Future;
+use std::io;
+use std::ops::Range;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// maximum number of bytes `sendfile` and `splice` transfer in a single call on linux
+const MAX_CHUNK: usize = 0x7fff_f000;
+
+fn chunk_len(remaining: u64) -> usize {
+	if remaining < MAX_CHUNK as u64 {
+		#[allow(clippy::cast_possible_truncation)] // checked above
+		{ remaining as usize }
+	} else {
+		MAX_CHUNK
+	}
+}
+
+fn result_len(n: libc::ssize_t) -> io::Result<usize> {
+	if n < 0 {
+		return Err(io::Error::last_os_error());
+	}
+	#[allow(clippy::cast_sign_loss)] // checked above
+	Ok(n as usize)
+}
+
+#[allow(clippy::cast_possible_wrap)] // offsets beyond `off_t` fail anyway
+fn sys_sendfile(socket: RawFd, file: RawFd, offset: u64, len: usize) -> io::Result<usize> {
+	let mut offset = offset as libc::off_t;
+	result_len(unsafe { libc::sendfile(socket, file, std::ptr::addr_of_mut!(offset), len) })
+}
+
+fn sys_splice(from: RawFd, to: RawFd, len: usize) -> io::Result<usize> {
+	let flags = libc::SPLICE_F_MOVE | libc::SPLICE_F_NONBLOCK;
+	result_len(unsafe { libc::splice(from, std::ptr::null_mut(), to, std::ptr::null_mut(), len, flags) })
+}
+
+// non-blocking pipe; (read end, write end)
+fn pipe() -> io::Result<(File, File)> {
+	let mut fds: [libc::c_int; 2] = [-1; 2];
+	if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK | libc::O_CLOEXEC) } < 0 {
+		return Err(io::Error::last_os_error());
+	}
+	Ok(unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) })
+}
+
+impl TcpStream {
+	/// Sends up to `len` bytes from `file` starting at `offset` using `sendfile`, without copying
+	/// through userspace. On success, returns the number of bytes sent; 0 means `offset` is at
+	/// (or past) the end of `file` (or `len` is 0).
+	///
+	/// The file position of `file` is not changed.
+	pub fn poll_send_file(&mut self, cx: &mut Context<'_>, file: &File, offset: u64, len: usize) -> Poll<io::Result<usize>> {
+		self.mio_stream.try_mut_write(cx, |io| {
+			async_io(|| sys_sendfile(io.as_raw_fd(), file.as_raw_fd(), offset, len))
+		})
+	}
+}
+
+/// Future for [`send_file`](fn.send_file.html)
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct SendFile<'a> {
+	stream: &'a mut TcpStream,
+	file: &'a File,
+	range: Range<u64>,
+	sent: u64,
+}
+
+/// Sends `range` of `file` on `stream` using `sendfile`, without copying through userspace.
+///
+/// Completes with the number of bytes sent; this is less than the length of `range` only if the
+/// file ends early.
+pub fn send_file<'a>(stream: &'a mut TcpStream, file: &'a File, range: Range<u64>) -> SendFile<'a> {
+	SendFile {
+		stream,
+		file,
+		range,
+		sent: 0,
+	}
+}
+
+impl Future for SendFile<'_> {
+	type Output = io::Result<u64>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.get_mut();
+		while this.range.start < this.range.end {
+			let len = chunk_len(this.range.end - this.range.start);
+			let n = futures_util::ready!(this.stream.poll_send_file(cx, this.file, this.range.start, len))?;
+			if n == 0 {
+				break;
+			}
+			this.range.start += n as u64;
+			this.sent += n as u64;
+		}
+		Poll::Ready(Ok(this.sent))
+	}
+}
+
+/// Future for [`splice`](fn.splice.html)
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct Splice<'a> {
+	from: &'a mut TcpStream,
+	to: &'a mut TcpStream,
+	// (read end, write end); created on first poll
+	pipe: Option<(File, File)>,
+	// bytes in the pipe not written to `to` yet
+	buffered: usize,
+	total: u64,
+}
+
+/// Copies all data from `from` to `to` until `from` reaches EOF, using `splice` through a pipe
+/// so the data doesn't get copied through userspace.
+///
+/// Completes with the number of bytes copied; the write direction of `to` is not shut down.
+pub fn splice<'a>(from: &'a mut TcpStream, to: &'a mut TcpStream) -> Splice<'a> {
+	Splice {
+		from,
+		to,
+		pipe: None,
+		buffered: 0,
+		total: 0,
+	}
+}
+
+impl Future for Splice<'_> {
+	type Output = io::Result<u64>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.get_mut();
+		if this.pipe.is_none() {
+			this.pipe = Some(pipe()?);
+		}
+		let (pipe_read, pipe_write) = this.pipe.as_ref().unwrap();
+		let (pipe_read, pipe_write) = (pipe_read.as_raw_fd(), pipe_write.as_raw_fd());
+		loop {
+			if this.buffered > 0 {
+				let buffered = this.buffered;
+				let n = futures_util::ready!(this.to.mio_stream.try_mut_write(cx, |io| {
+					async_io(|| sys_splice(pipe_read, io.as_raw_fd(), buffered))
+				}))?;
+				if n == 0 {
+					return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+				}
+				this.buffered -= n;
+				this.total += n as u64;
+			} else {
+				// the pipe is empty: "would block" means there is no data to read
+				let n = futures_util::ready!(this.from.mio_stream.try_mut_read(cx, |io| {
+					async_io(|| sys_splice(io.as_raw_fd(), pipe_write, MAX_CHUNK))
+				}))?;
+				if n == 0 {
+					return Poll::Ready(Ok(this.total));
+				}
+				this.buffered = n;
+			}
+		}
+	}
+}
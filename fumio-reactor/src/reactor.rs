@@ -1,36 +1,72 @@
 //! The reactor implementation and various low-level tools to use it.
 
+#[cfg(unix)]
+mod async_fd;
+mod close_offloader;
 mod evented;
+#[cfg(target_os = "linux")]
+mod eventfd;
 mod executor;
+#[cfg(target_os = "linux")]
+mod fd;
 mod lazy_handle;
+mod metrics;
+mod ready;
 mod registration;
+mod shutdown;
 mod task;
+#[cfg(target_os = "linux")]
+mod timerfd;
+mod user_event;
 mod waker;
 
+#[cfg(unix)]
+pub use self::async_fd::{AsyncFd, AsyncFdReadyGuard};
+#[cfg(all(unix, feature = "raw-socket"))]
+pub(crate) use self::async_fd::FdSource;
+pub use self::close_offloader::CloseOffloader;
 pub use self::evented::PollEvented;
+#[cfg(target_os = "linux")]
+pub use self::eventfd::{EventFd, EventFdRead};
 pub use self::executor::current;
 pub use self::lazy_handle::LazyHandle;
+pub use self::metrics::{PollStats, ReactorMetrics};
+use self::metrics::Counters;
+pub use self::ready::Ready;
 pub use self::registration::Registration;
+pub use self::shutdown::OnShutdown;
+use self::shutdown::ShutdownSignal;
 use self::task::{ReactorTask, Tasks};
+#[cfg(target_os = "linux")]
+pub use self::timerfd::{TimerFd, TimerFdExpired};
+pub use self::user_event::{UserEvent, UserEventNotifier, UserEventTriggered};
+pub use crate::Error;
 
-use futures_executor::Enter;
+use fumio_utils::enter::Enter;
 use std::io;
-use std::sync::{Arc, Weak};
-use std::time::Duration;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
 
 #[derive(Debug)]
 struct Inner {
-	poll: mio::Poll,
+	registry: mio::Registry,
 	waker: std::task::Waker,
-	tasks: Tasks,
+	tasks: Mutex<Tasks>,
+	// readiness triggered by `UserEventNotifier`s, dispatched in the next poll
+	user_events: Mutex<Vec<(mio::Token, Ready)>>,
+	counters: Counters,
+	shutdown: Arc<ShutdownSignal>,
 }
 
 /// A reactor to drive asynchronous IO in context of async/await futures.
 #[derive(Debug)]
 pub struct Reactor {
+	poll: mio::Poll,
 	handlep: HandlePriv,
 	events: mio::Events,
-	wake_target: mio::Registration,
+	// tasks to dispatch events to (outside the tasks lock)
+	dispatch: Vec<(ReactorTask, Ready)>,
+	#[allow(clippy::struct_field_names)] // `waker` is the method handing out wakers
 	reactor_waker: waker::ReactorWaker,
 }
 
@@ -38,19 +74,24 @@ impl Reactor {
 	/// Create a new reactor
 	pub fn new() -> io::Result<Self> {
 		let poll = mio::Poll::new()?;
-		let (wake_target, reactor_waker) = waker::ReactorWaker::new();
-		poll.register(&wake_target, mio::Token(0), mio::Ready::readable(), mio::PollOpt::edge())?;
+		let registry = poll.registry().try_clone()?;
+		let counters = Counters::default();
+		let reactor_waker = waker::ReactorWaker::new(&registry, mio::Token(0), counters.wake_counters())?;
 
 		Ok(Self {
+			poll,
 			handlep: HandlePriv {
 				inner: Arc::new(Inner {
-					poll,
+					registry,
 					waker: reactor_waker.waker(),
-					tasks: Tasks::new(),
+					tasks: Mutex::new(Tasks::new()),
+					user_events: Mutex::new(Vec::new()),
+					counters,
+					shutdown: Arc::default(),
 				}),
 			},
 			events: mio::Events::with_capacity(1024),
-			wake_target,
+			dispatch: Vec::new(),
 			reactor_waker,
 		})
 	}
@@ -63,6 +104,11 @@ impl Reactor {
 		self.handlep.waker()
 	}
 
+	/// Snapshot of the reactor's counters
+	pub fn metrics(&self) -> ReactorMetrics {
+		self.handlep.inner.counters.snapshot()
+	}
+
 	/// Returns a handle to the reactor
 	///
 	/// The handle is used to register new IO events (i.e. sockets to be polled).
@@ -70,28 +116,94 @@ impl Reactor {
 		self.handlep.downgrade()
 	}
 
+	/// Whether polling without timeout would block until woken from another thread.
+	///
+	/// True if no event sources (or user events) are registered and the reactor wasn't woken
+	/// since the last poll.
+	#[allow(clippy::missing_panics_doc)] // only on poisoned locks
+	pub fn is_stalled(&self) -> bool {
+		!self.reactor_waker.is_pending() && self.handlep.inner.tasks.lock().unwrap().is_empty()
+	}
+
 	/// Poll for event and wait up to `timeout` for at least one event.
 	///
 	/// Waits "forever" if `timeout` is None, and doesn't block at all if `timeout` is Some(0).
 	///
+	/// Might return early without any events, e.g. when interrupted by a signal (`EINTR` isn't
+	/// treated as an error).
+	///
 	/// See [`waker`](#method.waker) for another way to interrupt poll.
-	pub fn poll(&mut self, mut timeout: Option<Duration>) -> io::Result<()> {
+	pub fn poll(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+		self.poll_stats(timeout)?;
+		Ok(())
+	}
+
+	/// Like [`poll`](#method.poll), but returns statistics about this iteration.
+	#[allow(clippy::missing_panics_doc)] // only on poisoned locks
+	pub fn poll_stats(&mut self, mut timeout: Option<Duration>) -> io::Result<PollStats> {
 		let (pending, _poll) = self.reactor_waker.start_poll();
 		if pending {
 			timeout = Some(Duration::new(0, 0));
 		}
 
-		self.handlep.inner.poll.poll(&mut self.events, timeout)?;
+		let poll_start = Instant::now();
+		match self.poll.poll(&mut self.events, timeout) {
+			Ok(()) => (),
+			// spurious wakeup; `events` is empty
+			Err(ref e) if e.kind() == io::ErrorKind::Interrupted => self.events.clear(),
+			Err(e) => return Err(e),
+		}
+		let blocked = poll_start.elapsed();
+		let counters = &self.handlep.inner.counters;
+		counters.record_poll(blocked);
 
-		for event in &self.events {
-			if event.token().0 == 0 { continue; }
-			let task = ReactorTask::from_token(event.token());
-			task.update_ready(event.readiness());
+		let mut woken = pending;
+		{
+			let mut user_events = self.handlep.inner.user_events.lock().unwrap();
+			let tasks = self.handlep.inner.tasks.lock().unwrap();
+			for event in &self.events {
+				if event.token().0 == 0 {
+					woken = true;
+					continue;
+				}
+				// events for deregistered tasks are dropped
+				if let Some(task) = tasks.get(event.token()) {
+					self.dispatch.push((task.clone(), Ready::from_event(event)));
+				}
+			}
+			for (token, readiness) in user_events.drain(..) {
+				if let Some(task) = tasks.get(token) {
+					self.dispatch.push((task.clone(), readiness));
+				}
+			}
 		}
 
-		self.handlep.inner.tasks.cleanup_tasks();
+		let events = self.dispatch.len();
+		for (task, readiness) in self.dispatch.drain(..) {
+			task.update_ready(readiness);
+			counters.record_event();
+		}
 
-		Ok(())
+		Ok(PollStats { events, woken, blocked })
+	}
+}
+
+impl Drop for Reactor {
+	fn drop(&mut self) {
+		self.handlep.inner.shutdown.trigger();
+		// wake all registered tasks (outside the lock); they fail with `ReactorGone` when polled.
+		// `Inner` might live a little longer (handles upgraded right now), so don't wait for it.
+		let tasks = std::mem::replace(&mut *self.handlep.inner.tasks.lock().unwrap(), Tasks::new());
+		drop(tasks);
+	}
+}
+
+/// The OS polling handle (e.g. epoll) is readable while the reactor has pending events, so a
+/// reactor can be nested in another event loop.
+#[cfg(unix)]
+impl std::os::unix::io::AsRawFd for Reactor {
+	fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+		self.poll.as_raw_fd()
 	}
 }
 
@@ -100,8 +212,11 @@ impl fumio_utils::park::Park for Reactor {
 		self.handlep.waker()
 	}
 
-	fn park(&mut self, _enter: &mut futures_executor::Enter, timeout: Option<Duration>) {
-		self.poll(timeout).unwrap();
+	fn park(&mut self, _enter: &mut Enter, timeout: Option<Duration>) {
+		// nothing sensible to do if the OS polling itself breaks
+		if let Err(e) = self.poll(timeout) {
+			panic!("reactor poll failed: {}", e);
+		}
 	}
 }
 
@@ -118,10 +233,7 @@ impl Handle {
 	///
 	/// Also see [`Reactor::waker`](struct.Reactor.html#method.waker).
 	pub fn waker(&self) -> std::task::Waker {
-		match self.upgrade() {
-			Some(handlep) => handlep.waker(),
-			None => futures_util::task::noop_waker(),
-		}
+		self.upgrade().map_or_else(futures_util::task::noop_waker, |handlep| handlep.waker())
 	}
 
 	/// Enter a reactor handle.
@@ -145,15 +257,54 @@ impl Handle {
 		self::executor::enter(self, enter, f)
 	}
 
+	/// Enter a reactor handle without an `Enter` guard.
+	///
+	/// Like [`enter`](#method.enter), but meant for executors driven from within another executor;
+	/// `f` must not block the thread.
+	///
+	/// # Panics
+	///
+	/// Panics if a handle is already entered.
+	pub fn enter_nonblocking<F, T>(self, f: F) -> T
+	where
+		F: FnOnce() -> T
+	{
+		self::executor::enter_nonblocking(self, f)
+	}
+
+	/// Snapshot of the reactor's counters; `None` if the reactor is gone.
+	pub fn metrics(&self) -> Option<ReactorMetrics> {
+		Some(self.upgrade()?.inner.counters.snapshot())
+	}
+
+	/// Whether the reactor is still alive (i.e. wasn't dropped yet)
+	pub fn is_alive(&self) -> bool {
+		match self.upgrade() {
+			Some(handlep) => !handlep.inner.shutdown.is_done(),
+			None => false,
+		}
+	}
+
+	/// Completes when the reactor is dropped (or right away if it is already gone)
+	///
+	/// Lets long-lived components notice the runtime shutting down, instead of waiting for IO to
+	/// fail with [`Error::ReactorGone`](enum.Error.html#variant.ReactorGone).
+	pub fn on_shutdown(&self) -> OnShutdown {
+		OnShutdown::new(self.upgrade().map(|handlep| handlep.inner.shutdown.clone()))
+	}
+
+	/// Whether both handles refer to the same reactor
+	pub fn same_reactor(&self, other: &Self) -> bool {
+		Weak::ptr_eq(&self.inner, &other.inner)
+	}
+
 	pub(crate) fn upgrade(&self) -> Option<HandlePriv> {
 		let inner = self.inner.upgrade()?;
 		Some(HandlePriv { inner })
 	}
 
-	pub(crate) fn expect_upgrade(&self) -> io::Result<HandlePriv> {
-		self.upgrade().ok_or_else(|| {
-			io::Error::new(io::ErrorKind::Other, "reactor not running anymore")
-		})
+	pub(crate) fn expect_upgrade(&self) -> Result<HandlePriv, Error> {
+		self.upgrade().ok_or(Error::ReactorGone)
 	}
 }
 
@@ -168,33 +319,48 @@ impl HandlePriv {
 		Handle { inner }
 	}
 
-	fn register<E>(&self, io: &E, task: ReactorTask, interest: mio::Ready, opts: mio::PollOpt) -> io::Result<()>
+	fn register<E>(&self, io: &mut E, task: ReactorTask, interest: mio::Interest) -> io::Result<()>
 	where
-		E: mio::Evented,
+		E: mio::event::Source + ?Sized,
 	{
-		let token = ReactorTask::as_token(&task);
-		self.inner.tasks.add_task(task);
-		self.inner.poll.register(io, token, interest, opts)?;
-		self.inner.waker.wake_by_ref();
+		let token = self.inner.tasks.lock().unwrap().insert(task)?;
+		if let Err(e) = self.inner.registry.register(io, token, interest) {
+			self.inner.tasks.lock().unwrap().remove(token);
+			return Err(e);
+		}
 		Ok(())
 	}
 
-	fn reregister<E>(&self, io: &E, task: &ReactorTask, interest: mio::Ready, opts: mio::PollOpt) -> io::Result<()>
+	fn reregister<E>(&self, io: &mut E, task: &ReactorTask, interest: mio::Interest) -> io::Result<()>
 	where
-		E: mio::Evented,
+		E: mio::event::Source + ?Sized,
 	{
-		let token = ReactorTask::as_token(task);
-		self.inner.poll.reregister(io, token, interest, opts)?;
+		self.inner.registry.reregister(io, task.token(), interest)?;
+		Ok(())
+	}
+
+	// register task without an event source; readiness is set through `trigger`
+	fn register_user(&self, task: ReactorTask) -> io::Result<()> {
+		self.inner.tasks.lock().unwrap().insert(task)?;
 		Ok(())
 	}
 
-	fn deregister<E>(&self, io: &E, task: ReactorTask) -> io::Result<()>
+	fn deregister_user(&self, task: &ReactorTask) {
+		self.inner.tasks.lock().unwrap().remove(task.token());
+	}
+
+	fn trigger(&self, token: mio::Token, readiness: Ready) {
+		self.inner.user_events.lock().unwrap().push((token, readiness));
+		self.inner.waker.wake_by_ref();
+	}
+
+	fn deregister<E>(&self, io: &mut E, task: &ReactorTask) -> io::Result<()>
 	where
-		E: mio::Evented,
+		E: mio::event::Source + ?Sized,
 	{
-		self.inner.poll.deregister(io)?;
-		self.inner.tasks.deregister_task(task);
-		self.inner.waker.wake_by_ref();
+		// remove first: even if mio fails, no events reach the task anymore
+		self.inner.tasks.lock().unwrap().remove(task.token());
+		self.inner.registry.deregister(io)?;
 		Ok(())
 	}
 
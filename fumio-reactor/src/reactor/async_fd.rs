@@ -0,0 +1,181 @@
+use crate::reactor::{Error, LazyHandle, Ready, Registration};
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::task::{Context, Poll};
+
+// event source for any file descriptor
+#[derive(Debug)]
+pub(crate) struct FdSource<T>(pub(crate) T);
+
+impl<T: AsRawFd> mio::event::Source for FdSource<T> {
+	fn register(&mut self, registry: &mio::Registry, token: mio::Token, interests: mio::Interest) -> io::Result<()> {
+		mio::unix::SourceFd(&self.0.as_raw_fd()).register(registry, token, interests)
+	}
+
+	fn reregister(&mut self, registry: &mio::Registry, token: mio::Token, interests: mio::Interest) -> io::Result<()> {
+		mio::unix::SourceFd(&self.0.as_raw_fd()).reregister(registry, token, interests)
+	}
+
+	fn deregister(&mut self, registry: &mio::Registry) -> io::Result<()> {
+		mio::unix::SourceFd(&self.0.as_raw_fd()).deregister(registry)
+	}
+}
+
+/// Registers any (non-blocking) file descriptor with the reactor.
+///
+/// Useful for devices (tun/tap, GPIO, ...) and other custom file descriptors; the actual IO is
+/// done by the caller on the inner object after [`poll_read_ready`](#method.poll_read_ready) or
+/// [`poll_write_ready`](#method.poll_write_ready) signalled readiness.
+///
+/// The file descriptor must be in non-blocking mode (`O_NONBLOCK`); it is registered
+/// edge-triggered, i.e. readiness is only signalled again after an operation returned
+/// `WouldBlock`.
+#[derive(Debug)]
+pub struct AsyncFd<T>
+where
+	T: AsRawFd,
+{
+	registration: Registration<FdSource<T>>,
+}
+
+impl<T> AsyncFd<T>
+where
+	T: AsRawFd,
+{
+	/// Register file descriptor with the [`current`](fn.current.html) reactor.
+	pub fn new(inner: T) -> io::Result<Self> {
+		Self::with_handle(inner, LazyHandle::new())
+	}
+
+	/// Register file descriptor with the reactor `handle` is bound to.
+	#[allow(clippy::needless_pass_by_value)] // consistent with other constructors taking a `LazyHandle`
+	pub fn with_handle(inner: T, handle: LazyHandle) -> io::Result<Self> {
+		let handle = handle.bind().ok_or(Error::NoReactor)?;
+		let mut registration = Registration::new(
+			FdSource(inner),
+			Ready::READABLE | Ready::READ_CLOSED | Ready::ERROR | Ready::PRIORITY,
+			Ready::WRITABLE | Ready::WRITE_CLOSED | Ready::ERROR,
+		);
+		registration.register(&handle, mio::Interest::READABLE | mio::Interest::WRITABLE)?;
+		Ok(Self { registration })
+	}
+
+	/// Retrieve reference to the inner object
+	pub fn get_ref(&self) -> &T {
+		&self.registration.io_ref().0
+	}
+
+	/// Retrieve mutable reference to the inner object
+	pub fn get_mut(&mut self) -> &mut T {
+		&mut self.registration.io_mut().0
+	}
+
+	/// Handle of the reactor the file descriptor is registered with.
+	pub fn handle(&self) -> LazyHandle {
+		self.registration.handle()
+	}
+
+	/// Deregister file descriptor from reactor and extract inner object.
+	pub fn into_inner(self) -> T {
+		self.registration.into_inner().0
+	}
+
+	/// Wait for read events.
+	///
+	/// If no events were pending the waker in `context` is registered to be notified when new
+	/// read events are pending.
+	pub fn poll_read_ready(&self, context: &mut Context<'_>) -> Poll<io::Result<AsyncFdReadyGuard<'_, T>>> {
+		let ready = futures_util::ready!(self.registration.poll_read_ready(context))?;
+		Poll::Ready(Ok(AsyncFdReadyGuard {
+			async_fd: self,
+			ready: Some(ready),
+			write: false,
+		}))
+	}
+
+	/// Wait for write events.
+	///
+	/// If no events were pending the waker in `context` is registered to be notified when new
+	/// write events are pending.
+	pub fn poll_write_ready(&self, context: &mut Context<'_>) -> Poll<io::Result<AsyncFdReadyGuard<'_, T>>> {
+		let ready = futures_util::ready!(self.registration.poll_write_ready(context))?;
+		Poll::Ready(Ok(AsyncFdReadyGuard {
+			async_fd: self,
+			ready: Some(ready),
+			write: true,
+		}))
+	}
+}
+
+/// Read or write events returned by [`AsyncFd`](struct.AsyncFd.html).
+///
+/// Unless [`clear_ready`](#method.clear_ready) is called (directly or by
+/// [`try_io`](#method.try_io) when the operation would block) the events are still pending after
+/// the guard is dropped.
+#[derive(Debug)]
+#[must_use = "events are retained unless cleared"]
+pub struct AsyncFdReadyGuard<'a, T>
+where
+	T: AsRawFd,
+{
+	async_fd: &'a AsyncFd<T>,
+	ready: Option<Ready>, // None if cleared
+	write: bool,
+}
+
+impl<'a, T> AsyncFdReadyGuard<'a, T>
+where
+	T: AsRawFd,
+{
+	/// The pending events (empty after clearing them)
+	pub fn ready(&self) -> Ready {
+		self.ready.unwrap_or(Ready::EMPTY)
+	}
+
+	/// Mark events as consumed; use after an operation returned `WouldBlock`.
+	pub fn clear_ready(&mut self) {
+		self.ready = None;
+	}
+
+	/// Run IO operation; if it returns `WouldBlock` the events are cleared and `Poll::Pending`
+	/// is returned.
+	///
+	/// Doesn't register a waker; after `Poll::Pending` poll for readiness again.
+	pub fn try_io<F, R>(&mut self, op: F) -> Poll<io::Result<R>>
+	where
+		F: FnOnce(&T) -> io::Result<R>,
+	{
+		match op(self.get_inner()) {
+			Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+				self.clear_ready();
+				Poll::Pending
+			},
+			r => Poll::Ready(r),
+		}
+	}
+
+	/// The `AsyncFd` the events belong to
+	pub fn get_ref(&self) -> &'a AsyncFd<T> {
+		self.async_fd
+	}
+
+	/// The inner object of the `AsyncFd`
+	pub fn get_inner(&self) -> &'a T {
+		self.async_fd.get_ref()
+	}
+}
+
+impl<T> Drop for AsyncFdReadyGuard<'_, T>
+where
+	T: AsRawFd,
+{
+	fn drop(&mut self) {
+		if let Some(ready) = self.ready {
+			if self.write {
+				self.async_fd.registration.retain_write_ready(ready);
+			} else {
+				self.async_fd.registration.retain_read_ready(ready);
+			}
+		}
+	}
+}
@@ -0,0 +1,88 @@
+use std::fmt;
+use std::io;
+use std::sync::{mpsc, Arc, Mutex};
+
+type Closing = Box<dyn Send>;
+
+/// Closes (drops) IO objects in a helper thread.
+///
+/// Closing a socket can block or take a while, e.g. with `SO_LINGER` or large kernel buffers;
+/// IO with an offloader (see [`PollEvented::set_close_offloader`]) is deregistered in the
+/// dropping thread, but the file descriptor is closed in the helper thread.
+///
+/// Clones share the helper thread; it exits once all clones (and the IO handed to them) are gone.
+///
+/// [`PollEvented::set_close_offloader`]: struct.PollEvented.html#method.set_close_offloader
+#[derive(Clone)]
+pub struct CloseOffloader {
+	sender: Arc<Mutex<mpsc::Sender<Closing>>>,
+}
+
+impl CloseOffloader {
+	/// Start helper thread.
+	pub fn new() -> io::Result<Self> {
+		let (sender, receiver) = mpsc::channel::<Closing>();
+		std::thread::Builder::new()
+			.name("fumio-close".into())
+			.spawn(move || {
+				for io in receiver {
+					drop(io);
+				}
+			})?;
+		Ok(Self {
+			sender: Arc::new(Mutex::new(sender)),
+		})
+	}
+
+	/// Close `io` in the helper thread (or right away if the helper thread is gone).
+	#[allow(clippy::missing_panics_doc)] // only on poisoned locks
+	pub fn close<T>(&self, io: T)
+	where
+		T: Send + 'static,
+	{
+		let result = self.sender.lock().unwrap().send(Box::new(io));
+		// helper thread gone (panicked?): close here
+		drop(result);
+	}
+}
+
+impl fmt::Debug for CloseOffloader {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("CloseOffloader")
+			.field("sender", &())
+			.finish()
+	}
+}
+
+// remembers how to send the (type erased) IO of a registration to the offloader
+pub(super) struct Offload<E> {
+	offloader: CloseOffloader,
+	close: fn(&CloseOffloader, E),
+}
+
+impl<E> Offload<E>
+where
+	E: Send + 'static,
+{
+	pub(super) fn new(offloader: CloseOffloader) -> Self {
+		Self {
+			offloader,
+			close: CloseOffloader::close::<E>,
+		}
+	}
+}
+
+impl<E> Offload<E> {
+	pub(super) fn close(&self, io: E) {
+		(self.close)(&self.offloader, io);
+	}
+}
+
+impl<E> fmt::Debug for Offload<E> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Offload")
+			.field("offloader", &self.offloader)
+			.field("close", &())
+			.finish()
+	}
+}
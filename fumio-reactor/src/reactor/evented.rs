@@ -1,108 +1,221 @@
 use crate::helper::async_io;
-use crate::reactor::{LazyHandle, Registration};
+use crate::reactor::{CloseOffloader, Error, LazyHandle, Ready, Registration};
+use fumio_utils::coop;
 use std::io;
 use std::pin::Pin;
-use std::sync::Once;
 use std::task::{Context, Poll};
 
 /// A wrapper for `Read` and `Write` based IO sources.
 #[derive(Debug)]
 pub struct PollEvented<E>
 where
-	E: mio::Evented,
+	E: mio::event::Source,
 {
 	registration: Registration<E>,
-	registered: Once,
+	registered: bool,
+	// failed registration, reported by the next `poll_*` call
+	register_error: Option<io::Error>,
 	handle: LazyHandle,
 }
 
 impl<E> PollEvented<E>
 where
-	E: mio::Evented,
+	E: mio::event::Source,
 {
 	/// Wrap io and lazily bind to `handle` on first use.
+	///
+	/// Read operations wait for `READABLE | READ_CLOSED | ERROR | PRIORITY`, write operations for
+	/// `WRITABLE | WRITE_CLOSED | ERROR`.
 	pub fn new(io: E, handle: LazyHandle) -> Self {
+		Self::with_masks(
+			io,
+			Ready::READABLE | Ready::READ_CLOSED | Ready::ERROR | Ready::PRIORITY,
+			Ready::WRITABLE | Ready::WRITE_CLOSED | Ready::ERROR,
+			handle,
+		)
+	}
+
+	/// Wrap io and lazily bind to `handle` on first use; read and write operations wait for the
+	/// events in `read_mask` and `write_mask` respectively.
+	///
+	/// Events can be in both masks (e.g. `ERROR`) or none (they are dropped then).
+	pub fn with_masks(io: E, read_mask: Ready, write_mask: Ready, handle: LazyHandle) -> Self {
 		Self {
-			registration: Registration::new(
-				io,
-				mio::Ready::all() - mio::Ready::writable(),
-				mio::Ready::writable() | platform::hup(),
-			),
-			registered: Once::new(),
+			registration: Registration::new(io, read_mask, write_mask),
+			registered: false,
+			register_error: None,
 			handle,
 		}
 	}
 
-	fn register(&self) {
-		self.registered.call_once(|| {
-			let _ = self.registration.register(
-				&self.handle.bind().expect("no reactor present"),
-				mio::Ready::all(),
-				mio::PollOpt::edge(),
-			);
-		});
+	/// Register with the reactor now instead of on first use.
+	///
+	/// Required before using the shared [`try_read`](#method.try_read) and
+	/// [`try_write`](#method.try_write) operations.
+	///
+	/// If the registration fails (including when no reactor is present) the error is returned by
+	/// the next `poll_*` or `try_mut_*` call, and registering is tried again on the next use.
+	pub fn register(&mut self) {
+		if !self.registered {
+			let Some(handle) = self.handle.bind() else {
+				self.register_error = Some(Error::NoReactor.into());
+				return;
+			};
+			match self.registration.register(&handle, mio::Interest::READABLE | mio::Interest::WRITABLE) {
+				Ok(()) => self.registered = true,
+				Err(e) => self.register_error = Some(e),
+			}
+		}
+	}
+
+	// register if needed and report a failed registration (once)
+	fn check_register(&mut self) -> io::Result<()> {
+		self.register();
+		self.register_error.take().map_or(Ok(()), Err)
 	}
 
 	/// Try a read operation with mutable IO
 	///
 	/// If read operation fails make sure to get notified when read readiness is signalled.
+	///
+	/// Consumes cooperative budget (see `fumio_utils::coop`); returns `Poll::Pending` without
+	/// trying the operation when the budget is exhausted.
 	pub fn try_mut_read<F, T>(&mut self, context: &mut Context<'_>, mut read_op: F) -> Poll<io::Result<T>>
 	where
 		F: FnMut(&mut E) -> Poll<io::Result<T>>,
 	{
+		futures_util::ready!(coop::poll_proceed(context));
 		if let Poll::Ready(v) = read_op(self.io_mut()) {
 			return Poll::Ready(v);
 		}
-		self.register();
-		futures_util::ready!(self.registration.poll_read_ready(context))?;
-		if let Poll::Ready(v) = read_op(self.io_mut()) {
+		self.check_register()?;
+		loop {
+			futures_util::ready!(self.registration.poll_read_ready(context))?;
+			if let Poll::Ready(v) = read_op(self.io_mut()) {
+				return Poll::Ready(v);
+			}
+			// registration said we're ready, but read_op failed; the events were cleared, so
+			// polling readiness again registers for new events
+		}
+	}
+
+	/// Try a read operation with shared IO
+	///
+	/// Like [`try_mut_read`](#method.try_mut_read), but the IO must already be
+	/// [registered](#method.register) (fails otherwise).  Useful if read and write operations are
+	/// run from different tasks.
+	pub fn try_read<F, T>(&self, context: &mut Context<'_>, mut read_op: F) -> Poll<io::Result<T>>
+	where
+		F: FnMut(&E) -> Poll<io::Result<T>>,
+	{
+		futures_util::ready!(coop::poll_proceed(context));
+		if let Poll::Ready(v) = read_op(self.io_ref()) {
 			return Poll::Ready(v);
 		}
-		// registration said we're ready, but read_op failed
-		// come back later to try again
-		context.waker().wake_by_ref();
-		Poll::Pending
+		loop {
+			futures_util::ready!(self.registration.poll_read_ready(context))?;
+			if let Poll::Ready(v) = read_op(self.io_ref()) {
+				return Poll::Ready(v);
+			}
+			// registration said we're ready, but read_op failed; the events were cleared, so
+			// polling readiness again registers for new events
+		}
+	}
+
+	/// Current read and write events, without clearing them
+	///
+	/// Meant for diagnostics; empty if not registered (yet).
+	pub fn peek_ready(&self) -> Ready {
+		let read = self.registration.peek_read_ready().unwrap_or(Ready::EMPTY);
+		let write = self.registration.peek_write_ready().unwrap_or(Ready::EMPTY);
+		read | write
 	}
 
 	/// Clears all pending read events (and returns them)
 	///
 	/// If no events were pending (and possibly even if there were) the waker in `context` is
 	/// registered to be notified when new read events are pending.
-	pub fn poll_read_ready(&self, context: &mut Context<'_>) -> Poll<io::Result<mio::Ready>> {
-		self.register();
+	pub fn poll_read_ready(&mut self, context: &mut Context<'_>) -> Poll<io::Result<Ready>> {
+		self.check_register()?;
 		self.registration.poll_read_ready(context)
 	}
 
 	/// Try a write operation with mutable IO
 	///
 	/// If write operation fails make sure to get notified when write readiness is signalled.
+	///
+	/// Consumes cooperative budget (see `fumio_utils::coop`); returns `Poll::Pending` without
+	/// trying the operation when the budget is exhausted.
 	pub fn try_mut_write<F, T>(&mut self, context: &mut Context<'_>, mut write_op: F) -> Poll<io::Result<T>>
 	where
 		F: FnMut(&mut E) -> Poll<io::Result<T>>,
 	{
+		futures_util::ready!(coop::poll_proceed(context));
 		if let Poll::Ready(v) = write_op(self.io_mut()) {
 			return Poll::Ready(v);
 		}
-		self.register();
-		futures_util::ready!(self.registration.poll_write_ready(context))?;
-		if let Poll::Ready(v) = write_op(self.io_mut()) {
+		self.check_register()?;
+		loop {
+			futures_util::ready!(self.registration.poll_write_ready(context))?;
+			if let Poll::Ready(v) = write_op(self.io_mut()) {
+				return Poll::Ready(v);
+			}
+			// registration said we're ready, but write_op failed; the events were cleared, so
+			// polling readiness again registers for new events
+		}
+	}
+
+	/// Try a write operation with shared IO
+	///
+	/// Like [`try_mut_write`](#method.try_mut_write), but the IO must already be
+	/// [registered](#method.register) (fails otherwise).  Useful if read and write operations are
+	/// run from different tasks.
+	pub fn try_write<F, T>(&self, context: &mut Context<'_>, mut write_op: F) -> Poll<io::Result<T>>
+	where
+		F: FnMut(&E) -> Poll<io::Result<T>>,
+	{
+		futures_util::ready!(coop::poll_proceed(context));
+		if let Poll::Ready(v) = write_op(self.io_ref()) {
 			return Poll::Ready(v);
 		}
-		// registration said we're ready, but write_op failed
-		// come back later to try again
-		context.waker().wake_by_ref();
-		Poll::Pending
+		loop {
+			futures_util::ready!(self.registration.poll_write_ready(context))?;
+			if let Poll::Ready(v) = write_op(self.io_ref()) {
+				return Poll::Ready(v);
+			}
+			// registration said we're ready, but write_op failed; the events were cleared, so
+			// polling readiness again registers for new events
+		}
 	}
 
 	/// Clears all pending write events (and returns them)
 	///
 	/// If no events were pending (and possibly even if there were) the waker in `context` is
 	/// registered to be notified when new write events are pending.
-	pub fn poll_write_ready(&self, context: &mut Context<'_>) -> Poll<io::Result<mio::Ready>> {
-		self.register();
+	pub fn poll_write_ready(&mut self, context: &mut Context<'_>) -> Poll<io::Result<Ready>> {
+		self.check_register()?;
 		self.registration.poll_write_ready(context)
 	}
 
+	/// Move IO to the reactor `handle` is bound to, e.g. from a bootstrap runtime to a worker
+	/// runtime.
+	///
+	/// Pending events are kept, and tasks waiting for events are woken.  If not registered yet
+	/// only the handle to register with on first use is replaced.
+	pub fn rebind(&mut self, handle: LazyHandle) -> io::Result<()> {
+		self.handle = handle;
+		self.register_error = None;
+		if self.registered {
+			let handle = self.handle.bind().ok_or(Error::NoReactor)?;
+			if let Err(e) = self.registration.rebind(&handle) {
+				// register again on next use
+				self.registered = false;
+				return Err(e);
+			}
+		}
+		Ok(())
+	}
+
 	/// Retrieve reference to the contained IO
 	pub fn io_ref(&self) -> &E {
 		self.registration.io_ref()
@@ -124,42 +237,48 @@ where
 		}
 	}
 
+	/// Close IO in the helper thread of `offloader` when dropped (`None`: close right away, the
+	/// default).
+	pub fn set_close_offloader(&mut self, offloader: Option<CloseOffloader>)
+	where
+		E: Send + 'static,
+	{
+		self.registration.set_close_offloader(offloader);
+	}
+
 	/// Detach inner io from reactor and extract it.
 	pub fn into_inner(self) -> E {
 		self.registration.into_inner()
 	}
 }
 
-
-#[cfg(unix)]
-mod platform {
-	pub fn hup() -> mio::Ready {
-		mio::unix::UnixReady::hup().into()
-	}
-}
-
-#[cfg(not(unix))]
-mod platform {
-	pub fn hup() -> mio::Ready {
-		mio::Ready::empty()
-	}
-}
-
-impl<R: mio::Evented + io::Read + Unpin> futures_io::AsyncRead for PollEvented<R> {
+impl<R: mio::event::Source + io::Read + Unpin> futures_io::AsyncRead for PollEvented<R> {
 	fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
 		self.try_mut_read(cx, |io| {
 			async_io(|| io.read(buf))
 		})
 	}
+
+	fn poll_read_vectored(mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &mut [io::IoSliceMut<'_>]) -> Poll<io::Result<usize>> {
+		self.try_mut_read(cx, |io| {
+			async_io(|| io.read_vectored(bufs))
+		})
+	}
 }
 
-impl<R: mio::Evented + io::Write + Unpin> futures_io::AsyncWrite for PollEvented<R> {
+impl<R: mio::event::Source + io::Write + Unpin> futures_io::AsyncWrite for PollEvented<R> {
 	fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
 		self.try_mut_write(cx, |io| {
 			async_io(|| io.write(buf))
 		})
 	}
 
+	fn poll_write_vectored(mut self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[io::IoSlice<'_>]) -> Poll<io::Result<usize>> {
+		self.try_mut_write(cx, |io| {
+			async_io(|| io.write_vectored(bufs))
+		})
+	}
+
 	fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
 		self.try_mut_write(cx, |io| {
 			async_io(|| io.flush())
@@ -177,3 +296,146 @@ impl<R: mio::Evented + io::Write + Unpin> futures_io::AsyncWrite for PollEvented
 		Poll::Ready(Ok(()))
 	}
 }
+
+// uses the atomics outside of `loom::model`
+#[cfg(all(test, not(loom)))]
+mod test {
+	use super::*;
+	use crate::reactor::Reactor;
+
+	// event source the OS refuses to register
+	#[derive(Debug)]
+	struct Unpollable;
+
+	impl mio::event::Source for Unpollable {
+		fn register(&mut self, _registry: &mio::Registry, _token: mio::Token, _interests: mio::Interest) -> io::Result<()> {
+			Err(io::Error::from_raw_os_error(1)) // EPERM, like regular files on linux
+		}
+
+		fn reregister(&mut self, _registry: &mio::Registry, _token: mio::Token, _interests: mio::Interest) -> io::Result<()> {
+			unreachable!()
+		}
+
+		fn deregister(&mut self, _registry: &mio::Registry) -> io::Result<()> {
+			unreachable!()
+		}
+	}
+
+	#[test]
+	fn register_error() {
+		let reactor = Reactor::new().unwrap();
+		let mut evented = PollEvented::new(Unpollable, reactor.handle().into());
+		let waker = futures_util::task::noop_waker();
+		let mut context = Context::from_waker(&waker);
+		match evented.poll_read_ready(&mut context) {
+			Poll::Ready(Err(e)) => assert_eq!(e.raw_os_error(), Some(1)),
+			r => panic!("unexpected result: {:?}", r),
+		}
+		// registering is tried again (and fails again)
+		match evented.poll_write_ready(&mut context) {
+			Poll::Ready(Err(e)) => assert_eq!(e.raw_os_error(), Some(1)),
+			r => panic!("unexpected result: {:?}", r),
+		}
+	}
+
+	#[test]
+	fn no_reactor() {
+		let mut evented = PollEvented::new(Unpollable, LazyHandle::new());
+		let waker = futures_util::task::noop_waker();
+		let mut context = Context::from_waker(&waker);
+		match evented.poll_read_ready(&mut context) {
+			Poll::Ready(Err(e)) => assert_eq!(crate::Error::from_io(&e), Some(crate::Error::NoReactor)),
+			r => panic!("unexpected result: {:?}", r),
+		}
+	}
+
+	#[test]
+	fn rebind() {
+		let mut first = Reactor::new().unwrap();
+		let mut second = Reactor::new().unwrap();
+		let socket = mio::net::UdpSocket::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+		let target = socket.local_addr().unwrap();
+		let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+		let mut evented = PollEvented::new(socket, first.handle().into());
+		evented.register();
+		let waker = futures_util::task::noop_waker();
+		let mut context = Context::from_waker(&waker);
+		let timeout = Some(std::time::Duration::from_millis(100));
+
+		sender.send_to(b"1", target).unwrap();
+		first.poll(timeout).unwrap();
+		evented.rebind(second.handle().into()).unwrap();
+		// event received by the first reactor is still pending
+		match evented.poll_read_ready(&mut context) {
+			Poll::Ready(Ok(ready)) => assert!(ready.contains(Ready::READABLE)),
+			r => panic!("unexpected result: {:?}", r),
+		}
+		drop(first);
+
+		let mut buf = [0u8; 4];
+		evented.io_ref().recv_from(&mut buf).unwrap();
+		assert!(evented.poll_read_ready(&mut context).is_pending());
+		sender.send_to(b"2", target).unwrap();
+		second.poll(timeout).unwrap();
+		assert!(evented.poll_read_ready(&mut context).is_ready());
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn masks() {
+		let mut reactor = Reactor::new().unwrap();
+		let (sender, receiver) = mio::unix::pipe::new().unwrap();
+		drop(sender);
+		// hang up is only an error for this one; don't wake readers for it
+		let mut evented = PollEvented::with_masks(receiver, Ready::READABLE, Ready::READ_CLOSED, reactor.handle().into());
+		let waker = futures_util::task::noop_waker();
+		let mut context = Context::from_waker(&waker);
+		assert!(evented.poll_read_ready(&mut context).is_pending());
+		reactor.poll(Some(std::time::Duration::from_millis(0))).unwrap();
+		match evented.poll_write_ready(&mut context) {
+			Poll::Ready(Ok(ready)) => assert_eq!(ready, Ready::READ_CLOSED),
+			r => panic!("unexpected result: {:?}", r),
+		}
+		assert!(evented.poll_read_ready(&mut context).is_pending());
+	}
+
+	#[test]
+	fn spurious_readiness() {
+		use std::sync::Arc;
+		use std::sync::atomic::{AtomicUsize, Ordering};
+
+		struct CountWakes(AtomicUsize);
+		impl futures_util::task::ArcWake for CountWakes {
+			fn wake_by_ref(arc_self: &Arc<Self>) {
+				arc_self.0.fetch_add(1, Ordering::SeqCst);
+			}
+		}
+
+		let mut reactor = Reactor::new().unwrap();
+		let socket = mio::net::UdpSocket::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+		let target = socket.local_addr().unwrap();
+		let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+		let mut evented = PollEvented::new(socket, reactor.handle().into());
+		evented.register();
+		let counter = Arc::new(CountWakes(AtomicUsize::new(0)));
+		let waker = futures_util::task::waker(counter.clone());
+		let mut context = Context::from_waker(&waker);
+		let timeout = Some(std::time::Duration::from_millis(100));
+
+		sender.send_to(b"1", target).unwrap();
+		reactor.poll(timeout).unwrap();
+		// pretend the read failed despite readiness
+		let mut attempts = 0;
+		let r = evented.try_mut_read(&mut context, |_| -> Poll<io::Result<()>> {
+			attempts += 1;
+			Poll::Pending
+		});
+		assert!(r.is_pending());
+		assert_eq!(attempts, 2);
+		// no self-wake; registered for the next event instead
+		assert_eq!(counter.0.load(Ordering::SeqCst), 0);
+		sender.send_to(b"2", target).unwrap();
+		reactor.poll(timeout).unwrap();
+		assert_eq!(counter.0.load(Ordering::SeqCst), 1);
+	}
+}
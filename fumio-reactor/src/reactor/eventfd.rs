@@ -0,0 +1,73 @@
+use crate::reactor::fd::{poll_read_u64, FileDesc};
+use crate::reactor::{AsyncFd, LazyHandle};
+use std::future::Future;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Linux `eventfd` registered with the reactor.
+///
+/// The raw file descriptor can be passed to other libraries to signal events; reading returns
+/// (and resets) the counter.
+#[derive(Debug)]
+pub struct EventFd {
+	fd: AsyncFd<FileDesc>,
+}
+
+impl EventFd {
+	/// Create new eventfd with given initial counter value, registered with the
+	/// [`current`](fn.current.html) reactor.
+	pub fn new(initval: u32) -> io::Result<Self> {
+		Self::with_handle(initval, LazyHandle::new())
+	}
+
+	/// Create new eventfd with given initial counter value, registered with the reactor `handle`
+	/// is bound to.
+	pub fn with_handle(initval: u32, handle: LazyHandle) -> io::Result<Self> {
+		let fd = FileDesc::from_result(unsafe {
+			libc::eventfd(initval, libc::EFD_CLOEXEC | libc::EFD_NONBLOCK)
+		})?;
+		Ok(Self {
+			fd: AsyncFd::with_handle(fd, handle)?,
+		})
+	}
+
+	/// Add `value` to the counter.
+	///
+	/// Fails with `WouldBlock` if the counter would overflow.
+	pub fn notify(&self, value: u64) -> io::Result<()> {
+		self.fd.get_ref().write_u64(value)
+	}
+
+	/// Read and reset the counter, or register context to be woken once it is non-zero.
+	pub fn poll_read(&mut self, context: &mut Context<'_>) -> Poll<io::Result<u64>> {
+		poll_read_u64(&self.fd, context)
+	}
+
+	/// Wait for the counter to become non-zero; completes with the (now reset) counter value.
+	pub fn read(&mut self) -> EventFdRead<'_> {
+		EventFdRead { event_fd: self }
+	}
+}
+
+impl AsRawFd for EventFd {
+	fn as_raw_fd(&self) -> RawFd {
+		self.fd.get_ref().as_raw_fd()
+	}
+}
+
+/// Pending read of an [`EventFd`](struct.EventFd.html) counter
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct EventFdRead<'a> {
+	event_fd: &'a mut EventFd,
+}
+
+impl Future for EventFdRead<'_> {
+	type Output = io::Result<u64>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		self.event_fd.poll_read(cx)
+	}
+}
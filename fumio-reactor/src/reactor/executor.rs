@@ -1,9 +1,9 @@
 use fumio_utils::current::Current;
 use crate::reactor::Handle;
-use futures_executor::Enter;
+use fumio_utils::enter::Enter;
 
 thread_local! {
-	static CURRENT: Current<Handle> = Current::new();
+	static CURRENT: Current<Handle> = const { Current::new() };
 }
 
 pub(crate) fn enter<F, T>(handle: Handle, enter: &mut Enter, f: F) -> T
@@ -13,6 +13,13 @@ where
 	Current::enter(&CURRENT, enter, handle, f)
 }
 
+pub(crate) fn enter_nonblocking<F, T>(handle: Handle, f: F) -> T
+where
+	F: FnOnce() -> T
+{
+	Current::enter_nonblocking(&CURRENT, handle, f)
+}
+
 /// Retrieve the current handle.
 pub fn current() -> Option<Handle> {
 	#[allow(clippy::redundant_closure_for_method_calls)] // sadly the suggestion doesn't compile
@@ -0,0 +1,62 @@
+use crate::reactor::AsyncFd;
+use fumio_utils::coop;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::task::{Context, Poll};
+
+// owned (non-blocking) file descriptor
+#[derive(Debug)]
+pub(super) struct FileDesc(RawFd);
+
+impl FileDesc {
+	// takes ownership of the result of a libc call returning a new fd
+	pub(super) fn from_result(fd: libc::c_int) -> io::Result<Self> {
+		if fd < 0 {
+			Err(io::Error::last_os_error())
+		} else {
+			Ok(Self(fd))
+		}
+	}
+
+	pub(super) fn read_u64(&self) -> io::Result<u64> {
+		let mut buf = [0u8; 8];
+		let r = unsafe { libc::read(self.0, buf.as_mut_ptr().cast(), buf.len()) };
+		if r < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		assert_eq!(r, 8, "short read on eventfd/timerfd");
+		Ok(u64::from_ne_bytes(buf))
+	}
+
+	pub(super) fn write_u64(&self, value: u64) -> io::Result<()> {
+		let buf = value.to_ne_bytes();
+		let r = unsafe { libc::write(self.0, buf.as_ptr().cast(), buf.len()) };
+		if r < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		Ok(())
+	}
+}
+
+impl AsRawFd for FileDesc {
+	fn as_raw_fd(&self) -> RawFd {
+		self.0
+	}
+}
+
+impl Drop for FileDesc {
+	fn drop(&mut self) {
+		unsafe { libc::close(self.0); }
+	}
+}
+
+// read counter from eventfd/timerfd
+pub(super) fn poll_read_u64(fd: &AsyncFd<FileDesc>, context: &mut Context<'_>) -> Poll<io::Result<u64>> {
+	futures_util::ready!(coop::poll_proceed(context));
+	loop {
+		let mut guard = futures_util::ready!(fd.poll_read_ready(context))?;
+		if let Poll::Ready(r) = guard.try_io(FileDesc::read_u64) {
+			return Poll::Ready(r);
+		}
+	}
+}
@@ -25,10 +25,7 @@ impl LazyHandle {
 	/// Return the `Handle` this was created with or [`current`](fn.current.html) if no specific
 	/// handle was specified.
 	pub fn bind(&self) -> Option<Handle> {
-		match &self.handle {
-			Some(handle) => Some(handle.clone()),
-			None => super::current(),
-		}
+		self.handle.as_ref().map_or_else(super::current, |handle| Some(handle.clone()))
 	}
 }
 
@@ -0,0 +1,88 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Snapshot of the counters of a [`Reactor`](struct.Reactor.html).
+///
+/// All counters start at zero when the reactor is created and wrap around on overflow.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReactorMetrics {
+	/// Number of times the reactor polled for events
+	pub polls: u64,
+	/// Number of IO events dispatched to registered resources
+	pub events: u64,
+	/// Total time spent waiting for events (i.e. parked)
+	pub park_time: Duration,
+	/// Number of times the reactor [`waker`](struct.Reactor.html#method.waker) was woken
+	pub wakeups: u64,
+	/// Number of wakeups that had to interrupt a blocking poll (with a syscall); other wakeups
+	/// were coalesced with a pending one or happened while not polling
+	pub wakeup_syscalls: u64,
+}
+
+/// Statistics of a single [`Reactor::poll_stats`](struct.Reactor.html#method.poll_stats) call.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PollStats {
+	/// Number of events dispatched to registered resources
+	pub events: usize,
+	/// Whether the reactor was woken through its [`waker`](struct.Reactor.html#method.waker)
+	/// (before or while polling)
+	pub woken: bool,
+	/// Time spent waiting for events
+	pub blocked: Duration,
+}
+
+// the reactor updates the counters, handles can read them from any thread
+#[derive(Debug, Default)]
+pub(super) struct Counters {
+	polls: AtomicU64,
+	events: AtomicU64,
+	park_nanos: AtomicU64,
+	wakes: Arc<WakeCounters>,
+}
+
+impl Counters {
+	pub(super) fn record_poll(&self, park_time: Duration) {
+		// ~584 years of nanoseconds fit into u64
+		#[allow(clippy::cast_possible_truncation)]
+		let nanos = park_time.as_nanos() as u64;
+		self.polls.fetch_add(1, Ordering::Relaxed);
+		self.park_nanos.fetch_add(nanos, Ordering::Relaxed);
+	}
+
+	pub(super) fn record_event(&self) {
+		self.events.fetch_add(1, Ordering::Relaxed);
+	}
+
+	// shared with the reactor waker
+	pub(super) fn wake_counters(&self) -> Arc<WakeCounters> {
+		self.wakes.clone()
+	}
+
+	pub(super) fn snapshot(&self) -> ReactorMetrics {
+		ReactorMetrics {
+			polls: self.polls.load(Ordering::Relaxed),
+			events: self.events.load(Ordering::Relaxed),
+			park_time: Duration::from_nanos(self.park_nanos.load(Ordering::Relaxed)),
+			wakeups: self.wakes.wakes.load(Ordering::Relaxed),
+			wakeup_syscalls: self.wakes.syscalls.load(Ordering::Relaxed),
+		}
+	}
+}
+
+// updated by the reactor waker from any thread
+#[derive(Debug, Default)]
+pub(super) struct WakeCounters {
+	wakes: AtomicU64,
+	syscalls: AtomicU64,
+}
+
+impl WakeCounters {
+	pub(super) fn record_wake(&self) {
+		self.wakes.fetch_add(1, Ordering::Relaxed);
+	}
+
+	pub(super) fn record_syscall(&self) {
+		self.syscalls.fetch_add(1, Ordering::Relaxed);
+	}
+}
@@ -0,0 +1,151 @@
+use std::fmt;
+use std::ops;
+
+/// Readiness of an IO resource, as reported by the reactor.
+///
+/// A set of flags; combine them with `|`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Ready(usize);
+
+const READABLE: usize = 0b00_0001;
+const WRITABLE: usize = 0b00_0010;
+const READ_CLOSED: usize = 0b00_0100;
+const WRITE_CLOSED: usize = 0b00_1000;
+const ERROR: usize = 0b01_0000;
+const PRIORITY: usize = 0b10_0000;
+const ALL: usize = 0b11_1111;
+
+impl Ready {
+	/// No readiness
+	pub const EMPTY: Self = Self(0);
+	/// Readable
+	pub const READABLE: Self = Self(READABLE);
+	/// Writable
+	pub const WRITABLE: Self = Self(WRITABLE);
+	/// Read half closed (e.g. remote shut down writing)
+	pub const READ_CLOSED: Self = Self(READ_CLOSED);
+	/// Write half closed (e.g. remote closed the connection)
+	pub const WRITE_CLOSED: Self = Self(WRITE_CLOSED);
+	/// An error occured; retrieve it with a read, write or `take_error`
+	pub const ERROR: Self = Self(ERROR);
+	/// Priority (out-of-band) data available
+	pub const PRIORITY: Self = Self(PRIORITY);
+	/// All readiness flags
+	pub const ALL: Self = Self(ALL);
+
+	/// Whether no flag is set
+	pub const fn is_empty(self) -> bool {
+		0 == self.0
+	}
+
+	/// Whether all flags from `other` are set
+	pub const fn contains(self, other: Self) -> bool {
+		other.0 == self.0 & other.0
+	}
+
+	/// Whether `READABLE` is set
+	pub const fn is_readable(self) -> bool {
+		self.contains(Self::READABLE)
+	}
+
+	/// Whether `WRITABLE` is set
+	pub const fn is_writable(self) -> bool {
+		self.contains(Self::WRITABLE)
+	}
+
+	/// Whether `READ_CLOSED` is set
+	pub const fn is_read_closed(self) -> bool {
+		self.contains(Self::READ_CLOSED)
+	}
+
+	/// Whether `WRITE_CLOSED` is set
+	pub const fn is_write_closed(self) -> bool {
+		self.contains(Self::WRITE_CLOSED)
+	}
+
+	/// Whether `ERROR` is set
+	pub const fn is_error(self) -> bool {
+		self.contains(Self::ERROR)
+	}
+
+	/// Whether `PRIORITY` is set
+	pub const fn is_priority(self) -> bool {
+		self.contains(Self::PRIORITY)
+	}
+
+	pub(crate) fn from_event(event: &mio::event::Event) -> Self {
+		let mut ready = Self::EMPTY;
+		if event.is_readable() { ready |= Self::READABLE; }
+		if event.is_writable() { ready |= Self::WRITABLE; }
+		if event.is_read_closed() { ready |= Self::READ_CLOSED; }
+		if event.is_write_closed() { ready |= Self::WRITE_CLOSED; }
+		if event.is_error() { ready |= Self::ERROR; }
+		if event.is_priority() { ready |= Self::PRIORITY; }
+		ready
+	}
+
+	pub(crate) const fn as_usize(self) -> usize {
+		self.0
+	}
+
+	pub(crate) const fn from_usize(value: usize) -> Self {
+		Self(value & ALL)
+	}
+}
+
+impl ops::BitOr for Ready {
+	type Output = Self;
+
+	fn bitor(self, other: Self) -> Self {
+		Self(self.0 | other.0)
+	}
+}
+
+impl ops::BitOrAssign for Ready {
+	fn bitor_assign(&mut self, other: Self) {
+		self.0 |= other.0;
+	}
+}
+
+impl ops::BitAnd for Ready {
+	type Output = Self;
+
+	fn bitand(self, other: Self) -> Self {
+		Self(self.0 & other.0)
+	}
+}
+
+impl ops::Sub for Ready {
+	type Output = Self;
+
+	fn sub(self, other: Self) -> Self {
+		Self(self.0 & !other.0)
+	}
+}
+
+impl fmt::Debug for Ready {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		const NAMES: [(Ready, &str); 6] = [
+			(Ready::READABLE, "READABLE"),
+			(Ready::WRITABLE, "WRITABLE"),
+			(Ready::READ_CLOSED, "READ_CLOSED"),
+			(Ready::WRITE_CLOSED, "WRITE_CLOSED"),
+			(Ready::ERROR, "ERROR"),
+			(Ready::PRIORITY, "PRIORITY"),
+		];
+		let mut first = true;
+		for (flag, name) in &NAMES {
+			if self.contains(*flag) {
+				if !first {
+					f.write_str(" | ")?;
+				}
+				f.write_str(name)?;
+				first = false;
+			}
+		}
+		if first {
+			f.write_str("(empty)")?;
+		}
+		Ok(())
+	}
+}
@@ -1,182 +1,182 @@
-use super::*;
+use super::{CloseOffloader, Error, Handle, LazyHandle, ReactorTask, Ready};
+use super::close_offloader::Offload;
 use std::io;
-use std::mem::ManuallyDrop;
-use std::sync::atomic::{AtomicUsize, Ordering};
 use std::task::{Context, Poll};
 
-// spinlock for ReactorTask
-#[derive(Debug)]
-struct TaskState {
-	// low bit is lock bit
-	task: AtomicUsize,
-}
-
-impl TaskState {
-	const fn new() -> Self {
-		Self {
-			task: AtomicUsize::new(0),
-		}
-	}
-
-	fn lock(&self) -> TaskStateLock<'_> {
-		let mut state;
-		loop {
-			state = self.task.fetch_or(1, Ordering::Acquire);
-			if 0 == state & 1 {
-				break; // no lock before, so we acquired the lock
-			}
-			core::sync::atomic::spin_loop_hint();
-		}
-		TaskStateLock {
-			state: self,
-			task: Self::_task_from_state(state),
-		}
-	}
-
-	fn _task_from_state(state: usize) -> ManuallyDrop<Option<ReactorTask>> {
-		let task = state & !1;
-		ManuallyDrop::new(if task != 0 {
-			Some(ReactorTask::from_token(mio::Token(task)))
-		} else {
-			None
-		})
-	}
-}
-
-impl Drop for TaskState {
-	fn drop(&mut self) {
-		ManuallyDrop::into_inner(Self::_task_from_state(*self.task.get_mut()));
-	}
-}
-
-struct TaskStateLock<'a> {
-	state: &'a TaskState,
-	task: ManuallyDrop<Option<ReactorTask>>,
-}
-
-impl TaskStateLock<'_> {
-	fn as_ref(&self) -> Option<&ReactorTask> {
-		self.task.as_ref()
-	}
-
-	fn take(&mut self) -> Option<ReactorTask> {
-		if self.task.is_some() {
-			// clear state
-			self.state.task.store(1, Ordering::Relaxed);
-			// extract task
-			ManuallyDrop::into_inner(std::mem::replace(&mut self.task, ManuallyDrop::new(None)))
-		} else {
-			None
-		}
-	}
-
-	fn set(&mut self, task: ReactorTask) {
-		let token = task.as_token();
-		if self.task.is_some() {
-			// drop old task
-			ManuallyDrop::into_inner(std::mem::replace(&mut self.task, ManuallyDrop::new(None)));
-		}
-		self.task = ManuallyDrop::new(Some(task)); // steal refcount
-		self.state.task.store(token.0 | 1, Ordering::Relaxed);
-	}
-}
-
-impl Drop for TaskStateLock<'_> {
-	fn drop(&mut self) {
-		// clear lock
-		self.state.task.fetch_and(!1, Ordering::Release);
-	}
-}
-
 /// Low-level registration of an event source with the `Reactor`.
 ///
-/// One `mio::Evented` source can only be registered once; this abstraction allows two "parallel"
+/// One `mio::event::Source` can only be registered once; this abstraction allows two "parallel"
 /// sets of ready events to be polled.  For convenience one is called "read" and the other "write".
 /// On construction the set of "read" and "write" bits is given; everything else is ignored.
+///
+/// Both sides are independent (separate wakers and events) and only need `&self`, so a reader
+/// and a writer task can poll them concurrently.
 #[derive(Debug)]
 pub struct Registration<E>
 where
-	E: mio::Evented,
+	E: mio::event::Source,
 {
-	read_mask: mio::Ready,
-	write_mask: mio::Ready,
-	task: TaskState,
+	read_mask: Ready,
+	write_mask: Ready,
+	// only changed through `&mut self`: read and write side can be polled concurrently without locking
+	task: Option<ReactorTask>,
 	io: Option<E>, // only becomes None on `into_inner`
+	oneshot: bool,
+	interest: Option<mio::Interest>, // last registered interest
+	offload: Option<Offload<E>>, // close io in helper thread on drop
 }
 
 impl<E> Registration<E>
 where
-	E: mio::Evented,
+	E: mio::event::Source,
 {
 	/// Create new registration (but don't register it yet).
-	pub fn new(io: E, read_mask: mio::Ready, write_mask: mio::Ready) -> Self {
+	pub fn new(io: E, read_mask: Ready, write_mask: Ready) -> Self {
 		Self {
 			read_mask,
 			write_mask,
-			task: TaskState::new(),
+			task: None,
 			io: Some(io),
+			oneshot: false,
+			interest: None,
+			offload: None,
+		}
+	}
+
+	/// Deliver only the first event after registering (or [rearming](#method.rearm)); further
+	/// events are dropped until [`rearm`](#method.rearm) is called.
+	///
+	/// mio doesn't support oneshot registrations (anymore), so events are dropped in the reactor.
+	/// Takes effect on the next [`register`](#method.register).
+	pub fn set_oneshot(&mut self, oneshot: bool) {
+		self.oneshot = oneshot;
+	}
+
+	/// Deliver the next event of a oneshot registration.
+	///
+	/// Reregisters with the OS, so an event is delivered if the source is still ready.
+	///
+	/// # Panics
+	///
+	/// Only allowed while registered.
+	pub fn rearm(&mut self) -> io::Result<()> {
+		if let Some(task) = &self.task {
+			task.rearm();
 		}
+		let interest = self.interest.expect("rearm: not registered");
+		self.reregister(interest)
+	}
+
+	/// Return current read events without clearing them.
+	///
+	/// Doesn't register for wakeups; the events are still returned by the next
+	/// `poll_read_ready`.
+	pub fn peek_read_ready(&self) -> io::Result<Ready> {
+		let task = self.task.as_ref().ok_or(Error::NotRegistered)?;
+		Ok(task.peek_read_ready())
 	}
 
 	/// Return and clear current read events.
-	pub fn clear_read_ready(&self) -> io::Result<mio::Ready> {
-		let taskl = self.task.lock();
-		let task = taskl.as_ref().ok_or_else(|| {
-			io::Error::new(io::ErrorKind::Other, "clear_read_ready: not registered")
-		})?;
-		task.clear_read_ready()
+	pub fn clear_read_ready(&self) -> io::Result<Ready> {
+		let task = self.task.as_ref().ok_or(Error::NotRegistered)?;
+		Ok(task.clear_read_ready())
 	}
 
 	/// Check for new read events and register context to be woken on new read events if no read
 	/// events were pending.
-	pub fn poll_read_ready(&self, context: &mut Context<'_>) -> Poll<io::Result<mio::Ready>> {
-		let taskl = self.task.lock();
-		let task = taskl.as_ref().ok_or_else(|| {
-			io::Error::new(io::ErrorKind::Other, "poll_read_ready: not registered")
-		})?;
+	pub fn poll_read_ready(&self, context: &mut Context<'_>) -> Poll<io::Result<Ready>> {
+		let task = self.task.as_ref().ok_or(Error::NotRegistered)?;
 		task.poll_read_ready(context)
 	}
 
+	/// Mark read events as pending again (e.g. if they weren't consumed after all).
+	///
+	/// Doesn't wake anyone; the next `poll_read_ready` returns them.
+	pub fn retain_read_ready(&self, ready: Ready) {
+		if let Some(task) = &self.task {
+			task.retain_read_ready(ready);
+		}
+	}
+
+	/// Return current write events without clearing them.
+	///
+	/// Doesn't register for wakeups; the events are still returned by the next
+	/// `poll_write_ready`.
+	pub fn peek_write_ready(&self) -> io::Result<Ready> {
+		let task = self.task.as_ref().ok_or(Error::NotRegistered)?;
+		Ok(task.peek_write_ready())
+	}
+
 	/// Return and clear current write events.
-	pub fn clear_write_ready(&self) -> io::Result<mio::Ready> {
-		let taskl = self.task.lock();
-		let task = taskl.as_ref().ok_or_else(|| {
-			io::Error::new(io::ErrorKind::Other, "clear_write_ready: not registered")
-		})?;
-		task.clear_write_ready()
+	pub fn clear_write_ready(&self) -> io::Result<Ready> {
+		let task = self.task.as_ref().ok_or(Error::NotRegistered)?;
+		Ok(task.clear_write_ready())
 	}
 
 	/// Check for new (and clear) write events and register context to be woken on new write events
 	/// if no write events were pending.
-	pub fn poll_write_ready(&self, context: &mut Context<'_>) -> Poll<io::Result<mio::Ready>> {
-		let taskl = self.task.lock();
-		let task = taskl.as_ref().ok_or_else(|| {
-			io::Error::new(io::ErrorKind::Other, "poll_write_ready: not registered")
-		}).unwrap();
+	pub fn poll_write_ready(&self, context: &mut Context<'_>) -> Poll<io::Result<Ready>> {
+		let task = self.task.as_ref().ok_or(Error::NotRegistered)?;
 		task.poll_write_ready(context)
 	}
 
-	/// Register event.
+	/// Mark write events as pending again (e.g. if they weren't consumed after all).
+	///
+	/// Doesn't wake anyone; the next `poll_write_ready` returns them.
+	pub fn retain_write_ready(&self, ready: Ready) {
+		if let Some(task) = &self.task {
+			task.retain_write_ready(ready);
+		}
+	}
+
+	/// Register event (edge-triggered).
 	///
 	/// Deregisters automatically if it was registered before.
-	pub fn register(&self, handle: &Handle, interest: mio::Ready, opts: mio::PollOpt) -> io::Result<()> {
-		let io = self.io.as_ref().expect("missing io");
+	#[allow(clippy::missing_panics_doc)] // `io` is only gone after `into_inner`
+	pub fn register(&mut self, handle: &Handle, interest: mio::Interest) -> io::Result<()> {
 		self.deregister()?;
-		let mut taskl = self.task.lock();
+		let io = self.io.as_mut().expect("missing io");
 		let reactor = handle.expect_upgrade()?;
-		let task = ReactorTask::new(handle.clone(), self.read_mask, self.write_mask);;
-		reactor.register(io, task.clone(), interest, opts)?;
-		taskl.set(task);
+		let task = ReactorTask::with_oneshot(handle.clone(), self.read_mask, self.write_mask, self.oneshot);
+		reactor.register(io, task.clone(), interest)?;
+		self.task = Some(task);
+		self.interest = Some(interest);
 		Ok(())
 	}
 
-	/// Only allowed while registered
-	pub fn reregister(&self, interest: mio::Ready, opts: mio::PollOpt) -> io::Result<()> {
-		let io = self.io.as_ref().expect("missing io");
-		let taskl = self.task.lock();
-		let task = taskl.as_ref().expect("reregister: not registered");
+	/// Move registration to the reactor `handle` belongs to, keeping pending events.
+	///
+	/// Registers with the last registered interest; tasks waiting for events on the old
+	/// registration are woken (and should poll again).
+	///
+	/// # Panics
+	///
+	/// Only allowed while registered.
+	pub fn rebind(&mut self, handle: &Handle) -> io::Result<()> {
+		let interest = self.interest.expect("rebind: not registered");
+		let old_task = self.task.clone().expect("rebind: not registered");
+		let read_ready = old_task.clear_read_ready();
+		let write_ready = old_task.clear_write_ready();
+		let result = self.register(handle, interest);
+		if let Some(task) = &self.task {
+			task.retain_read_ready(read_ready);
+			task.retain_write_ready(write_ready);
+		}
+		old_task.wake();
+		result
+	}
+
+	/// Change the interest of the registration.
+	///
+	/// # Panics
+	///
+	/// Only allowed while registered.
+	pub fn reregister(&mut self, interest: mio::Interest) -> io::Result<()> {
+		let io = self.io.as_mut().expect("missing io");
+		let task = self.task.as_ref().expect("reregister: not registered");
 		let reactor = task.reactor().expect_upgrade()?;
-		reactor.reregister(io, task, interest, opts)?;
+		reactor.reregister(io, task, interest)?;
+		self.interest = Some(interest);
 		Ok(())
 	}
 
@@ -184,37 +184,48 @@ where
 	///
 	/// Only fails if mio itself fails.  If it wasn't registered or reactor is gone nothing
 	/// happens.
-	pub fn deregister(&self) -> io::Result<()> {
-		let io = self.io.as_ref().expect("missing io");
-		let mut task = self.task.lock();
-		if let Some(task) = task.take() {
+	#[allow(clippy::missing_panics_doc)] // `io` is only gone after `into_inner`
+	pub fn deregister(&mut self) -> io::Result<()> {
+		self.interest = None;
+		if let Some(task) = self.task.take() {
 			if let Some(reactor) = task.reactor().upgrade() {
-				reactor.deregister(io, task)?;
+				let io = self.io.as_mut().expect("missing io");
+				reactor.deregister(io, &task)?;
 			}
 		}
 		Ok(())
 	}
 
 	/// Retrieve reference to the contained IO
+	#[allow(clippy::missing_panics_doc)] // `io` is only gone after `into_inner`
 	pub fn io_ref(&self) -> &E {
 		self.io.as_ref().expect("missing io")
 	}
 
 	/// Retrieve mutable reference to the contained IO
+	#[allow(clippy::missing_panics_doc)] // `io` is only gone after `into_inner`
 	pub fn io_mut(&mut self) -> &mut E {
 		self.io.as_mut().expect("missing io")
 	}
 
 	/// Handle of registration or unbound `LazyHandle`.
 	pub fn handle(&self) -> LazyHandle {
-		let taskl = self.task.lock();
-		match taskl.as_ref() {
-			Some(task) => task.reactor().clone().into(),
-			None => LazyHandle::new(),
-		}
+		self.task.as_ref().map_or_else(LazyHandle::new, |task| task.reactor().clone().into())
+	}
+
+	/// Close IO in the helper thread of `offloader` when dropped (`None`: close right away, the
+	/// default).
+	///
+	/// Deregistering still happens when dropped.
+	pub fn set_close_offloader(&mut self, offloader: Option<CloseOffloader>)
+	where
+		E: Send + 'static,
+	{
+		self.offload = offloader.map(Offload::new);
 	}
 
 	/// Extract inner io from Registration (deregisters the io from the reactor).
+	#[allow(clippy::missing_panics_doc)] // `io` is only gone after `into_inner`
 	pub fn into_inner(mut self) -> E {
 		let _ = self.deregister(); // so dropping later doesn't panic
 		self.io.take().expect("missing io")
@@ -223,9 +234,73 @@ where
 
 impl<E> Drop for Registration<E>
 where
-	E: mio::Evented,
+	E: mio::event::Source,
 {
 	fn drop(&mut self) {
 		let _ = self.deregister();
+		if let Some(offload) = &self.offload {
+			if let Some(io) = self.io.take() {
+				offload.close(io);
+			}
+		}
+	}
+}
+
+// uses the atomics outside of `loom::model`
+#[cfg(all(test, not(loom)))]
+mod test {
+	use super::*;
+	use crate::reactor::Reactor;
+	use std::time::Duration;
+
+	#[test]
+	fn oneshot() {
+		let mut reactor = Reactor::new().unwrap();
+		let socket = mio::net::UdpSocket::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+		let target = socket.local_addr().unwrap();
+		let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+		let mut registration = Registration::new(socket, Ready::READABLE, Ready::EMPTY);
+		registration.set_oneshot(true);
+		registration.register(&reactor.handle(), mio::Interest::READABLE).unwrap();
+
+		let waker = futures_util::task::noop_waker();
+		let mut context = Context::from_waker(&waker);
+		let mut poll_read = |reactor: &mut Reactor, registration: &Registration<_>| {
+			reactor.poll(Some(Duration::from_millis(0))).unwrap();
+			match registration.poll_read_ready(&mut context) {
+				Poll::Ready(r) => !r.unwrap().is_empty(),
+				Poll::Pending => false,
+			}
+		};
+
+		sender.send_to(b"1", target).unwrap();
+		assert!(poll_read(&mut reactor, &registration));
+		// new datagram (new edge), but not rearmed yet
+		sender.send_to(b"2", target).unwrap();
+		assert!(!poll_read(&mut reactor, &registration));
+		// still readable: rearming delivers an event
+		registration.rearm().unwrap();
+		assert!(poll_read(&mut reactor, &registration));
+	}
+
+	#[test]
+	fn peek() {
+		let mut reactor = Reactor::new().unwrap();
+		let socket = mio::net::UdpSocket::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+		let target = socket.local_addr().unwrap();
+		let sender = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+		let mut registration = Registration::new(socket, Ready::READABLE, Ready::WRITABLE);
+		assert!(registration.peek_read_ready().is_err());
+		registration.register(&reactor.handle(), mio::Interest::READABLE).unwrap();
+		assert_eq!(registration.peek_read_ready().unwrap(), Ready::EMPTY);
+
+		sender.send_to(b"1", target).unwrap();
+		reactor.poll(Some(Duration::from_millis(100))).unwrap();
+		// peeking doesn't consume the event
+		assert_eq!(registration.peek_read_ready().unwrap(), Ready::READABLE);
+		assert_eq!(registration.peek_read_ready().unwrap(), Ready::READABLE);
+		assert_eq!(registration.peek_write_ready().unwrap(), Ready::EMPTY);
+		assert_eq!(registration.clear_read_ready().unwrap(), Ready::READABLE);
+		assert_eq!(registration.peek_read_ready().unwrap(), Ready::EMPTY);
 	}
 }
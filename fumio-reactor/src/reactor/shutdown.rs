@@ -0,0 +1,89 @@
+use slab::Slab;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+#[derive(Debug, Default)]
+struct State {
+	done: bool,
+	wakers: Slab<Waker>,
+}
+
+// triggered when the reactor is dropped
+#[derive(Debug, Default)]
+pub(super) struct ShutdownSignal {
+	state: Mutex<State>,
+}
+
+impl ShutdownSignal {
+	pub(super) fn is_done(&self) -> bool {
+		self.state.lock().unwrap().done
+	}
+
+	pub(super) fn trigger(&self) {
+		let mut wakers = {
+			let mut state = self.state.lock().unwrap();
+			state.done = true;
+			std::mem::take(&mut state.wakers)
+		};
+		// wake outside the lock
+		for waker in wakers.drain() {
+			waker.wake();
+		}
+	}
+}
+
+/// Completes when the reactor is dropped
+///
+/// See [`Handle::on_shutdown`](struct.Handle.html#method.on_shutdown).
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct OnShutdown {
+	// `None` if the reactor was already gone
+	signal: Option<Arc<ShutdownSignal>>,
+	key: Option<usize>,
+}
+
+impl OnShutdown {
+	pub(super) fn new(signal: Option<Arc<ShutdownSignal>>) -> Self {
+		Self { signal, key: None }
+	}
+}
+
+impl Future for OnShutdown {
+	type Output = ();
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+		let this = self.get_mut();
+		let Some(signal) = &this.signal else {
+			return Poll::Ready(());
+		};
+		let mut state = signal.state.lock().unwrap();
+		if state.done {
+			this.key = None;
+			return Poll::Ready(());
+		}
+		match this.key {
+			Some(key) => {
+				let waker = &mut state.wakers[key];
+				if !waker.will_wake(cx.waker()) {
+					waker.clone_from(cx.waker());
+				}
+			},
+			None => this.key = Some(state.wakers.insert(cx.waker().clone())),
+		}
+		Poll::Pending
+	}
+}
+
+impl Drop for OnShutdown {
+	fn drop(&mut self) {
+		if let (Some(signal), Some(key)) = (&self.signal, self.key) {
+			let mut state = signal.state.lock().unwrap();
+			if !state.done {
+				state.wakers.remove(key);
+			}
+		}
+	}
+}
@@ -1,116 +1,84 @@
-use super::Handle;
-use futures_util::task::AtomicWaker;
-use std::mem::ManuallyDrop;
-use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use super::{Error, Handle, Ready};
+use crate::sync::AtomicWaker;
+use crate::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use slab::Slab;
 use std::io;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 
-fumio_utils::mpsc! {
-	mod mpsc_task_list {
-		link TaskListLink;
-		head TaskListHead;
-		member next of InnerTask;
-	}
-}
-
-fumio_utils::local_dl_list! {
-	mod loc_dl_list {
-		link LocalTaskListLink;
-		head LocalTaskListHead;
-		member local_link of InnerTask;
-	}
-}
+// tokens are `(generation << INDEX_BITS) | (index + 1)`; token 0 is reserved for the reactor
+// waker, and `index + 1` never has all bits set so the token can't be `usize::MAX` (reserved by
+// mio).
+const INDEX_BITS: usize = std::mem::size_of::<usize>() * 4;
+const INDEX_MASK: usize = (1 << INDEX_BITS) - 1;
+const GENERATION_MASK: usize = !INDEX_MASK >> INDEX_BITS;
 
+// Registered tasks, keyed by mio token.
+//
+// Events for deregistered tasks might still be pending in the kernel (or in the current list of
+// events); the generation in the token makes sure they don't reach a task reusing the slot.
 #[derive(Debug)]
 pub(super) struct Tasks {
-	// tasks to process (new, deregister)
-	list: TaskListHead,
-	// make sure to keep Tasks alive until they are deregistered, so we can reinterpret tokens as
-	// pointers to tasks.
-	local_list: LocalTaskListHead,
+	// (generation, task)
+	slab: Slab<(usize, ReactorTask)>,
+	next_generation: usize,
 }
 
-unsafe impl Send for Tasks {}
-unsafe impl Sync for Tasks {}
-
 impl Tasks {
 	pub(super) fn new() -> Self {
 		Self {
-			list: TaskListHead::new(),
-			local_list: LocalTaskListHead::new(),
+			slab: Slab::new(),
+			next_generation: 0,
 		}
 	}
 
-	pub(super) fn add_task(&self, task: ReactorTask) {
-		let prev = task.inner.state.compare_and_swap(0, STATE_QUEUED, Ordering::Release);
-		if 0 != prev {
-			// someone else queued it... wtf. anyway, no need to continue
-			return;
+	pub(super) fn insert(&mut self, task: ReactorTask) -> io::Result<mio::Token> {
+		let entry = self.slab.vacant_entry();
+		let index = entry.key() + 1;
+		if index >= INDEX_MASK {
+			return Err(Error::TooManyRegistrations.into());
 		}
-
-		self.list.push(task.inner);
+		let generation = self.next_generation;
+		self.next_generation = (generation + 1) & GENERATION_MASK;
+		let token = mio::Token((generation << INDEX_BITS) | index);
+		task.inner.token.store(token.0, Ordering::Relaxed);
+		entry.insert((generation, task));
+		Ok(token)
 	}
 
-	pub(super) fn deregister_task(&self, task: ReactorTask) {
-		let prev = task.inner.state.swap(STATE_QUEUED | STATE_DEREGISTERED, Ordering::Release);
-		if 0 != prev {
-			// either already deregistered or already queued
-			// not queuing again
-			return;
+	pub(super) fn get(&self, token: mio::Token) -> Option<&ReactorTask> {
+		let index = (token.0 & INDEX_MASK).checked_sub(1)?;
+		let generation = token.0 >> INDEX_BITS;
+		match self.slab.get(index) {
+			Some((gen, task)) if *gen == generation => Some(task),
+			_ => None,
 		}
-
-		self.list.push(task.inner);
 	}
 
-	// call after an event loop iteration is done
-	pub(super) fn cleanup_tasks(&self) {
-		for task_inner in unsafe { self.list.start_pop() } {
-			let prev = task_inner.state.fetch_and(!STATE_QUEUED, Ordering::Acquire);
-			assert!(0 != STATE_QUEUED & prev, "invalid internal state");
-			if 0 != STATE_DEREGISTERED & prev {
-				self.local_remove(task_inner)
-			} else {
-				self.local_add(task_inner)
-			}
-		}
+	pub(super) fn is_empty(&self) -> bool {
+		self.slab.is_empty()
 	}
 
-	fn local_add(&self, task_inner: Arc<InnerTask>) {
-		if task_inner.local_link.is_unlinked() {
-			// move reference to local list
-			let task_inner = ManuallyDrop::new(task_inner);
-			unsafe { self.local_list.append(&task_inner); }
-		}
-	}
-
-	#[allow(clippy::needless_pass_by_value)]
-	fn local_remove(&self, task_inner: Arc<InnerTask>) {
-		if !task_inner.local_link.is_unlinked() {
-			unsafe { task_inner.local_link.unlink(); }
-			unsafe { Arc::from_raw(&*task_inner); } // drop refcount hold by list
-		}
+	pub(super) fn remove(&mut self, token: mio::Token) -> Option<ReactorTask> {
+		self.get(token)?;
+		let (_, task) = self.slab.remove((token.0 & INDEX_MASK) - 1);
+		Some(task)
 	}
 }
 
 impl Drop for Tasks {
 	fn drop(&mut self) {
-		self.cleanup_tasks();
-
-		while let Some(task_inner) = unsafe { self.local_list.pop_front() } {
-			let _task_inner = unsafe { Arc::from_raw(task_inner) };
+		// the reactor is gone: let waiting tasks notice
+		for (_, task) in self.slab.drain() {
+			task.wake();
 		}
 	}
 }
 
-const STATE_DEREGISTERED: u8 = 0b01;
-const STATE_QUEUED: u8 = 0b10;
-
 #[derive(Debug)]
 struct InnerTask {
-	state: AtomicU8,
-	next: TaskListLink,
-	local_link: LocalTaskListLink,
+	// mio token while registered
+	token: AtomicUsize,
 	reactor: Handle,
 	read_mask: usize,
 	write_mask: usize,
@@ -118,6 +86,9 @@ struct InnerTask {
 	read_waker: AtomicWaker,
 	write_readiness: AtomicUsize,
 	write_waker: AtomicWaker,
+	// oneshot tasks drop events after the first one until rearmed
+	oneshot: bool,
+	armed: AtomicBool,
 }
 
 #[derive(Debug, Clone)]
@@ -126,11 +97,13 @@ pub(super) struct ReactorTask {
 }
 
 impl ReactorTask {
-	pub(super) fn new(reactor: Handle, read_mask: mio::Ready, write_mask: mio::Ready) -> Self {
+	pub(super) fn new(reactor: Handle, read_mask: Ready, write_mask: Ready) -> Self {
+		Self::with_oneshot(reactor, read_mask, write_mask, false)
+	}
+
+	pub(super) fn with_oneshot(reactor: Handle, read_mask: Ready, write_mask: Ready, oneshot: bool) -> Self {
 		let inner = Arc::new(InnerTask {
-			state: AtomicU8::new(0),
-			next: TaskListLink::new(),
-			local_link: LocalTaskListLink::new(),
+			token: AtomicUsize::new(0),
 			reactor,
 			read_mask: read_mask.as_usize(),
 			write_mask: write_mask.as_usize(),
@@ -138,6 +111,8 @@ impl ReactorTask {
 			read_waker: AtomicWaker::new(),
 			write_readiness: AtomicUsize::new(0),
 			write_waker: AtomicWaker::new(),
+			oneshot,
+			armed: AtomicBool::new(true),
 		});
 		Self { inner }
 	}
@@ -146,15 +121,20 @@ impl ReactorTask {
 		&self.inner.reactor
 	}
 
-	fn take_read_ready(&self) -> mio::Ready {
-		mio::Ready::from_usize(self.inner.read_readiness.swap(0, Ordering::Relaxed))
+	fn take_read_ready(&self) -> Ready {
+		Ready::from_usize(self.inner.read_readiness.swap(0, Ordering::Relaxed))
 	}
 
-	pub(super) fn clear_read_ready(&self) -> io::Result<mio::Ready> {
-		Ok(self.take_read_ready())
+	// current read events (without clearing them)
+	pub(super) fn peek_read_ready(&self) -> Ready {
+		Ready::from_usize(self.inner.read_readiness.load(Ordering::Relaxed))
 	}
 
-	pub(super) fn poll_read_ready(&self, context: &mut Context<'_>) -> Poll<io::Result<mio::Ready>> {
+	pub(super) fn clear_read_ready(&self) -> Ready {
+		self.take_read_ready()
+	}
+
+	pub(super) fn poll_read_ready(&self, context: &Context<'_>) -> Poll<io::Result<Ready>> {
 		let ready = self.take_read_ready();
 		if !ready.is_empty() {
 			return Poll::Ready(Ok(ready));
@@ -164,19 +144,32 @@ impl ReactorTask {
 		if !ready.is_empty() {
 			return Poll::Ready(Ok(ready));
 		}
-		self.inner.reactor.expect_upgrade()?; // make sure reactor still lives
+		// make sure reactor still lives; it wakes all tasks when dropped
+		if !self.inner.reactor.is_alive() {
+			return Poll::Ready(Err(Error::ReactorGone.into()));
+		}
 		Poll::Pending
 	}
 
-	fn take_write_ready(&self) -> mio::Ready {
-		mio::Ready::from_usize(self.inner.write_readiness.swap(0, Ordering::Relaxed))
+	// put (unconsumed) events back without waking anyone
+	pub(super) fn retain_read_ready(&self, ready: Ready) {
+		self.inner.read_readiness.fetch_or(self.inner.read_mask & ready.as_usize(), Ordering::Relaxed);
+	}
+
+	fn take_write_ready(&self) -> Ready {
+		Ready::from_usize(self.inner.write_readiness.swap(0, Ordering::Relaxed))
+	}
+
+	// current write events (without clearing them)
+	pub(super) fn peek_write_ready(&self) -> Ready {
+		Ready::from_usize(self.inner.write_readiness.load(Ordering::Relaxed))
 	}
 
-	pub(super) fn clear_write_ready(&self) -> io::Result<mio::Ready> {
-		Ok(self.take_write_ready())
+	pub(super) fn clear_write_ready(&self) -> Ready {
+		self.take_write_ready()
 	}
 
-	pub(super) fn poll_write_ready(&self, context: &mut Context<'_>) -> Poll<io::Result<mio::Ready>> {
+	pub(super) fn poll_write_ready(&self, context: &Context<'_>) -> Poll<io::Result<Ready>> {
 		let ready = self.take_write_ready();
 		if !ready.is_empty() {
 			return Poll::Ready(Ok(ready));
@@ -186,26 +179,42 @@ impl ReactorTask {
 		if !ready.is_empty() {
 			return Poll::Ready(Ok(ready));
 		}
-		self.inner.reactor.expect_upgrade()?; // make sure reactor still lives
+		// make sure reactor still lives; it wakes all tasks when dropped
+		if !self.inner.reactor.is_alive() {
+			return Poll::Ready(Err(Error::ReactorGone.into()));
+		}
 		Poll::Pending
 	}
 
-	// token doesn't own a reference!
-	pub(super) fn as_token(&self) -> mio::Token {
-		let raw: *const InnerTask = &*self.inner;
-		mio::Token(raw as usize)
+	// put (unconsumed) events back without waking anyone
+	pub(super) fn retain_write_ready(&self, ready: Ready) {
+		self.inner.write_readiness.fetch_or(self.inner.write_mask & ready.as_usize(), Ordering::Relaxed);
 	}
 
-	// use only in event loop between poll and cleanup_tasks
-	pub(super) fn from_token(token: mio::Token) -> Self {
-		let raw: *const InnerTask = token.0 as _;
-		let inner = unsafe { Arc::from_raw(raw) };
-		// increase ref count by 1
-		std::mem::forget(inner.clone());
-		Self { inner }
+	// token the task was registered with
+	pub(super) fn token(&self) -> mio::Token {
+		mio::Token(self.inner.token.load(Ordering::Relaxed))
+	}
+
+	// pointer value identifying the task
+	fn as_raw(&self) -> usize {
+		Arc::as_ptr(&self.inner) as usize
 	}
 
-	pub(super) fn update_ready(&self, readiness: mio::Ready) {
+	// wake read and write waiters (without new events)
+	pub(super) fn wake(&self) {
+		self.inner.read_waker.wake();
+		self.inner.write_waker.wake();
+	}
+
+	pub(super) fn rearm(&self) {
+		self.inner.armed.store(true, Ordering::Relaxed);
+	}
+
+	pub(super) fn update_ready(&self, readiness: Ready) {
+		if self.inner.oneshot && !self.inner.armed.swap(false, Ordering::Relaxed) {
+			return;
+		}
 		let read_bits = self.inner.read_mask & readiness.as_usize();
 		if 0 != read_bits {
 			self.inner.read_readiness.fetch_or(read_bits, Ordering::Relaxed);
@@ -227,6 +236,70 @@ impl std::cmp::PartialEq for ReactorTask {
 impl std::cmp::Eq for ReactorTask { }
 impl std::hash::Hash for ReactorTask {
 	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-		self.as_token().hash(state)
+		self.as_raw().hash(state);
+	}
+}
+
+// uses the atomics outside of `loom::model`
+#[cfg(all(test, not(loom)))]
+mod test {
+	use super::*;
+
+	#[test]
+	fn stale_tokens() {
+		let mut tasks = Tasks::new();
+		let task = ReactorTask::new(Handle { inner: std::sync::Weak::new() }, Ready::READABLE, Ready::WRITABLE);
+		let token1 = tasks.insert(task.clone()).unwrap();
+		assert_ne!(token1, mio::Token(0));
+		assert!(tasks.get(token1) == Some(&task));
+		assert!(tasks.remove(token1).is_some());
+
+		// reuses the slot with a new generation
+		let token2 = tasks.insert(task.clone()).unwrap();
+		assert_ne!(token1, token2);
+		assert!(tasks.get(token1).is_none());
+		assert!(tasks.remove(token1).is_none());
+		assert!(tasks.get(token2) == Some(&task));
+	}
+}
+
+#[cfg(all(test, loom))]
+mod loom_test {
+	use super::*;
+	use crate::sync::atomic::AtomicBool;
+	use loom::thread;
+
+	#[derive(Default)]
+	struct WakeFlag(AtomicBool);
+
+	impl futures_util::task::ArcWake for WakeFlag {
+		fn wake_by_ref(arc_self: &Arc<Self>) {
+			arc_self.0.store(true, Ordering::Release);
+		}
+	}
+
+	#[test]
+	fn ready_or_woken() {
+		loom::model(|| {
+			let task = ReactorTask::new(Handle { inner: std::sync::Weak::new() }, Ready::READABLE, Ready::WRITABLE);
+			let flag = Arc::new(WakeFlag::default());
+			let waker = futures_util::task::waker(flag.clone());
+
+			let update = {
+				let task = task.clone();
+				thread::spawn(move || task.update_ready(Ready::READABLE))
+			};
+			// without a reactor a task that would wait fails with `ReactorGone`
+			let ready = match task.poll_read_ready(&mut Context::from_waker(&waker)) {
+				Poll::Ready(Ok(ready)) => !ready.is_empty(),
+				_ => false,
+			};
+			update.join().unwrap();
+			if !ready {
+				// the event must not get lost
+				assert!(flag.0.load(Ordering::Acquire));
+				assert_eq!(task.peek_read_ready(), Ready::READABLE);
+			}
+		});
 	}
 }
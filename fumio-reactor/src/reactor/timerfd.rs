@@ -0,0 +1,101 @@
+use crate::reactor::fd::{poll_read_u64, FileDesc};
+use crate::reactor::{AsyncFd, LazyHandle};
+use std::future::Future;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+#[allow(clippy::cast_possible_wrap, clippy::cast_lossless)] // `time_t` and `c_long` sizes depend on the platform
+fn timespec(d: Duration) -> libc::timespec {
+	libc::timespec {
+		tv_sec: d.as_secs() as libc::time_t,
+		tv_nsec: d.subsec_nanos() as libc::c_long,
+	}
+}
+
+/// Linux `timerfd` (using `CLOCK_MONOTONIC`) registered with the reactor.
+///
+/// Unlike the timers of the runtime it doesn't depend on the timer wheel granularity.
+#[derive(Debug)]
+pub struct TimerFd {
+	fd: AsyncFd<FileDesc>,
+}
+
+impl TimerFd {
+	/// Create new (disarmed) timer registered with the [`current`](fn.current.html) reactor.
+	pub fn new() -> io::Result<Self> {
+		Self::with_handle(LazyHandle::new())
+	}
+
+	/// Create new (disarmed) timer registered with the reactor `handle` is bound to.
+	pub fn with_handle(handle: LazyHandle) -> io::Result<Self> {
+		let fd = FileDesc::from_result(unsafe {
+			libc::timerfd_create(libc::CLOCK_MONOTONIC, libc::TFD_CLOEXEC | libc::TFD_NONBLOCK)
+		})?;
+		Ok(Self {
+			fd: AsyncFd::with_handle(fd, handle)?,
+		})
+	}
+
+	fn settime(&self, value: libc::timespec, interval: libc::timespec) -> io::Result<()> {
+		let new_value = libc::itimerspec {
+			it_interval: interval,
+			it_value: value,
+		};
+		let r = unsafe {
+			libc::timerfd_settime(self.fd.get_ref().as_raw_fd(), 0, std::ptr::addr_of!(new_value), std::ptr::null_mut())
+		};
+		if r < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		Ok(())
+	}
+
+	/// Arm timer to expire after `value`, and then (if given) repeatedly after each `interval`.
+	///
+	/// Replaces previous settings; a zero `value` expires (almost) immediately.
+	pub fn set(&self, value: Duration, interval: Option<Duration>) -> io::Result<()> {
+		// a zero value would disarm the timer
+		let value = std::cmp::max(value, Duration::from_nanos(1));
+		self.settime(timespec(value), timespec(interval.unwrap_or_default()))
+	}
+
+	/// Disarm timer.
+	pub fn disarm(&self) -> io::Result<()> {
+		self.settime(timespec(Duration::default()), timespec(Duration::default()))
+	}
+
+	/// Return number of expirations since the last call, or register context to be woken once
+	/// the timer expires.
+	pub fn poll_expired(&mut self, context: &mut Context<'_>) -> Poll<io::Result<u64>> {
+		poll_read_u64(&self.fd, context)
+	}
+
+	/// Wait for the timer to expire; completes with the number of expirations.
+	pub fn expired(&mut self) -> TimerFdExpired<'_> {
+		TimerFdExpired { timer_fd: self }
+	}
+}
+
+impl AsRawFd for TimerFd {
+	fn as_raw_fd(&self) -> RawFd {
+		self.fd.get_ref().as_raw_fd()
+	}
+}
+
+/// Pending expiration of a [`TimerFd`](struct.TimerFd.html)
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct TimerFdExpired<'a> {
+	timer_fd: &'a mut TimerFd,
+}
+
+impl Future for TimerFdExpired<'_> {
+	type Output = io::Result<u64>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		self.timer_fd.poll_expired(cx)
+	}
+}
@@ -0,0 +1,95 @@
+use crate::reactor::{Error, Handle, LazyHandle, ReactorTask, Ready};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// An event triggered by the user through a [`UserEventNotifier`](struct.UserEventNotifier.html).
+///
+/// Notifications are delivered through the reactor (like IO events): a notifier can be used from
+/// any thread to wake up exactly the task waiting for this event, instead of the whole event loop
+/// like [`Reactor::waker`](struct.Reactor.html#method.waker).
+///
+/// Multiple notifications before the event is polled are merged into one.
+#[derive(Debug)]
+pub struct UserEvent {
+	task: ReactorTask,
+}
+
+impl UserEvent {
+	/// Create new event registered with the [`current`](fn.current.html) reactor.
+	pub fn new() -> io::Result<Self> {
+		Self::with_handle(LazyHandle::new())
+	}
+
+	/// Create new event registered with the reactor `handle` is bound to.
+	#[allow(clippy::needless_pass_by_value)] // consistent with other constructors taking a `LazyHandle`
+	pub fn with_handle(handle: LazyHandle) -> io::Result<Self> {
+		let handle = handle.bind().ok_or(Error::NoReactor)?;
+		let reactor = handle.expect_upgrade()?;
+		let task = ReactorTask::new(handle, Ready::READABLE, Ready::EMPTY);
+		reactor.register_user(task.clone())?;
+		Ok(Self { task })
+	}
+
+	/// Create a (thread-safe) notifier to trigger this event.
+	pub fn notifier(&self) -> UserEventNotifier {
+		UserEventNotifier {
+			reactor: self.task.reactor().clone(),
+			token: self.task.token(),
+		}
+	}
+
+	/// Clear pending notification, or register context to be woken on the next notification if
+	/// there was none.
+	pub fn poll_triggered(&mut self, context: &mut Context<'_>) -> Poll<io::Result<()>> {
+		futures_util::ready!(self.task.poll_read_ready(context))?;
+		Poll::Ready(Ok(()))
+	}
+
+	/// Wait for the next notification.
+	pub fn triggered(&mut self) -> UserEventTriggered<'_> {
+		UserEventTriggered { event: self }
+	}
+}
+
+impl Drop for UserEvent {
+	fn drop(&mut self) {
+		if let Some(reactor) = self.task.reactor().upgrade() {
+			reactor.deregister_user(&self.task);
+		}
+	}
+}
+
+/// Triggers a [`UserEvent`](struct.UserEvent.html).
+///
+/// Notifying after the event was dropped (or the reactor is gone) does nothing.
+#[derive(Clone, Debug)]
+pub struct UserEventNotifier {
+	reactor: Handle,
+	token: mio::Token,
+}
+
+impl UserEventNotifier {
+	/// Trigger the event.
+	pub fn notify(&self) {
+		if let Some(reactor) = self.reactor.upgrade() {
+			reactor.trigger(self.token, Ready::READABLE);
+		}
+	}
+}
+
+/// Pending notification of a [`UserEvent`](struct.UserEvent.html)
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct UserEventTriggered<'a> {
+	event: &'a mut UserEvent,
+}
+
+impl Future for UserEventTriggered<'_> {
+	type Output = io::Result<()>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		self.event.poll_triggered(cx)
+	}
+}
@@ -1,9 +1,12 @@
-use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
+use crate::reactor::metrics::WakeCounters;
+use crate::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 #[derive(Debug)]
 struct Inner {
 	state: AtomicUsize,
-	set_readiness: mio::SetReadiness,
+	waker: mio::Waker,
+	counters: Arc<WakeCounters>,
 }
 
 const STATE_POLLING: usize = 0b01;
@@ -11,6 +14,11 @@ const STATE_PENDING: usize = 0b10;
 
 impl futures_util::task::ArcWake for Inner {
 	fn wake_by_ref(arc_self: &Arc<Self>) {
+		arc_self.counters.record_wake();
+		// cheap check first: many threads waking the same reactor shouldn't all write the state
+		if 0 != arc_self.state.load(Ordering::Relaxed) & STATE_PENDING {
+			return;
+		}
 		let prev = arc_self.state.fetch_or(STATE_PENDING, Ordering::Release);
 		if 0 != prev & STATE_PENDING {
 			// a previous pending flag wasn't reset yet, nothing to do
@@ -22,7 +30,8 @@ impl futures_util::task::ArcWake for Inner {
 		}
 
 		// wakeup poll
-		let _ = arc_self.set_readiness.set_readiness(mio::Ready::readable());
+		arc_self.counters.record_syscall();
+		let _ = arc_self.waker.wake();
 	}
 }
 
@@ -32,19 +41,23 @@ pub(super) struct ReactorWaker {
 }
 
 impl ReactorWaker {
-	pub fn new() -> (mio::Registration, Self) {
-		let (reg, set) = mio::Registration::new2();
+	pub fn new(registry: &mio::Registry, token: mio::Token, counters: Arc<WakeCounters>) -> std::io::Result<Self> {
 		let inner = Arc::new(Inner {
 			state: AtomicUsize::new(0),
-			set_readiness: set,
+			waker: mio::Waker::new(registry, token)?,
+			counters,
 		});
-		(reg, Self { inner })
+		Ok(Self { inner })
 	}
 
 	pub fn waker(&self) -> std::task::Waker {
 		futures_util::task::waker(self.inner.clone())
 	}
 
+	pub fn is_pending(&self) -> bool {
+		0 != self.inner.state.load(Ordering::Acquire) & STATE_PENDING
+	}
+
 	pub fn start_poll(&mut self) -> (bool, ReactorWakerPollling<'_>) {
 		// optimization
 		if 0 != self.inner.state.load(Ordering::Acquire) & STATE_PENDING {
@@ -70,3 +83,31 @@ impl Drop for ReactorWakerPollling<'_> {
 		self.waker.inner.state.swap(0, Ordering::Acquire);
 	}
 }
+
+#[cfg(all(test, loom))]
+mod loom_test {
+	use super::*;
+	use loom::thread;
+	use std::time::Duration;
+
+	#[test]
+	fn wake_while_polling() {
+		loom::model(|| {
+			let mut poll = mio::Poll::new().unwrap();
+			let mut reactor_waker = ReactorWaker::new(poll.registry(), mio::Token(0), Arc::default()).unwrap();
+			let waker = reactor_waker.waker();
+
+			let wake = thread::spawn(move || waker.wake());
+			let (pending, polling) = reactor_waker.start_poll();
+			wake.join().unwrap();
+			if !pending {
+				// the reactor would block: it must be woken up by the event
+				let mut events = mio::Events::with_capacity(4);
+				poll.poll(&mut events, Some(Duration::from_secs(0))).unwrap();
+				assert!(events.iter().any(|event| event.token() == mio::Token(0)));
+			}
+			drop(polling);
+			assert!(!reactor_waker.is_pending());
+		});
+	}
+}
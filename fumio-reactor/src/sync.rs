@@ -0,0 +1,26 @@
+// atomics and `AtomicWaker`; the `loom` types with `cfg(loom)` (see `fumio_utils::sync`)
+
+pub(crate) use fumio_utils::sync::atomic;
+
+#[cfg(not(loom))]
+pub(crate) use futures_util::task::AtomicWaker;
+
+// same interface as `futures_util::task::AtomicWaker`
+#[cfg(loom)]
+#[derive(Debug, Default)]
+pub(crate) struct AtomicWaker(loom::future::AtomicWaker);
+
+#[cfg(loom)]
+impl AtomicWaker {
+	pub(crate) fn new() -> Self {
+		Self(loom::future::AtomicWaker::new())
+	}
+
+	pub(crate) fn register(&self, waker: &std::task::Waker) {
+		self.0.register_by_ref(waker);
+	}
+
+	pub(crate) fn wake(&self) {
+		self.0.wake();
+	}
+}
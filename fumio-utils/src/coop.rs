@@ -0,0 +1,64 @@
+//! Cooperative scheduling budget.
+//!
+//! An executor sets a budget while polling a task; leaf futures (like IO resources) call
+//! [`poll_proceed`](fn.poll_proceed.html) before doing actual work.  Once the budget is exhausted
+//! `poll_proceed` returns `Poll::Pending` (and wakes the task again), forcing a task that always
+//! finds more work to do to yield to the other tasks.
+//!
+//! Outside of a budgeted scope `poll_proceed` is always ready.
+
+use std::cell::Cell;
+use std::task::{Context, Poll};
+
+/// Default budget per task poll.
+pub const DEFAULT_BUDGET: u32 = 128;
+
+thread_local! {
+	// `None`: unconstrained
+	static BUDGET: Cell<Option<u32>> = const { Cell::new(None) };
+}
+
+struct ResetBudget(Option<u32>);
+
+impl Drop for ResetBudget {
+	fn drop(&mut self) {
+		// ignore error
+		let _ = BUDGET.try_with(|b| b.set(self.0));
+	}
+}
+
+/// Run callback with the given budget (`None` means unconstrained).
+///
+/// The previous budget is restored afterwards.
+pub fn with_budget<F, R>(budget: Option<u32>, f: F) -> R
+where
+	F: FnOnce() -> R,
+{
+	let _reset = ResetBudget(BUDGET.with(|b| b.replace(budget)));
+	f()
+}
+
+/// Consume one unit of the current budget.
+///
+/// If the budget is exhausted the task is woken and `Poll::Pending` is returned; the caller should
+/// return `Poll::Pending` too.
+pub fn poll_proceed(cx: &mut Context<'_>) -> Poll<()> {
+	BUDGET.with(|b| {
+		match b.get() {
+			None => Poll::Ready(()),
+			Some(0) => {
+				cx.waker().wake_by_ref();
+				Poll::Pending
+			},
+			Some(n) => {
+				b.set(Some(n - 1));
+				Poll::Ready(())
+			},
+		}
+	})
+}
+
+/// Whether the current budget still allows progress (without consuming anything).
+pub fn has_budget_remaining() -> bool {
+	BUDGET.with(|b| b.get() != Some(0))
+}
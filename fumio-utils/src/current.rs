@@ -1,13 +1,22 @@
 //! Generic implementation for "current" (thread-local) instances for "executor" handles.
 //!
-//! Requires a `futures_executor::Enter` reference to set.
+//! Requires an [`Enter`](../enter/struct.Enter.html) reference to set.
+//!
+//! Instances created with [`Current::nested`](struct.Current.html#method.nested) can be entered
+//! again while entered; the previous value is restored on exit.
+//!
+//! A fallback value can be installed lazily with [`Current::with_or`](struct.Current.html#method.with_or);
+//! it is used while nothing is entered.
+//!
+//! Without the `std` feature only the methods taking `&self` are available; the executor has to
+//! provide its own storage for the instance then.
 //!
 //! # Example
 //!
 //! ```
 //! pub struct Handle;
 //!
-//! use futures_executor::Enter;
+//! use fumio_utils::enter::Enter;
 //! use fumio_utils::current::Current;
 //!
 //! thread_local! {
@@ -30,25 +39,47 @@
 //!
 //! ```
 
-use futures_executor::Enter;
-use std::cell::RefCell;
+#[cfg(feature = "std")]
+use crate::enter::Enter;
+use core::cell::RefCell;
+use core::fmt;
+#[cfg(feature = "std")]
 use std::thread::LocalKey;
 
-struct Reset<T: 'static> {
-	current: &'static LocalKey<Current<T>>,
+struct Reset<'a, T> {
+	current: &'a Current<T>,
+	previous: Option<T>,
 }
 
-impl<T> Drop for Reset<T> {
+impl<T> Drop for Reset<'_, T> {
 	fn drop(&mut self) {
-		// ignore error
-		let _ = self.current.try_with(|c| *c.inner.borrow_mut() = None);
+		*self.current.inner.borrow_mut() = self.previous.take();
+	}
+}
+
+/// Error returned by the `try_*` methods of [`Current`](struct.Current.html) if the instance
+/// already was entered
+#[derive(Debug)]
+pub struct AlreadyEntered {
+	_priv: (),
+}
+
+impl fmt::Display for AlreadyEntered {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("can't enter more than once at a time")
 	}
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for AlreadyEntered {}
+
 /// Holds a value when entered or nothing when not.
 #[derive(Debug)]
 pub struct Current<T> {
 	inner: RefCell<Option<T>>,
+	// used while not entered
+	fallback: RefCell<Option<T>>,
+	nested: bool,
 }
 
 impl<T> Current<T> {
@@ -56,9 +87,123 @@ impl<T> Current<T> {
 	pub const fn new() -> Self {
 		Self {
 			inner: RefCell::new(None),
+			fallback: RefCell::new(None),
+			nested: false,
+		}
+	}
+
+	/// Construct a new (empty) instance that can be entered while entered.
+	///
+	/// Entering saves the previous value and restores it on exit.
+	pub const fn nested() -> Self {
+		Self {
+			inner: RefCell::new(None),
+			fallback: RefCell::new(None),
+			nested: true,
 		}
 	}
 
+	/// Set instance to `value` while running the callback.
+	///
+	/// On exit the instance is cleared (or the previous value restored).
+	///
+	/// # Panics
+	///
+	/// Panics if the instance already was entered (and isn't nested).
+	pub fn scoped<F, R>(&self, value: T, f: F) -> R
+	where
+		F: FnOnce() -> R,
+	{
+		match self.try_scoped(value, f) {
+			Ok(result) => result,
+			Err(e) => panic!("{}", e),
+		}
+	}
+
+	/// Set instance to `value` while running the callback.
+	///
+	/// On exit the instance is cleared (or the previous value restored).
+	///
+	/// # Errors
+	///
+	/// Fails if the instance already was entered (and isn't nested); doesn't run the callback then.
+	pub fn try_scoped<F, R>(&self, value: T, f: F) -> Result<R, AlreadyEntered>
+	where
+		F: FnOnce() -> R,
+	{
+		let previous = {
+			let mut inner = self.inner.borrow_mut();
+			if inner.is_some() && !self.nested {
+				return Err(AlreadyEntered { _priv: () });
+			}
+			inner.replace(value)
+		};
+		let _reset = Reset { current: self, previous };
+		Ok(f())
+	}
+
+	/// Run callback with a reference to the value (if there is one)
+	///
+	/// # Panics
+	///
+	/// Panics if the inner value is currently locked exclusively by a `with_mut_ref` call.
+	pub fn with_ref<F, R>(&self, f: F) -> R
+	where
+		F: FnOnce(Option<&T>) -> R,
+	{
+		f(self.inner.borrow().as_ref())
+	}
+
+	/// Run callback with a reference to the value, or to the fallback value if not entered
+	///
+	/// The fallback is created by `default` on first use and kept until the instance is dropped.
+	///
+	/// # Panics
+	///
+	/// Panics if the inner value is currently locked exclusively by a `with_mut_ref` call.
+	pub fn with_ref_or<D, F, R>(&self, default: D, f: F) -> R
+	where
+		D: FnOnce() -> T,
+		F: FnOnce(&T) -> R,
+	{
+		{
+			let inner = self.inner.borrow();
+			if let Some(value) = inner.as_ref() {
+				return f(value);
+			}
+		}
+		if self.fallback.borrow().is_none() {
+			// create without holding a lock; `default` might use the instance too
+			let value = default();
+			let mut fallback = self.fallback.borrow_mut();
+			if fallback.is_none() {
+				*fallback = Some(value);
+			}
+		}
+		f(self.fallback.borrow().as_ref().unwrap())
+	}
+
+	/// Run callback with a mutable reference to the value (if there is one)
+	///
+	/// # Panics
+	///
+	/// Panics if the inner value is currently locked by a `with_ref` or a `with_mut_ref` call.
+	pub fn with_mut_ref<F, R>(&self, f: F) -> R
+	where
+		F: FnOnce(Option<&mut T>) -> R,
+	{
+		f(self.inner.borrow_mut().as_mut())
+	}
+}
+
+impl<T> Default for Current<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(feature = "std")]
+impl<T> Current<T> {
 	/// Set instance to `value` while running the callback.
 	///
 	/// On exit the instance is cleared.
@@ -71,15 +216,55 @@ impl<T> Current<T> {
 	where
 		F: FnOnce(&mut Enter) -> R,
 	{
-		this.with(|c| {
-			{
-				let mut inner = c.inner.borrow_mut();
-				assert!(inner.is_none(), "can't enter more than once at a time");
-				*inner = Some(value);
-			}
-			let _reset = Reset { current: this };
-			f(enter)
-		})
+		Self::enter_nonblocking(this, value, move || f(enter))
+	}
+
+	/// Set instance to `value` while running the callback.
+	///
+	/// On exit the instance is cleared (or the previous value restored).
+	///
+	/// # Errors
+	///
+	/// Fails if the instance already was entered (and isn't nested).
+	#[inline]
+	pub fn try_enter<F, R>(this: &'static LocalKey<Self>, enter: &mut Enter, value: T, f: F) -> Result<R, AlreadyEntered>
+	where
+		F: FnOnce(&mut Enter) -> R,
+	{
+		Self::try_enter_nonblocking(this, value, move || f(enter))
+	}
+
+	/// Set instance to `value` while running the callback, without requiring an `Enter` guard.
+	///
+	/// Meant for executors that are driven from within another executor (which already holds the
+	/// `Enter` guard); the callback must not block the thread.
+	///
+	/// On exit the instance is cleared.
+	///
+	/// # Panics
+	///
+	/// Panics if the instance already was entered.
+	#[inline]
+	pub fn enter_nonblocking<F, R>(this: &'static LocalKey<Self>, value: T, f: F) -> R
+	where
+		F: FnOnce() -> R,
+	{
+		this.with(|c| c.scoped(value, f))
+	}
+
+	/// Set instance to `value` while running the callback, without requiring an `Enter` guard.
+	///
+	/// See [`enter_nonblocking`](#method.enter_nonblocking).
+	///
+	/// # Errors
+	///
+	/// Fails if the instance already was entered (and isn't nested).
+	#[inline]
+	pub fn try_enter_nonblocking<F, R>(this: &'static LocalKey<Self>, value: T, f: F) -> Result<R, AlreadyEntered>
+	where
+		F: FnOnce() -> R,
+	{
+		this.with(|c| c.try_scoped(value, f))
 	}
 
 	/// Run callback with a reference to the current value (if there is one)
@@ -94,9 +279,23 @@ impl<T> Current<T> {
 	where
 		F: FnOnce(Option<&T>) -> R,
 	{
-		this.with(|c| {
-			f(c.inner.borrow().as_ref())
-		})
+		this.with(|c| c.with_ref(f))
+	}
+
+	/// Run callback with a reference to the current value, or to the fallback value if not entered
+	///
+	/// The fallback is created by `default` on first use and kept until the thread exits.
+	///
+	/// # Panics
+	///
+	/// Panics if the inner value is currently locked exclusively by a `with_mut` call.
+	#[inline]
+	pub fn with_or<D, F, R>(this: &'static LocalKey<Self>, default: D, f: F) -> R
+	where
+		D: FnOnce() -> T,
+		F: FnOnce(&T) -> R,
+	{
+		this.with(|c| c.with_ref_or(default, f))
 	}
 
 	/// Run callback with a reference to the current value (if there is one)
@@ -111,8 +310,57 @@ impl<T> Current<T> {
 	where
 		F: FnOnce(Option<&mut T>) -> R,
 	{
-		this.with(|c| {
-			f(c.inner.borrow_mut().as_mut())
-		})
+		this.with(|c| c.with_mut_ref(f))
+	}
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+	use super::Current;
+
+	thread_local! {
+		static SINGLE: Current<u32> = const { Current::new() };
+		static NESTED: Current<u32> = const { Current::nested() };
+	}
+
+	fn get(current: &'static std::thread::LocalKey<Current<u32>>) -> Option<u32> {
+		#[allow(clippy::redundant_closure_for_method_calls)] // sadly the suggestion doesn't compile
+		Current::with(current, |v| v.copied())
+	}
+
+	#[test]
+	fn try_enter() {
+		Current::enter_nonblocking(&SINGLE, 1, || {
+			assert!(Current::try_enter_nonblocking(&SINGLE, 2, || unreachable!()).is_err());
+			assert_eq!(get(&SINGLE), Some(1));
+		});
+		assert_eq!(Current::try_enter_nonblocking(&SINGLE, 3, || get(&SINGLE)).unwrap(), Some(3));
+		assert_eq!(get(&SINGLE), None);
+	}
+
+	#[test]
+	fn with_or() {
+		let mut created = 0;
+		for _ in 0..2 {
+			assert_eq!(Current::with_or(&SINGLE, || { created += 1; 10 }, |v| *v), 10);
+		}
+		assert_eq!(created, 1);
+		// the fallback isn't the entered value
+		assert_eq!(get(&SINGLE), None);
+		Current::enter_nonblocking(&SINGLE, 1, || {
+			assert_eq!(Current::with_or(&SINGLE, || unreachable!(), |v| *v), 1);
+		});
+		assert_eq!(Current::with_or(&SINGLE, || unreachable!(), |v| *v), 10);
+	}
+
+	#[test]
+	fn nested() {
+		Current::enter_nonblocking(&NESTED, 1, || {
+			Current::enter_nonblocking(&NESTED, 2, || {
+				assert_eq!(get(&NESTED), Some(2));
+			});
+			assert_eq!(get(&NESTED), Some(1));
+		});
+		assert_eq!(get(&NESTED), None);
 	}
 }
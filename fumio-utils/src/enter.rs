@@ -0,0 +1,76 @@
+//! Marks the current thread as running a blocking executor.
+//!
+//! Blocking executors (and the "current" handles they set) require an [`Enter`](struct.Enter.html)
+//! guard; as there can only be one guard per thread at a time, this prevents blocking executors
+//! from being nested (which would deadlock).
+//!
+//! # Example
+//!
+//! ```
+//! let enter = fumio_utils::enter::enter().unwrap();
+//! assert!(fumio_utils::enter::enter().is_err());
+//! drop(enter);
+//! assert!(fumio_utils::enter::enter().is_ok());
+//! ```
+
+use std::cell::Cell;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+
+thread_local! {
+	static ENTERED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Guard marking the current thread as running a blocking executor
+///
+/// Created by [`enter`](fn.enter.html); the thread is released when the guard is dropped.
+pub struct Enter {
+	// bound to the thread
+	_marker: PhantomData<*mut ()>,
+}
+
+impl fmt::Debug for Enter {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Enter").finish()
+	}
+}
+
+impl Drop for Enter {
+	fn drop(&mut self) {
+		ENTERED.with(|entered| {
+			debug_assert!(entered.get());
+			entered.set(false);
+		});
+	}
+}
+
+/// Error returned by [`enter`](fn.enter.html) if the thread is already running an executor
+#[derive(Debug)]
+pub struct EnterError {
+	_priv: (),
+}
+
+impl fmt::Display for EnterError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("an executor is already running on this thread")
+	}
+}
+
+impl Error for EnterError {}
+
+/// Mark the current thread as running a blocking executor
+///
+/// # Errors
+///
+/// Fails if the thread already is marked (i.e. an `Enter` guard is alive on this thread).
+pub fn enter() -> Result<Enter, EnterError> {
+	ENTERED.with(|entered| {
+		if entered.get() {
+			Err(EnterError { _priv: () })
+		} else {
+			entered.set(true);
+			Ok(Enter { _marker: PhantomData })
+		}
+	})
+}
@@ -14,14 +14,28 @@
 	clippy::module_name_repetitions, // often hidden modules and reexported
 	clippy::if_not_else, // `... != 0` is a positive condition
 	clippy::multiple_crate_versions, // not useful
+	clippy::must_use_candidate, // would mark nearly every getter
+	clippy::missing_const_for_fn, // `const` is an API promise; only added where it is needed
+	clippy::redundant_pub_crate, // stays crate-internal even if the module becomes public
 )]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
-#[doc(hidden)]
 pub mod mpsc;
 
+pub mod sync;
+
 #[doc(hidden)]
 pub mod local_dl_list;
 
+#[cfg(feature = "std")]
+pub mod coop;
+
 pub mod current;
 
+#[cfg(feature = "std")]
+pub mod enter;
+
+#[cfg(feature = "std")]
 pub mod park;
@@ -1,11 +1,13 @@
-use std::cell::Cell;
-use std::ptr;
+use core::cell::Cell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ptr;
 
 #[doc(hidden)]
 #[derive(Debug)]
 pub struct LocalDLHead {
-	prev: Cell<*const LocalDLHead>,
-	next: Cell<*const LocalDLHead>,
+	prev: Cell<*const Self>,
+	next: Cell<*const Self>,
 }
 
 impl LocalDLHead {
@@ -25,7 +27,7 @@ impl LocalDLHead {
 	}
 
 	pub fn is_unlinked(&self) -> bool {
-		self.next.get().is_null() || self.next.get() == (self as _)
+		self.next.get().is_null() || ptr::eq(self.next.get(), self)
 	}
 
 	pub unsafe fn unlink(&self) {
@@ -39,7 +41,7 @@ impl LocalDLHead {
 
 	pub unsafe fn insert_after(&self, node: &Self) {
 		debug_assert!(node.is_unlinked());
-		assert_ne!(self as *const _, node as *const _);
+		assert!(!ptr::eq(self, node));
 		self.init();
 		node.next.set(self.next.get());
 		node.prev.set(self);
@@ -49,7 +51,7 @@ impl LocalDLHead {
 
 	pub unsafe fn insert_before(&self, node: &Self) {
 		debug_assert!(node.is_unlinked());
-		assert_ne!(self as *const _, node as *const _);
+		assert!(!ptr::eq(self, node));
 		self.init();
 		node.next.set(self);
 		node.prev.set(self.prev.get());
@@ -75,6 +77,69 @@ impl LocalDLHead {
 		Some(node)
 	}
 
+	pub fn is_empty(&self) -> bool {
+		self.is_unlinked()
+	}
+
+	// number of other nodes in the list (O(n))
+	pub fn len(&self) -> usize {
+		if self.is_unlinked() {
+			return 0;
+		}
+		let mut len = 0;
+		let mut node = self.next.get();
+		while !ptr::eq(node, self) {
+			len += 1;
+			node = unsafe { &*node }.next.get();
+		}
+		len
+	}
+
+	// call `f` for all other nodes in the list (nodes must stay alive and linked during the call)
+	pub unsafe fn for_each<F: FnMut(*const Self)>(&self, mut f: F) {
+		if self.is_unlinked() {
+			return;
+		}
+		let mut node = self.next.get();
+		while !ptr::eq(node, self) {
+			let next = /* unsafe */ { &*node }.next.get();
+			f(node);
+			node = next;
+		}
+	}
+
+	// iterate over all other nodes in the list (nodes must stay alive and linked while iterating,
+	// apart from the last returned one)
+	pub unsafe fn iter<T>(&self, from_node: fn(*const Self) -> *const T) -> LocalDLIter<'_, T> {
+		let next = if self.is_unlinked() { ptr::from_ref(self) } else { self.next.get() };
+		LocalDLIter { head: self, next, from_node, _marker: PhantomData }
+	}
+
+	// cursor starting at the first node (same requirements as `iter`); `len` is decremented for
+	// removed nodes
+	pub unsafe fn cursor_front_mut<'a, T>(&'a self, from_node: fn(*const Self) -> *const T, len: Option<&'a Cell<usize>>) -> LocalDLCursorMut<'a, T> {
+		let current = if self.is_unlinked() { ptr::from_ref(self) } else { self.next.get() };
+		LocalDLCursorMut { head: self, current, len, from_node, _marker: PhantomData }
+	}
+
+	// move all nodes from `other` to the end of this list (O(1))
+	pub unsafe fn append_all(&self, other: &Self) {
+		assert!(!ptr::eq(self, other));
+		if other.is_unlinked() {
+			return;
+		}
+		self.init();
+		let first = other.next.get();
+		let last = other.prev.get();
+		let tail = self.prev.get();
+		/* unsafe */ { &*tail }.next.set(first);
+		/* unsafe */ { &*first }.prev.set(tail);
+		/* unsafe */ { &*last }.next.set(self);
+		self.prev.set(last);
+		other.next.set(ptr::null());
+		other.prev.set(ptr::null());
+	}
+
 	pub unsafe fn take_from(&mut self, other: &Self) {
 		debug_assert!(self.is_unlinked());
 		if !other.is_unlinked() {
@@ -96,50 +161,150 @@ impl Drop for LocalDLHead {
 	}
 }
 
+#[doc(hidden)]
+pub struct LocalDLIter<'a, T> {
+	head: &'a LocalDLHead,
+	next: *const LocalDLHead,
+	from_node: fn(*const LocalDLHead) -> *const T,
+	_marker: PhantomData<&'a T>,
+}
+
+impl<T> fmt::Debug for LocalDLIter<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("LocalDLIter")
+			.field("head", self.head)
+			.field("next", &self.next)
+			.field("from_node", &())
+			.field("_marker", &self._marker)
+			.finish()
+	}
+}
+
+impl<'a, T> Iterator for LocalDLIter<'a, T> {
+	type Item = &'a T;
+
+	fn next(&mut self) -> Option<&'a T> {
+		if ptr::eq(self.next, self.head) {
+			return None;
+		}
+		let node = self.next;
+		// read `next` now, so the returned node can be unlinked
+		self.next = unsafe { &*node }.next.get();
+		Some(unsafe { &*(self.from_node)(node) })
+	}
+}
+
+/// Cursor over a list; can remove nodes while iterating
+///
+/// Points either at a node or at the "ghost" position (the head) between the last and the first
+/// node.
+#[doc(hidden)]
+pub struct LocalDLCursorMut<'a, T> {
+	head: &'a LocalDLHead,
+	current: *const LocalDLHead,
+	len: Option<&'a Cell<usize>>,
+	from_node: fn(*const LocalDLHead) -> *const T,
+	_marker: PhantomData<&'a T>,
+}
+
+impl<'a, T> LocalDLCursorMut<'a, T> {
+	/// Current node (`None` at the ghost position)
+	pub fn current(&self) -> Option<&'a T> {
+		if ptr::eq(self.current, self.head) {
+			return None;
+		}
+		Some(unsafe { &*(self.from_node)(self.current) })
+	}
+
+	/// Move to the next node (or the ghost position after the last node)
+	pub fn move_next(&mut self) {
+		if !self.head.is_unlinked() {
+			self.current = unsafe { &*self.current }.next.get();
+		}
+	}
+
+	/// Move to the previous node (or the ghost position before the first node)
+	pub fn move_prev(&mut self) {
+		if !self.head.is_unlinked() {
+			self.current = unsafe { &*self.current }.prev.get();
+		}
+	}
+
+	/// Unlink the current node and move to the next one
+	///
+	/// Returns `None` (and does nothing) at the ghost position.
+	pub fn remove_current(&mut self) -> Option<*const T> {
+		if ptr::eq(self.current, self.head) {
+			return None;
+		}
+		let node = self.current;
+		self.current = unsafe { &*node }.next.get();
+		unsafe { (*node).unlink() };
+		if let Some(len) = self.len {
+			len.set(len.get() - 1);
+		}
+		Some((self.from_node)(node))
+	}
+}
+
+impl<T> fmt::Debug for LocalDLCursorMut<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("LocalDLCursorMut")
+			.field("head", self.head)
+			.field("current", &self.current)
+			.field("len", &self.len)
+			.field("from_node", &())
+			.field("_marker", &self._marker)
+			.finish()
+	}
+}
+
 /// Create a "link" and "head" datatype for a non thread-safe double linked list; the "link" type
 /// must be used in a data structure as member.
 ///
 /// The list itself doesn't manage any ownership / reference counts, which is why most
 /// modifications are `unsafe`, and returned nodes are raw pointers.
 ///
+/// With `counted head ...;` the head tracks its length, making `len()` O(1); nodes of such a list
+/// must only be removed through the head (`remove`, `pop_*`, cursors), not with `unlink` on their
+/// link.
+///
 /// # Example
 /// 
 /// ```
-/// fn main() {
-///     mod example {
-///         fumio_utils::local_dl_list! {
-///             pub mod ex1 {
-///                 link TestLink;
-///                 head TestHead;
-///                 member link of Test;
-///             }
+/// mod example {
+///     fumio_utils::local_dl_list! {
+///         pub mod ex1 {
+///             link TestLink;
+///             head TestHead;
+///             member link of Test;
 ///         }
+///     }
 /// 
-///         pub struct Test {
-///             link: TestLink,
-///             pub value: usize,
-///         }
+///     pub struct Test {
+///         link: TestLink,
+///         pub value: usize,
+///     }
 /// 
-///         impl Test {
-///             pub fn new(value: usize) -> Self {
-///                 Test {
-///                     link: TestLink::new(),
-///                     value,
-///                 }
+///     impl Test {
+///         pub fn new(value: usize) -> Self {
+///             Test {
+///                 link: TestLink::new(),
+///                 value,
 ///             }
 ///         }
 ///     }
-///     use example::*;
+/// }
+/// use example::*;
 /// 
-///     let head = TestHead::new();
-///     let node1 = Test::new(1);
-///     let node2 = Test::new(2);
-///     unsafe {
-///         head.append(&node1);
-///         head.append(&node2);
-///         assert_eq!( { &*head.pop_front().unwrap() }.value, 1);
-///         assert_eq!( { &*head.pop_front().unwrap() }.value, 2);
-///     }
+/// let head = TestHead::new();
+/// let node1 = Test::new(1);
+/// let node2 = Test::new(2);
+/// unsafe {
+///     head.append(&node1);
+///     head.append(&node2);
+///     assert_eq!( { &*head.pop_front().unwrap() }.value, 1);
+///     assert_eq!( { &*head.pop_front().unwrap() }.value, 2);
 /// }
 /// ```
 #[macro_export]
@@ -164,10 +329,36 @@ macro_rules! local_dl_list {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! _local_dl_list {
+	([$vis:vis] [$innervis:vis] mod $modname:ident {
+		link $link_name:ident;
+		counted head $head_name:ident;
+		member $member:ident of $parent:ident;
+	}) => {
+		$crate::_local_dl_list! {
+			@impl [$vis] [$innervis] [Some(core::cell::Cell::new(0))] mod $modname {
+				link $link_name;
+				head $head_name;
+				member $member of $parent;
+			}
+		}
+	};
 	([$vis:vis] [$innervis:vis] mod $modname:ident {
 		link $link_name:ident;
 		head $head_name:ident;
 		member $member:ident of $parent:ident;
+	}) => {
+		$crate::_local_dl_list! {
+			@impl [$vis] [$innervis] [None] mod $modname {
+				link $link_name;
+				head $head_name;
+				member $member of $parent;
+			}
+		}
+	};
+	(@impl [$vis:vis] [$innervis:vis] [$len:expr] mod $modname:ident {
+		link $link_name:ident;
+		head $head_name:ident;
+		member $member:ident of $parent:ident;
 	}) => {
 		mod $modname {
 			use super::$parent;
@@ -180,14 +371,16 @@ macro_rules! _local_dl_list {
 
 			#[allow(dead_code)]
 			impl $link_name {
+				// points back to the `$parent` containing the link
+				#[allow(clippy::cast_ptr_alignment)]
 				fn __base_from_node(ptr: *const LocalDLHead) -> *const $parent {
 					let member_offset: usize = {
 						let p = core::ptr::NonNull::<$parent>::dangling();
 						let $parent { $member: member, .. } = unsafe { &*p.as_ptr() };
 						let head: &LocalDLHead = &member.head;
-						(head as *const _ as usize) - (p.as_ptr() as *const _ as usize)
+						(core::ptr::from_ref(head) as usize) - (p.as_ptr() as usize)
 					};
-					unsafe { (ptr as *const u8).sub(member_offset) as *const $parent }
+					unsafe { ptr.cast::<u8>().sub(member_offset).cast::<$parent>() }
 				}
 
 				$innervis const fn new() -> Self {
@@ -218,6 +411,8 @@ macro_rules! _local_dl_list {
 			#[derive(Debug)]
 			$innervis struct $head_name {
 				head: LocalDLHead,
+				// only for `counted` heads
+				len: Option<core::cell::Cell<usize>>,
 			}
 
 			#[allow(dead_code)]
@@ -225,6 +420,7 @@ macro_rules! _local_dl_list {
 				$innervis const fn new() -> Self {
 					Self {
 						head: LocalDLHead::new(),
+						len: $len,
 					}
 				}
 
@@ -232,28 +428,94 @@ macro_rules! _local_dl_list {
 					self.head.is_unlinked()
 				}
 
+				/// O(1) for `counted` heads, O(n) otherwise
+				$innervis fn len(&self) -> usize {
+					match &self.len {
+						Some(len) => len.get(),
+						None => self.head.len(),
+					}
+				}
+
+				fn __len_add(&self, n: usize) {
+					if let Some(len) = &self.len {
+						len.set(len.get() + n);
+					}
+				}
+
+				fn __len_sub(&self, n: usize) {
+					if let Some(len) = &self.len {
+						len.set(len.get() - n);
+					}
+				}
+
+				// length of `other` if it needs to be tracked (`other` is counted too)
+				fn __len_of(&self, other: &Self) -> usize {
+					if self.len.is_some() { other.len() } else { 0 }
+				}
+
 				$innervis unsafe fn prepend(&self, node: &$parent) {
 					let node_link: &$link_name = &node.$member;
 					self.head.insert_after(&node_link.head);
+					self.__len_add(1);
 				}
 
 				$innervis unsafe fn append(&self, node: &$parent) {
 					let node_link: &$link_name = &node.$member;
 					self.head.insert_before(&node_link.head);
+					self.__len_add(1);
+				}
+
+				/// `node` must be linked in this list
+				$innervis unsafe fn remove(&self, node: &$parent) {
+					let node_link: &$link_name = &node.$member;
+					debug_assert!(!node_link.is_unlinked());
+					node_link.head.unlink();
+					self.__len_sub(1);
 				}
 
 				$innervis unsafe fn pop_front(&self) -> Option<*const $parent> {
 					let node_link = self.head.pop_front()?;
+					self.__len_sub(1);
 					Some($link_name::__base_from_node(node_link))
 				}
 
 				$innervis unsafe fn pop_back(&self) -> Option<*const $parent> {
 					let node_link = self.head.pop_back()?;
+					self.__len_sub(1);
 					Some($link_name::__base_from_node(node_link))
 				}
 
 				$innervis unsafe fn take_from(&mut self, other: &Self) {
+					let n = self.__len_of(other);
 					self.head.take_from(&other.head);
+					self.__len_add(n);
+					other.__len_sub(n);
+				}
+
+				/// Move all nodes from `other` to the end of this list (O(1))
+				$innervis unsafe fn append_all(&self, other: &Self) {
+					let n = self.__len_of(other);
+					self.head.append_all(&other.head);
+					self.__len_add(n);
+					other.__len_sub(n);
+				}
+
+				$innervis unsafe fn for_each<F: FnMut(&$parent)>(&self, mut f: F) {
+					self.head.for_each(|node_link| {
+						f(/* unsafe */ { &*$link_name::__base_from_node(node_link) })
+					});
+				}
+
+				/// Nodes must stay alive and linked while iterating (apart from the last returned
+				/// one, which may be unlinked).
+				$innervis unsafe fn iter(&self) -> $crate::local_dl_list::LocalDLIter<'_, $parent> {
+					self.head.iter($link_name::__base_from_node)
+				}
+
+				/// Cursor starting at the first node; nodes must stay alive and only be unlinked
+				/// through the cursor while it is used.
+				$innervis unsafe fn cursor_front_mut(&self) -> $crate::local_dl_list::LocalDLCursorMut<'_, $parent> {
+					self.head.cursor_front_mut($link_name::__base_from_node, self.len.as_ref())
 				}
 			}
 		}
@@ -262,6 +524,7 @@ macro_rules! _local_dl_list {
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_pub_self)] // the `mod` form of the macro expands to `pub(self)`
 mod test {
 	local_dl_list! {
 		mod ex1 {
@@ -271,6 +534,28 @@ mod test {
 		}
 	}
 
+	local_dl_list! {
+		mod ex2 {
+			link CountedLink;
+			counted head CountedHead;
+			member counted_link of Counted;
+		}
+	}
+
+	struct Counted {
+		counted_link: CountedLink,
+		value: usize,
+	}
+
+	impl Counted {
+		const fn new(value: usize) -> Self {
+			Self {
+				counted_link: CountedLink::new(),
+				value,
+			}
+		}
+	}
+
 	struct Test {
 		link: TestLink,
 		value: usize,
@@ -293,8 +578,102 @@ mod test {
 		unsafe {
 			head.append(&node1);
 			head.append(&node2);
+			assert_eq!(head.len(), 2);
+			let mut values = alloc::vec::Vec::new();
+			head.for_each(|node| values.push(node.value));
+			assert_eq!(values, [1, 2]);
 			assert_eq!( { &*head.pop_front().unwrap() }.value, 1);
 			assert_eq!( { &*head.pop_front().unwrap() }.value, 2);
 		}
 	}
+
+	#[test]
+	fn cursor() {
+		let head = TestHead::new();
+		let nodes: alloc::vec::Vec<_> = (1..=4).map(Test::new).collect();
+		unsafe {
+			assert!(head.iter().next().is_none());
+			assert!(head.cursor_front_mut().current().is_none());
+			for node in &nodes {
+				head.append(node);
+			}
+			let values: alloc::vec::Vec<_> = head.iter().map(|node| node.value).collect();
+			assert_eq!(values, [1, 2, 3, 4]);
+
+			// remove even values in place
+			let mut cursor = head.cursor_front_mut();
+			while let Some(node) = cursor.current() {
+				if node.value % 2 == 0 {
+					assert_eq!({ &*cursor.remove_current().unwrap() }.value, node.value);
+				} else {
+					cursor.move_next();
+				}
+			}
+			assert!(cursor.remove_current().is_none());
+			cursor.move_prev();
+			assert_eq!(cursor.current().unwrap().value, 3);
+			cursor.move_next();
+			cursor.move_next();
+			assert_eq!(cursor.current().unwrap().value, 1);
+
+			let values: alloc::vec::Vec<_> = head.iter().map(|node| node.value).collect();
+			assert_eq!(values, [1, 3]);
+			assert!(nodes[1].link.is_unlinked());
+			while head.pop_front().is_some() {}
+		}
+	}
+
+	#[test]
+	fn append_all() {
+		let head1 = TestHead::new();
+		let head2 = TestHead::new();
+		let nodes: alloc::vec::Vec<_> = (1..=4).map(Test::new).collect();
+		unsafe {
+			// splicing an empty list
+			head1.append_all(&head2);
+			assert!(head1.is_empty());
+			head1.append(&nodes[0]);
+			head2.append(&nodes[1]);
+			head2.append(&nodes[2]);
+			head1.append_all(&head2);
+			assert!(head2.is_empty());
+			head2.append(&nodes[3]);
+			head1.append_all(&head2);
+			let values: alloc::vec::Vec<_> = head1.iter().map(|node| node.value).collect();
+			assert_eq!(values, [1, 2, 3, 4]);
+			while head1.pop_back().is_some() {}
+		}
+	}
+
+	#[test]
+	fn counted() {
+		let mut head1 = CountedHead::new();
+		let head2 = CountedHead::new();
+		let nodes: alloc::vec::Vec<_> = (1..=5).map(Counted::new).collect();
+		unsafe {
+			for node in &nodes[..3] {
+				head1.append(node);
+			}
+			head2.prepend(&nodes[3]);
+			head2.prepend(&nodes[4]);
+			assert_eq!((head1.len(), head2.len()), (3, 2));
+			head2.append_all(&head1);
+			assert_eq!((head1.len(), head2.len()), (0, 5));
+			head2.remove(&nodes[0]);
+			head2.pop_front();
+			assert_eq!(head2.len(), 3);
+			{
+				let mut cursor = head2.cursor_front_mut();
+				assert_eq!(cursor.current().unwrap().value, 4);
+				cursor.remove_current();
+			}
+			assert_eq!(head2.len(), 2);
+			head1.take_from(&head2);
+			assert_eq!((head1.len(), head2.len()), (2, 0));
+			let values: alloc::vec::Vec<_> = head1.iter().map(|node| node.value).collect();
+			assert_eq!(values, [2, 3]);
+			while head1.pop_front().is_some() {}
+			assert_eq!(head1.len(), 0);
+		}
+	}
 }
@@ -1,9 +1,22 @@
-use std::cell::Cell;
-use std::ptr::{NonNull, null_mut};
-use std::sync::atomic::{AtomicPtr, Ordering};
+//! Intrusive multi-producer single-consumer queue of `Arc` nodes
+//!
+//! Either use the generic [`Queue`](struct.Queue.html) with a [`QueueLink`](struct.QueueLink.html)
+//! member, or generate link and head types with the [`mpsc!`](../macro.mpsc.html) macro.
+
+use alloc::boxed::Box;
+use core::cell::Cell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ptr::{self, NonNull, null_mut};
+use crate::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+// for the macro expansion (the using crate might be `no_std`)
+#[doc(hidden)]
+pub use alloc::sync::Arc;
 
 #[doc(hidden)]
 #[derive(Debug)]
+#[allow(clippy::struct_field_names)] // head and tail of the queue
 pub struct Head {
 	tail: AtomicPtr<Link>, // non-null too
 	head: Cell<NonNull<Link>>,
@@ -16,27 +29,41 @@ impl Head {
 	pub fn new() -> Self {
 		let stub = Box::new(Link::new());
 		Self {
-			tail: AtomicPtr::new(&*stub as *const Link as *mut _),
+			tail: AtomicPtr::new(ptr::addr_of!(*stub).cast_mut()),
 			head: Cell::new(NonNull::from(&*stub)),
 			stub,
 		}
 	}
 
-	// multiple producers, thread-safe
+	/// Push a link (multiple producers, thread-safe)
+	///
+	/// # Safety
+	///
+	/// The link must stay alive and must not be pushed again until it was popped.
 	pub unsafe fn push(&self, link: *const Link) {
-		self._push(link);
+		self.push_link(link);
 	}
 
-	fn _push(&self, link: *const Link) {
-		let link = link as *mut Link; // AtomicPtr wants *mut ...
-		let prev = self.tail.swap(link, Ordering::Release);
+	fn push_link(&self, link: *const Link) {
+		let link = link.cast_mut(); // AtomicPtr wants *mut ...
+		// acquire: `prev` might have been initialized (and pushed) by another thread
+		let prev = self.tail.swap(link, Ordering::AcqRel);
 		unsafe { &*prev }.next.store(link, Ordering::Release);
 	}
 
-	// single consumer, must be only called by a single thread!
-	// at most one pop operation must be in progress at a time.
+	/// Pop links (single consumer)
+	///
+	/// # Safety
+	///
+	/// Must only be called by a single thread; at most one pop operation must be in progress at
+	/// a time.
 	#[allow(clippy::mut_from_ref)]
 	pub unsafe fn start_pop(&self) -> impl Iterator<Item = *const Link> + '_ {
+		self.pop_all()
+	}
+
+	// same requirements as `start_pop`
+	unsafe fn pop_all(&self) -> PopAll<'_> {
 		PopAll { this: self, pos: self.head.get(), repushed_stub: false }
 	}
 }
@@ -49,7 +76,7 @@ impl Default for Head {
 
 impl Drop for Head {
 	fn drop(&mut self) {
-		assert_eq!(self.tail.load(Ordering::Relaxed) as *const Link, &*self.stub);
+		assert!(ptr::eq(self.tail.load(Ordering::Relaxed), ptr::addr_of!(*self.stub)));
 	}
 }
 
@@ -70,15 +97,18 @@ impl Iterator for PopAll<'_> {
 
 	fn next(&mut self) -> Option<Self::Item> {
 		loop {
-			let next = NonNull::new(unsafe { self.pos.as_ref() }.next.swap(null_mut(), Ordering::Acquire))?;
+			let pos_next = &unsafe { self.pos.as_ref() }.next;
+			let next = NonNull::new(pos_next.load(Ordering::Acquire))?;
+			// only reset once set: a producer might still be storing to it until then
+			pos_next.store(null_mut(), Ordering::Relaxed);
 			// pop this.pos as we have another node after it to set as next head
-			let item = self.pos.as_ptr() as *const Link;
+			let item = self.pos.as_ptr().cast_const();
 			self.pos = next;
 			if item == &*self.this.stub {
 				// break the loop when pushing stub the second time to avoid starvation
 				let break_loop = self.repushed_stub;
 				self.repushed_stub = true;
-				self.this._push(item);
+				self.this.push_link(item);
 				if break_loop {
 					return None;
 				}
@@ -92,15 +122,24 @@ impl Iterator for PopAll<'_> {
 #[doc(hidden)]
 #[derive(Debug)]
 pub struct Link {
-	next: AtomicPtr<Link>,
+	next: AtomicPtr<Self>,
 }
 
 impl Link {
+	#[cfg(not(loom))]
 	pub const fn new() -> Self {
 		Self {
 			next: AtomicPtr::new(null_mut()),
 		}
 	}
+
+	// loom atomics can't be created in const context
+	#[cfg(loom)]
+	pub fn new() -> Self {
+		Self {
+			next: AtomicPtr::new(null_mut()),
+		}
+	}
 }
 
 impl Default for Link {
@@ -109,6 +148,328 @@ impl Default for Link {
 	}
 }
 
+/// Link to embed in nodes of a [`Queue`](struct.Queue.html)
+///
+/// Also tracks whether the node is queued, so a node can't be pushed twice.
+#[derive(Default)]
+pub struct QueueLink {
+	link: Link,
+	queued: AtomicBool,
+}
+
+impl QueueLink {
+	/// New (unqueued) link
+	#[cfg(not(loom))]
+	pub const fn new() -> Self {
+		Self {
+			link: Link::new(),
+			queued: AtomicBool::new(false),
+		}
+	}
+
+	/// New (unqueued) link
+	#[cfg(loom)]
+	pub fn new() -> Self {
+		Self {
+			link: Link::new(),
+			queued: AtomicBool::new(false),
+		}
+	}
+
+	/// Whether the node is currently in a queue
+	pub fn is_queued(&self) -> bool {
+		self.queued.load(Ordering::Relaxed)
+	}
+}
+
+impl fmt::Debug for QueueLink {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("QueueLink")
+			.field("link", &())
+			.field("queued", &self.is_queued())
+			.finish()
+	}
+}
+
+/// Nodes that can be pushed to a [`Queue`](struct.Queue.html)
+///
+/// # Safety
+///
+/// `queue_link` must always return a reference to the same member of `self`; the link must not
+/// be used for anything else.
+pub unsafe trait HasLink {
+	/// The embedded link
+	fn queue_link(&self) -> &QueueLink;
+}
+
+/// Intrusive multi-producer single-consumer queue of `Arc<T>`
+///
+/// A node can only be in one queue at a time; pushing a node that already is queued is rejected.
+/// Nodes can be pushed from any thread; popping is serialized.
+///
+/// # Example
+///
+// loom atomics only work within `loom::model`
+#[cfg_attr(loom, doc = "```ignore")]
+#[cfg_attr(not(loom), doc = "```")]
+/// use fumio_utils::mpsc::{HasLink, Queue, QueueLink};
+/// use std::sync::Arc;
+///
+/// struct MyData {
+///     link: QueueLink,
+///     value: u32,
+/// }
+///
+/// unsafe impl HasLink for MyData {
+///     fn queue_link(&self) -> &QueueLink {
+///         &self.link
+///     }
+/// }
+///
+/// let queue = Queue::new();
+/// let data = Arc::new(MyData { link: QueueLink::new(), value: 1 });
+/// assert!(queue.push(data.clone()));
+/// assert!(!queue.push(data.clone())); // already queued
+/// let values: Vec<u32> = queue.try_pop_all().unwrap().map(|item| item.value).collect();
+/// assert_eq!(values, [1]);
+/// ```
+pub struct Queue<T: HasLink> {
+	head: Head,
+	// offset of the `Link` in `T`
+	offset: AtomicUsize,
+	len: AtomicUsize,
+	popping: AtomicBool,
+	_marker: PhantomData<Arc<T>>,
+}
+
+// SAFETY: the raw pointers in `head` only point to the (boxed) stub and to queued `Arc<T>`
+// nodes owned by the queue, so moving the queue to another thread moves nodes of a `Send + Sync`
+// type; the consumer side is protected by `popping`.
+#[allow(clippy::non_send_fields_in_send_ty)]
+unsafe impl<T: HasLink + Send + Sync> Send for Queue<T> {}
+unsafe impl<T: HasLink + Send + Sync> Sync for Queue<T> {}
+
+impl<T: HasLink> Queue<T> {
+	/// New empty queue
+	pub fn new() -> Self {
+		Self {
+			head: Head::new(),
+			offset: AtomicUsize::new(0),
+			len: AtomicUsize::new(0),
+			popping: AtomicBool::new(false),
+			_marker: PhantomData,
+		}
+	}
+
+	/// Number of queued nodes
+	///
+	/// Only a snapshot if other threads push or pop concurrently.
+	pub fn len(&self) -> usize {
+		self.len.load(Ordering::Relaxed)
+	}
+
+	/// Whether the queue is empty
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// Push a node to the queue
+	///
+	/// Returns `false` (and drops `node`) if the node already is queued (in any queue).
+	pub fn push(&self, node: Arc<T>) -> bool {
+		if !Self::mark(&node) {
+			return false;
+		}
+		self.len.fetch_add(1, Ordering::Relaxed);
+		self.enqueue(node);
+		true
+	}
+
+	// returns false if already queued
+	fn mark(node: &T) -> bool {
+		// no write if already queued (racing with the consumer clearing it)
+		node.queue_link().queued.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_ok()
+	}
+
+	// node must be marked, and `len` already counts it
+	fn enqueue(&self, node: Arc<T>) {
+		let link = node.queue_link();
+		let offset = (ptr::addr_of!(link.link) as usize).wrapping_sub(Arc::as_ptr(&node) as usize);
+		// all nodes have the same offset; the queue synchronizes it with the consumer
+		self.offset.store(offset, Ordering::Relaxed);
+		let link = ptr::addr_of!(link.link);
+		// the queue takes the reference
+		let _ = Arc::into_raw(node);
+		unsafe { self.head.push(link) };
+	}
+
+	/// Pop all nodes
+	///
+	/// Nodes pushed while iterating might only be returned by the next call.
+	///
+	/// Returns `None` if another thread currently is popping.
+	pub fn try_pop_all(&self) -> Option<QueuePop<'_, T>> {
+		if self.popping.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+			return None;
+		}
+		Some(QueuePop {
+			queue: self,
+			iter: unsafe { self.head.pop_all() },
+			_guard: PopGuard { popping: &self.popping },
+		})
+	}
+}
+
+impl<T: HasLink> Default for Queue<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: HasLink> fmt::Debug for Queue<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Queue")
+			.field("head", &self.head)
+			.field("offset", &self.offset)
+			.field("len", &self.len)
+			.field("popping", &self.popping)
+			.finish()
+	}
+}
+
+impl<T: HasLink> Drop for Queue<T> {
+	fn drop(&mut self) {
+		for _ in self.try_pop_all().expect("can't be popping while dropped") {}
+	}
+}
+
+struct PopGuard<'a> {
+	popping: &'a AtomicBool,
+}
+
+impl Drop for PopGuard<'_> {
+	fn drop(&mut self) {
+		self.popping.store(false, Ordering::Release);
+	}
+}
+
+/// Iterator returned by [`Queue::try_pop_all`](struct.Queue.html#method.try_pop_all)
+pub struct QueuePop<'a, T: HasLink> {
+	queue: &'a Queue<T>,
+	iter: PopAll<'a>,
+	// dropped after `iter` stored the new head
+	_guard: PopGuard<'a>,
+}
+
+impl<T: HasLink> fmt::Debug for QueuePop<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("QueuePop")
+			.field("queue", self.queue)
+			.field("iter", &())
+			.field("_guard", &())
+			.finish()
+	}
+}
+
+impl<T: HasLink> Iterator for QueuePop<'_, T> {
+	type Item = Arc<T>;
+
+	fn next(&mut self) -> Option<Arc<T>> {
+		let link = self.iter.next()?;
+		let offset = self.queue.offset.load(Ordering::Relaxed);
+		let node = unsafe { Arc::from_raw(link.cast::<u8>().wrapping_sub(offset).cast::<T>()) };
+		node.queue_link().queued.store(false, Ordering::Release);
+		self.queue.len.fetch_sub(1, Ordering::Relaxed);
+		Some(node)
+	}
+}
+
+/// Bounded variant of [`Queue`](struct.Queue.html)
+///
+/// Pushing fails when the queue is full, so producers can apply backpressure.
+pub struct BoundedQueue<T: HasLink> {
+	queue: Queue<T>,
+	capacity: usize,
+}
+
+impl<T: HasLink> BoundedQueue<T> {
+	/// New empty queue holding at most `capacity` nodes
+	///
+	/// # Panics
+	///
+	/// Panics if `capacity` is zero.
+	pub fn new(capacity: usize) -> Self {
+		assert!(capacity != 0, "capacity must not be zero");
+		Self {
+			queue: Queue::new(),
+			capacity,
+		}
+	}
+
+	/// Maximum number of queued nodes
+	pub const fn capacity(&self) -> usize {
+		self.capacity
+	}
+
+	/// Number of queued nodes
+	///
+	/// Only a snapshot if other threads push or pop concurrently.
+	pub fn len(&self) -> usize {
+		self.queue.len()
+	}
+
+	/// Whether the queue is empty
+	pub fn is_empty(&self) -> bool {
+		self.queue.is_empty()
+	}
+
+	/// Push a node to the queue
+	///
+	/// Returns `Ok(false)` (and drops `node`) if the node already is queued (in any queue).
+	///
+	/// # Errors
+	///
+	/// Returns the node if the queue is full.  Concurrent pushes of already queued nodes can
+	/// make the queue appear full for a short time.
+	pub fn push(&self, node: Arc<T>) -> Result<bool, Arc<T>> {
+		// reserve a slot first
+		let mut len = self.queue.len.load(Ordering::Relaxed);
+		loop {
+			if len >= self.capacity {
+				return Err(node);
+			}
+			match self.queue.len.compare_exchange_weak(len, len + 1, Ordering::Relaxed, Ordering::Relaxed) {
+				Ok(_) => break,
+				Err(current) => len = current,
+			}
+		}
+		if !Queue::<T>::mark(&node) {
+			self.queue.len.fetch_sub(1, Ordering::Relaxed);
+			return Ok(false);
+		}
+		self.queue.enqueue(node);
+		Ok(true)
+	}
+
+	/// Pop all nodes
+	///
+	/// Nodes pushed while iterating might only be returned by the next call.
+	///
+	/// Returns `None` if another thread currently is popping.
+	pub fn try_pop_all(&self) -> Option<QueuePop<'_, T>> {
+		self.queue.try_pop_all()
+	}
+}
+
+impl<T: HasLink> fmt::Debug for BoundedQueue<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("BoundedQueue")
+			.field("queue", &self.queue)
+			.field("capacity", &self.capacity)
+			.finish()
+	}
+}
+
 /// Create a list "link" and "head" datatype; the "link" type must be used in a data structure as
 /// member; this data structure then can be pushed as `Arc<...>` to the list head.
 ///
@@ -123,44 +484,44 @@ impl Default for Link {
 ///
 /// Memory synchronization missing as it is only a single thread example.
 ///
-/// ```
-/// fn main() {
-///     use std::sync::Arc;
-///     mod example {
-///         fumio_utils::mpsc! {
-///             pub mod ex1 {
-///                 link MyLink;
-///                 head MyHead;
-///                 member my_link of MyData;
-///             }
+// loom atomics only work within `loom::model`
+#[cfg_attr(loom, doc = "```ignore")]
+#[cfg_attr(not(loom), doc = "```")]
+/// use std::sync::Arc;
+/// mod example {
+///     fumio_utils::mpsc! {
+///         pub mod ex1 {
+///             link MyLink;
+///             head MyHead;
+///             member my_link of MyData;
 ///         }
+///     }
 ///
-///         pub struct MyData {
-///             my_link: MyLink,
-///             pub value: u32,
-///         }
+///     pub struct MyData {
+///         my_link: MyLink,
+///         pub value: u32,
+///     }
 ///
-///         impl MyData {
-///             pub fn new(value: u32) -> Self {
-///                 MyData {
-///                     my_link: MyLink::default(),
-///                     value,
-///                 }
+///     impl MyData {
+///         pub fn new(value: u32) -> Self {
+///             MyData {
+///                 my_link: MyLink::default(),
+///                 value,
 ///             }
 ///         }
 ///     }
-///     use example::*;
+/// }
+/// use example::*;
 ///
-///     let list = MyHead::new();
-///     let data1 = Arc::new(MyData::new(1));
-///     let data2 = Arc::new(MyData::new(2));
-///     list.push(data1);
-///     list.push(data2);
-///     let mut counter = 1;
-///     for item in unsafe { list.start_pop() } {
-///         assert_eq!(counter, item.value);
-///         counter += 1;
-///     }
+/// let list = MyHead::new();
+/// let data1 = Arc::new(MyData::new(1));
+/// let data2 = Arc::new(MyData::new(2));
+/// list.push(data1);
+/// list.push(data2);
+/// let mut counter = 1;
+/// for item in unsafe { list.start_pop() } {
+///     assert_eq!(counter, item.value);
+///     counter += 1;
 /// }
 /// ```
 #[macro_export]
@@ -191,8 +552,7 @@ macro_rules! _mpsc {
 		member $member:ident of $parent:ident;
 	}) => {
 		mod $modname {
-			use std::sync::Arc;
-			use $crate::mpsc::{Link, Head};
+			use $crate::mpsc::{Arc, Link, Head};
 			use super::$parent;
 
 			#[derive(Debug, Default)]
@@ -205,21 +565,24 @@ macro_rules! _mpsc {
 
 			impl $link_name {
 				/// Create a new link for a list.
+				// not `const`: `Link::new` isn't with `cfg(loom)`
 				#[allow(dead_code)]
-				$innervis const fn new() -> Self {
+				$innervis fn new() -> Self {
 					Self {
 						link: Link::new(),
 					}
 				}
 
+				// points back to the `$parent` containing the link
+				#[allow(clippy::cast_ptr_alignment)]
 				fn __base_from_node(ptr: *const Link) -> *const $parent {
 					let member_offset: usize = {
 						let p = core::ptr::NonNull::<$parent>::dangling();
 						let $parent { $member: member, .. } = unsafe { &*p.as_ptr() };
 						let head: &Link = &(member as &Self).link;
-						(head as *const _ as usize) - (p.as_ptr() as *const _ as usize)
+						(core::ptr::from_ref(head) as usize) - (p.as_ptr() as usize)
 					};
-					unsafe { (ptr as *const u8).sub(member_offset) as *const $parent }
+					unsafe { ptr.cast::<u8>().sub(member_offset).cast::<$parent>() }
 				}
 			}
 
@@ -229,6 +592,8 @@ macro_rules! _mpsc {
 			}
 
 			unsafe impl Sync for $head_name {}
+			// the consumer side (`Cell`) is only used through `unsafe` methods
+			#[allow(clippy::non_send_fields_in_send_ty)]
 			unsafe impl Send for $head_name {}
 
 			impl $head_name {
@@ -278,9 +643,12 @@ macro_rules! _mpsc {
 	};
 }
 
-#[cfg(test)]
+// uses the atomics outside of `loom::model`
+#[cfg(all(test, not(loom)))]
+#[allow(clippy::needless_pub_self)] // the `mod` form of the macro expands to `pub(self)`
 mod test {
-	use std::sync::Arc;
+	use alloc::sync::Arc;
+	use alloc::vec::Vec;
 	mpsc! {
 		mod ex1 {
 			link MyLink;
@@ -303,6 +671,64 @@ mod test {
 		}
 	}
 
+	struct QueueData {
+		link: super::QueueLink,
+		value: u32,
+	}
+
+	unsafe impl super::HasLink for QueueData {
+		fn queue_link(&self) -> &super::QueueLink {
+			&self.link
+		}
+	}
+
+	#[test]
+	fn queue() {
+		let queue = super::Queue::new();
+		let data1 = Arc::new(QueueData { link: super::QueueLink::new(), value: 1 });
+		let data2 = Arc::new(QueueData { link: super::QueueLink::new(), value: 2 });
+		assert!(queue.push(data1.clone()));
+		assert!(queue.push(data2.clone()));
+		assert!(!queue.push(data1.clone()));
+		{
+			let mut pop = queue.try_pop_all().unwrap();
+			assert!(queue.try_pop_all().is_none());
+			assert_eq!(pop.next().unwrap().value, 1);
+			// can be pushed again once popped
+			assert!(!data1.link.is_queued());
+			assert!(queue.push(data1.clone()));
+			assert_eq!(pop.next().unwrap().value, 2);
+		}
+		let values: Vec<u32> = queue.try_pop_all().unwrap().map(|item| item.value).collect();
+		assert_eq!(values, [1]);
+		assert!(queue.push(data2.clone()));
+		drop(queue);
+		// queue released its references
+		assert_eq!(Arc::strong_count(&data1), 1);
+		assert_eq!(Arc::strong_count(&data2), 1);
+	}
+
+	#[test]
+	fn bounded_queue() {
+		let queue = super::BoundedQueue::new(2);
+		let data: Vec<_> = (1..=3).map(|value| Arc::new(QueueData { link: super::QueueLink::new(), value })).collect();
+		assert_eq!(queue.push(data[0].clone()).ok(), Some(true));
+		assert_eq!(queue.push(data[0].clone()).ok(), Some(false));
+		assert_eq!(queue.len(), 1);
+		assert_eq!(queue.push(data[1].clone()).ok(), Some(true));
+		match queue.push(data[2].clone()) {
+			Err(rejected) => assert!(Arc::ptr_eq(&rejected, &data[2])),
+			Ok(_) => panic!("queue should be full"),
+		}
+		assert!(!data[2].link.is_queued());
+		assert_eq!(queue.len(), 2);
+
+		let values: Vec<u32> = queue.try_pop_all().unwrap().map(|item| item.value).collect();
+		assert_eq!(values, [1, 2]);
+		assert!(queue.is_empty());
+		assert_eq!(queue.push(data[2].clone()).ok(), Some(true));
+	}
+
 	#[test]
 	fn twoitems() {
 		let list = MyHead::new();
@@ -310,10 +736,103 @@ mod test {
 		let data2 = Arc::new(MyData::new(2));
 		list.push(data1);
 		list.push(data2);
-		let mut counter = 1;
-		for item in unsafe { list.start_pop() } {
+		for (counter, item) in (1..).zip(unsafe { list.start_pop() }) {
 			assert_eq!(counter, item.value);
-			counter += 1;
 		}
 	}
 }
+
+#[cfg(all(test, loom))]
+mod loom_test {
+	use super::{HasLink, Queue, QueueLink};
+	use alloc::sync::Arc;
+	use alloc::vec::Vec;
+	use loom::thread;
+
+	struct QueueData {
+		link: QueueLink,
+		value: u32,
+	}
+
+	unsafe impl HasLink for QueueData {
+		fn queue_link(&self) -> &QueueLink {
+			&self.link
+		}
+	}
+
+	fn data(value: u32) -> Arc<QueueData> {
+		Arc::new(QueueData { link: QueueLink::new(), value })
+	}
+
+	// values popped by the consumer while the producers push
+	fn pop_values(queue: &Queue<QueueData>, values: &mut Vec<u32>) {
+		if let Some(pop) = queue.try_pop_all() {
+			values.extend(pop.map(|item| item.value));
+		}
+	}
+
+	#[test]
+	fn concurrent_push_pop() {
+		loom::model(|| {
+			let queue = Arc::new(Queue::new());
+			let producers: Vec<_> = (1..=2).map(|value| {
+				let queue = queue.clone();
+				thread::spawn(move || assert!(queue.push(data(value))))
+			}).collect();
+
+			let mut values = Vec::new();
+			pop_values(&queue, &mut values);
+			for producer in producers {
+				producer.join().unwrap();
+			}
+			pop_values(&queue, &mut values);
+			values.sort_unstable();
+			assert_eq!(values, [1, 2]);
+			assert!(queue.is_empty());
+		});
+	}
+
+	#[test]
+	fn repush_after_pop() {
+		loom::model(|| {
+			let queue = Arc::new(Queue::new());
+			let node = data(1);
+			assert!(queue.push(node.clone()));
+
+			let pusher = {
+				let queue = queue.clone();
+				let node = node.clone();
+				// either still queued (rejected) or popped already (pushed again)
+				thread::spawn(move || queue.push(node))
+			};
+			let mut values = Vec::new();
+			pop_values(&queue, &mut values);
+			let repushed = pusher.join().unwrap();
+			pop_values(&queue, &mut values);
+			assert_eq!(values.len(), if repushed { 2 } else { 1 });
+			assert!(!node.link.is_queued());
+		});
+	}
+
+	#[test]
+	fn single_consumer() {
+		loom::model(|| {
+			let queue = Arc::new(Queue::new());
+			assert!(queue.push(data(1)));
+			let consumer = {
+				let queue = queue.clone();
+				thread::spawn(move || {
+					let mut values = Vec::new();
+					pop_values(&queue, &mut values);
+					values
+				})
+			};
+			let mut values = Vec::new();
+			pop_values(&queue, &mut values);
+			values.extend(consumer.join().unwrap());
+			// `try_pop_all` fails while the other thread is popping
+			pop_values(&queue, &mut values);
+			assert_eq!(values, [1]);
+		});
+	}
+}
@@ -1,9 +1,20 @@
 //! `Park` trait
 
-use futures_executor::Enter;
+use crate::enter::Enter;
 use std::task::Waker;
 use std::time::Duration;
 
+mod notify;
+
+mod budget;
+pub use self::budget::BudgetPark;
+
+mod chain;
+pub use self::chain::{Chain, Layer};
+
+mod manual;
+pub use self::manual::ManualPark;
+
 #[cfg(feature = "park-thread")]
 mod park_thread;
 #[cfg(feature = "park-thread")]
@@ -0,0 +1,151 @@
+use crate::enter::Enter;
+use crate::park::Park;
+use crate::park::notify::{self, Notify};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::Waker;
+use std::time::Duration;
+
+// sets `woken` and interrupts the inner park
+#[derive(Debug)]
+struct SpinNotify {
+	woken: AtomicBool,
+	inner: Waker,
+}
+
+impl Notify for SpinNotify {
+	fn notify(&self) {
+		self.woken.store(true, Ordering::Release);
+		self.inner.wake_by_ref();
+	}
+}
+
+/// `BudgetPark` polls the inner `Park` without blocking (zero timeout) up to a configurable number
+/// of times before actually blocking.
+///
+/// Spinning stops as soon as a waker returned by [`waker`](trait.Park.html#tymethod.waker) is
+/// woken; this reduces wakeup latency at the cost of burning CPU while idle.
+#[derive(Debug)]
+pub struct BudgetPark<P> {
+	inner: P,
+	spins: u32,
+	notify: Arc<SpinNotify>,
+}
+
+impl<P: Park> BudgetPark<P> {
+	/// Spin up to `spins` times before blocking in `inner`
+	pub fn new(inner: P, spins: u32) -> Self {
+		let notify = Arc::new(SpinNotify {
+			woken: AtomicBool::new(false),
+			inner: inner.waker(),
+		});
+		Self { inner, spins, notify }
+	}
+
+	/// Number of non-blocking polls before blocking
+	pub const fn spins(&self) -> u32 {
+		self.spins
+	}
+
+	/// Set number of non-blocking polls before blocking
+	pub fn set_spins(&mut self, spins: u32) {
+		self.spins = spins;
+	}
+
+	/// Access the inner `Park`
+	pub const fn get_ref(&self) -> &P {
+		&self.inner
+	}
+
+	/// Mutable access to the inner `Park`
+	pub fn get_mut(&mut self) -> &mut P {
+		&mut self.inner
+	}
+
+	/// Extract the inner `Park`
+	pub fn into_inner(self) -> P {
+		self.inner
+	}
+}
+
+impl<P: Park> Park for BudgetPark<P> {
+	fn waker(&self) -> Waker {
+		notify::waker(self.notify.clone())
+	}
+
+	fn park(&mut self, enter: &mut Enter, duration: Option<Duration>) {
+		if duration == Some(Duration::new(0, 0)) {
+			self.inner.park(enter, duration);
+		} else {
+			let mut spins = self.spins;
+			loop {
+				if self.notify.woken.swap(false, Ordering::Acquire) {
+					break;
+				}
+				if spins == 0 {
+					self.inner.park(enter, duration);
+					break;
+				}
+				spins -= 1;
+				self.inner.park(enter, Some(Duration::new(0, 0)));
+			}
+		}
+		// leaving `park` resets the notification
+		self.notify.woken.store(false, Ordering::Relaxed);
+	}
+}
+
+#[cfg(all(test, feature = "park-thread"))]
+mod test {
+	use super::BudgetPark;
+	use crate::park::{Park, ParkThread};
+	use std::task::Waker;
+	use std::time::Duration;
+
+	// counts non-blocking polls; wakes the `BudgetPark` on the given poll
+	struct Counter {
+		polls: u32,
+		wake_on: Option<(u32, Waker)>,
+	}
+
+	impl Park for Counter {
+		fn waker(&self) -> Waker {
+			ParkThread::new().waker()
+		}
+
+		fn park(&mut self, enter: &mut crate::enter::Enter, duration: Option<Duration>) {
+			if duration == Some(Duration::new(0, 0)) {
+				self.polls += 1;
+				if let Some((on, waker)) = &self.wake_on {
+					if *on == self.polls {
+						waker.wake_by_ref();
+					}
+				}
+			} else {
+				ParkThread::new().park(enter, Some(Duration::from_millis(1)));
+			}
+		}
+	}
+
+	#[test]
+	fn spin() {
+		let mut enter = crate::enter::enter().unwrap();
+		let mut park = BudgetPark::new(Counter { polls: 0, wake_on: None }, 5);
+		park.park(&mut enter, None);
+		assert_eq!(park.get_ref().polls, 5);
+
+		// woken while spinning
+		let waker = park.waker();
+		park.get_mut().polls = 0;
+		park.get_mut().wake_on = Some((2, waker));
+		park.park(&mut enter, None);
+		assert_eq!(park.get_ref().polls, 2);
+
+		// woken before parking
+		park.get_mut().polls = 0;
+		park.get_mut().wake_on = None;
+		park.waker().wake();
+		park.park(&mut enter, None);
+		assert_eq!(park.get_ref().polls, 0);
+	}
+}
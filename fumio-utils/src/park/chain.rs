@@ -0,0 +1,121 @@
+use crate::enter::Enter;
+use crate::park::Park;
+use std::task::Waker;
+use std::time::Duration;
+
+/// Outer layer of a [`Chain`](struct.Chain.html)
+///
+/// A layer does its bookkeeping around the inner (blocking) `Park`: e.g. a timer limits the
+/// duration to its next deadline before parking and fires expired timers afterwards.
+pub trait Layer {
+	/// Called before parking the inner component; returns the duration to park it with
+	///
+	/// Usually shortens `duration` if the layer has work due earlier.
+	fn before_park(&mut self, duration: Option<Duration>) -> Option<Duration> {
+		duration
+	}
+
+	/// Called after the inner component returned from `park`
+	fn after_park(&mut self) {}
+}
+
+/// Combines a [`Layer`](trait.Layer.html) with an inner `Park`
+///
+/// Parking calls the layer's `before_park`, parks the inner component and then calls
+/// `after_park`; wakers come from the inner component.
+///
+/// Chains can be nested: `Chain<A, Chain<B, C>>` runs the hooks of `A` around those of `B`.
+#[derive(Debug)]
+pub struct Chain<A, B> {
+	outer: A,
+	inner: B,
+}
+
+impl<A, B> Chain<A, B> {
+	/// Put `outer` around `inner`
+	pub const fn new(outer: A, inner: B) -> Self {
+		Self { outer, inner }
+	}
+
+	/// Access the outer layer
+	pub const fn outer(&self) -> &A {
+		&self.outer
+	}
+
+	/// Mutable access to the outer layer
+	pub fn outer_mut(&mut self) -> &mut A {
+		&mut self.outer
+	}
+
+	/// Access the inner component
+	pub const fn inner(&self) -> &B {
+		&self.inner
+	}
+
+	/// Mutable access to the inner component
+	pub fn inner_mut(&mut self) -> &mut B {
+		&mut self.inner
+	}
+
+	/// Extract outer layer and inner component
+	pub fn into_inner(self) -> (A, B) {
+		(self.outer, self.inner)
+	}
+}
+
+impl<A, B> Park for Chain<A, B>
+where
+	A: Layer,
+	B: Park,
+{
+	fn waker(&self) -> Waker {
+		self.inner.waker()
+	}
+
+	fn park(&mut self, enter: &mut Enter, duration: Option<Duration>) {
+		let duration = self.outer.before_park(duration);
+		self.inner.park(enter, duration);
+		self.outer.after_park();
+	}
+}
+
+#[cfg(all(test, feature = "park-thread"))]
+mod test {
+	use super::{Chain, Layer};
+	use crate::park::{Park, ParkThread};
+	use std::time::Duration;
+
+	#[derive(Default)]
+	struct Limit {
+		max: Option<Duration>,
+		before: Vec<Option<Duration>>,
+		after: usize,
+	}
+
+	impl Layer for Limit {
+		fn before_park(&mut self, duration: Option<Duration>) -> Option<Duration> {
+			self.before.push(duration);
+			match (duration, self.max) {
+				(Some(d), Some(max)) => Some(std::cmp::min(d, max)),
+				(d, max) => d.or(max),
+			}
+		}
+
+		fn after_park(&mut self) {
+			self.after += 1;
+		}
+	}
+
+	#[test]
+	fn nested() {
+		let outer = Limit { max: Some(Duration::from_secs(10)), ..Limit::default() };
+		let inner = Limit { max: Some(Duration::new(0, 0)), ..Limit::default() };
+		let mut park = Chain::new(outer, Chain::new(inner, ParkThread::new()));
+		let mut enter = crate::enter::enter().unwrap();
+		// would block forever without the layers
+		park.park(&mut enter, None);
+		assert_eq!(park.outer().before, [None]);
+		assert_eq!(park.inner().outer().before, [Some(Duration::from_secs(10))]);
+		assert_eq!((park.outer().after, park.inner().outer().after), (1, 1));
+	}
+}
@@ -0,0 +1,103 @@
+use crate::enter::Enter;
+use crate::park::Park;
+use crate::park::notify::{self, Notify};
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::task::Waker;
+use std::time::Duration;
+
+#[derive(Debug)]
+struct WakeCounter(AtomicUsize);
+
+impl Notify for WakeCounter {
+	fn notify(&self) {
+		self.0.fetch_add(1, Ordering::SeqCst);
+	}
+}
+
+/// `ManualPark` implements `Park` for deterministic tests: it never blocks, but records all `park`
+/// calls and counts wakeups.
+///
+/// An optional hook runs on each `park` call, letting the test make progress (e.g. complete a
+/// pending operation) between steps of an executor.
+pub struct ManualPark {
+	parks: Vec<Option<Duration>>,
+	wakeups: Arc<WakeCounter>,
+	hook: Option<Box<dyn FnMut(Option<Duration>)>>,
+	max_parks: Option<usize>,
+}
+
+impl ManualPark {
+	/// Create new `ManualPark` instance.
+	pub fn new() -> Self {
+		Self {
+			parks: Vec::new(),
+			wakeups: Arc::new(WakeCounter(AtomicUsize::new(0))),
+			hook: None,
+			max_parks: None,
+		}
+	}
+
+	/// Run `hook` with the requested duration on each `park` call
+	pub fn set_hook<F>(&mut self, hook: F)
+	where
+		F: FnMut(Option<Duration>) + 'static,
+	{
+		self.hook = Some(Box::new(hook));
+	}
+
+	/// Panic when parked more than `max` times (an executor waiting for something that never
+	/// happens would spin forever otherwise)
+	pub fn set_max_parks(&mut self, max: Option<usize>) {
+		self.max_parks = max;
+	}
+
+	/// Durations of all `park` calls so far
+	pub fn parks(&self) -> &[Option<Duration>] {
+		&self.parks
+	}
+
+	/// Forget recorded `park` calls
+	pub fn clear_parks(&mut self) {
+		self.parks.clear();
+	}
+
+	/// Number of wakeups (through any waker returned by `waker`) so far
+	pub fn wakeups(&self) -> usize {
+		self.wakeups.0.load(Ordering::SeqCst)
+	}
+}
+
+impl Default for ManualPark {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl fmt::Debug for ManualPark {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ManualPark")
+			.field("parks", &self.parks)
+			.field("wakeups", &self.wakeups)
+			.field("hook", &self.hook.as_ref().map(|_| ()))
+			.field("max_parks", &self.max_parks)
+			.finish()
+	}
+}
+
+impl Park for ManualPark {
+	fn waker(&self) -> Waker {
+		notify::waker(self.wakeups.clone())
+	}
+
+	fn park(&mut self, _enter: &mut Enter, duration: Option<Duration>) {
+		if let Some(max) = self.max_parks {
+			assert!(self.parks.len() < max, "parked more than {} times", max);
+		}
+		self.parks.push(duration);
+		if let Some(hook) = &mut self.hook {
+			hook(duration);
+		}
+	}
+}
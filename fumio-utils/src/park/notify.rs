@@ -0,0 +1,39 @@
+use std::sync::Arc;
+use std::task::{RawWaker, RawWakerVTable, Waker};
+
+// wakeup target for `waker`
+pub(crate) trait Notify: Send + Sync + 'static {
+	fn notify(&self);
+}
+
+pub(crate) fn waker<N: Notify>(notify: Arc<N>) -> Waker {
+	unsafe { Waker::from_raw(into_raw(notify)) }
+}
+
+fn into_raw<N: Notify>(notify: Arc<N>) -> RawWaker {
+	RawWaker::new(Arc::into_raw(notify).cast::<()>(), vtable::<N>())
+}
+
+fn vtable<N: Notify>() -> &'static RawWakerVTable {
+	&RawWakerVTable::new(clone_raw::<N>, wake_raw::<N>, wake_by_ref_raw::<N>, drop_raw::<N>)
+}
+
+unsafe fn clone_raw<N: Notify>(data: *const ()) -> RawWaker {
+	let notify = Arc::from_raw(data.cast::<N>());
+	let clone = notify.clone();
+	std::mem::forget(notify);
+	into_raw(clone)
+}
+
+unsafe fn wake_raw<N: Notify>(data: *const ()) {
+	wake_by_ref_raw::<N>(data);
+	drop_raw::<N>(data);
+}
+
+unsafe fn wake_by_ref_raw<N: Notify>(data: *const ()) {
+	(*(data.cast::<N>())).notify();
+}
+
+unsafe fn drop_raw<N: Notify>(data: *const ()) {
+	drop(Arc::from_raw(data.cast::<N>()));
+}
@@ -1,4 +1,4 @@
-use futures_executor::Enter;
+use crate::enter::Enter;
 use futures_util::task::{ArcWake, waker};
 use std::sync::Arc;
 use std::task::Waker;
@@ -0,0 +1,24 @@
+//! Atomics used by the lock-free parts of the fumio crates.
+//!
+//! When built with `RUSTFLAGS="--cfg loom"` these are the [`loom`](https://docs.rs/loom) types
+//! instead, so the memory orderings can be model checked:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release -p fumio-utils -p fumio-reactor --lib loom
+//! ```
+//!
+//! The `loom` types can only be used within `loom::model`; only run the `loom` tests with that
+//! configuration. Doc tests using them are ignored if rustdoc gets the flag too:
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" RUSTDOCFLAGS="--cfg loom" cargo test -p fumio-utils
+//! ```
+
+/// Atomic types (`core::sync::atomic` or `loom::sync::atomic`)
+pub mod atomic {
+	#[cfg(not(loom))]
+	pub use core::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+	#[cfg(loom)]
+	pub use loom::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+}
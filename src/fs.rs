@@ -0,0 +1,21 @@
+//! Filesystem utilities
+
+#[cfg(any(
+	target_os = "linux",
+	target_os = "macos",
+	target_os = "ios",
+	target_os = "freebsd",
+	target_os = "dragonfly",
+	target_os = "openbsd",
+))]
+mod watcher;
+
+#[cfg(any(
+	target_os = "linux",
+	target_os = "macos",
+	target_os = "ios",
+	target_os = "freebsd",
+	target_os = "dragonfly",
+	target_os = "openbsd",
+))]
+pub use self::watcher::{Event, EventKind, WatchId, Watcher};
@@ -0,0 +1,125 @@
+use crate::reactor::LazyHandle;
+use futures_core::Stream;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+#[cfg(target_os = "linux")]
+mod inotify;
+#[cfg(target_os = "linux")]
+use self::inotify as sys;
+
+#[cfg(not(target_os = "linux"))]
+mod kqueue;
+#[cfg(not(target_os = "linux"))]
+use self::kqueue as sys;
+
+/// Kind of a filesystem change
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EventKind {
+	/// File or directory was created
+	Create,
+	/// File content was modified
+	Modify,
+	/// Metadata (permissions, timestamps, ...) changed
+	Metadata,
+	/// File or directory was removed
+	Remove,
+	/// File or directory was renamed; this is the old name
+	MovedFrom,
+	/// File or directory was renamed; this is the new name
+	MovedTo,
+	/// The kernel queue overflowed and events were lost; `path` is empty (inotify only)
+	Overflow,
+}
+
+/// A filesystem change reported by a [`Watcher`](struct.Watcher.html)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+	/// Watch the event belongs to (`None` for `Overflow`)
+	pub watch: Option<WatchId>,
+	/// Changed path: the watched path, or an entry of a watched directory
+	pub path: PathBuf,
+	/// What changed
+	pub kind: EventKind,
+}
+
+/// Identifies a watched path
+///
+/// Ids aren't reused while the watcher is alive (kqueue), or only after all other ids were used
+/// (inotify).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WatchId(u64);
+
+/// Watches files and directories for changes
+///
+/// A `Stream` of change [`Event`](struct.Event.html)s; watching a directory reports changes of
+/// the directory itself and of its direct entries (not recursive).
+///
+/// Uses inotify on linux and kqueue (`EVFILT_VNODE`) on macos, ios, freebsd, dragonfly and
+/// openbsd.  kqueue only reports that a directory changed: the watcher then lists the directory
+/// and compares it with the previous listing, so entries created and removed again in between
+/// are missed, renames are only detected within the directory, and content or metadata changes
+/// of entries are only reported for entries that are watched themselves.
+#[derive(Debug)]
+pub struct Watcher {
+	inner: sys::Watcher,
+}
+
+impl Watcher {
+	/// Create watcher registered with the [`current`](../reactor/fn.current.html) reactor.
+	///
+	/// # Errors
+	///
+	/// Fails if the OS watcher can't be created or registered with the reactor.
+	pub fn new() -> io::Result<Self> {
+		Self::with_handle(LazyHandle::new())
+	}
+
+	/// Create watcher registered with the reactor `handle` is bound to.
+	///
+	/// # Errors
+	///
+	/// Fails if the OS watcher can't be created or registered with the reactor.
+	pub fn with_handle(handle: LazyHandle) -> io::Result<Self> {
+		Ok(Self {
+			inner: sys::Watcher::with_handle(handle)?,
+		})
+	}
+
+	/// Start watching `path`
+	///
+	/// Watching the same path again returns the same id.
+	///
+	/// # Errors
+	///
+	/// Fails if `path` can't be watched (e.g. it doesn't exist).
+	pub fn watch<P: AsRef<Path>>(&mut self, path: P) -> io::Result<WatchId> {
+		self.inner.watch(path.as_ref())
+	}
+
+	/// Stop watching
+	///
+	/// Events already queued for the watch might still be reported.
+	///
+	/// # Errors
+	///
+	/// Fails if `watch` isn't watched (anymore).
+	pub fn unwatch(&mut self, watch: WatchId) -> io::Result<()> {
+		self.inner.unwatch(watch)
+	}
+
+	/// Watched path of `watch`
+	pub fn path(&self, watch: WatchId) -> Option<&Path> {
+		self.inner.path(watch)
+	}
+}
+
+impl Stream for Watcher {
+	type Item = io::Result<Event>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		self.get_mut().inner.poll_next(cx).map(Some)
+	}
+}
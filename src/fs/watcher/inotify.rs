@@ -0,0 +1,151 @@
+use super::{Event, EventKind, WatchId};
+use crate::reactor::{AsyncFd, LazyHandle};
+use std::collections::{HashMap, VecDeque};
+use std::ffi::{CString, OsStr};
+use std::fs::File;
+use std::io::{self, Read};
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::path::{Path, PathBuf};
+use std::task::{Context, Poll};
+
+// enough for a few events with long names
+const BUFFER_SIZE: usize = 16 * 1024;
+
+const WATCH_MASK: u32 = libc::IN_CREATE
+	| libc::IN_MODIFY
+	| libc::IN_ATTRIB
+	| libc::IN_DELETE
+	| libc::IN_DELETE_SELF
+	| libc::IN_MOVED_FROM
+	| libc::IN_MOVED_TO
+	| libc::IN_MOVE_SELF;
+
+// inotify hands out watch descriptors cyclically, so they can serve as ids
+#[allow(clippy::cast_sign_loss)] // watch descriptors are positive
+const fn watch_id(wd: libc::c_int) -> WatchId {
+	WatchId(wd as u64)
+}
+
+#[allow(clippy::cast_possible_truncation)] // ids are watch descriptors
+const fn watch_descriptor(watch: WatchId) -> libc::c_int {
+	watch.0 as libc::c_int
+}
+
+#[derive(Debug)]
+pub(super) struct Watcher {
+	fd: AsyncFd<File>,
+	watches: HashMap<libc::c_int, PathBuf>,
+	buf: Box<[u8]>,
+	pending: VecDeque<Event>,
+}
+
+impl Watcher {
+	pub(super) fn with_handle(handle: LazyHandle) -> io::Result<Self> {
+		let fd = unsafe { libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC) };
+		if fd < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		let file = unsafe { File::from_raw_fd(fd) };
+		Ok(Self {
+			fd: AsyncFd::with_handle(file, handle)?,
+			watches: HashMap::new(),
+			buf: vec![0; BUFFER_SIZE].into_boxed_slice(),
+			pending: VecDeque::new(),
+		})
+	}
+
+	pub(super) fn watch(&mut self, path: &Path) -> io::Result<WatchId> {
+		let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+		let wd = unsafe { libc::inotify_add_watch(self.fd.get_ref().as_raw_fd(), c_path.as_ptr(), WATCH_MASK) };
+		if wd < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		self.watches.insert(wd, path.to_owned());
+		Ok(watch_id(wd))
+	}
+
+	pub(super) fn unwatch(&mut self, watch: WatchId) -> io::Result<()> {
+		let wd = watch_descriptor(watch);
+		let r = unsafe { libc::inotify_rm_watch(self.fd.get_ref().as_raw_fd(), wd) };
+		if r < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		self.watches.remove(&wd);
+		Ok(())
+	}
+
+	pub(super) fn path(&self, watch: WatchId) -> Option<&Path> {
+		self.watches.get(&watch_descriptor(watch)).map(PathBuf::as_path)
+	}
+
+	#[allow(clippy::cast_ptr_alignment)] // read_unaligned
+	fn parse(&mut self, len: usize) {
+		let header = mem::size_of::<libc::inotify_event>();
+		let mut pos = 0;
+		while pos + header <= len {
+			let event: libc::inotify_event = unsafe { std::ptr::read_unaligned(self.buf[pos..].as_ptr().cast()) };
+			let name_start = pos + header;
+			pos = name_start + event.len as usize;
+			let name = &self.buf[name_start..pos];
+			// name is padded with NULs
+			let name = &name[..name.iter().position(|&c| c == 0).unwrap_or(name.len())];
+
+			if event.mask & libc::IN_Q_OVERFLOW != 0 {
+				self.pending.push_back(Event {
+					watch: None,
+					path: PathBuf::new(),
+					kind: EventKind::Overflow,
+				});
+				continue;
+			}
+			if event.mask & libc::IN_IGNORED != 0 {
+				// watch was removed (explicitly or because the path is gone)
+				self.watches.remove(&event.wd);
+				continue;
+			}
+			let Some(base) = self.watches.get(&event.wd) else {
+				continue; // already unwatched
+			};
+			let path = if name.is_empty() { base.clone() } else { base.join(OsStr::from_bytes(name)) };
+			let kind = if event.mask & libc::IN_CREATE != 0 {
+				EventKind::Create
+			} else if event.mask & libc::IN_MODIFY != 0 {
+				EventKind::Modify
+			} else if event.mask & libc::IN_ATTRIB != 0 {
+				EventKind::Metadata
+			} else if event.mask & (libc::IN_DELETE | libc::IN_DELETE_SELF) != 0 {
+				EventKind::Remove
+			} else if event.mask & (libc::IN_MOVED_FROM | libc::IN_MOVE_SELF) != 0 {
+				EventKind::MovedFrom
+			} else if event.mask & libc::IN_MOVED_TO != 0 {
+				EventKind::MovedTo
+			} else {
+				continue;
+			};
+			self.pending.push_back(Event {
+				watch: Some(watch_id(event.wd)),
+				path,
+				kind,
+			});
+		}
+	}
+
+	pub(super) fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Event>> {
+		loop {
+			if let Some(event) = self.pending.pop_front() {
+				return Poll::Ready(Ok(event));
+			}
+			let buf = &mut self.buf;
+			let n = {
+				let mut guard = futures_util::ready!(self.fd.poll_read_ready(cx))?;
+				match guard.try_io(|mut file| file.read(buf)) {
+					Poll::Ready(result) => result?,
+					Poll::Pending => continue,
+				}
+			};
+			self.parse(n);
+		}
+	}
+}
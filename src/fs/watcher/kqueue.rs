@@ -0,0 +1,236 @@
+use super::{Event, EventKind, WatchId};
+use crate::reactor::{AsyncFd, LazyHandle};
+use std::collections::{HashMap, VecDeque};
+use std::ffi::{CString, OsString};
+use std::fs::{self, File};
+use std::io;
+use std::mem;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::DirEntryExt;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::task::{Context, Poll};
+
+// events fetched per `kevent` call
+const EVENT_BATCH: usize = 32;
+
+const VNODE_FLAGS: u32 = libc::NOTE_DELETE
+	| libc::NOTE_WRITE
+	| libc::NOTE_EXTEND
+	| libc::NOTE_ATTRIB
+	| libc::NOTE_LINK
+	| libc::NOTE_RENAME
+	| libc::NOTE_REVOKE;
+
+// only for events; doesn't prevent unmounting
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+const OPEN_FLAGS: libc::c_int = libc::O_EVTONLY | libc::O_CLOEXEC;
+#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+const OPEN_FLAGS: libc::c_int = libc::O_RDONLY | libc::O_CLOEXEC;
+
+// entry names of a directory and their inodes
+type Entries = HashMap<OsString, u64>;
+
+fn list_dir(path: &Path) -> io::Result<Entries> {
+	let mut entries = HashMap::new();
+	for entry in fs::read_dir(path)? {
+		let entry = entry?;
+		entries.insert(entry.file_name(), entry.ino());
+	}
+	Ok(entries)
+}
+
+#[derive(Debug)]
+struct Watch {
+	path: PathBuf,
+	// closing the file removes the kqueue filter
+	file: File,
+	// `Some` for directories: last listing to compare with
+	entries: Option<Entries>,
+}
+
+impl Watch {
+	// compare the directory listing with the previous one
+	fn diff_entries(&mut self, watch: WatchId, pending: &mut VecDeque<Event>) {
+		let path = &self.path;
+		let Some(old) = &mut self.entries else {
+			return;
+		};
+		// fails if the directory is gone; the event for that follows
+		let Ok(new) = list_dir(path) else {
+			return;
+		};
+		let mut removed: Vec<_> = old.iter().filter(|&(name, ino)| new.get(name) != Some(ino)).collect();
+		let mut created: Vec<_> = new.iter().filter(|&(name, ino)| old.get(name) != Some(ino)).collect();
+		removed.sort();
+		created.sort();
+		let event = |name, kind| Event {
+			watch: Some(watch),
+			path: path.join(name),
+			kind,
+		};
+		for &(name, ino) in &removed {
+			// renamed within the directory: the inode shows up with another name
+			if let Some(pos) = created.iter().position(|&(_, new_ino)| new_ino == ino) {
+				let (new_name, _) = created.remove(pos);
+				pending.push_back(event(name, EventKind::MovedFrom));
+				pending.push_back(event(new_name, EventKind::MovedTo));
+			} else {
+				pending.push_back(event(name, EventKind::Remove));
+			}
+		}
+		for &(name, _) in &created {
+			pending.push_back(event(name, EventKind::Create));
+		}
+		*old = new;
+	}
+}
+
+#[derive(Debug)]
+pub(super) struct Watcher {
+	kq: AsyncFd<File>,
+	// file descriptors get reused after `unwatch`; ids don't, so queued events of a removed
+	// watch can't be mistaken for a new one
+	next_id: u64,
+	watches: HashMap<u64, Watch>,
+	// watch id by file descriptor of the watched path
+	ids: HashMap<libc::c_int, u64>,
+	pending: VecDeque<Event>,
+}
+
+impl Watcher {
+	pub(super) fn with_handle(handle: LazyHandle) -> io::Result<Self> {
+		let fd = unsafe { libc::kqueue() };
+		if fd < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		let file = unsafe { File::from_raw_fd(fd) };
+		if unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		Ok(Self {
+			kq: AsyncFd::with_handle(file, handle)?,
+			next_id: 0,
+			watches: HashMap::new(),
+			ids: HashMap::new(),
+			pending: VecDeque::new(),
+		})
+	}
+
+	pub(super) fn watch(&mut self, path: &Path) -> io::Result<WatchId> {
+		if let Some((&id, _)) = self.watches.iter().find(|(_, watch)| watch.path == path) {
+			return Ok(WatchId(id));
+		}
+		let c_path = CString::new(path.as_os_str().as_bytes()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+		let fd = unsafe { libc::open(c_path.as_ptr(), OPEN_FLAGS) };
+		if fd < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		let file = unsafe { File::from_raw_fd(fd) };
+		let entries = if file.metadata()?.is_dir() { Some(list_dir(path)?) } else { None };
+		self.register(fd)?;
+		let id = self.next_id;
+		self.next_id += 1;
+		self.watches.insert(id, Watch {
+			path: path.to_owned(),
+			file,
+			entries,
+		});
+		self.ids.insert(fd, id);
+		Ok(WatchId(id))
+	}
+
+	fn remove(&mut self, id: u64) -> Option<Watch> {
+		let watch = self.watches.remove(&id)?;
+		self.ids.remove(&watch.file.as_raw_fd());
+		Some(watch)
+	}
+
+	#[allow(clippy::cast_sign_loss)] // fd >= 0
+	fn register(&self, fd: libc::c_int) -> io::Result<()> {
+		let mut change: libc::kevent = unsafe { mem::zeroed() };
+		change.ident = fd as libc::uintptr_t;
+		change.filter = libc::EVFILT_VNODE;
+		change.flags = libc::EV_ADD | libc::EV_CLEAR;
+		change.fflags = VNODE_FLAGS;
+		// without room for events this returns right away
+		let r = unsafe { libc::kevent(self.kq.get_ref().as_raw_fd(), ptr::addr_of!(change), 1, ptr::null_mut(), 0, ptr::null()) };
+		if r < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		Ok(())
+	}
+
+	pub(super) fn unwatch(&mut self, watch: WatchId) -> io::Result<()> {
+		match self.remove(watch.0) {
+			Some(_) => Ok(()),
+			None => Err(io::Error::from_raw_os_error(libc::EINVAL)),
+		}
+	}
+
+	pub(super) fn path(&self, watch: WatchId) -> Option<&Path> {
+		self.watches.get(&watch.0).map(|watch| watch.path.as_path())
+	}
+
+	#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)] // ident is a file descriptor
+	fn handle(&mut self, event: &libc::kevent) {
+		let fd = event.ident as libc::c_int;
+		let Some(&watch_id) = self.ids.get(&fd) else {
+			return; // already unwatched
+		};
+		let watch = self.watches.get_mut(&watch_id).expect("watch of known file descriptor");
+		let id = WatchId(watch_id);
+		let fflags = event.fflags;
+		if fflags & (libc::NOTE_WRITE | libc::NOTE_EXTEND | libc::NOTE_LINK) != 0 {
+			if watch.entries.is_some() {
+				watch.diff_entries(id, &mut self.pending);
+			} else if fflags & (libc::NOTE_WRITE | libc::NOTE_EXTEND) != 0 {
+				self.pending.push_back(Event { watch: Some(id), path: watch.path.clone(), kind: EventKind::Modify });
+			}
+		}
+		if fflags & libc::NOTE_ATTRIB != 0 {
+			self.pending.push_back(Event { watch: Some(id), path: watch.path.clone(), kind: EventKind::Metadata });
+		}
+		if fflags & libc::NOTE_RENAME != 0 {
+			self.pending.push_back(Event { watch: Some(id), path: watch.path.clone(), kind: EventKind::MovedFrom });
+		}
+		if fflags & (libc::NOTE_DELETE | libc::NOTE_REVOKE) != 0 {
+			self.pending.push_back(Event { watch: Some(id), path: watch.path.clone(), kind: EventKind::Remove });
+			// like inotify: the watch is gone with the path
+			self.remove(watch_id);
+		}
+	}
+
+	#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap, clippy::cast_sign_loss)] // EVENT_BATCH is small; n >= 0
+	pub(super) fn poll_next(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<Event>> {
+		loop {
+			if let Some(event) = self.pending.pop_front() {
+				return Poll::Ready(Ok(event));
+			}
+			let mut events: [libc::kevent; EVENT_BATCH] = unsafe { mem::zeroed() };
+			let n = {
+				let mut guard = futures_util::ready!(self.kq.poll_read_ready(cx))?;
+				let result = guard.try_io(|kq| {
+					// don't block
+					let timeout = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+					let n = unsafe {
+						libc::kevent(kq.as_raw_fd(), ptr::null(), 0, events.as_mut_ptr(), EVENT_BATCH as libc::c_int, ptr::addr_of!(timeout))
+					};
+					match n {
+						n if n < 0 => Err(io::Error::last_os_error()),
+						0 => Err(io::ErrorKind::WouldBlock.into()),
+						n => Ok(n as usize),
+					}
+				});
+				match result {
+					Poll::Ready(result) => result?,
+					Poll::Pending => continue,
+				}
+			};
+			for event in &events[..n] {
+				self.handle(event);
+			}
+		}
+	}
+}
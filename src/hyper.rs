@@ -0,0 +1,139 @@
+//! Adapters to run [`hyper`](https://docs.rs/hyper/0.14) servers on a (single-threaded) runtime
+//!
+//! Requires the `hyper-compat` feature.
+//!
+//! ```ignore
+//! let mut listener = fumio::net::TcpListener::bind(addr)?;
+//! hyper::Server::builder(HyperAccept::new(listener.incoming()))
+//!     .executor(HyperExecutor::current())
+//!     .serve(make_service)
+//!     .await?;
+//! ```
+
+use crate::net::{TcpIncoming, TcpStream};
+use crate::pool::LocalSpawner;
+use futures_io::{AsyncRead, AsyncWrite};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// `hyper` executor spawning onto a local pool; the futures don't need to be `Send`
+#[derive(Clone, Debug)]
+pub struct HyperExecutor {
+	spawner: LocalSpawner,
+}
+
+impl HyperExecutor {
+	/// Spawn onto the pool of `spawner`
+	pub const fn new(spawner: LocalSpawner) -> Self {
+		Self { spawner }
+	}
+
+	/// Spawn onto the pool of the current runtime
+	///
+	/// # Panics
+	///
+	/// Panics if there is no current pool.
+	pub fn current() -> Self {
+		Self::new(fumio_pool::current_local().expect("no current pool"))
+	}
+}
+
+impl<F> ::hyper::rt::Executor<F> for HyperExecutor
+where
+	F: Future + 'static,
+{
+	fn execute(&self, future: F) {
+		// hyper has no way to handle failures; the tasks are dropped with the pool anyway
+		let _ = self.spawner.spawn(async move {
+			future.await;
+		});
+	}
+}
+
+/// Wraps a `futures` IO object to implement the `tokio` IO traits `hyper` needs
+#[derive(Debug)]
+pub struct HyperIo<T> {
+	inner: T,
+}
+
+impl<T> HyperIo<T> {
+	/// Wrap IO object
+	pub const fn new(inner: T) -> Self {
+		Self { inner }
+	}
+
+	/// Access the inner IO object
+	pub const fn get_ref(&self) -> &T {
+		&self.inner
+	}
+
+	/// Mutable access to the inner IO object
+	pub fn get_mut(&mut self) -> &mut T {
+		&mut self.inner
+	}
+
+	/// Extract the inner IO object
+	pub fn into_inner(self) -> T {
+		self.inner
+	}
+}
+
+impl<T> ::tokio::io::AsyncRead for HyperIo<T>
+where
+	T: AsyncRead + Unpin,
+{
+	fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ::tokio::io::ReadBuf<'_>) -> Poll<io::Result<()>> {
+		let n = futures_util::ready!(Pin::new(&mut self.get_mut().inner).poll_read(cx, buf.initialize_unfilled()))?;
+		buf.advance(n);
+		Poll::Ready(Ok(()))
+	}
+}
+
+impl<T> ::tokio::io::AsyncWrite for HyperIo<T>
+where
+	T: AsyncWrite + Unpin,
+{
+	fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+		Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+	}
+
+	fn poll_write_vectored(self: Pin<&mut Self>, cx: &mut Context<'_>, bufs: &[io::IoSlice<'_>]) -> Poll<io::Result<usize>> {
+		Pin::new(&mut self.get_mut().inner).poll_write_vectored(cx, bufs)
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+	}
+
+	fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Pin::new(&mut self.get_mut().inner).poll_close(cx)
+	}
+}
+
+/// Accepts connections for a `hyper` server from [`TcpIncoming`](../net/struct.TcpIncoming.html)
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct HyperAccept<'a> {
+	incoming: TcpIncoming<'a>,
+}
+
+impl<'a> HyperAccept<'a> {
+	/// Accept connections from `incoming`
+	pub const fn new(incoming: TcpIncoming<'a>) -> Self {
+		Self { incoming }
+	}
+}
+
+impl ::hyper::server::accept::Accept for HyperAccept<'_> {
+	type Conn = HyperIo<TcpStream>;
+	type Error = io::Error;
+
+	fn poll_accept(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<io::Result<Self::Conn>>> {
+		match futures_util::ready!(Pin::new(&mut self.get_mut().incoming).poll(cx)) {
+			Ok((stream, _)) => Poll::Ready(Some(Ok(HyperIo::new(stream)))),
+			Err(e) => Poll::Ready(Some(Err(e))),
+		}
+	}
+}
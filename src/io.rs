@@ -0,0 +1,20 @@
+//! IO utilities
+
+mod bandwidth;
+mod buf_stream;
+mod copy_bidirectional;
+#[cfg(all(unix, feature = "serial"))]
+mod serial;
+mod timeout;
+
+pub use self::bandwidth::BandwidthLimited;
+pub use self::buf_stream::BufStream;
+pub use self::copy_bidirectional::{copy_bidirectional, CopyBidirectional};
+#[cfg(all(unix, feature = "serial"))]
+pub use self::serial::SerialPort;
+pub use self::timeout::{TimeoutReader, TimeoutWriter};
+/// `serialport` to configure a [`SerialPort`](struct.SerialPort.html)
+#[cfg(all(unix, feature = "serial"))]
+pub use serialport;
+#[cfg(target_os = "linux")]
+pub use fumio_reactor::net::{send_file, splice, SendFile, Splice};
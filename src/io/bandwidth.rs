@@ -0,0 +1,108 @@
+use crate::timer::RateLimiter;
+use futures_io::{AsyncRead, AsyncWrite};
+use std::convert::TryFrom;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+fn byte_limiter(bytes_per_second: u32) -> RateLimiter {
+	assert!(bytes_per_second != 0, "bandwidth must not be zero");
+	let interval = std::cmp::max(Duration::from_secs(1) / bytes_per_second, Duration::from_nanos(1));
+	// allow bursts of 100ms worth of data
+	RateLimiter::new(interval, std::cmp::max(bytes_per_second / 10, 1))
+}
+
+/// Limits the bandwidth of an IO object
+///
+/// Reads and writes are metered separately; each direction transfers at most `bytes_per_second`
+/// on average, with bursts of up to a tenth of that.  Operations are shortened to the currently
+/// available budget, and wait for the timer if it is exhausted.
+///
+/// Needs the timer of the current runtime.
+#[derive(Debug)]
+pub struct BandwidthLimited<T> {
+	inner: T,
+	read: RateLimiter,
+	write: RateLimiter,
+}
+
+impl<T> BandwidthLimited<T> {
+	/// Wrap `inner`, limiting reads and writes to `bytes_per_second` each
+	///
+	/// # Panics
+	///
+	/// Panics if `bytes_per_second` is zero.
+	pub fn new(inner: T, bytes_per_second: u32) -> Self {
+		Self {
+			inner,
+			read: byte_limiter(bytes_per_second),
+			write: byte_limiter(bytes_per_second),
+		}
+	}
+
+	/// Access the inner IO object
+	pub const fn get_ref(&self) -> &T {
+		&self.inner
+	}
+
+	/// Mutable access to the inner IO object (bypasses the limit)
+	pub fn get_mut(&mut self) -> &mut T {
+		&mut self.inner
+	}
+
+	/// Extract the inner IO object
+	pub fn into_inner(self) -> T {
+		self.inner
+	}
+}
+
+// maximum number of bytes to transfer with the given budget
+fn limit(len: usize, budget: u32) -> usize {
+	// budget fits in usize on all supported platforms
+	std::cmp::min(len, budget as usize)
+}
+
+impl<T> AsyncRead for BandwidthLimited<T>
+where
+	T: AsyncRead + Unpin,
+{
+	fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+		let this = self.get_mut();
+		if buf.is_empty() {
+			return Pin::new(&mut this.inner).poll_read(cx, buf);
+		}
+		let budget = futures_util::ready!(this.read.poll_available(cx));
+		let len = limit(buf.len(), budget);
+		let n = futures_util::ready!(Pin::new(&mut this.inner).poll_read(cx, &mut buf[..len]))?;
+		// n <= len <= budget
+		this.read.take(u32::try_from(n).unwrap());
+		Poll::Ready(Ok(n))
+	}
+}
+
+impl<T> AsyncWrite for BandwidthLimited<T>
+where
+	T: AsyncWrite + Unpin,
+{
+	fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+		let this = self.get_mut();
+		if buf.is_empty() {
+			return Pin::new(&mut this.inner).poll_write(cx, buf);
+		}
+		let budget = futures_util::ready!(this.write.poll_available(cx));
+		let len = limit(buf.len(), budget);
+		let n = futures_util::ready!(Pin::new(&mut this.inner).poll_write(cx, &buf[..len]))?;
+		// n <= len <= budget
+		this.write.take(u32::try_from(n).unwrap());
+		Poll::Ready(Ok(n))
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+	}
+
+	fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Pin::new(&mut self.get_mut().inner).poll_close(cx)
+	}
+}
@@ -0,0 +1,176 @@
+use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+use std::fmt;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// large enough for typical TCP segments and TLS records
+const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+/// Buffers reads and writes of an IO object
+///
+/// Implements `AsyncBufRead` for line or frame oriented protocols; writes are collected until the
+/// buffer is full or the stream is flushed.  Reads and writes larger than the buffer bypass it.
+pub struct BufStream<T> {
+	inner: T,
+	read_buf: Box<[u8]>,
+	// unconsumed data is `read_buf[read_pos..read_filled]`
+	read_pos: usize,
+	read_filled: usize,
+	write_buf: Vec<u8>,
+	// `write_buf[..write_pos]` was already written
+	write_pos: usize,
+}
+
+impl<T> BufStream<T> {
+	/// Wrap `inner` with 8 KiB read and write buffers
+	pub fn new(inner: T) -> Self {
+		Self::with_capacity(DEFAULT_CAPACITY, DEFAULT_CAPACITY, inner)
+	}
+
+	/// Wrap `inner` with the given read and write buffer sizes
+	///
+	/// # Panics
+	///
+	/// Panics if a capacity is zero.
+	pub fn with_capacity(read_capacity: usize, write_capacity: usize, inner: T) -> Self {
+		assert!(read_capacity != 0 && write_capacity != 0, "buffer capacity must not be zero");
+		Self {
+			inner,
+			read_buf: vec![0; read_capacity].into_boxed_slice(),
+			read_pos: 0,
+			read_filled: 0,
+			write_buf: Vec::with_capacity(write_capacity),
+			write_pos: 0,
+		}
+	}
+
+	/// Access the inner IO object
+	pub const fn get_ref(&self) -> &T {
+		&self.inner
+	}
+
+	/// Mutable access to the inner IO object (bypasses the buffers)
+	pub fn get_mut(&mut self) -> &mut T {
+		&mut self.inner
+	}
+
+	/// Buffered data not read yet
+	pub fn read_buffer(&self) -> &[u8] {
+		&self.read_buf[self.read_pos..self.read_filled]
+	}
+
+	/// Data written but not flushed yet
+	pub fn write_buffer(&self) -> &[u8] {
+		&self.write_buf[self.write_pos..]
+	}
+
+	/// Extract the inner IO object
+	///
+	/// Buffered data is lost; flush before and check [`read_buffer`](#method.read_buffer).
+	pub fn into_inner(self) -> T {
+		self.inner
+	}
+}
+
+impl<T> BufStream<T>
+where
+	T: AsyncWrite + Unpin,
+{
+	// write out the complete write buffer
+	fn poll_flush_buf(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		while self.write_pos < self.write_buf.len() {
+			let n = futures_util::ready!(Pin::new(&mut self.inner).poll_write(cx, &self.write_buf[self.write_pos..]))?;
+			if n == 0 {
+				return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+			}
+			self.write_pos += n;
+		}
+		self.write_buf.clear();
+		self.write_pos = 0;
+		Poll::Ready(Ok(()))
+	}
+}
+
+impl<T> fmt::Debug for BufStream<T>
+where
+	T: fmt::Debug,
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("BufStream")
+			.field("inner", &self.inner)
+			.field("read_buf", &format_args!("{}/{}", self.read_filled - self.read_pos, self.read_buf.len()))
+			.field("read_pos", &self.read_pos)
+			.field("read_filled", &self.read_filled)
+			.field("write_buf", &format_args!("{}/{}", self.write_buf.len() - self.write_pos, self.write_buf.capacity()))
+			.field("write_pos", &self.write_pos)
+			.finish()
+	}
+}
+
+impl<T> AsyncRead for BufStream<T>
+where
+	T: AsyncRead + Unpin,
+{
+	fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+		let this = self.get_mut();
+		if this.read_pos == this.read_filled && buf.len() >= this.read_buf.len() {
+			return Pin::new(&mut this.inner).poll_read(cx, buf);
+		}
+		let available = futures_util::ready!(Pin::new(&mut *this).poll_fill_buf(cx))?;
+		let n = std::cmp::min(available.len(), buf.len());
+		buf[..n].copy_from_slice(&available[..n]);
+		this.read_pos += n;
+		Poll::Ready(Ok(n))
+	}
+}
+
+impl<T> AsyncBufRead for BufStream<T>
+where
+	T: AsyncRead + Unpin,
+{
+	fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+		let this = self.get_mut();
+		if this.read_pos == this.read_filled {
+			let n = futures_util::ready!(Pin::new(&mut this.inner).poll_read(cx, &mut this.read_buf))?;
+			this.read_pos = 0;
+			this.read_filled = n;
+		}
+		Poll::Ready(Ok(&this.read_buf[this.read_pos..this.read_filled]))
+	}
+
+	fn consume(self: Pin<&mut Self>, amt: usize) {
+		let this = self.get_mut();
+		this.read_pos = std::cmp::min(this.read_pos + amt, this.read_filled);
+	}
+}
+
+impl<T> AsyncWrite for BufStream<T>
+where
+	T: AsyncWrite + Unpin,
+{
+	fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+		let this = self.get_mut();
+		if this.write_buf.len() + buf.len() > this.write_buf.capacity() {
+			futures_util::ready!(this.poll_flush_buf(cx))?;
+		}
+		if buf.len() >= this.write_buf.capacity() {
+			Pin::new(&mut this.inner).poll_write(cx, buf)
+		} else {
+			this.write_buf.extend_from_slice(buf);
+			Poll::Ready(Ok(buf.len()))
+		}
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		let this = self.get_mut();
+		futures_util::ready!(this.poll_flush_buf(cx))?;
+		Pin::new(&mut this.inner).poll_flush(cx)
+	}
+
+	fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		let this = self.get_mut();
+		futures_util::ready!(this.poll_flush_buf(cx))?;
+		Pin::new(&mut this.inner).poll_close(cx)
+	}
+}
@@ -0,0 +1,146 @@
+use futures_io::{AsyncRead, AsyncWrite};
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+const BUFFER_SIZE: usize = 8 * 1024;
+
+// copies one direction; closes the writer after EOF from the reader
+struct CopyBuffer {
+	buf: Box<[u8]>,
+	// data not written yet is `buf[pos..cap]`
+	pos: usize,
+	cap: usize,
+	amount: u64,
+	read_done: bool,
+	need_flush: bool,
+	closed: bool,
+}
+
+impl CopyBuffer {
+	fn new() -> Self {
+		Self {
+			buf: vec![0; BUFFER_SIZE].into_boxed_slice(),
+			pos: 0,
+			cap: 0,
+			amount: 0,
+			read_done: false,
+			need_flush: false,
+			closed: false,
+		}
+	}
+
+	fn poll_copy<R, W>(&mut self, cx: &mut Context<'_>, mut reader: Pin<&mut R>, mut writer: Pin<&mut W>) -> Poll<io::Result<u64>>
+	where
+		R: AsyncRead + ?Sized,
+		W: AsyncWrite + ?Sized,
+	{
+		if self.closed {
+			return Poll::Ready(Ok(self.amount));
+		}
+		loop {
+			if self.pos == self.cap && !self.read_done {
+				match reader.as_mut().poll_read(cx, &mut self.buf) {
+					Poll::Ready(result) => {
+						let n = result?;
+						if n == 0 {
+							self.read_done = true;
+						} else {
+							self.pos = 0;
+							self.cap = n;
+						}
+					},
+					Poll::Pending => {
+						// don't keep written data in the writer's buffers while waiting
+						if self.need_flush {
+							futures_util::ready!(writer.as_mut().poll_flush(cx))?;
+							self.need_flush = false;
+						}
+						return Poll::Pending;
+					},
+				}
+			}
+
+			while self.pos < self.cap {
+				let n = futures_util::ready!(writer.as_mut().poll_write(cx, &self.buf[self.pos..self.cap]))?;
+				if n == 0 {
+					return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+				}
+				self.pos += n;
+				self.amount += n as u64;
+				self.need_flush = true;
+			}
+
+			if self.read_done {
+				futures_util::ready!(writer.as_mut().poll_flush(cx))?;
+				// half-close: the peer sees EOF, the other direction keeps going
+				futures_util::ready!(writer.as_mut().poll_close(cx))?;
+				self.closed = true;
+				return Poll::Ready(Ok(self.amount));
+			}
+		}
+	}
+}
+
+impl fmt::Debug for CopyBuffer {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("CopyBuffer")
+			.field("buf", &())
+			.field("pos", &self.pos)
+			.field("cap", &self.cap)
+			.field("amount", &self.amount)
+			.field("read_done", &self.read_done)
+			.field("need_flush", &self.need_flush)
+			.field("closed", &self.closed)
+			.finish()
+	}
+}
+
+/// Future for [`copy_bidirectional`](fn.copy_bidirectional.html)
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct CopyBidirectional<'a, A: ?Sized, B: ?Sized> {
+	a: &'a mut A,
+	b: &'a mut B,
+	a_to_b: CopyBuffer,
+	b_to_a: CopyBuffer,
+}
+
+/// Copies data in both directions between `a` and `b` at the same time
+///
+/// When one side reaches EOF the other side is flushed and closed (for TCP streams this shuts
+/// down the write direction), while data keeps flowing in the other direction.  Completes with
+/// the number of bytes copied from `a` to `b` and from `b` to `a` after both directions are
+/// done, or with the first error.
+pub fn copy_bidirectional<'a, A, B>(a: &'a mut A, b: &'a mut B) -> CopyBidirectional<'a, A, B>
+where
+	A: AsyncRead + AsyncWrite + Unpin + ?Sized,
+	B: AsyncRead + AsyncWrite + Unpin + ?Sized,
+{
+	CopyBidirectional {
+		a,
+		b,
+		a_to_b: CopyBuffer::new(),
+		b_to_a: CopyBuffer::new(),
+	}
+}
+
+impl<A, B> Future for CopyBidirectional<'_, A, B>
+where
+	A: AsyncRead + AsyncWrite + Unpin + ?Sized,
+	B: AsyncRead + AsyncWrite + Unpin + ?Sized,
+{
+	type Output = io::Result<(u64, u64)>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.get_mut();
+		let a_to_b = this.a_to_b.poll_copy(cx, Pin::new(&mut *this.a), Pin::new(&mut *this.b))?;
+		let b_to_a = this.b_to_a.poll_copy(cx, Pin::new(&mut *this.b), Pin::new(&mut *this.a))?;
+		match (a_to_b, b_to_a) {
+			(Poll::Ready(a_to_b), Poll::Ready(b_to_a)) => Poll::Ready(Ok((a_to_b, b_to_a))),
+			_ => Poll::Pending,
+		}
+	}
+}
@@ -0,0 +1,168 @@
+// wrappers: errors come from the serial port (or registering it with the reactor)
+#![allow(clippy::missing_errors_doc)]
+
+use crate::reactor::{AsyncFd, LazyHandle};
+use futures_io::{AsyncRead, AsyncWrite};
+use serialport::{DataBits, FlowControl, Parity, SerialPortBuilder, StopBits, TTYPort};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::mem::ManuallyDrop;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+fn serial_error(e: serialport::Error) -> io::Error {
+	e.into()
+}
+
+fn set_nonblocking(port: &TTYPort) -> io::Result<()> {
+	let fd = port.as_raw_fd();
+	let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+	if flags < 0 || unsafe { libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) } < 0 {
+		return Err(io::Error::last_os_error());
+	}
+	Ok(())
+}
+
+// plain read/write on the file descriptor; `TTYPort` itself waits with a timeout
+fn with_file<F, R>(port: &TTYPort, op: F) -> R
+where
+	F: FnOnce(&File) -> R,
+{
+	// must not close the file descriptor
+	let file = ManuallyDrop::new(unsafe { File::from_raw_fd(port.as_raw_fd()) });
+	op(&file)
+}
+
+/// Serial port (unix only)
+///
+/// Requires the `serial` feature; ports are opened and configured through
+/// [`serialport`](https://docs.rs/serialport/4).
+#[derive(Debug)]
+pub struct SerialPort {
+	fd: AsyncFd<TTYPort>,
+}
+
+impl SerialPort {
+	/// Open serial port at `path` with `baud_rate` (8N1, no flow control)
+	pub fn open(path: &str, baud_rate: u32) -> io::Result<Self> {
+		Self::open_with(&serialport::new(path, baud_rate), LazyHandle::new())
+	}
+
+	/// Open serial port configured by `builder`
+	pub fn open_with(builder: &SerialPortBuilder, handle: LazyHandle) -> io::Result<Self> {
+		Self::from_port(TTYPort::open(builder).map_err(serial_error)?, handle)
+	}
+
+	/// Wraps an opened port
+	pub fn from_port(port: TTYPort, handle: LazyHandle) -> io::Result<Self> {
+		set_nonblocking(&port)?;
+		Ok(Self {
+			fd: AsyncFd::with_handle(port, handle)?,
+		})
+	}
+
+	/// Create a connected pair of pseudo terminals (e.g. for testing)
+	pub fn pair() -> io::Result<(Self, Self)> {
+		let (master, slave) = TTYPort::pair().map_err(serial_error)?;
+		Ok((Self::from_port(master, LazyHandle::new())?, Self::from_port(slave, LazyHandle::new())?))
+	}
+
+	/// Access the port (e.g. for control lines)
+	pub fn get_ref(&self) -> &TTYPort {
+		self.fd.get_ref()
+	}
+
+	/// Mutable access to the port
+	///
+	/// Reading or writing the port directly blocks (with a timeout).
+	pub fn get_mut(&mut self) -> &mut TTYPort {
+		self.fd.get_mut()
+	}
+
+	/// Deregisters from the reactor and returns the port (still in non-blocking mode).
+	pub fn into_inner(self) -> TTYPort {
+		self.fd.into_inner()
+	}
+
+	/// Current baud rate
+	pub fn baud_rate(&self) -> io::Result<u32> {
+		serialport::SerialPort::baud_rate(self.get_ref()).map_err(serial_error)
+	}
+
+	/// Set baud rate
+	pub fn set_baud_rate(&mut self, baud_rate: u32) -> io::Result<()> {
+		serialport::SerialPort::set_baud_rate(self.get_mut(), baud_rate).map_err(serial_error)
+	}
+
+	/// Current parity checking mode
+	pub fn parity(&self) -> io::Result<Parity> {
+		serialport::SerialPort::parity(self.get_ref()).map_err(serial_error)
+	}
+
+	/// Set parity checking mode
+	pub fn set_parity(&mut self, parity: Parity) -> io::Result<()> {
+		serialport::SerialPort::set_parity(self.get_mut(), parity).map_err(serial_error)
+	}
+
+	/// Current number of data bits per character
+	pub fn data_bits(&self) -> io::Result<DataBits> {
+		serialport::SerialPort::data_bits(self.get_ref()).map_err(serial_error)
+	}
+
+	/// Set number of data bits per character
+	pub fn set_data_bits(&mut self, data_bits: DataBits) -> io::Result<()> {
+		serialport::SerialPort::set_data_bits(self.get_mut(), data_bits).map_err(serial_error)
+	}
+
+	/// Current number of stop bits
+	pub fn stop_bits(&self) -> io::Result<StopBits> {
+		serialport::SerialPort::stop_bits(self.get_ref()).map_err(serial_error)
+	}
+
+	/// Set number of stop bits
+	pub fn set_stop_bits(&mut self, stop_bits: StopBits) -> io::Result<()> {
+		serialport::SerialPort::set_stop_bits(self.get_mut(), stop_bits).map_err(serial_error)
+	}
+
+	/// Current flow control mode
+	pub fn flow_control(&self) -> io::Result<FlowControl> {
+		serialport::SerialPort::flow_control(self.get_ref()).map_err(serial_error)
+	}
+
+	/// Set flow control mode
+	pub fn set_flow_control(&mut self, flow_control: FlowControl) -> io::Result<()> {
+		serialport::SerialPort::set_flow_control(self.get_mut(), flow_control).map_err(serial_error)
+	}
+}
+
+impl AsyncRead for SerialPort {
+	fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+		loop {
+			let mut guard = futures_util::ready!(self.fd.poll_read_ready(cx))?;
+			if let Poll::Ready(result) = guard.try_io(|port| with_file(port, |mut file| file.read(buf))) {
+				return Poll::Ready(result);
+			}
+		}
+	}
+}
+
+impl AsyncWrite for SerialPort {
+	fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+		loop {
+			let mut guard = futures_util::ready!(self.fd.poll_write_ready(cx))?;
+			if let Poll::Ready(result) = guard.try_io(|port| with_file(port, |mut file| file.write(buf))) {
+				return Poll::Ready(result);
+			}
+		}
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		// written data is in the kernel buffers; waiting for transmission (`tcdrain`) would block
+		Poll::Ready(Ok(()))
+	}
+
+	fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Poll::Ready(Ok(()))
+	}
+}
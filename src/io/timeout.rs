@@ -0,0 +1,219 @@
+use futures_io::{AsyncBufRead, AsyncRead, AsyncWrite};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio_timer::Delay;
+
+// timer started when an operation first returns `Pending`, cleared when an operation completes
+#[derive(Debug)]
+struct TimeoutState {
+	timeout: Option<Duration>,
+	delay: Option<Delay>,
+}
+
+impl TimeoutState {
+	const fn new(timeout: Option<Duration>) -> Self {
+		Self {
+			timeout,
+			delay: None,
+		}
+	}
+
+	fn set_timeout(&mut self, timeout: Option<Duration>) {
+		self.timeout = timeout;
+		self.delay = None;
+	}
+
+	fn poll<T>(&mut self, cx: &mut Context<'_>, result: Poll<io::Result<T>>) -> Poll<io::Result<T>> {
+		if result.is_ready() {
+			self.delay = None;
+			return result;
+		}
+		let Some(timeout) = self.timeout else {
+			return Poll::Pending;
+		};
+		let delay = self.delay.get_or_insert_with(|| tokio_timer::delay(Instant::now() + timeout));
+		futures_util::ready!(Pin::new(delay).poll(cx));
+		self.delay = None;
+		Poll::Ready(Err(io::Error::new(io::ErrorKind::TimedOut, "IO operation timed out")))
+	}
+}
+
+/// Fails reads that don't make progress within a timeout
+///
+/// The timer starts when a read has to wait, and is reset by every completed read; on expiry
+/// the pending read fails with `ErrorKind::TimedOut`.  Writes are passed through.
+///
+/// Needs the timer of the current runtime.
+#[derive(Debug)]
+pub struct TimeoutReader<T> {
+	inner: T,
+	state: TimeoutState,
+}
+
+impl<T> TimeoutReader<T> {
+	/// Wrap `inner` with the given read timeout (`None` disables it)
+	pub const fn new(inner: T, timeout: Option<Duration>) -> Self {
+		Self {
+			inner,
+			state: TimeoutState::new(timeout),
+		}
+	}
+
+	/// Current read timeout
+	pub const fn timeout(&self) -> Option<Duration> {
+		self.state.timeout
+	}
+
+	/// Set read timeout (`None` disables it); restarts the timer of a pending read
+	pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+		self.state.set_timeout(timeout);
+	}
+
+	/// Access the inner IO object
+	pub const fn get_ref(&self) -> &T {
+		&self.inner
+	}
+
+	/// Mutable access to the inner IO object
+	pub fn get_mut(&mut self) -> &mut T {
+		&mut self.inner
+	}
+
+	/// Extract the inner IO object
+	pub fn into_inner(self) -> T {
+		self.inner
+	}
+}
+
+impl<T> AsyncRead for TimeoutReader<T>
+where
+	T: AsyncRead + Unpin,
+{
+	fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+		let this = self.get_mut();
+		let result = Pin::new(&mut this.inner).poll_read(cx, buf);
+		this.state.poll(cx, result)
+	}
+}
+
+impl<T> AsyncBufRead for TimeoutReader<T>
+where
+	T: AsyncBufRead + Unpin,
+{
+	fn poll_fill_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<&[u8]>> {
+		let this = self.get_mut();
+		match Pin::new(&mut this.inner).poll_fill_buf(cx) {
+			Poll::Ready(result) => {
+				this.state.delay = None;
+				Poll::Ready(result)
+			},
+			Poll::Pending => match this.state.poll::<()>(cx, Poll::Pending) {
+				Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+				_ => Poll::Pending,
+			},
+		}
+	}
+
+	fn consume(self: Pin<&mut Self>, amt: usize) {
+		Pin::new(&mut self.get_mut().inner).consume(amt);
+	}
+}
+
+impl<T> AsyncWrite for TimeoutReader<T>
+where
+	T: AsyncWrite + Unpin,
+{
+	fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+		Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+	}
+
+	fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Pin::new(&mut self.get_mut().inner).poll_close(cx)
+	}
+}
+
+/// Fails writes (including flush and close) that don't make progress within a timeout
+///
+/// The timer starts when an operation has to wait, and is reset by every completed operation;
+/// on expiry the pending operation fails with `ErrorKind::TimedOut`.  Reads are passed through.
+///
+/// Needs the timer of the current runtime.
+#[derive(Debug)]
+pub struct TimeoutWriter<T> {
+	inner: T,
+	state: TimeoutState,
+}
+
+impl<T> TimeoutWriter<T> {
+	/// Wrap `inner` with the given write timeout (`None` disables it)
+	pub const fn new(inner: T, timeout: Option<Duration>) -> Self {
+		Self {
+			inner,
+			state: TimeoutState::new(timeout),
+		}
+	}
+
+	/// Current write timeout
+	pub const fn timeout(&self) -> Option<Duration> {
+		self.state.timeout
+	}
+
+	/// Set write timeout (`None` disables it); restarts the timer of a pending write
+	pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+		self.state.set_timeout(timeout);
+	}
+
+	/// Access the inner IO object
+	pub const fn get_ref(&self) -> &T {
+		&self.inner
+	}
+
+	/// Mutable access to the inner IO object
+	pub fn get_mut(&mut self) -> &mut T {
+		&mut self.inner
+	}
+
+	/// Extract the inner IO object
+	pub fn into_inner(self) -> T {
+		self.inner
+	}
+}
+
+impl<T> AsyncRead for TimeoutWriter<T>
+where
+	T: AsyncRead + Unpin,
+{
+	fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+		Pin::new(&mut self.get_mut().inner).poll_read(cx, buf)
+	}
+}
+
+impl<T> AsyncWrite for TimeoutWriter<T>
+where
+	T: AsyncWrite + Unpin,
+{
+	fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+		let this = self.get_mut();
+		let result = Pin::new(&mut this.inner).poll_write(cx, buf);
+		this.state.poll(cx, result)
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		let this = self.get_mut();
+		let result = Pin::new(&mut this.inner).poll_flush(cx);
+		this.state.poll(cx, result)
+	}
+
+	fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		let this = self.get_mut();
+		let result = Pin::new(&mut this.inner).poll_close(cx);
+		this.state.poll(cx, result)
+	}
+}
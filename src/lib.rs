@@ -14,10 +14,13 @@
 	clippy::module_name_repetitions, // often hidden modules and reexported
 	clippy::if_not_else, // `... != 0` is a positive condition
 	clippy::multiple_crate_versions, // not useful
+	clippy::must_use_candidate, // would mark nearly every getter
+	clippy::missing_const_for_fn, // `const` is an API promise; only added where it is needed
+	clippy::redundant_pub_crate, // stays crate-internal even if the module becomes public
 )]
 
 pub use fumio_reactor::reactor as reactor;
-pub use fumio_reactor::net as net;
+pub use fumio_utils::enter::{enter, Enter, EnterError};
 
 pub mod pool {
 	//! Single-threaded pool of (non-`Send`) futures
@@ -25,38 +28,65 @@ pub mod pool {
 	pub use fumio_pool::{
 		LocalPool,
 		LocalSpawner,
+		PanicPolicy,
+		PoolMetrics,
+		Priority,
+		TaskId,
+		TaskInfo,
+		TaskPanic,
 		current_local,
 	};
+	pub use crate::supervisor::{Supervise, Supervisor};
 }
 
-pub mod timer {
-	//! Time based events
+pub mod task {
+	//! Task utilities
 
-	pub use tokio_timer::{
-		Delay,
-		DelayQueue,
-		Interval,
-		Timeout,
+	pub use fumio_pool::{
+		yield_now,
+		YieldNow,
 	};
 }
 
+#[cfg(feature = "hyper-compat")]
+pub mod hyper;
+pub mod fs;
+pub mod io;
+pub mod net;
+pub mod shutdown;
+pub mod sync;
+pub mod testing;
+pub mod timer;
+
 mod runtime;
-pub use self::runtime::{Handle, Runtime};
+pub use self::runtime::{current, spread, spread_pinned, Builder, Drive, Handle, Metrics, Runtime, Spread};
+mod supervisor;
 mod timer_reactor;
+mod waiters;
 
 use std::future::Future;
 
 /// Runs a future until completion with IO reactor and timer in a local pool
 ///
+/// Runs the [shutdown hooks](struct.Handle.html#method.on_shutdown) after the future completed.
+///
 /// Available environment:
 /// - [`fumio::reactor::current()`](reactor/fn.current.html), also automatically used by
 ///   [`fumio::reactor::LazyHandle`](reactor/struct.LazyHandle.html)
 /// - [`fumio::pool::current_local()`](fumio/pool/fn.current_local.html)
-/// - [`tokio_timer::timer::TimerHandle::current()`](https://docs.rs/tokio-timer/0.3.0-alpha.2/tokio_timer/timer/struct.Handle.html#method.current)
+/// - [`tokio_timer::timer::TimerHandle::current()`](https://docs.rs/tokio-timer/0.3.0-alpha.5/tokio_timer/timer/struct.Handle.html#method.current)
+/// - [`fumio::current()`](fn.current.html) and [`fumio::shutdown::current()`](shutdown/fn.current.html)
+///
+/// # Panics
+///
+/// Panics if the runtime can't be created or if the thread already runs an executor (see
+/// [`fumio::enter`](fn.enter.html)).
 pub fn run<F, T>(future: F) -> T
 where
 	F: Future<Output = T>,
 {
 	let mut runtime = Runtime::new().unwrap();
-	runtime.run_until(future)
+	let result = runtime.run_until(future);
+	runtime.run_shutdown_hooks();
+	result
 }
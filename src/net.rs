@@ -0,0 +1,12 @@
+//! Various network abstractions
+//!
+//! Everything from [`fumio_reactor::net`](../../fumio_reactor/net/index.html), plus operations
+//! needing timers.
+
+pub use fumio_reactor::net::*;
+
+mod happy_eyeballs;
+#[cfg(feature = "tls")]
+pub mod tls;
+
+pub use self::happy_eyeballs::{connect_happy, ConnectHappy};
@@ -0,0 +1,142 @@
+use crate::net::{TcpConnectFuture, TcpStream};
+use crate::reactor::LazyHandle;
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+// "Connection Attempt Delay" (RFC 8305, section 5)
+const ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+// alternate address families, starting with the family of the first address (RFC 8305, section 4)
+fn interleave(addrs: impl IntoIterator<Item = SocketAddr>) -> Vec<SocketAddr> {
+	let mut addrs = addrs.into_iter();
+	let Some(first) = addrs.next() else {
+		return Vec::new();
+	};
+	let (preferred, other): (Vec<_>, Vec<_>) = addrs.partition(|a| a.is_ipv4() == first.is_ipv4());
+	let mut preferred = std::iter::once(first).chain(preferred);
+	let mut other = other.into_iter();
+	let mut result = Vec::new();
+	loop {
+		match (preferred.next(), other.next()) {
+			(None, None) => return result,
+			(a, b) => result.extend(a.into_iter().chain(b)),
+		}
+	}
+}
+
+/// Connect to the first reachable address using "Happy Eyeballs" (RFC 8305).
+///
+/// Connection attempts alternate between IPv6 and IPv4 (starting with the family of the first
+/// address); a new attempt is started every 250ms or when the previous attempt failed.  The first
+/// established connection wins, all other attempts are aborted.
+///
+/// Fails with the error of the last failed attempt if no connection could be established.
+///
+/// Needs the timer of the current runtime.
+pub fn connect_happy<I>(addrs: I) -> ConnectHappy
+where
+	I: IntoIterator<Item = SocketAddr>,
+{
+	ConnectHappy {
+		addrs: interleave(addrs).into_iter(),
+		attempts: Vec::new(),
+		next_attempt: None,
+		last_error: None,
+	}
+}
+
+/// Pending [`connect_happy`](fn.connect_happy.html) operation
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct ConnectHappy {
+	addrs: std::vec::IntoIter<SocketAddr>,
+	attempts: Vec<TcpConnectFuture>,
+	// start next attempt when this expires
+	next_attempt: Option<tokio_timer::Delay>,
+	last_error: Option<io::Error>,
+}
+
+impl ConnectHappy {
+	// returns whether a new attempt was started
+	fn start_attempt(&mut self) -> bool {
+		for addr in &mut self.addrs {
+			match TcpStream::connect_addr(addr, LazyHandle::new()) {
+				Ok(attempt) => {
+					self.attempts.push(attempt);
+					self.next_attempt = Some(tokio_timer::delay_for(ATTEMPT_DELAY));
+					return true;
+				},
+				Err(e) => self.last_error = Some(e),
+			}
+		}
+		self.next_attempt = None;
+		false
+	}
+}
+
+impl Future for ConnectHappy {
+	type Output = io::Result<TcpStream>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.get_mut();
+		loop {
+			let mut failed = false;
+			let mut i = 0;
+			while i < this.attempts.len() {
+				match Pin::new(&mut this.attempts[i]).poll(cx) {
+					Poll::Ready(Ok(stream)) => {
+						// dropping the other attempts aborts them
+						this.attempts.clear();
+						return Poll::Ready(Ok(stream));
+					},
+					Poll::Ready(Err(e)) => {
+						this.last_error = Some(e);
+						drop(this.attempts.remove(i));
+						failed = true;
+					},
+					Poll::Pending => i += 1,
+				}
+			}
+
+			let start_next = failed
+				|| this.attempts.is_empty()
+				|| this.next_attempt.as_mut().is_some_and(|delay| Pin::new(delay).poll(cx).is_ready());
+			if !start_next {
+				return Poll::Pending;
+			}
+			if !this.start_attempt() && this.attempts.is_empty() {
+				return Poll::Ready(Err(this.last_error.take().unwrap_or_else(|| {
+					io::Error::new(io::ErrorKind::InvalidInput, "no addresses to connect to")
+				})));
+			}
+			// poll new attempt (and the timer for the next one)
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn interleave_families() {
+		let addrs: Vec<SocketAddr> = vec![
+			"[::1]:1".parse().unwrap(),
+			"[::2]:1".parse().unwrap(),
+			"[::3]:1".parse().unwrap(),
+			"127.0.0.1:1".parse().unwrap(),
+		];
+		let expected: Vec<SocketAddr> = vec![
+			"[::1]:1".parse().unwrap(),
+			"127.0.0.1:1".parse().unwrap(),
+			"[::2]:1".parse().unwrap(),
+			"[::3]:1".parse().unwrap(),
+		];
+		assert_eq!(interleave(addrs), expected);
+		assert!(interleave(Vec::new()).is_empty());
+	}
+}
@@ -0,0 +1,277 @@
+//! TLS streams using [`rustls`](https://docs.rs/rustls/0.21)
+//!
+//! Requires the `tls` feature.
+//!
+//! ```ignore
+//! let connector = TlsConnector::from(Arc::new(client_config));
+//! let stream = TcpStream::connect(addr).await?;
+//! let mut stream = connector.connect("example.com", stream)?.await?;
+//! ```
+
+pub use rustls;
+
+use crate::net::TcpStream;
+use futures_io::{AsyncRead, AsyncWrite};
+use rustls::{ClientConfig, ClientConnection, Connection, ServerConfig, ServerConnection, ServerName};
+use std::convert::TryFrom;
+use std::fmt;
+use std::future::Future;
+use std::io::{self, Read, Write};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+fn tls_error(e: rustls::Error) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+// blocking IO adapter for rustls; "would block" registers the task for readiness
+struct SyncIo<'a, 'b> {
+	io: &'a mut TcpStream,
+	cx: &'a mut Context<'b>,
+}
+
+impl Read for SyncIo<'_, '_> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		match Pin::new(&mut *self.io).poll_read(self.cx, buf) {
+			Poll::Ready(result) => result,
+			Poll::Pending => Err(io::ErrorKind::WouldBlock.into()),
+		}
+	}
+}
+
+impl Write for SyncIo<'_, '_> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		match Pin::new(&mut *self.io).poll_write(self.cx, buf) {
+			Poll::Ready(result) => result,
+			Poll::Pending => Err(io::ErrorKind::WouldBlock.into()),
+		}
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		match Pin::new(&mut *self.io).poll_flush(self.cx) {
+			Poll::Ready(result) => result,
+			Poll::Pending => Err(io::ErrorKind::WouldBlock.into()),
+		}
+	}
+}
+
+fn into_poll<T>(result: io::Result<T>) -> Poll<io::Result<T>> {
+	match result {
+		Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => Poll::Pending,
+		result => Poll::Ready(result),
+	}
+}
+
+/// TLS connection over a [`TcpStream`](../struct.TcpStream.html) (client or server side)
+pub struct TlsStream {
+	io: TcpStream,
+	session: Connection,
+}
+
+impl TlsStream {
+	/// Access the TCP stream and the TLS session
+	pub const fn get_ref(&self) -> (&TcpStream, &Connection) {
+		(&self.io, &self.session)
+	}
+
+	/// Mutable access to the TCP stream and the TLS session
+	///
+	/// Reading or writing the TCP stream directly breaks the TLS connection.
+	pub fn get_mut(&mut self) -> (&mut TcpStream, &mut Connection) {
+		(&mut self.io, &mut self.session)
+	}
+
+	/// Extract TCP stream and TLS session
+	pub fn into_inner(self) -> (TcpStream, Connection) {
+		(self.io, self.session)
+	}
+
+	// read and process TLS records; returns 0 on EOF
+	fn poll_read_tls(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+		let n = futures_util::ready!(into_poll(self.session.read_tls(&mut SyncIo { io: &mut self.io, cx })))?;
+		self.session.process_new_packets().map_err(|e| {
+			// try to send the alert; the error is reported anyway
+			let _ = self.session.write_tls(&mut SyncIo { io: &mut self.io, cx });
+			tls_error(e)
+		})?;
+		Poll::Ready(Ok(n))
+	}
+
+	// write all pending TLS records
+	fn poll_write_tls(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		while self.session.wants_write() {
+			let n = futures_util::ready!(into_poll(self.session.write_tls(&mut SyncIo { io: &mut self.io, cx })))?;
+			if n == 0 {
+				return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+			}
+		}
+		Poll::Ready(Ok(()))
+	}
+
+	fn poll_handshake(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		while self.session.is_handshaking() {
+			futures_util::ready!(self.poll_write_tls(cx))?;
+			if self.session.is_handshaking() && self.session.wants_read() {
+				let n = futures_util::ready!(self.poll_read_tls(cx))?;
+				if n == 0 {
+					return Poll::Ready(Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed during TLS handshake")));
+				}
+			}
+		}
+		self.poll_write_tls(cx)
+	}
+}
+
+impl fmt::Debug for TlsStream {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("TlsStream")
+			.field("io", &self.io)
+			.field("session", &())
+			.finish()
+	}
+}
+
+impl AsyncRead for TlsStream {
+	fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+		let this = self.get_mut();
+		loop {
+			match this.session.reader().read(buf) {
+				// `Ok(0)` after `close_notify`, `UnexpectedEof` if the peer closed without sending one
+				Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => (),
+				result => return Poll::Ready(result),
+			}
+			// responses to received records (e.g. key updates); doesn't need to complete
+			if let Poll::Ready(Err(e)) = this.poll_write_tls(cx) {
+				return Poll::Ready(Err(e));
+			}
+			futures_util::ready!(this.poll_read_tls(cx))?;
+		}
+	}
+}
+
+impl AsyncWrite for TlsStream {
+	fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+		let this = self.get_mut();
+		loop {
+			let n = this.session.writer().write(buf)?;
+			match this.poll_write_tls(cx) {
+				Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+				// rustls buffered the data; it is sent on the next write or flush
+				Poll::Pending if n != 0 => return Poll::Ready(Ok(n)),
+				Poll::Pending => return Poll::Pending,
+				Poll::Ready(Ok(())) => (),
+			}
+			if n != 0 || buf.is_empty() {
+				return Poll::Ready(Ok(n));
+			}
+		}
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		let this = self.get_mut();
+		this.session.writer().flush()?;
+		futures_util::ready!(this.poll_write_tls(cx))?;
+		Pin::new(&mut this.io).poll_flush(cx)
+	}
+
+	fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		let this = self.get_mut();
+		// only queues the alert once
+		this.session.send_close_notify();
+		futures_util::ready!(this.poll_write_tls(cx))?;
+		Pin::new(&mut this.io).poll_close(cx)
+	}
+}
+
+/// Future completing the TLS handshake of a [`TlsStream`](struct.TlsStream.html)
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct TlsHandshake {
+	stream: Option<TlsStream>,
+}
+
+impl TlsHandshake {
+	fn new(io: TcpStream, session: Connection) -> Self {
+		Self {
+			stream: Some(TlsStream { io, session }),
+		}
+	}
+}
+
+impl Future for TlsHandshake {
+	type Output = io::Result<TlsStream>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let this = self.get_mut();
+		let stream = this.stream.as_mut().expect("TlsHandshake polled after completion");
+		futures_util::ready!(stream.poll_handshake(cx))?;
+		Poll::Ready(Ok(this.stream.take().unwrap()))
+	}
+}
+
+/// Creates client side TLS connections
+#[derive(Clone)]
+pub struct TlsConnector {
+	config: Arc<ClientConfig>,
+}
+
+impl TlsConnector {
+	/// Start TLS handshake on `stream`, verifying the server certificate for `domain`
+	///
+	/// # Errors
+	///
+	/// Fails if `domain` is not a valid DNS name or IP address, or if rustls fails to create the
+	/// session.
+	pub fn connect(&self, domain: &str, stream: TcpStream) -> io::Result<TlsHandshake> {
+		let name = ServerName::try_from(domain).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+		let session = ClientConnection::new(self.config.clone(), name).map_err(tls_error)?;
+		Ok(TlsHandshake::new(stream, session.into()))
+	}
+}
+
+impl From<Arc<ClientConfig>> for TlsConnector {
+	fn from(config: Arc<ClientConfig>) -> Self {
+		Self { config }
+	}
+}
+
+impl fmt::Debug for TlsConnector {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("TlsConnector")
+			.field("config", &())
+			.finish()
+	}
+}
+
+/// Accepts server side TLS connections
+#[derive(Clone)]
+pub struct TlsAcceptor {
+	config: Arc<ServerConfig>,
+}
+
+impl TlsAcceptor {
+	/// Start TLS handshake on an accepted `stream`
+	///
+	/// # Errors
+	///
+	/// Fails if rustls fails to create the session.
+	pub fn accept(&self, stream: TcpStream) -> io::Result<TlsHandshake> {
+		let session = ServerConnection::new(self.config.clone()).map_err(tls_error)?;
+		Ok(TlsHandshake::new(stream, session.into()))
+	}
+}
+
+impl From<Arc<ServerConfig>> for TlsAcceptor {
+	fn from(config: Arc<ServerConfig>) -> Self {
+		Self { config }
+	}
+}
+
+impl fmt::Debug for TlsAcceptor {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("TlsAcceptor")
+			.field("config", &())
+			.finish()
+	}
+}
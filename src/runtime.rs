@@ -1,10 +1,47 @@
+mod affinity;
+mod builder;
+mod metrics;
+mod spread;
+#[cfg(unix)]
+mod drive_wakeup;
+#[cfg(all(unix, feature = "task-dump"))]
+pub(crate) mod task_dump;
+mod test_runtime;
+
+pub use self::builder::Builder;
+pub use self::metrics::Metrics;
+pub use self::spread::{spread, spread_pinned, Spread};
+pub use self::test_runtime::TestRuntime;
+
 use crate::timer_reactor::TimerReactor;
 use crate::pool::{LocalPool, LocalSpawner};
-use futures_core::future::{FutureObj, LocalFutureObj};
-use futures_core::task::{Spawn, LocalSpawn, SpawnError};
-use futures_executor::Enter;
+use crate::shutdown::CancellationToken;
+use fumio_utils::current::Current;
+use fumio_utils::enter::Enter;
+use futures_core::stream::Stream;
+use futures_util::future::{FutureObj, LocalFutureObj};
+use futures_util::task::{Spawn, LocalSpawn, SpawnError};
+use std::cell::RefCell;
 use std::future::Future;
 use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+type ShutdownHooks = RefCell<Vec<LocalFutureObj<'static, ()>>>;
+type ExtraReactors = Rc<Vec<(String, crate::reactor::Handle)>>;
+
+thread_local! {
+	static CURRENT: Current<Handle> = const { Current::new() };
+}
+
+/// Retrieve the current runtime handle.
+pub fn current() -> Option<Handle> {
+	#[allow(clippy::redundant_closure_for_method_calls)] // sadly the suggestion doesn't compile
+	Current::with(&CURRENT, |h| h.cloned())
+}
 
 /// Runtime
 ///
@@ -12,19 +49,40 @@ use std::io;
 /// - [`fumio::reactor::current()`](reactor/fn.current.html), also automatically used by
 ///   [`fumio::reactor::LazyHandle`](reactor/struct.LazyHandle.html)
 /// - [`fumio::pool::current_local()`](fumio/pool/fn.current_local.html)
-/// - [`tokio_timer::timer::TimerHandle::current()`](https://docs.rs/tokio-timer/0.3.0-alpha.2/tokio_timer/timer/struct.Handle.html#method.current)
+/// - [`tokio_timer::timer::TimerHandle::current()`](https://docs.rs/tokio-timer/0.3.0-alpha.5/tokio_timer/timer/struct.Handle.html#method.current)
+/// - [`fumio::current()`](fn.current.html) and [`fumio::shutdown::current()`](shutdown/fn.current.html)
+///
+/// Dropping the runtime cancels the [`shutdown_token`](#method.shutdown_token) and drops all
+/// remaining tasks and shutdown hooks (on the dropping thread) while the reactor is still alive,
+/// so their IO gets deregistered; panics while dropping them are swallowed.  Use one of the
+/// `shutdown` methods for a graceful shutdown.
 #[derive(Debug)]
 pub struct Runtime {
 	timer_reactor: TimerReactor,
 	local_pool: LocalPool,
+	shutdown: CancellationToken,
+	shutdown_hooks: Rc<ShutdownHooks>,
+	extra_reactors: ExtraReactors,
 }
 
 impl Runtime {
-	/// Create new runtime
+	/// Builder to create a runtime with custom configuration
+	pub fn builder() -> Builder {
+		Builder::new()
+	}
+
+	/// Create new runtime (with default configuration)
+	///
+	/// # Errors
+	///
+	/// Fails if the reactor can't be created.
 	pub fn new() -> io::Result<Self> {
 		Ok(Self {
 			timer_reactor: TimerReactor::new()?,
 			local_pool: LocalPool::new(),
+			shutdown: CancellationToken::new(),
+			shutdown_hooks: Rc::default(),
+			extra_reactors: Rc::default(),
 		})
 	}
 
@@ -34,9 +92,50 @@ impl Runtime {
 			reactor_handle: self.timer_reactor.reactor_handle(),
 			timer_handle: self.timer_reactor.timer_handle(),
 			local_spawner: self.local_pool.spawner(),
+			shutdown: self.shutdown.clone(),
+			shutdown_hooks: Rc::downgrade(&self.shutdown_hooks),
+			extra_reactors: self.extra_reactors.clone(),
 		}
 	}
 
+	/// Handle to an extra reactor added with
+	/// [`Builder::extra_reactor`](struct.Builder.html#method.extra_reactor)
+	pub fn extra_reactor(&self, name: &str) -> Option<crate::reactor::Handle> {
+		find_extra_reactor(&self.extra_reactors, name)
+	}
+
+	// called by the builder after adding extra reactors
+	fn update_extra_reactors(&mut self) {
+		self.extra_reactors = Rc::new(self.timer_reactor.extra_reactor_handles());
+	}
+
+	/// Token cancelled when the runtime is shut down
+	pub fn shutdown_token(&self) -> CancellationToken {
+		self.shutdown.clone()
+	}
+
+	/// Snapshot of the runtime's counters
+	pub fn metrics(&self) -> Metrics {
+		Metrics {
+			pool: self.local_pool.metrics(),
+			reactor: self.timer_reactor.reactor_metrics(),
+		}
+	}
+
+	/// Register future to run when the runtime is shut down
+	///
+	/// See [`Handle::on_shutdown`](struct.Handle.html#method.on_shutdown).
+	pub fn on_shutdown<F>(&self, future: F)
+	where
+		F: Future<Output=()> + 'static,
+	{
+		self.shutdown_hooks.borrow_mut().push(Box::pin(future).into());
+	}
+
+	fn take_shutdown_hooks(&self) -> Vec<LocalFutureObj<'static, ()>> {
+		std::mem::take(&mut *self.shutdown_hooks.borrow_mut())
+	}
+
 	fn enter<F, T>(&mut self, enter: &mut Enter, f: F) -> T
 	where
 		F: FnOnce(&mut Self, &mut Enter) -> T,
@@ -46,22 +145,105 @@ impl Runtime {
 			let _scoped_timer = tokio_timer::timer::set_default(&timer_handle);
 
 			self.local_pool.spawner().enter(enter, move |enter| {
-				f(self, enter)
+				let handle = self.handle();
+				tokio_executor::with_default(&mut handle.clone(), move || {
+					Current::enter(&CURRENT, enter, handle, move |enter| {
+						f(self, enter)
+					})
+				})
+			})
+		})
+	}
+
+	fn enter_nonblocking<F, T>(&mut self, f: F) -> T
+	where
+		F: FnOnce(&mut Self) -> T,
+	{
+		self.timer_reactor.reactor_handle().enter_nonblocking(move || {
+			let timer_handle = self.timer_reactor.timer_handle();
+			let _scoped_timer = tokio_timer::timer::set_default(&timer_handle);
+
+			self.local_pool.spawner().enter_nonblocking(move || {
+				let handle = self.handle();
+				tokio_executor::with_default(&mut handle.clone(), move || {
+					Current::enter_nonblocking(&CURRENT, handle, move || {
+						f(self)
+					})
+				})
 			})
 		})
 	}
 
 	/// Spawn future on runtime
-	pub fn spawn<F>(&self, future: F)
+	///
+	/// # Errors
+	///
+	/// Fails like [`Handle::spawn`](struct.Handle.html#method.spawn) if the pool doesn't accept
+	/// new tasks; as the runtime owns its pool this doesn't happen currently.
+	pub fn spawn<F>(&self, future: F) -> Result<(), SpawnError>
+	where
+		F: Future<Output=()> + 'static,
+	{
+		self.spawn_local_obj(Box::pin(future).into())
+	}
+
+	/// Spawn future on runtime with the given priority
+	///
+	/// See [`pool::Priority`](pool/enum.Priority.html).
+	///
+	/// # Errors
+	///
+	/// See [`spawn`](#method.spawn).
+	pub fn spawn_with_priority<F>(&self, priority: crate::pool::Priority, future: F) -> Result<(), SpawnError>
 	where
 		F: Future<Output=()> + 'static,
 	{
-		self.local_pool.spawn(Box::pin(future).into())
+		self.local_pool.spawn_with_priority(priority, Box::pin(future).into());
+		Ok(())
+	}
+
+	/// Spawn named future on runtime
+	///
+	/// The name is shown in [`LocalPool::tasks`](pool/struct.LocalPool.html#method.tasks) (and
+	/// task dumps).
+	///
+	/// # Errors
+	///
+	/// See [`spawn`](#method.spawn).
+	pub fn spawn_named<N, F>(&self, name: N, future: F) -> Result<(), SpawnError>
+	where
+		N: Into<String>,
+		F: Future<Output=()> + 'static,
+	{
+		self.local_pool.spawn_named(name, Box::pin(future).into());
+		Ok(())
+	}
+
+	/// Dump all tasks (id, name, priority, state and time since the last poll) to stderr on
+	/// `SIGUSR1` (unix only, requires the `task-dump` feature).
+	///
+	/// The signal wakes up the reactor, which writes the dump; no task is spawned for it, so it
+	/// neither keeps [`run`](#method.run) from returning nor hides deadlocks.  The signal handler
+	/// is removed when the runtime is dropped.
+	///
+	/// # Errors
+	///
+	/// Fails if the signal handler can't be registered.
+	#[cfg(all(unix, feature = "task-dump"))]
+	pub fn enable_task_dump(&mut self) -> io::Result<()> {
+		let task_dump = self::task_dump::TaskDump::new(self.local_pool.spawner(), fumio_utils::park::Park::waker(&self.timer_reactor))?;
+		self.timer_reactor.set_task_dump(task_dump);
+		Ok(())
 	}
 
 	/// Spawn future object on runtime
-	pub fn spawn_local_obj(&self, future: LocalFutureObj<'static, ()>) {
-		self.local_pool.spawn(future)
+	///
+	/// # Errors
+	///
+	/// See [`spawn`](#method.spawn).
+	pub fn spawn_local_obj(&self, future: LocalFutureObj<'static, ()>) -> Result<(), SpawnError> {
+		self.local_pool.spawn(future);
+		Ok(())
 	}
 
 	/// Runs all the tasks in the pool until the given future completes.
@@ -95,11 +277,15 @@ impl Runtime {
 	/// be inert after the call completes, but can continue with further use of
 	/// one of the pool's run or poll methods. While the function is running,
 	/// however, all tasks in the pool will try to make progress.
+	///
+	/// # Panics
+	///
+	/// Panics if the thread already runs an executor (see [`fumio::enter`](fn.enter.html)).
 	pub fn run_until<F, T>(&mut self, future: F) -> T
 	where
 		F: Future<Output = T>,
 	{
-		let mut enter = futures_executor::enter().unwrap();
+		let mut enter = fumio_utils::enter::enter().unwrap();
 		self.enter_run_until(&mut enter, future)
 	}
 
@@ -109,23 +295,180 @@ impl Runtime {
 	/// completed, including any spawned while running existing tasks.
 	pub fn enter_run(&mut self, enter: &mut Enter) {
 		self.enter(enter, |this, enter| {
-			this.local_pool.run(&mut this.timer_reactor, enter)
-		})
+			this.local_pool.run(&mut this.timer_reactor, enter);
+		});
 	}
 
 	/// Run all tasks in the pool to completion.
 	///
 	/// The function will block the calling thread until *all* tasks in the pool
 	/// completed, including any spawned while running existing tasks.
+	///
+	/// # Panics
+	///
+	/// Panics if the thread already runs an executor (see [`fumio::enter`](fn.enter.html)).
 	pub fn run<F, T>(&mut self) {
-		let mut enter = futures_executor::enter().unwrap();
-		self.enter_run(&mut enter)
+		let mut enter = fumio_utils::enter::enter().unwrap();
+		self.enter_run(&mut enter);
+	}
+
+	/// Run tasks (and the reactor) only until `budget` is spent, then return control to the
+	/// caller (e.g. a game loop rendering frames).
+	///
+	/// See [`LocalPool::run_for`](pool/struct.LocalPool.html#method.run_for); returns whether all
+	/// tasks completed.
+	pub fn enter_run_frame(&mut self, enter: &mut Enter, budget: Duration) -> bool {
+		self.enter(enter, |this, enter| {
+			this.local_pool.run_for(&mut this.timer_reactor, enter, budget)
+		})
+	}
+
+	/// Run tasks (and the reactor) only until `budget` is spent, then return control to the
+	/// caller (e.g. a game loop rendering frames).
+	///
+	/// See [`LocalPool::run_for`](pool/struct.LocalPool.html#method.run_for); returns whether all
+	/// tasks completed.
+	///
+	/// # Panics
+	///
+	/// Panics if the thread already runs an executor (see [`fumio::enter`](fn.enter.html)).
+	pub fn run_frame(&mut self, budget: Duration) -> bool {
+		let mut enter = fumio_utils::enter::enter().unwrap();
+		self.enter_run_frame(&mut enter, budget)
+	}
+
+	/// Run all registered shutdown hooks to completion.
+	///
+	/// Other tasks in the pool make progress while the hooks are running; hooks registered while
+	/// running hooks are run too.
+	pub fn enter_run_shutdown_hooks(&mut self, enter: &mut Enter) {
+		loop {
+			let hooks = self.take_shutdown_hooks();
+			if hooks.is_empty() {
+				return;
+			}
+			self.enter_run_until(enter, futures_util::future::join_all(hooks));
+		}
+	}
+
+	/// Run all registered shutdown hooks to completion.
+	///
+	/// Other tasks in the pool make progress while the hooks are running; hooks registered while
+	/// running hooks are run too.
+	///
+	/// # Panics
+	///
+	/// Panics if the thread already runs an executor (see [`fumio::enter`](fn.enter.html)).
+	pub fn run_shutdown_hooks(&mut self) {
+		let mut enter = fumio_utils::enter::enter().unwrap();
+		self.enter_run_shutdown_hooks(&mut enter);
+	}
+
+	/// Cancel the [`shutdown_token`](#method.shutdown_token), run all tasks to completion, and
+	/// then run the shutdown hooks.
+	///
+	/// Tasks are expected to watch the token (or a child token) and finish their work gracefully.
+	pub fn enter_shutdown(&mut self, enter: &mut Enter) {
+		self.shutdown.cancel();
+		self.enter_run(enter);
+		self.enter_run_shutdown_hooks(enter);
+	}
+
+	/// Cancel the [`shutdown_token`](#method.shutdown_token), run all tasks to completion, and
+	/// then run the shutdown hooks.
+	///
+	/// Tasks are expected to watch the token (or a child token) and finish their work gracefully.
+	///
+	/// # Panics
+	///
+	/// Panics if the thread already runs an executor (see [`fumio::enter`](fn.enter.html)).
+	pub fn shutdown(&mut self) {
+		let mut enter = fumio_utils::enter::enter().unwrap();
+		self.enter_shutdown(&mut enter);
+	}
+
+	/// Cancel the [`shutdown_token`](#method.shutdown_token) and run all tasks until they
+	/// complete or `timeout` passed, then run the shutdown hooks (within the same `timeout`).
+	///
+	/// Tasks and hooks still alive after `timeout` are dropped (which also deregisters their IO).
+	/// Returns whether all tasks and hooks completed gracefully.
+	pub fn enter_shutdown_timeout(&mut self, enter: &mut Enter, timeout: Duration) -> bool {
+		let deadline = Instant::now() + timeout;
+		self.shutdown.cancel();
+		self.enter(enter, |this, enter| {
+			let mut completed = this.local_pool.run_timeout(&mut this.timer_reactor, enter, timeout);
+			// drop remaining futures while still entered
+			this.local_pool.clear();
+			loop {
+				let hooks = this.take_shutdown_hooks();
+				if hooks.is_empty() {
+					return completed;
+				}
+				for hook in hooks {
+					this.local_pool.spawn(hook);
+				}
+				let remaining = deadline.saturating_duration_since(Instant::now());
+				completed &= this.local_pool.run_timeout(&mut this.timer_reactor, enter, remaining);
+				this.local_pool.clear();
+			}
+		})
+	}
+
+	/// Cancel the [`shutdown_token`](#method.shutdown_token) and run all tasks until they
+	/// complete or `timeout` passed, then run the shutdown hooks (within the same `timeout`).
+	///
+	/// Tasks and hooks still alive after `timeout` are dropped (which also deregisters their IO).
+	/// Returns whether all tasks and hooks completed gracefully.
+	///
+	/// # Panics
+	///
+	/// Panics if the thread already runs an executor (see [`fumio::enter`](fn.enter.html)).
+	pub fn shutdown_timeout(&mut self, timeout: Duration) -> bool {
+		let mut enter = fumio_utils::enter::enter().unwrap();
+		self.enter_shutdown_timeout(&mut enter, timeout)
+	}
+
+	/// Drive the runtime from within another executor on the same thread.
+	///
+	/// Each poll of the returned stream fires expired timers, dispatches pending IO events
+	/// (without blocking) and polls all pending tasks at most once, and then yields an item; the
+	/// stream ends when all tasks in the pool completed.
+	///
+	/// If no task was pending the stream returns `Pending` instead; the polling task is woken
+	/// by IO events, the next timer and tasks woken from other threads.  As the reactor can't
+	/// block inside another executor, a helper thread waits for IO events and timers (unix only;
+	/// elsewhere the stream wakes itself right away, i.e. it keeps the executor busy).
+	pub fn drive(&mut self) -> Drive<'_> {
+		Drive {
+			runtime: self,
+			#[cfg(unix)]
+			wakeup: None,
+		}
+	}
+}
+
+impl Drop for Runtime {
+	fn drop(&mut self) {
+		self.shutdown.cancel();
+		// dropping a future might panic; never panic in drop (might abort while unwinding)
+		while panic::catch_unwind(AssertUnwindSafe(|| self.local_pool.clear())).is_err() {}
+		loop {
+			let hooks = self.take_shutdown_hooks();
+			if hooks.is_empty() {
+				break;
+			}
+			for hook in hooks {
+				let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(hook)));
+			}
+		}
+		// dropping the hooks might have spawned tasks
+		while panic::catch_unwind(AssertUnwindSafe(|| self.local_pool.clear())).is_err() {}
 	}
 }
 
 impl Spawn for Runtime {
 	fn spawn_obj(
-		&mut self,
+		&self,
 		future: FutureObj<'static, ()>,
 	) -> Result<(), SpawnError> {
 		self.spawn_local_obj(future.into())
@@ -138,7 +481,7 @@ impl Spawn for Runtime {
 
 impl LocalSpawn for Runtime {
 	fn spawn_local_obj(
-		&mut self,
+		&self,
 		future: LocalFutureObj<'static, ()>,
 	) -> Result<(), SpawnError> {
 		self.local_pool.spawn_local_obj(future)
@@ -149,18 +492,87 @@ impl LocalSpawn for Runtime {
 	}
 }
 
+/// Stream driving a [`Runtime`](struct.Runtime.html) one step per poll.
+///
+/// See [`Runtime::drive`](struct.Runtime.html#method.drive).
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct Drive<'a> {
+	runtime: &'a mut Runtime,
+	// started when the runtime gets idle the first time
+	#[cfg(unix)]
+	wakeup: Option<self::drive_wakeup::DriveWakeup>,
+}
+
+impl Drive<'_> {
+	// wake `cx` when the idle runtime has something to do after `next_timer`
+	#[cfg(unix)]
+	fn wake_on_event(&mut self, cx: &Context<'_>, next_timer: Option<Duration>) {
+		if self.wakeup.is_none() {
+			if let Ok(wakeup) = self::drive_wakeup::DriveWakeup::new(self.runtime.timer_reactor.reactor_fd()) {
+				self.wakeup = Some(wakeup);
+			} else {
+				// no helper thread: poll again right away
+				cx.waker().wake_by_ref();
+				return;
+			}
+		}
+		self.wakeup.as_ref().unwrap().wake_on_event(cx.waker(), next_timer);
+	}
+
+	#[cfg(not(unix))]
+	fn wake_on_event(&mut self, cx: &Context<'_>, _next_timer: Option<Duration>) {
+		cx.waker().wake_by_ref();
+	}
+}
+
+impl Stream for Drive<'_> {
+	type Item = ();
+
+	fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let (done, idle, next_timer) = self.runtime.enter_nonblocking(|this| {
+			this.timer_reactor.turn_nonblocking();
+			let polled = !this.local_pool.is_idle();
+			let done = this.local_pool.poll_pool(cx);
+			if done.is_ready() || polled || !this.local_pool.is_idle() {
+				return (done, false, None);
+			}
+			// dispatch events arrived meanwhile, and get the next timer
+			let next_timer = this.timer_reactor.turn_nonblocking();
+			(done, this.local_pool.is_idle(), next_timer)
+		});
+		if done.is_ready() {
+			return Poll::Ready(None);
+		}
+		if !idle || next_timer == Some(Duration::new(0, 0)) {
+			return Poll::Ready(Some(()));
+		}
+		// tasks woken from other threads wake `cx` through the pool
+		self.wake_on_event(cx, next_timer);
+		Poll::Pending
+	}
+}
+
 /// Handle to runtime
 ///
 /// Contains handles for the subsystems.
 #[derive(Clone, Debug)]
+#[allow(clippy::struct_field_names)] // handles of the subsystems
 pub struct Handle {
 	reactor_handle: crate::reactor::Handle,
 	timer_handle: tokio_timer::timer::Handle,
 	local_spawner: LocalSpawner,
+	shutdown: CancellationToken,
+	shutdown_hooks: Weak<ShutdownHooks>,
+	extra_reactors: ExtraReactors,
 }
 
 impl Handle {
-	/// Set thread-local "current" handles for reactor, timer and spawner while executing `f`.
+	/// Set thread-local "current" handles for reactor, timer, spawner and the runtime itself while
+	/// executing `f`.
+	///
+	/// The runtime is also set as default `tokio_executor` executor, so `tokio_executor::spawn`
+	/// spawns onto the local pool.
 	pub fn enter<F, T>(&self, enter: &mut Enter, f: F) -> T
 	where
 		F: FnOnce(&mut Enter) -> T,
@@ -169,30 +581,130 @@ impl Handle {
 			let _scoped_timer = tokio_timer::timer::set_default(&self.timer_handle);
 
 			self.local_spawner.clone().enter(enter, move |enter| {
-				f(enter)
+				tokio_executor::with_default(&mut self.clone(), move || {
+					Current::enter(&CURRENT, enter, self.clone(), f)
+				})
 			})
 		})
 	}
 
+	/// Whether both handles refer to the same runtime (same reactor and pool)
+	///
+	/// Compare [`reactor()`](#method.reactor) with the handle of an IO object (using
+	/// [`same_reactor`](reactor/struct.Handle.html#method.same_reactor)) to check it belongs to
+	/// this runtime.
+	pub fn ptr_eq(&self, other: &Self) -> bool {
+		self.reactor_handle.same_reactor(&other.reactor_handle)
+			&& self.local_spawner.same_pool(&other.local_spawner)
+	}
+
 	/// Retrieve handle to reactor
 	pub fn reactor(&self) -> crate::reactor::Handle {
 		self.reactor_handle.clone()
 	}
 
+	/// Retrieve handle to an extra reactor added with
+	/// [`Builder::extra_reactor`](struct.Builder.html#method.extra_reactor)
+	///
+	/// Pass it (as `LazyHandle`) when creating sockets to register them with that reactor.
+	pub fn extra_reactor(&self, name: &str) -> Option<crate::reactor::Handle> {
+		find_extra_reactor(&self.extra_reactors, name)
+	}
+
 	/// Retrieve handle to timer
+	///
+	/// This is a type of the pre-release `tokio-timer` 0.3.0-alpha.5 and will change when the
+	/// timer gets replaced.
 	pub fn timer(&self) -> tokio_timer::timer::Handle {
 		self.timer_handle.clone()
 	}
 
+	/// Spawn future on the runtime
+	///
+	/// # Errors
+	///
+	/// Fails if the runtime is gone.
+	pub fn spawn<F>(&self, future: F) -> Result<(), SpawnError>
+	where
+		F: Future<Output=()> + 'static,
+	{
+		self.local_spawner.spawn(future)
+	}
+
+	/// Spawn future on the runtime with the given priority
+	///
+	/// See [`pool::Priority`](pool/enum.Priority.html).
+	///
+	/// # Errors
+	///
+	/// Fails if the runtime is gone.
+	pub fn spawn_with_priority<F>(&self, priority: crate::pool::Priority, future: F) -> Result<(), SpawnError>
+	where
+		F: Future<Output=()> + 'static,
+	{
+		self.local_spawner.spawn_with_priority(priority, future)
+	}
+
+	/// Spawn named future on the runtime
+	///
+	/// See [`Runtime::spawn_named`](struct.Runtime.html#method.spawn_named).
+	///
+	/// # Errors
+	///
+	/// Fails if the runtime is gone.
+	pub fn spawn_named<N, F>(&self, name: N, future: F) -> Result<(), SpawnError>
+	where
+		N: Into<String>,
+		F: Future<Output=()> + 'static,
+	{
+		self.local_spawner.spawn_named(name, future)
+	}
+
 	/// Retrieve handle to spawner
 	pub fn spawner(&self) -> LocalSpawner {
 		self.local_spawner.clone()
 	}
+
+	/// Retrieve token cancelled when the runtime is shut down
+	pub fn shutdown_token(&self) -> CancellationToken {
+		self.shutdown.clone()
+	}
+
+	/// Snapshot of the runtime's counters; `None` if the runtime is gone.
+	pub fn metrics(&self) -> Option<Metrics> {
+		Some(Metrics {
+			pool: self.local_spawner.metrics()?,
+			reactor: self.reactor_handle.metrics()?,
+		})
+	}
+
+	/// Register future to run when the runtime is shut down
+	///
+	/// Shutdown hooks run after the main future of [`fumio::run`](fn.run.html) completed, or
+	/// after all tasks completed in [`Runtime::shutdown`](struct.Runtime.html#method.shutdown);
+	/// in any case before the reactor is dropped, so they can still use IO (e.g. to flush
+	/// connection pools and caches).
+	///
+	/// # Errors
+	///
+	/// Fails if the runtime is already gone.
+	pub fn on_shutdown<F>(&self, future: F) -> Result<(), SpawnError>
+	where
+		F: Future<Output=()> + 'static,
+	{
+		let hooks = self.shutdown_hooks.upgrade().ok_or_else(SpawnError::shutdown)?;
+		hooks.borrow_mut().push(Box::pin(future).into());
+		Ok(())
+	}
+}
+
+fn find_extra_reactor(extra_reactors: &[(String, crate::reactor::Handle)], name: &str) -> Option<crate::reactor::Handle> {
+	extra_reactors.iter().find(|(n, _)| n == name).map(|(_, handle)| handle.clone())
 }
 
 impl Spawn for Handle {
 	fn spawn_obj(
-		&mut self,
+		&self,
 		future: FutureObj<'static, ()>,
 	) -> Result<(), SpawnError> {
 		self.spawn_local_obj(future.into())
@@ -203,9 +715,40 @@ impl Spawn for Handle {
 	}
 }
 
+// tokio's `SpawnError` can't carry the reason; the pool only fails when it is gone
+fn tokio_spawn_error(_: SpawnError) -> tokio_executor::SpawnError {
+	tokio_executor::SpawnError::shutdown()
+}
+
+impl tokio_executor::Executor for Handle {
+	fn spawn(
+		&mut self,
+		future: Pin<Box<dyn Future<Output = ()> + Send>>,
+	) -> Result<(), tokio_executor::SpawnError> {
+		self.local_spawner.spawn(future).map_err(tokio_spawn_error)
+	}
+
+	fn status(&self) -> Result<(), tokio_executor::SpawnError> {
+		self.local_spawner.status_local().map_err(tokio_spawn_error)
+	}
+}
+
+impl<T> tokio_executor::TypedExecutor<T> for Handle
+where
+	T: Future<Output = ()> + 'static,
+{
+	fn spawn(&mut self, future: T) -> Result<(), tokio_executor::SpawnError> {
+		self.local_spawner.spawn(future).map_err(tokio_spawn_error)
+	}
+
+	fn status(&self) -> Result<(), tokio_executor::SpawnError> {
+		self.local_spawner.status_local().map_err(tokio_spawn_error)
+	}
+}
+
 impl LocalSpawn for Handle {
 	fn spawn_local_obj(
-		&mut self,
+		&self,
 		future: LocalFutureObj<'static, ()>,
 	) -> Result<(), SpawnError> {
 		self.local_spawner.spawn_local_obj(future)
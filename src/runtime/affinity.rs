@@ -0,0 +1,24 @@
+use std::io;
+
+// pin the current thread to CPU `core`
+#[cfg(target_os = "linux")]
+pub(super) fn pin_current_thread(core: usize) -> io::Result<()> {
+	#[allow(clippy::cast_sign_loss)] // positive constant
+	let max = libc::CPU_SETSIZE as usize;
+	if core >= max {
+		return Err(io::Error::new(io::ErrorKind::InvalidInput, "CPU core id out of range"));
+	}
+	unsafe {
+		let mut set: libc::cpu_set_t = std::mem::zeroed();
+		libc::CPU_SET(core, &mut set);
+		if 0 != libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), std::ptr::addr_of!(set)) {
+			return Err(io::Error::last_os_error());
+		}
+	}
+	Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(super) fn pin_current_thread(_core: usize) -> io::Result<()> {
+	Err(io::Error::other("CPU affinity not supported on this platform"))
+}
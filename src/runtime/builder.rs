@@ -0,0 +1,212 @@
+use crate::pool::{PanicPolicy, TaskId, TaskPanic};
+use crate::runtime::Runtime;
+use std::fmt;
+use std::io;
+use std::rc::Rc;
+use std::time::Duration;
+
+type PanicHook = Rc<dyn Fn(&TaskPanic<'_>)>;
+type DeadlockHook = Rc<dyn Fn(&[TaskId])>;
+
+/// Builds a [`Runtime`](struct.Runtime.html) with custom configuration.
+#[derive(Clone)]
+pub struct Builder {
+	panic_policy: PanicPolicy,
+	panic_hook: Option<PanicHook>,
+	coop_budget: Option<u32>,
+	lifo_slot: bool,
+	max_tasks_per_tick: Option<usize>,
+	shuffle_seed: Option<u64>,
+	deadlock_hook: Option<DeadlockHook>,
+	extra_reactors: Vec<String>,
+	core: Option<usize>,
+	timer_slack: Duration,
+}
+
+impl Default for Builder {
+	fn default() -> Self {
+		Self {
+			panic_policy: PanicPolicy::default(),
+			panic_hook: None,
+			coop_budget: Some(fumio_utils::coop::DEFAULT_BUDGET),
+			lifo_slot: true,
+			max_tasks_per_tick: None,
+			shuffle_seed: None,
+			deadlock_hook: None,
+			extra_reactors: Vec::new(),
+			core: None,
+			timer_slack: Duration::new(0, 0),
+		}
+	}
+}
+
+impl fmt::Debug for Builder {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Builder")
+			.field("panic_policy", &self.panic_policy)
+			.field("panic_hook", &self.panic_hook.as_ref().map(|_| ()))
+			.field("coop_budget", &self.coop_budget)
+			.field("lifo_slot", &self.lifo_slot)
+			.field("max_tasks_per_tick", &self.max_tasks_per_tick)
+			.field("shuffle_seed", &self.shuffle_seed)
+			.field("deadlock_hook", &self.deadlock_hook.as_ref().map(|_| ()))
+			.field("extra_reactors", &self.extra_reactors)
+			.field("core", &self.core)
+			.field("timer_slack", &self.timer_slack)
+			.finish()
+	}
+}
+
+impl Builder {
+	/// New builder with default configuration
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Set how panics in tasks are handled.
+	///
+	/// See [`LocalPool::set_panic_policy`](pool/struct.LocalPool.html#method.set_panic_policy).
+	pub fn panic_policy(&mut self, policy: PanicPolicy) -> &mut Self {
+		self.panic_policy = policy;
+		self
+	}
+
+	/// Set a hook called for each panicking task.
+	///
+	/// See [`LocalPool::set_panic_hook`](pool/struct.LocalPool.html#method.set_panic_hook).
+	pub fn panic_hook<F>(&mut self, hook: F) -> &mut Self
+	where
+		F: Fn(&TaskPanic<'_>) + 'static,
+	{
+		self.panic_hook = Some(Rc::new(hook));
+		self
+	}
+
+	/// Set the cooperative budget for a single poll of a task (`None` disables it).
+	///
+	/// See [`LocalPool::set_coop_budget`](pool/struct.LocalPool.html#method.set_coop_budget).
+	pub fn coop_budget(&mut self, budget: Option<u32>) -> &mut Self {
+		self.coop_budget = budget;
+		self
+	}
+
+	/// Enable or disable the LIFO slot (enabled by default).
+	///
+	/// See [`LocalPool::set_lifo_slot`](pool/struct.LocalPool.html#method.set_lifo_slot).
+	pub fn lifo_slot(&mut self, enabled: bool) -> &mut Self {
+		self.lifo_slot = enabled;
+		self
+	}
+
+	/// Limit how many tasks are polled before the reactor and timers get polled again.
+	///
+	/// See [`LocalPool::set_max_tasks_per_tick`](pool/struct.LocalPool.html#method.set_max_tasks_per_tick).
+	///
+	/// # Panics
+	///
+	/// Panics if `max` is `Some(0)`.
+	pub fn max_tasks_per_tick(&mut self, max: Option<usize>) -> &mut Self {
+		assert_ne!(max, Some(0), "need to poll at least one task per tick");
+		self.max_tasks_per_tick = max;
+		self
+	}
+
+	/// Poll pending tasks in a random order derived from `seed`, e.g. to reproduce bugs depending
+	/// on the order of tasks.
+	///
+	/// See [`LocalPool::set_shuffle_seed`](pool/struct.LocalPool.html#method.set_shuffle_seed).
+	pub fn shuffle_seed(&mut self, seed: Option<u64>) -> &mut Self {
+		self.shuffle_seed = seed;
+		self
+	}
+
+	/// Set a hook called when the runtime is about to block forever: there are no timers, no IO
+	/// registrations and no pending wakeups.
+	///
+	/// The hook gets the ids of the tasks still in the pool (the future passed to `run_until` is
+	/// stuck too, but not listed).  Only wakeups from other threads can make progress then, so
+	/// this is a diagnostic: the hook can log the tasks, or panic to abort.
+	pub fn deadlock_hook<F>(&mut self, hook: F) -> &mut Self
+	where
+		F: Fn(&[TaskId]) + 'static,
+	{
+		self.deadlock_hook = Some(Rc::new(hook));
+		self
+	}
+
+	/// Panic (listing the stuck tasks) when the runtime is about to block forever.
+	///
+	/// See [`deadlock_hook`](#method.deadlock_hook); don't use it if tasks are woken from other
+	/// threads.
+	#[allow(clippy::missing_panics_doc)] // panicking is the point
+	pub fn panic_on_deadlock(&mut self) -> &mut Self {
+		self.deadlock_hook(|tasks| {
+			let tasks: Vec<String> = tasks.iter().map(ToString::to_string).collect();
+			panic!("runtime deadlocked: no timers, IO or wakeups left; stuck tasks: [{}]", tasks.join(", "));
+		})
+	}
+
+	/// Add another reactor with the given `name` (unix only).
+	///
+	/// Sockets are registered with it by passing its handle (see
+	/// [`Handle::extra_reactor`](struct.Handle.html#method.extra_reactor)) as `LazyHandle`, e.g.
+	/// to isolate latency-critical sockets from bulk transfers.  Each turn the default reactor is
+	/// polled first, then the extra reactors in the order they were added; tasks woken by
+	/// earlier reactors run first.
+	pub fn extra_reactor(&mut self, name: &str) -> &mut Self {
+		self.extra_reactors.push(name.to_string());
+		self
+	}
+
+	/// Pin the thread building the runtime (which also has to run it) to CPU `core`.
+	///
+	/// Keeps the caches of the single-threaded loop warm; only supported on linux (`build` fails
+	/// on other platforms).
+	pub fn pin_to_core(&mut self, core: usize) -> &mut Self {
+		self.core = Some(core);
+		self
+	}
+
+	/// Let timers fire up to `slack` late, so timers expiring close together fire in a single
+	/// wakeup (zero, the default, disables it).
+	///
+	/// Wakeups for timers are aligned to multiples of `slack`, e.g. with thousands of idle
+	/// connection timeouts a slack of a few milliseconds saves many reactor turns.  IO events and
+	/// wakeups are not delayed.
+	pub fn timer_slack(&mut self, slack: Duration) -> &mut Self {
+		self.timer_slack = slack;
+		self
+	}
+
+	/// Create runtime
+	///
+	/// # Errors
+	///
+	/// Fails if the runtime (or an extra reactor) can't be created or the thread can't be pinned.
+	pub fn build(&self) -> io::Result<Runtime> {
+		if let Some(core) = self.core {
+			super::affinity::pin_current_thread(core)?;
+		}
+		let mut runtime = Runtime::new()?;
+		for name in &self.extra_reactors {
+			runtime.timer_reactor.add_extra_reactor(name)?;
+		}
+		runtime.update_extra_reactors();
+		runtime.timer_reactor.set_timer_slack(self.timer_slack);
+		runtime.local_pool.set_panic_policy(self.panic_policy);
+		runtime.local_pool.set_coop_budget(self.coop_budget);
+		runtime.local_pool.set_lifo_slot(self.lifo_slot);
+		runtime.local_pool.set_max_tasks_per_tick(self.max_tasks_per_tick);
+		runtime.local_pool.set_shuffle_seed(self.shuffle_seed);
+		if let Some(hook) = self.panic_hook.clone() {
+			runtime.local_pool.set_panic_hook(move |p| hook(p));
+		}
+		if let Some(hook) = self.deadlock_hook.clone() {
+			let spawner = runtime.local_pool.spawner();
+			runtime.timer_reactor.set_stall_hook(move || {
+				hook(&spawner.task_ids().unwrap_or_default());
+			});
+		}
+		Ok(runtime)
+	}
+}
@@ -0,0 +1,125 @@
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::Waker;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+// task to wake once the reactor has events or `deadline` passed
+#[derive(Debug)]
+struct Request {
+	waker: Waker,
+	deadline: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+	request: Option<Request>,
+	closed: bool,
+}
+
+#[derive(Debug, Default)]
+struct Shared {
+	state: Mutex<State>,
+	condvar: Condvar,
+}
+
+// wakes the task polling a `Drive` stream while the runtime is idle: the reactor can't block
+// inside a foreign executor, so a helper thread waits for the reactor fd (and the next timer)
+// instead
+#[derive(Debug)]
+pub(super) struct DriveWakeup {
+	shared: Arc<Shared>,
+	// interrupts the helper thread waiting for an outdated request
+	interrupt: UnixStream,
+	thread: Option<JoinHandle<()>>,
+}
+
+impl DriveWakeup {
+	// `reactor_fd` must stay open until `self` is dropped
+	pub(super) fn new(reactor_fd: RawFd) -> io::Result<Self> {
+		let (interrupt, interrupted) = UnixStream::pair()?;
+		interrupt.set_nonblocking(true)?;
+		interrupted.set_nonblocking(true)?;
+		let shared = Arc::new(Shared::default());
+		let thread_shared = shared.clone();
+		let thread = std::thread::Builder::new()
+			.name("fumio-drive-wakeup".into())
+			.spawn(move || run(&thread_shared, reactor_fd, interrupted))?;
+		Ok(Self {
+			shared,
+			interrupt,
+			thread: Some(thread),
+		})
+	}
+
+	// wake `waker` when the reactor has events or after `timeout` (replaces the previous request)
+	pub(super) fn wake_on_event(&self, waker: &Waker, timeout: Option<Duration>) {
+		let request = Request {
+			waker: waker.clone(),
+			deadline: timeout.map(|timeout| Instant::now() + timeout),
+		};
+		self.shared.state.lock().unwrap().request = Some(request);
+		self.shared.condvar.notify_one();
+		self.interrupt();
+	}
+
+	fn interrupt(&self) {
+		// full: an interrupt is pending anyway
+		let _ = (&self.interrupt).write(b"\x01");
+	}
+}
+
+impl Drop for DriveWakeup {
+	fn drop(&mut self) {
+		self.shared.state.lock().unwrap().closed = true;
+		self.shared.condvar.notify_one();
+		self.interrupt();
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+// milliseconds until `deadline`, rounded up (-1: forever)
+fn poll_timeout(deadline: Option<Instant>) -> libc::c_int {
+	deadline.map_or(-1, |deadline| {
+		let timeout = deadline.saturating_duration_since(Instant::now());
+		let ms = timeout.as_millis() + u128::from(timeout.subsec_nanos() % 1_000_000 != 0);
+		libc::c_int::try_from(ms).unwrap_or(libc::c_int::MAX)
+	})
+}
+
+fn run(shared: &Shared, reactor_fd: RawFd, mut interrupted: UnixStream) {
+	let mut state = shared.state.lock().unwrap();
+	loop {
+		if state.closed {
+			return;
+		}
+		let Some(request) = &state.request else {
+			state = shared.condvar.wait(state).unwrap();
+			continue;
+		};
+		let deadline = request.deadline;
+		drop(state);
+		let mut fds = [
+			libc::pollfd { fd: reactor_fd, events: libc::POLLIN, revents: 0 },
+			libc::pollfd { fd: interrupted.as_raw_fd(), events: libc::POLLIN, revents: 0 },
+		];
+		// errors (EINTR) just repeat the loop
+		let _ = unsafe { libc::poll(fds.as_mut_ptr(), 2, poll_timeout(deadline)) };
+		if 0 != fds[1].revents {
+			let _ = interrupted.read(&mut [0u8; 64]);
+		}
+		state = shared.state.lock().unwrap();
+		let expired = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+		if 0 != fds[0].revents || expired {
+			// if the request was replaced meanwhile the new one gets woken (spuriously)
+			if let Some(request) = state.request.take() {
+				request.waker.wake();
+			}
+		}
+	}
+}
@@ -0,0 +1,11 @@
+use crate::pool::PoolMetrics;
+use crate::reactor::ReactorMetrics;
+
+/// Snapshot of the counters of a [`Runtime`](struct.Runtime.html).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Metrics {
+	/// Counters of the task pool (spawned and completed tasks, wakeups, ...)
+	pub pool: PoolMetrics,
+	/// Counters of the IO reactor (polls, dispatched events, time spent parked)
+	pub reactor: ReactorMetrics,
+}
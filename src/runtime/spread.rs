@@ -0,0 +1,154 @@
+use crate::runtime::Runtime;
+use crate::shutdown::CancellationToken;
+use std::future::Future;
+use std::io;
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+/// Start `threads` threads, each running the future returned by `factory(index)` in its own
+/// [`Runtime`](struct.Runtime.html).
+///
+/// Each runtime's [shutdown token](struct.Runtime.html#method.shutdown_token) is a child of
+/// [`Spread::shutdown_token`](struct.Spread.html#method.shutdown_token), so the futures can wait
+/// for [`fumio::shutdown::current()`](shutdown/fn.current.html) to finish gracefully.  Like
+/// [`fumio::run`](fn.run.html) the shutdown hooks are run after the future completed.
+///
+/// To share a listening port each thread can bind its own listener with
+/// [`TcpListener::bind_reuse_port`](net/struct.TcpListener.html#method.bind_reuse_port); the OS
+/// distributes the connections.
+///
+/// # Errors
+///
+/// Fails (after stopping the other threads) if a thread or its runtime can't be created.
+pub fn spread<F, Fut>(threads: usize, factory: F) -> io::Result<Spread>
+where
+	F: Fn(usize) -> Fut + Send + Sync + 'static,
+	Fut: Future<Output = ()> + 'static,
+{
+	start(vec![None; threads], factory)
+}
+
+/// Like [`spread`](fn.spread.html), but starts one thread per entry in `cores` and pins it to
+/// that CPU (see [`Builder::pin_to_core`](struct.Builder.html#method.pin_to_core)).
+///
+/// # Errors
+///
+/// Fails like [`spread`](fn.spread.html), also if a thread can't be pinned.
+pub fn spread_pinned<F, Fut>(cores: &[usize], factory: F) -> io::Result<Spread>
+where
+	F: Fn(usize) -> Fut + Send + Sync + 'static,
+	Fut: Future<Output = ()> + 'static,
+{
+	start(cores.iter().copied().map(Some).collect(), factory)
+}
+
+fn start<F, Fut>(cores: Vec<Option<usize>>, factory: F) -> io::Result<Spread>
+where
+	F: Fn(usize) -> Fut + Send + Sync + 'static,
+	Fut: Future<Output = ()> + 'static,
+{
+	let threads = cores.len();
+	let factory = Arc::new(factory);
+	let shutdown = CancellationToken::new();
+	let (started_tx, started_rx) = mpsc::channel();
+	let mut spread = Spread {
+		threads: Vec::with_capacity(threads),
+		shutdown,
+	};
+	for (index, core) in cores.into_iter().enumerate() {
+		let factory = factory.clone();
+		let shutdown = spread.shutdown.child_token();
+		let started = started_tx.clone();
+		let thread = thread::Builder::new().name(["fumio-", &index.to_string()].concat()).spawn(move || {
+			let mut builder = Runtime::builder();
+			if let Some(core) = core {
+				builder.pin_to_core(core);
+			}
+			let mut runtime = match builder.build() {
+				Ok(runtime) => runtime,
+				Err(e) => {
+					let _ = started.send(Err(e));
+					return;
+				},
+			};
+			runtime.shutdown = shutdown;
+			let _ = started.send(Ok(()));
+			drop(started);
+			runtime.run_until(factory(index));
+			runtime.run_shutdown_hooks();
+		});
+		match thread {
+			Ok(thread) => spread.threads.push(thread),
+			Err(e) => {
+				let _ = spread.shutdown();
+				return Err(e);
+			},
+		}
+	}
+	drop(started_tx);
+	for _ in 0..threads {
+		// a thread only hangs up without result if it panicked; `join` reports that
+		if let Ok(Err(e)) = started_rx.recv() {
+			let _ = spread.shutdown();
+			return Err(e);
+		}
+	}
+	Ok(spread)
+}
+
+/// Threads started by [`spread`](fn.spread.html).
+///
+/// Dropping it detaches the threads.
+#[derive(Debug)]
+pub struct Spread {
+	threads: Vec<thread::JoinHandle<()>>,
+	shutdown: CancellationToken,
+}
+
+impl Spread {
+	/// Token to shut down all runtimes; parent of each runtime's shutdown token.
+	pub fn shutdown_token(&self) -> CancellationToken {
+		self.shutdown.clone()
+	}
+
+	/// Number of threads
+	pub fn len(&self) -> usize {
+		self.threads.len()
+	}
+
+	/// Whether no threads were started
+	pub fn is_empty(&self) -> bool {
+		self.threads.is_empty()
+	}
+
+	/// Wait for all threads to finish.
+	///
+	/// # Errors
+	///
+	/// Returns the panic of the first thread that panicked (after waiting for all).
+	pub fn join(self) -> thread::Result<()> {
+		let mut result = Ok(());
+		for thread in self.threads {
+			let r = thread.join();
+			if result.is_ok() {
+				result = r;
+			}
+		}
+		result
+	}
+
+	/// Cancel the shutdown token and wait for all threads to finish.
+	///
+	/// # Errors
+	///
+	/// Returns the panic of the first thread that panicked, like [`join`](#method.join).
+	pub fn shutdown(self) -> thread::Result<()> {
+		self.shutdown.cancel();
+		self.join()
+	}
+
+	/// Extract the join handles (detaching them from the shutdown token)
+	pub fn into_join_handles(self) -> Vec<thread::JoinHandle<()>> {
+		self.threads
+	}
+}
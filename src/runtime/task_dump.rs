@@ -0,0 +1,60 @@
+use crate::pool::LocalSpawner;
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::Waker;
+
+// dumps all tasks of the pool after `SIGUSR1`; checked by the timer reactor after parking, so
+// neither a task nor an IO registration keeps the runtime busy
+pub(crate) struct TaskDump {
+	id: signal_hook_registry::SigId,
+	requested: Arc<AtomicBool>,
+	spawner: LocalSpawner,
+}
+
+impl TaskDump {
+	// `waker` wakes the reactor when the signal arrives
+	pub(crate) fn new(spawner: LocalSpawner, waker: Waker) -> io::Result<Self> {
+		let requested = Arc::new(AtomicBool::new(false));
+		let flag = requested.clone();
+		let id = unsafe {
+			signal_hook_registry::register(libc::SIGUSR1, move || {
+				// async-signal-safe: atomics and a write to the reactor's wakeup fd
+				flag.store(true, Ordering::Release);
+				waker.wake_by_ref();
+			})
+		}?;
+		Ok(Self { id, requested, spawner })
+	}
+
+	// write the dump if a signal arrived
+	pub(crate) fn poll(&self) {
+		if self.requested.swap(false, Ordering::Acquire) {
+			self.dump();
+		}
+	}
+
+	fn dump(&self) {
+		let Some(tasks) = self.spawner.tasks() else {
+			return;
+		};
+		let stderr = io::stderr();
+		let mut out = stderr.lock();
+		let _ = writeln!(out, "fumio task dump ({} tasks):", tasks.len());
+		for task in &tasks {
+			let _ = writeln!(out, "  {task}");
+		}
+	}
+}
+
+impl Drop for TaskDump {
+	fn drop(&mut self) {
+		signal_hook_registry::unregister(self.id);
+	}
+}
+
+impl std::fmt::Debug for TaskDump {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("TaskDump")
+	}
+}
@@ -0,0 +1,280 @@
+use super::{Handle, CURRENT};
+use crate::pool::LocalPool;
+use crate::reactor;
+use crate::shutdown::CancellationToken;
+use fumio_utils::current::Current;
+use futures_util::task::ArcWake;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant};
+use tokio_timer::clock::Clock;
+use tokio_timer::Timer;
+
+// source of time only moved by `TestRuntime::advance`
+#[derive(Clone, Debug)]
+struct ManualNow(Arc<Mutex<Instant>>);
+
+impl tokio_timer::clock::Now for ManualNow {
+	fn now(&self) -> Instant {
+		*self.0.lock().unwrap()
+	}
+}
+
+#[derive(Debug)]
+struct Unpark(Waker);
+
+impl tokio_executor::park::Unpark for Unpark {
+	fn unpark(&self) {
+		self.0.wake_by_ref();
+	}
+}
+
+// never blocks: all "parking" only dispatches pending IO events
+#[derive(Debug)]
+struct TestPark {
+	reactor: reactor::Reactor,
+}
+
+impl TestPark {
+	fn poll(&mut self) {
+		if let Err(e) = self.reactor.poll(Some(Duration::new(0, 0))) {
+			panic!("reactor poll failed: {}", e);
+		}
+	}
+}
+
+impl tokio_executor::park::Park for TestPark {
+	type Unpark = Unpark;
+	type Error = std::convert::Infallible;
+
+	fn unpark(&self) -> Self::Unpark {
+		Unpark(self.reactor.waker())
+	}
+
+	fn park(&mut self) -> Result<(), Self::Error> {
+		self.poll();
+		Ok(())
+	}
+
+	fn park_timeout(&mut self, _timeout: Duration) -> Result<(), Self::Error> {
+		self.poll();
+		Ok(())
+	}
+}
+
+#[derive(Debug, Default)]
+struct WakeFlag(AtomicBool);
+
+impl ArcWake for WakeFlag {
+	fn wake_by_ref(arc_self: &Arc<Self>) {
+		arc_self.0.store(true, Ordering::SeqCst);
+	}
+}
+
+/// Runtime for deterministic tests
+///
+/// Uses a real reactor (so IO works), but time only moves forward through
+/// [`advance`](#method.advance), and the runtime never blocks the thread: tasks only run in
+/// [`run_until_stalled`](#method.run_until_stalled) (which also fires expired timers and
+/// dispatches IO events that are already pending).
+///
+/// While running tasks or [`poll_fn`](#method.poll_fn) the same subsystems as for
+/// [`Runtime`](../struct.Runtime.html) are available; `tokio_timer::clock::now()` returns the
+/// manual time.
+#[derive(Debug)]
+pub struct TestRuntime {
+	timer: Timer<TestPark, Clock>,
+	local_pool: LocalPool,
+	now: ManualNow,
+	clock: Clock,
+	shutdown: CancellationToken,
+	woken: Arc<WakeFlag>,
+}
+
+impl TestRuntime {
+	/// Create new test runtime; the manual clock starts at the current (real) time.
+	///
+	/// # Errors
+	///
+	/// Fails if the reactor can't be created.
+	pub fn new() -> io::Result<Self> {
+		let now = ManualNow(Arc::new(Mutex::new(Instant::now())));
+		let clock = Clock::new_with_now(now.clone());
+		let park = TestPark {
+			reactor: reactor::Reactor::new()?,
+		};
+		Ok(Self {
+			timer: Timer::new_with_now(park, clock.clone()),
+			local_pool: LocalPool::new(),
+			now,
+			clock,
+			shutdown: CancellationToken::new(),
+			woken: Arc::default(),
+		})
+	}
+
+	/// Handle to the test runtime
+	///
+	/// Shutdown hooks are not supported ([`Handle::on_shutdown`](../struct.Handle.html#method.on_shutdown)
+	/// fails).
+	pub fn handle(&self) -> Handle {
+		Handle {
+			reactor_handle: self.timer.get_park().reactor.handle(),
+			timer_handle: self.timer.handle(),
+			local_spawner: self.local_pool.spawner(),
+			shutdown: self.shutdown.clone(),
+			shutdown_hooks: Weak::new(),
+			extra_reactors: Rc::default(),
+		}
+	}
+
+	/// Current manual time
+	pub fn now(&self) -> Instant {
+		self.clock.now()
+	}
+
+	/// Move manual time forward by `duration` and run until stalled (firing expired timers).
+	#[allow(clippy::missing_panics_doc)] // only on a poisoned lock
+	pub fn advance(&mut self, duration: Duration) {
+		{
+			let mut now = self.now.0.lock().unwrap();
+			*now += duration;
+		}
+		self.run_until_stalled();
+	}
+
+	/// Fire expired timers and dispatch pending IO events (without blocking or running tasks).
+	pub fn park(&mut self) {
+		if let Err(never) = self.timer.turn(Some(Duration::new(0, 0))) {
+			match never {}
+		}
+	}
+
+	/// Spawn future on the test runtime; it only runs in
+	/// [`run_until_stalled`](#method.run_until_stalled).
+	pub fn spawn<F>(&self, future: F)
+	where
+		F: Future<Output=()> + 'static,
+	{
+		self.local_pool.spawn(Box::pin(future).into());
+	}
+
+	/// Poll pending tasks in a random order derived from `seed`
+	///
+	/// See [`LocalPool::set_shuffle_seed`](../pool/struct.LocalPool.html#method.set_shuffle_seed).
+	pub fn set_shuffle_seed(&mut self, seed: Option<u64>) {
+		self.local_pool.set_shuffle_seed(seed);
+	}
+
+	/// Seed set with [`set_shuffle_seed`](#method.set_shuffle_seed); print it when a test fails
+	/// to reproduce the polling order.
+	pub fn shuffle_seed(&self) -> Option<u64> {
+		self.local_pool.shuffle_seed()
+	}
+
+	/// Number of tasks in the pool
+	pub fn len(&self) -> usize {
+		self.local_pool.len()
+	}
+
+	/// Whether all spawned tasks completed
+	pub fn is_empty(&self) -> bool {
+		self.local_pool.is_empty()
+	}
+
+	/// Run tasks until none is pending, firing expired timers and dispatching IO events that are
+	/// already pending in between.
+	///
+	/// Returns whether all tasks completed.  Doesn't return while a task keeps waking itself.
+	pub fn run_until_stalled(&mut self) -> bool {
+		let waker = futures_util::task::noop_waker();
+		let mut cx = Context::from_waker(&waker);
+		loop {
+			self.entered(|this| {
+				while this.local_pool.try_run_one(&mut cx) {}
+			});
+			self.park();
+			if self.local_pool.is_idle() {
+				return self.local_pool.is_empty();
+			}
+		}
+	}
+
+	/// Poll `future` and run tasks until it completes.
+	///
+	/// # Panics
+	///
+	/// Panics if `future` stalls, i.e. isn't woken after running all tasks until stalled (time
+	/// doesn't move on its own; use [`advance`](#method.advance) for timers).
+	pub fn block_on<F>(&mut self, future: F) -> F::Output
+	where
+		F: Future,
+	{
+		futures_util::pin_mut!(future);
+		loop {
+			if let Poll::Ready(output) = self.poll(future.as_mut()) {
+				return output;
+			}
+			self.run_until_stalled();
+			assert!(self.is_woken(), "TestRuntime::block_on: future stalled");
+		}
+	}
+
+	/// Poll `future` once (in the runtime context) with a waker tracked by
+	/// [`is_woken`](#method.is_woken).
+	pub fn poll<F>(&mut self, future: Pin<&mut F>) -> Poll<F::Output>
+	where
+		F: Future + ?Sized,
+	{
+		let mut future = future;
+		self.poll_fn(move |cx| future.as_mut().poll(cx))
+	}
+
+	/// Call `f` (in the runtime context) with a context whose waker is tracked by
+	/// [`is_woken`](#method.is_woken).
+	///
+	/// Useful to test `poll_*` methods of IO objects, timers and other futures step by step.
+	pub fn poll_fn<F, T>(&mut self, f: F) -> Poll<T>
+	where
+		F: FnOnce(&mut Context<'_>) -> Poll<T>,
+	{
+		self.woken.0.store(false, Ordering::SeqCst);
+		let waker = futures_util::task::waker(self.woken.clone());
+		let mut cx = Context::from_waker(&waker);
+		self.entered(move |_| f(&mut cx))
+	}
+
+	/// Whether the waker passed by the last [`poll_fn`](#method.poll_fn) (or
+	/// [`poll`](#method.poll)) was woken since.
+	pub fn is_woken(&self) -> bool {
+		self.woken.0.load(Ordering::SeqCst)
+	}
+
+	// like `Runtime::enter_nonblocking`, with the manual clock as default clock
+	fn entered<F, T>(&mut self, f: F) -> T
+	where
+		F: FnOnce(&mut Self) -> T,
+	{
+		let handle = self.handle();
+		let clock = self.clock.clone();
+		tokio_timer::clock::with_default(&clock, move || {
+			handle.reactor_handle.clone().enter_nonblocking(move || {
+				let timer_handle = handle.timer();
+				let _scoped_timer = tokio_timer::timer::set_default(&timer_handle);
+
+				handle.local_spawner.clone().enter_nonblocking(move || {
+					tokio_executor::with_default(&mut handle.clone(), move || {
+						Current::enter_nonblocking(&CURRENT, handle, move || {
+							f(self)
+						})
+					})
+				})
+			})
+		})
+	}
+}
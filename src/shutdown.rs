@@ -0,0 +1,131 @@
+//! Graceful shutdown
+//!
+//! Each [`Runtime`](../struct.Runtime.html) owns a root [`CancellationToken`](struct.CancellationToken.html),
+//! which gets cancelled by [`Runtime::shutdown`](../struct.Runtime.html#method.shutdown).  Tasks
+//! can wait for it (or for a child token) to finish their work gracefully, e.g. a server stops
+//! accepting new connections but completes the requests in flight.
+
+use crate::waiters::Waiters;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll};
+
+/// Retrieve the shutdown token of the current runtime.
+pub fn current() -> Option<CancellationToken> {
+	crate::current().map(|h| h.shutdown_token())
+}
+
+#[derive(Debug, Default)]
+struct State {
+	// wakers of pending `Cancelled` futures
+	waiters: Waiters,
+	children: Vec<Weak<Inner>>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+	cancelled: AtomicBool,
+	state: Mutex<State>,
+}
+
+impl Inner {
+	fn cancel(&self) {
+		if self.cancelled.swap(true, Ordering::AcqRel) {
+			return; // already cancelled
+		}
+		// `cancelled` is set, so nothing gets registered anymore
+		let mut state = std::mem::take(&mut *self.state.lock().unwrap());
+		state.waiters.wake_all();
+		for child in state.children {
+			if let Some(child) = child.upgrade() {
+				child.cancel();
+			}
+		}
+	}
+}
+
+/// A token to signal cancellation to (possibly many) tasks.
+///
+/// Clones refer to the same token; cancelling a token also cancels all tokens created by
+/// [`child_token`](#method.child_token) (recursively), but not the other way round.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+	inner: Arc<Inner>,
+}
+
+impl CancellationToken {
+	/// Create a new (not cancelled) token.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Create a new token that gets cancelled when this token is cancelled.
+	///
+	/// If this token is already cancelled the new token is cancelled too.
+	#[must_use]
+	#[allow(clippy::missing_panics_doc)] // only on a poisoned lock
+	pub fn child_token(&self) -> Self {
+		let child = Self::new();
+		let mut state = self.inner.state.lock().unwrap();
+		if self.inner.cancelled.load(Ordering::Acquire) {
+			child.inner.cancelled.store(true, Ordering::Release);
+		} else {
+			state.children.retain(|c| c.strong_count() > 0);
+			state.children.push(Arc::downgrade(&child.inner));
+		}
+		child
+	}
+
+	/// Cancel the token (and all its children) and wake all tasks waiting for it.
+	pub fn cancel(&self) {
+		self.inner.cancel();
+	}
+
+	/// Whether the token was cancelled.
+	pub fn is_cancelled(&self) -> bool {
+		self.inner.cancelled.load(Ordering::Acquire)
+	}
+
+	/// A future completing when the token is cancelled.
+	pub fn cancelled(&self) -> Cancelled {
+		Cancelled {
+			inner: self.inner.clone(),
+			slot: None,
+		}
+	}
+}
+
+/// A future completing when a [`CancellationToken`](struct.CancellationToken.html) is cancelled.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct Cancelled {
+	inner: Arc<Inner>,
+	slot: Option<usize>,
+}
+
+impl Future for Cancelled {
+	type Output = ();
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		if self.inner.cancelled.load(Ordering::Acquire) {
+			return Poll::Ready(());
+		}
+		let inner = self.inner.clone();
+		let mut state = inner.state.lock().unwrap();
+		// check again while locked; `cancel` sets the flag before locking
+		if inner.cancelled.load(Ordering::Acquire) {
+			return Poll::Ready(());
+		}
+		state.waiters.register(&mut self.slot, cx.waker());
+		Poll::Pending
+	}
+}
+
+impl Drop for Cancelled {
+	fn drop(&mut self) {
+		// waiters are gone after cancel
+		self.inner.state.lock().unwrap().waiters.remove(&mut self.slot);
+	}
+}
@@ -0,0 +1,169 @@
+use futures_util::task::SpawnError;
+use std::fmt;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio_timer::{clock, Delay};
+
+/// Restart policy for tasks that should keep running
+///
+/// A supervised task is created by a factory closure; whenever it completes or panics, a new
+/// one is created after a backoff delay.  The delay starts at the initial backoff and doubles
+/// with each restart up to the maximum; it is reset if a task ran for at least the maximum
+/// backoff.
+///
+/// Panics are caught by the supervisor, so the [panic policy](enum.PanicPolicy.html) and hooks
+/// of the pool don't see them.
+///
+/// Restarts need the timer of the current runtime.
+#[derive(Clone, Debug)]
+pub struct Supervisor {
+	initial_backoff: Duration,
+	max_backoff: Duration,
+	max_restarts: Option<u32>,
+}
+
+impl Default for Supervisor {
+	fn default() -> Self {
+		Self {
+			initial_backoff: Duration::from_millis(100),
+			max_backoff: Duration::from_secs(30),
+			max_restarts: None,
+		}
+	}
+}
+
+impl Supervisor {
+	/// New supervisor: backoff from 100ms up to 30s, unlimited restarts
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Set initial and maximum backoff delay before restarting a task
+	///
+	/// # Panics
+	///
+	/// Panics if `initial` is larger than `max`.
+	pub fn backoff(&mut self, initial: Duration, max: Duration) -> &mut Self {
+		assert!(initial <= max, "initial backoff must not exceed maximum backoff");
+		self.initial_backoff = initial;
+		self.max_backoff = max;
+		self
+	}
+
+	/// Limit the total number of restarts (`None` restarts forever)
+	pub fn max_restarts(&mut self, limit: Option<u32>) -> &mut Self {
+		self.max_restarts = limit;
+		self
+	}
+
+	/// Supervise tasks created by `factory`
+	///
+	/// The returned future completes when the restart limit is exceeded.
+	pub fn supervise<F, Fut>(&self, factory: F) -> Supervise<F, Fut>
+	where
+		F: FnMut() -> Fut,
+		Fut: Future<Output = ()>,
+	{
+		Supervise {
+			policy: self.clone(),
+			factory,
+			task: None,
+			started: clock::now(),
+			delay: None,
+			backoff: self.initial_backoff,
+			restarts: 0,
+		}
+	}
+
+	/// Spawn [`supervise`](#method.supervise) in the current local pool
+	///
+	/// # Errors
+	///
+	/// Fails if there is no current pool or it is gone.
+	pub fn spawn<F, Fut>(&self, factory: F) -> Result<(), SpawnError>
+	where
+		F: FnMut() -> Fut + 'static,
+		Fut: Future<Output = ()> + 'static,
+	{
+		fumio_pool::current_local().ok_or_else(SpawnError::shutdown)?.spawn(self.supervise(factory))
+	}
+}
+
+/// Future returned by [`Supervisor::supervise`](struct.Supervisor.html#method.supervise)
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Supervise<F, Fut> {
+	policy: Supervisor,
+	factory: F,
+	task: Option<Pin<Box<Fut>>>,
+	started: Instant,
+	delay: Option<Delay>,
+	backoff: Duration,
+	restarts: u32,
+}
+
+impl<F, Fut> Supervise<F, Fut> {
+	/// Number of restarts so far
+	pub const fn restarts(&self) -> u32 {
+		self.restarts
+	}
+}
+
+impl<F, Fut> fmt::Debug for Supervise<F, Fut> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Supervise")
+			.field("policy", &self.policy)
+			.field("factory", &())
+			.field("task", &self.task.as_ref().map(|_| ()))
+			.field("started", &self.started)
+			.field("delay", &self.delay)
+			.field("backoff", &self.backoff)
+			.field("restarts", &self.restarts)
+			.finish()
+	}
+}
+
+// the task is boxed, nothing else is pinned
+impl<F, Fut> Unpin for Supervise<F, Fut> {}
+
+impl<F, Fut> Future for Supervise<F, Fut>
+where
+	F: FnMut() -> Fut,
+	Fut: Future<Output = ()>,
+{
+	type Output = ();
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+		let this = self.get_mut();
+		loop {
+			if let Some(delay) = &mut this.delay {
+				futures_util::ready!(Pin::new(delay).poll(cx));
+				this.delay = None;
+			}
+			if this.task.is_none() {
+				this.task = Some(Box::pin((this.factory)()));
+				this.started = clock::now();
+			}
+			let task = this.task.as_mut().unwrap();
+			match panic::catch_unwind(AssertUnwindSafe(|| task.as_mut().poll(cx))) {
+				Ok(Poll::Pending) => return Poll::Pending,
+				// a panic counts like completion
+				Ok(Poll::Ready(())) | Err(_) => (),
+			}
+			this.task = None;
+
+			if this.policy.max_restarts.is_some_and(|limit| this.restarts >= limit) {
+				return Poll::Ready(());
+			}
+			this.restarts += 1;
+			let now = clock::now();
+			if now - this.started >= this.policy.max_backoff {
+				this.backoff = this.policy.initial_backoff;
+			}
+			this.delay = Some(tokio_timer::delay(now + this.backoff));
+			this.backoff = std::cmp::min(this.backoff * 2, this.policy.max_backoff);
+		}
+	}
+}
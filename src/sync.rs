@@ -0,0 +1,17 @@
+//! Synchronization primitives for tasks running in the same (local) pool
+//!
+//! None of these are thread-safe; they are cheaper than their thread-safe counterparts as all
+//! tasks of a [`Runtime`](../struct.Runtime.html) run on the same thread.
+
+pub mod broadcast;
+mod lock;
+mod mutex;
+mod notify;
+mod rwlock;
+mod semaphore;
+pub mod watch;
+
+pub use self::mutex::{Mutex, MutexGuard, MutexLock};
+pub use self::notify::{Notified, Notify};
+pub use self::rwlock::{RwLock, RwLockRead, RwLockReadGuard, RwLockWrite, RwLockWriteGuard};
+pub use self::semaphore::{Semaphore, SemaphoreAcquire, SemaphorePermit};
@@ -0,0 +1,234 @@
+//! A bounded channel delivering each value to all receivers.
+//!
+//! Keeps the last `capacity` values; receivers falling further behind miss values (and get
+//! notified about it with [`RecvError::Lagged`](enum.RecvError.html#variant.Lagged)).
+
+use crate::waiters::Waiters;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+/// Create a new channel keeping up to `capacity` values.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+	assert!(capacity > 0, "broadcast channel needs a capacity");
+	let shared = Rc::new(RefCell::new(State {
+		buffer: VecDeque::with_capacity(capacity),
+		first: 0,
+		capacity,
+		senders: 1,
+		receivers: 1,
+		waiters: Waiters::default(),
+	}));
+	let receiver = Receiver {
+		shared: shared.clone(),
+		next: 0,
+		slot: None,
+	};
+	(Sender { shared }, receiver)
+}
+
+#[derive(Debug)]
+struct State<T> {
+	buffer: VecDeque<T>,
+	// sequence number of `buffer[0]`
+	first: u64,
+	capacity: usize,
+	senders: usize,
+	receivers: usize,
+	waiters: Waiters,
+}
+
+impl<T> State<T> {
+	// sequence number of the next value sent
+	fn end(&self) -> u64 {
+		self.first + self.buffer.len() as u64
+	}
+}
+
+/// Error returned by [`Sender::send`](struct.Sender.html#method.send) if all receivers are gone;
+/// contains the value that couldn't be sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Display for SendError<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("broadcast channel closed")
+	}
+}
+
+impl<T: fmt::Debug> Error for SendError<T> {}
+
+/// Error returned by [`Receiver::recv`](struct.Receiver.html#method.recv)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecvError {
+	/// All senders are gone and all values were received
+	Closed,
+	/// The receiver fell behind and missed this many values; receiving again continues with the
+	/// oldest value still available.
+	Lagged(u64),
+}
+
+impl fmt::Display for RecvError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Closed => f.write_str("broadcast channel closed"),
+			Self::Lagged(n) => {
+				f.write_str("broadcast receiver missed ")?;
+				fmt::Display::fmt(n, f)?;
+				f.write_str(" values")
+			},
+		}
+	}
+}
+
+impl Error for RecvError {}
+
+/// Sending half of a broadcast channel
+#[derive(Debug)]
+pub struct Sender<T> {
+	shared: Rc<RefCell<State<T>>>,
+}
+
+impl<T> Sender<T> {
+	/// Send value to all receivers; returns the number of receivers.
+	///
+	/// Drops the oldest value if the channel is full.
+	///
+	/// # Errors
+	///
+	/// Fails if all receivers are gone.
+	pub fn send(&self, value: T) -> Result<usize, SendError<T>> {
+		let mut state = self.shared.borrow_mut();
+		if 0 == state.receivers {
+			return Err(SendError(value));
+		}
+		if state.buffer.len() == state.capacity {
+			state.buffer.pop_front();
+			state.first += 1;
+		}
+		state.buffer.push_back(value);
+		state.waiters.wake_all();
+		Ok(state.receivers)
+	}
+
+	/// Create a new receiver; it only gets values sent after subscribing.
+	pub fn subscribe(&self) -> Receiver<T> {
+		let mut state = self.shared.borrow_mut();
+		state.receivers += 1;
+		Receiver {
+			shared: self.shared.clone(),
+			next: state.end(),
+			slot: None,
+		}
+	}
+
+	/// Number of receivers
+	pub fn receiver_count(&self) -> usize {
+		self.shared.borrow().receivers
+	}
+}
+
+impl<T> Clone for Sender<T> {
+	fn clone(&self) -> Self {
+		self.shared.borrow_mut().senders += 1;
+		Self {
+			shared: self.shared.clone(),
+		}
+	}
+}
+
+impl<T> Drop for Sender<T> {
+	fn drop(&mut self) {
+		let mut state = self.shared.borrow_mut();
+		state.senders -= 1;
+		if 0 == state.senders {
+			state.waiters.wake_all();
+		}
+	}
+}
+
+/// Receiving half of a broadcast channel
+///
+/// Clones continue at the same position.
+#[derive(Debug)]
+pub struct Receiver<T> {
+	shared: Rc<RefCell<State<T>>>,
+	// sequence number of the next value to receive
+	next: u64,
+	slot: Option<usize>,
+}
+
+impl<T: Clone> Receiver<T> {
+	/// Receive the next value.
+	pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<T, RecvError>> {
+		let mut state = self.shared.borrow_mut();
+		if self.next < state.first {
+			let missed = state.first - self.next;
+			self.next = state.first;
+			state.waiters.remove(&mut self.slot);
+			return Poll::Ready(Err(RecvError::Lagged(missed)));
+		}
+		#[allow(clippy::cast_possible_truncation)] // index is less than the buffer length
+		let index = (self.next - state.first) as usize;
+		if let Some(value) = state.buffer.get(index) {
+			let value = value.clone();
+			self.next += 1;
+			state.waiters.remove(&mut self.slot);
+			return Poll::Ready(Ok(value));
+		}
+		if 0 == state.senders {
+			state.waiters.remove(&mut self.slot);
+			return Poll::Ready(Err(RecvError::Closed));
+		}
+		state.waiters.register(&mut self.slot, cx.waker());
+		Poll::Pending
+	}
+
+	/// Receive the next value.
+	pub fn recv(&mut self) -> Recv<'_, T> {
+		Recv { receiver: self }
+	}
+}
+
+impl<T> Clone for Receiver<T> {
+	fn clone(&self) -> Self {
+		self.shared.borrow_mut().receivers += 1;
+		Self {
+			shared: self.shared.clone(),
+			next: self.next,
+			slot: None,
+		}
+	}
+}
+
+impl<T> Drop for Receiver<T> {
+	fn drop(&mut self) {
+		let mut state = self.shared.borrow_mut();
+		state.receivers -= 1;
+		state.waiters.remove(&mut self.slot);
+	}
+}
+
+/// Future returned by [`Receiver::recv`](struct.Receiver.html#method.recv)
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct Recv<'a, T> {
+	receiver: &'a mut Receiver<T>,
+}
+
+impl<T: Clone> Future for Recv<'_, T> {
+	type Output = Result<T, RecvError>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		self.receiver.poll_recv(cx)
+	}
+}
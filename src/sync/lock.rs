@@ -0,0 +1,158 @@
+// fair (FIFO) semaphore shared by `Semaphore`, `Mutex` and `RwLock`
+//
+// Waiting futures are queued on an intrusive list; the nodes live in the (pinned) futures.  A
+// mutex is a semaphore with a single permit; readers of a `RwLock` take one permit, writers all.
+
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomPinned;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+fumio_utils::local_dl_list! {
+	mod waiter_list {
+		link WaiterLink;
+		head WaiterHead;
+		member link of Waiter;
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+	Idle,
+	Queued,
+	// lock was handed over, but the future didn't return it yet
+	Acquired,
+	Done,
+}
+
+// node in the wait queue
+#[derive(Debug)]
+struct Waiter {
+	link: WaiterLink,
+	permits: usize,
+	state: Cell<State>,
+	waker: RefCell<Option<Waker>>,
+}
+
+impl Waiter {
+	fn grant(&self) {
+		self.state.set(State::Acquired);
+		let waker = self.waker.borrow_mut().take();
+		if let Some(waker) = waker {
+			waker.wake();
+		}
+	}
+}
+
+#[derive(Debug)]
+pub(super) struct RawSemaphore {
+	// available permits
+	permits: Cell<usize>,
+	// boxed: the (empty) list head points to itself
+	waiters: Box<WaiterHead>,
+}
+
+impl RawSemaphore {
+	pub(super) fn new(permits: usize) -> Self {
+		Self {
+			permits: Cell::new(permits),
+			waiters: Box::new(WaiterHead::new()),
+		}
+	}
+
+	pub(super) fn available_permits(&self) -> usize {
+		self.permits.get()
+	}
+
+	// doesn't overtake queued waiters
+	pub(super) fn try_acquire(&self, permits: usize) -> bool {
+		if !self.waiters.is_empty() || self.permits.get() < permits {
+			return false;
+		}
+		self.permits.set(self.permits.get() - permits);
+		true
+	}
+
+	pub(super) fn release(&self, permits: usize) {
+		self.permits.set(self.permits.get() + permits);
+		self.grant_waiters();
+	}
+
+	// hand permits to the waiters at the front of the queue
+	fn grant_waiters(&self) {
+		// queued waiters are alive: futures remove themselves from the queue when dropped
+		while let Some(waiter) = unsafe { self.waiters.pop_front() } {
+			let waiter = unsafe { &*waiter };
+			if self.permits.get() < waiter.permits {
+				unsafe { self.waiters.prepend(waiter); }
+				return;
+			}
+			self.permits.set(self.permits.get() - waiter.permits);
+			waiter.grant();
+		}
+	}
+}
+
+// future acquiring permits of a `RawSemaphore`
+#[derive(Debug)]
+pub(super) struct Acquire<'a> {
+	lock: &'a RawSemaphore,
+	waiter: Waiter,
+	_pin: PhantomPinned,
+}
+
+impl<'a> Acquire<'a> {
+	pub(super) fn new(lock: &'a RawSemaphore, permits: usize) -> Self {
+		Self {
+			lock,
+			waiter: Waiter {
+				link: WaiterLink::new(),
+				permits,
+				state: Cell::new(State::Idle),
+				waker: RefCell::new(None),
+			},
+			_pin: PhantomPinned,
+		}
+	}
+
+	// the waiter must not move while queued
+	pub(super) fn poll_acquire(self: Pin<&Self>, cx: &Context<'_>) -> Poll<()> {
+		let this = self.get_ref();
+		match this.waiter.state.get() {
+			State::Idle => {
+				if this.lock.try_acquire(this.waiter.permits) {
+					this.waiter.state.set(State::Done);
+					return Poll::Ready(());
+				}
+				*this.waiter.waker.borrow_mut() = Some(cx.waker().clone());
+				this.waiter.state.set(State::Queued);
+				unsafe { this.lock.waiters.append(&this.waiter); }
+				Poll::Pending
+			},
+			State::Queued => {
+				*this.waiter.waker.borrow_mut() = Some(cx.waker().clone());
+				Poll::Pending
+			},
+			State::Acquired => {
+				this.waiter.state.set(State::Done);
+				Poll::Ready(())
+			},
+			State::Done => panic!("lock future polled after completion"),
+		}
+	}
+}
+
+impl Drop for Acquire<'_> {
+	fn drop(&mut self) {
+		match self.waiter.state.get() {
+			State::Queued => {
+				unsafe { self.waiter.link.unlink(); }
+				// waiters behind it might fit the available permits now
+				self.lock.grant_waiters();
+			},
+			// permits were handed over but never returned; pass them on
+			State::Acquired => self.lock.release(self.waiter.permits),
+			State::Idle | State::Done => (),
+		}
+	}
+}
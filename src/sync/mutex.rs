@@ -0,0 +1,121 @@
+use super::lock::{Acquire, RawSemaphore};
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// An async mutex for tasks on the same thread
+///
+/// Waiting tasks get the lock in FIFO order.
+pub struct Mutex<T: ?Sized> {
+	raw: RawSemaphore,
+	value: UnsafeCell<T>,
+}
+
+impl<T> Mutex<T> {
+	/// Create a new (unlocked) mutex
+	pub fn new(value: T) -> Self {
+		Self {
+			raw: RawSemaphore::new(1),
+			value: UnsafeCell::new(value),
+		}
+	}
+
+	/// Extract the value
+	pub fn into_inner(self) -> T {
+		self.value.into_inner()
+	}
+}
+
+impl<T: ?Sized> Mutex<T> {
+	/// Wait for the lock
+	pub fn lock(&self) -> MutexLock<'_, T> {
+		MutexLock {
+			mutex: self,
+			acquire: Acquire::new(&self.raw, 1),
+		}
+	}
+
+	/// Take the lock if nobody holds or waits for it
+	pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+		if self.raw.try_acquire(1) {
+			Some(MutexGuard { mutex: self })
+		} else {
+			None
+		}
+	}
+
+	/// Access the value without locking (it can't be locked while borrowed mutably)
+	pub fn get_mut(&mut self) -> &mut T {
+		unsafe { &mut *self.value.get() }
+	}
+}
+
+impl<T: Default> Default for Mutex<T> {
+	fn default() -> Self {
+		Self::new(T::default())
+	}
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Mutex<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.try_lock() {
+			Some(guard) => f.debug_struct("Mutex").field("value", &&*guard).finish(),
+			None => f.debug_struct("Mutex").field("value", &"<locked>").finish(),
+		}
+	}
+}
+
+/// Future returned by [`Mutex::lock`](struct.Mutex.html#method.lock)
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct MutexLock<'a, T: ?Sized> {
+	mutex: &'a Mutex<T>,
+	acquire: Acquire<'a>,
+}
+
+impl<'a, T: ?Sized> Future for MutexLock<'a, T> {
+	type Output = MutexGuard<'a, T>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let mutex = self.mutex;
+		// structural pinning of `acquire`
+		let acquire = unsafe { self.map_unchecked_mut(|this| &mut this.acquire) };
+		futures_util::ready!(acquire.into_ref().poll_acquire(cx));
+		Poll::Ready(MutexGuard { mutex })
+	}
+}
+
+/// Access to the value of a locked [`Mutex`](struct.Mutex.html); unlocks when dropped.
+#[must_use = "if unused the Mutex will immediately unlock"]
+pub struct MutexGuard<'a, T: ?Sized> {
+	mutex: &'a Mutex<T>,
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &T {
+		unsafe { &*self.mutex.value.get() }
+	}
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+	fn deref_mut(&mut self) -> &mut T {
+		unsafe { &mut *self.mutex.value.get() }
+	}
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MutexGuard<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&**self, f)
+	}
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+	fn drop(&mut self) {
+		self.mutex.raw.release(1);
+	}
+}
@@ -0,0 +1,154 @@
+use std::cell::{Cell, RefCell};
+use std::future::Future;
+use std::marker::PhantomPinned;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+fumio_utils::local_dl_list! {
+	mod waiter_list {
+		link WaiterLink;
+		head WaiterHead;
+		member link of Waiter;
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+	Idle,
+	Queued,
+	// removed from the queue by `notify_one`
+	NotifiedOne,
+	// removed from the queue by `notify_waiters`
+	NotifiedAll,
+	Done,
+}
+
+// node in the wait queue
+#[derive(Debug)]
+struct Waiter {
+	link: WaiterLink,
+	state: Cell<State>,
+	waker: RefCell<Option<Waker>>,
+}
+
+impl Waiter {
+	fn notify(&self, state: State) {
+		self.state.set(state);
+		let waker = self.waker.borrow_mut().take();
+		if let Some(waker) = waker {
+			waker.wake();
+		}
+	}
+}
+
+/// Notifies tasks waiting on the same thread
+///
+/// Like a condition variable: tasks wait for [`notified`](#method.notified), others wake them
+/// with [`notify_one`](#method.notify_one) or [`notify_waiters`](#method.notify_waiters).
+#[derive(Debug)]
+pub struct Notify {
+	// stored `notify_one` without waiter
+	permit: Cell<bool>,
+	// boxed: the (empty) list head points to itself
+	waiters: Box<WaiterHead>,
+}
+
+impl Notify {
+	/// Create a new `Notify` without stored notification
+	pub fn new() -> Self {
+		Self {
+			permit: Cell::new(false),
+			waiters: Box::new(WaiterHead::new()),
+		}
+	}
+
+	/// Wake the longest waiting task; if no task is waiting the notification is stored and
+	/// completes the next `notified` future immediately.
+	///
+	/// Multiple stored notifications are merged into one.
+	pub fn notify_one(&self) {
+		// queued waiters are alive: futures remove themselves from the queue when dropped
+		match unsafe { self.waiters.pop_front() } {
+			Some(waiter) => unsafe { &*waiter }.notify(State::NotifiedOne),
+			None => self.permit.set(true),
+		}
+	}
+
+	/// Wake all waiting tasks (doesn't store a notification)
+	///
+	/// Only futures that were polled before (i.e. are waiting) get notified.
+	pub fn notify_waiters(&self) {
+		while let Some(waiter) = unsafe { self.waiters.pop_front() } {
+			unsafe { &*waiter }.notify(State::NotifiedAll);
+		}
+	}
+
+	/// Wait for a notification
+	pub fn notified(&self) -> Notified<'_> {
+		Notified {
+			notify: self,
+			waiter: Waiter {
+				link: WaiterLink::new(),
+				state: Cell::new(State::Idle),
+				waker: RefCell::new(None),
+			},
+			_pin: PhantomPinned,
+		}
+	}
+}
+
+impl Default for Notify {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Future returned by [`Notify::notified`](struct.Notify.html#method.notified)
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct Notified<'a> {
+	notify: &'a Notify,
+	waiter: Waiter,
+	_pin: PhantomPinned,
+}
+
+impl Future for Notified<'_> {
+	type Output = ();
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		// the waiter must not move while queued
+		let this = self.into_ref().get_ref();
+		match this.waiter.state.get() {
+			State::Idle => {
+				if this.notify.permit.replace(false) {
+					this.waiter.state.set(State::Done);
+					return Poll::Ready(());
+				}
+				*this.waiter.waker.borrow_mut() = Some(cx.waker().clone());
+				this.waiter.state.set(State::Queued);
+				unsafe { this.notify.waiters.append(&this.waiter); }
+				Poll::Pending
+			},
+			State::Queued => {
+				*this.waiter.waker.borrow_mut() = Some(cx.waker().clone());
+				Poll::Pending
+			},
+			State::NotifiedOne | State::NotifiedAll => {
+				this.waiter.state.set(State::Done);
+				Poll::Ready(())
+			},
+			State::Done => panic!("Notified polled after completion"),
+		}
+	}
+}
+
+impl Drop for Notified<'_> {
+	fn drop(&mut self) {
+		match self.waiter.state.get() {
+			State::Queued => unsafe { self.waiter.link.unlink() },
+			// notification was never consumed; pass it on
+			State::NotifiedOne => self.notify.notify_one(),
+			State::Idle | State::NotifiedAll | State::Done => (),
+		}
+	}
+}
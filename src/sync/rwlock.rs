@@ -0,0 +1,188 @@
+use super::lock::{Acquire, RawSemaphore};
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+// a writer takes all permits
+const MAX_READERS: usize = !0 >> 1;
+
+/// An async reader-writer lock for tasks on the same thread
+///
+/// Waiting tasks get the lock in FIFO order; consecutive waiting readers share the lock.  New
+/// readers wait while a writer is waiting.
+pub struct RwLock<T: ?Sized> {
+	raw: RawSemaphore,
+	value: UnsafeCell<T>,
+}
+
+impl<T> RwLock<T> {
+	/// Create a new (unlocked) lock
+	pub fn new(value: T) -> Self {
+		Self {
+			raw: RawSemaphore::new(MAX_READERS),
+			value: UnsafeCell::new(value),
+		}
+	}
+
+	/// Extract the value
+	pub fn into_inner(self) -> T {
+		self.value.into_inner()
+	}
+}
+
+impl<T: ?Sized> RwLock<T> {
+	/// Wait for shared read access
+	pub fn read(&self) -> RwLockRead<'_, T> {
+		RwLockRead {
+			lock: self,
+			acquire: Acquire::new(&self.raw, 1),
+		}
+	}
+
+	/// Wait for exclusive write access
+	pub fn write(&self) -> RwLockWrite<'_, T> {
+		RwLockWrite {
+			lock: self,
+			acquire: Acquire::new(&self.raw, MAX_READERS),
+		}
+	}
+
+	/// Get read access if there is no writer (holding or waiting for the lock)
+	pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+		if self.raw.try_acquire(1) {
+			Some(RwLockReadGuard { lock: self })
+		} else {
+			None
+		}
+	}
+
+	/// Get write access if nobody holds or waits for the lock
+	pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+		if self.raw.try_acquire(MAX_READERS) {
+			Some(RwLockWriteGuard { lock: self })
+		} else {
+			None
+		}
+	}
+
+	/// Access the value without locking (it can't be locked while borrowed mutably)
+	pub fn get_mut(&mut self) -> &mut T {
+		unsafe { &mut *self.value.get() }
+	}
+}
+
+impl<T: Default> Default for RwLock<T> {
+	fn default() -> Self {
+		Self::new(T::default())
+	}
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLock<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.try_read() {
+			Some(guard) => f.debug_struct("RwLock").field("value", &&*guard).finish(),
+			None => f.debug_struct("RwLock").field("value", &"<locked>").finish(),
+		}
+	}
+}
+
+/// Future returned by [`RwLock::read`](struct.RwLock.html#method.read)
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct RwLockRead<'a, T: ?Sized> {
+	lock: &'a RwLock<T>,
+	acquire: Acquire<'a>,
+}
+
+impl<'a, T: ?Sized> Future for RwLockRead<'a, T> {
+	type Output = RwLockReadGuard<'a, T>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let lock = self.lock;
+		// structural pinning of `acquire`
+		let acquire = unsafe { self.map_unchecked_mut(|this| &mut this.acquire) };
+		futures_util::ready!(acquire.into_ref().poll_acquire(cx));
+		Poll::Ready(RwLockReadGuard { lock })
+	}
+}
+
+/// Future returned by [`RwLock::write`](struct.RwLock.html#method.write)
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct RwLockWrite<'a, T: ?Sized> {
+	lock: &'a RwLock<T>,
+	acquire: Acquire<'a>,
+}
+
+impl<'a, T: ?Sized> Future for RwLockWrite<'a, T> {
+	type Output = RwLockWriteGuard<'a, T>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let lock = self.lock;
+		// structural pinning of `acquire`
+		let acquire = unsafe { self.map_unchecked_mut(|this| &mut this.acquire) };
+		futures_util::ready!(acquire.into_ref().poll_acquire(cx));
+		Poll::Ready(RwLockWriteGuard { lock })
+	}
+}
+
+/// Shared access to the value of an [`RwLock`](struct.RwLock.html); unlocks when dropped.
+#[must_use = "if unused the RwLock will immediately unlock"]
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+	lock: &'a RwLock<T>,
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &T {
+		unsafe { &*self.lock.value.get() }
+	}
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockReadGuard<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&**self, f)
+	}
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+	fn drop(&mut self) {
+		self.lock.raw.release(1);
+	}
+}
+
+/// Exclusive access to the value of an [`RwLock`](struct.RwLock.html); unlocks when dropped.
+#[must_use = "if unused the RwLock will immediately unlock"]
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+	lock: &'a RwLock<T>,
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+	type Target = T;
+
+	fn deref(&self) -> &T {
+		unsafe { &*self.lock.value.get() }
+	}
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+	fn deref_mut(&mut self) -> &mut T {
+		unsafe { &mut *self.lock.value.get() }
+	}
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockWriteGuard<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Debug::fmt(&**self, f)
+	}
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+	fn drop(&mut self) {
+		self.lock.raw.release(MAX_READERS);
+	}
+}
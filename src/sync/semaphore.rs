@@ -0,0 +1,109 @@
+use super::lock::{Acquire, RawSemaphore};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// An async semaphore for tasks on the same thread
+///
+/// Limits concurrency (connections, requests, pooled resources, ...); waiting tasks get their
+/// permits in FIFO order, i.e. a large request blocks smaller requests queued after it.
+#[derive(Debug)]
+pub struct Semaphore {
+	raw: RawSemaphore,
+}
+
+impl Semaphore {
+	/// Create a semaphore with `permits` available permits
+	pub fn new(permits: usize) -> Self {
+		Self {
+			raw: RawSemaphore::new(permits),
+		}
+	}
+
+	/// Number of currently available permits
+	pub fn available_permits(&self) -> usize {
+		self.raw.available_permits()
+	}
+
+	/// Add permits (e.g. to grow a pool, or to return [forgotten](struct.SemaphorePermit.html#method.forget) permits)
+	pub fn add_permits(&self, permits: usize) {
+		self.raw.release(permits);
+	}
+
+	/// Wait for a single permit
+	pub fn acquire(&self) -> SemaphoreAcquire<'_> {
+		self.acquire_many(1)
+	}
+
+	/// Wait for `permits` permits (taken all at once)
+	pub fn acquire_many(&self, permits: usize) -> SemaphoreAcquire<'_> {
+		SemaphoreAcquire {
+			semaphore: self,
+			permits,
+			acquire: Acquire::new(&self.raw, permits),
+		}
+	}
+
+	/// Take a single permit if available (and nobody is waiting)
+	pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+		self.try_acquire_many(1)
+	}
+
+	/// Take `permits` permits if available (and nobody is waiting)
+	pub fn try_acquire_many(&self, permits: usize) -> Option<SemaphorePermit<'_>> {
+		if self.raw.try_acquire(permits) {
+			Some(SemaphorePermit { semaphore: self, permits })
+		} else {
+			None
+		}
+	}
+}
+
+/// Future returned by [`Semaphore::acquire`](struct.Semaphore.html#method.acquire)
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct SemaphoreAcquire<'a> {
+	semaphore: &'a Semaphore,
+	permits: usize,
+	acquire: Acquire<'a>,
+}
+
+impl<'a> Future for SemaphoreAcquire<'a> {
+	type Output = SemaphorePermit<'a>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		let (semaphore, permits) = (self.semaphore, self.permits);
+		// structural pinning of `acquire`
+		let acquire = unsafe { self.map_unchecked_mut(|this| &mut this.acquire) };
+		futures_util::ready!(acquire.into_ref().poll_acquire(cx));
+		Poll::Ready(SemaphorePermit { semaphore, permits })
+	}
+}
+
+/// Permits taken from a [`Semaphore`](struct.Semaphore.html); returned when dropped.
+#[must_use = "if unused the permits are returned immediately"]
+#[derive(Debug)]
+pub struct SemaphorePermit<'a> {
+	semaphore: &'a Semaphore,
+	permits: usize,
+}
+
+impl SemaphorePermit<'_> {
+	/// Number of permits held
+	pub fn permits(&self) -> usize {
+		self.permits
+	}
+
+	/// Drop the permits without returning them to the semaphore
+	pub fn forget(mut self) {
+		self.permits = 0;
+	}
+}
+
+impl Drop for SemaphorePermit<'_> {
+	fn drop(&mut self) {
+		if self.permits != 0 {
+			self.semaphore.raw.release(self.permits);
+		}
+	}
+}
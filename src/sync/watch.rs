@@ -0,0 +1,200 @@
+//! A channel holding a single value; receivers see only the latest value.
+//!
+//! Useful to distribute configuration updates to many tasks.
+
+use crate::waiters::Waiters;
+use std::cell::{Cell, Ref, RefCell};
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+/// Create a new channel with an initial value
+///
+/// The initial value is considered "seen" by the receiver.
+pub fn channel<T>(init: T) -> (Sender<T>, Receiver<T>) {
+	let shared = Rc::new(Shared {
+		value: RefCell::new(init),
+		version: Cell::new(0),
+		sender_alive: Cell::new(true),
+		receivers: Cell::new(1),
+		waiters: RefCell::default(),
+	});
+	let receiver = Receiver {
+		shared: shared.clone(),
+		version: 0,
+		slot: None,
+	};
+	(Sender { shared }, receiver)
+}
+
+#[derive(Debug)]
+struct Shared<T> {
+	value: RefCell<T>,
+	version: Cell<u64>,
+	sender_alive: Cell<bool>,
+	receivers: Cell<usize>,
+	waiters: RefCell<Waiters>,
+}
+
+/// Error returned by [`Sender::send`](struct.Sender.html#method.send) if all receivers are gone;
+/// contains the value that couldn't be sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SendError<T>(pub T);
+
+impl<T> fmt::Display for SendError<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("watch channel closed")
+	}
+}
+
+impl<T: fmt::Debug> Error for SendError<T> {}
+
+/// Error returned by [`Receiver::changed`](struct.Receiver.html#method.changed) if the sender is
+/// gone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecvError;
+
+impl fmt::Display for RecvError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("watch channel closed")
+	}
+}
+
+impl Error for RecvError {}
+
+/// Sending half of a watch channel
+#[derive(Debug)]
+pub struct Sender<T> {
+	shared: Rc<Shared<T>>,
+}
+
+impl<T> Sender<T> {
+	/// Replace the value and notify all receivers.
+	///
+	/// # Errors
+	///
+	/// Fails if all receivers are gone.
+	pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+		if 0 == self.shared.receivers.get() {
+			return Err(SendError(value));
+		}
+		*self.shared.value.borrow_mut() = value;
+		self.shared.version.set(self.shared.version.get() + 1);
+		self.shared.waiters.borrow_mut().wake_all();
+		Ok(())
+	}
+
+	/// Borrow the current value
+	///
+	/// Don't hold it across `.await`: `send` panics while the value is borrowed.
+	pub fn borrow(&self) -> Ref<'_, T> {
+		self.shared.value.borrow()
+	}
+
+	/// Create a new receiver; the current value is considered "seen".
+	pub fn subscribe(&self) -> Receiver<T> {
+		self.shared.receivers.set(self.shared.receivers.get() + 1);
+		Receiver {
+			shared: self.shared.clone(),
+			version: self.shared.version.get(),
+			slot: None,
+		}
+	}
+
+	/// Number of receivers
+	pub fn receiver_count(&self) -> usize {
+		self.shared.receivers.get()
+	}
+}
+
+impl<T> Drop for Sender<T> {
+	fn drop(&mut self) {
+		self.shared.sender_alive.set(false);
+		self.shared.waiters.borrow_mut().wake_all();
+	}
+}
+
+/// Receiving half of a watch channel
+///
+/// Clones start with the same "seen" state.
+#[derive(Debug)]
+pub struct Receiver<T> {
+	shared: Rc<Shared<T>>,
+	version: u64,
+	slot: Option<usize>,
+}
+
+impl<T> Receiver<T> {
+	/// Borrow the current value (doesn't mark it as "seen")
+	///
+	/// Don't hold it across `.await`: `send` panics while the value is borrowed.
+	pub fn borrow(&self) -> Ref<'_, T> {
+		self.shared.value.borrow()
+	}
+
+	/// Whether the current value wasn't "seen" yet
+	pub fn has_changed(&self) -> bool {
+		self.version != self.shared.version.get()
+	}
+
+	/// Wait for a value not "seen" yet, and mark it as "seen".
+	///
+	/// Fails if the sender is gone (and all values were seen).
+	pub fn poll_changed(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), RecvError>> {
+		let version = self.shared.version.get();
+		if self.version != version {
+			self.version = version;
+			self.shared.waiters.borrow_mut().remove(&mut self.slot);
+			return Poll::Ready(Ok(()));
+		}
+		if !self.shared.sender_alive.get() {
+			self.shared.waiters.borrow_mut().remove(&mut self.slot);
+			return Poll::Ready(Err(RecvError));
+		}
+		self.shared.waiters.borrow_mut().register(&mut self.slot, cx.waker());
+		Poll::Pending
+	}
+
+	/// Wait for a value not "seen" yet, and mark it as "seen".
+	///
+	/// Fails if the sender is gone (and all values were seen).
+	pub fn changed(&mut self) -> Changed<'_, T> {
+		Changed { receiver: self }
+	}
+}
+
+impl<T> Clone for Receiver<T> {
+	fn clone(&self) -> Self {
+		self.shared.receivers.set(self.shared.receivers.get() + 1);
+		Self {
+			shared: self.shared.clone(),
+			version: self.version,
+			slot: None,
+		}
+	}
+}
+
+impl<T> Drop for Receiver<T> {
+	fn drop(&mut self) {
+		self.shared.receivers.set(self.shared.receivers.get() - 1);
+		self.shared.waiters.borrow_mut().remove(&mut self.slot);
+	}
+}
+
+/// Future returned by [`Receiver::changed`](struct.Receiver.html#method.changed)
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct Changed<'a, T> {
+	receiver: &'a mut Receiver<T>,
+}
+
+impl<T> Future for Changed<'_, T> {
+	type Output = Result<(), RecvError>;
+
+	fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+		self.receiver.poll_changed(cx)
+	}
+}
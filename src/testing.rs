@@ -0,0 +1,58 @@
+//! Helpers for deterministic tests of code using fumio
+//!
+//! [`TestRuntime`](struct.TestRuntime.html) runs tasks with manual time and without ever blocking
+//! the thread; [`assert_ready!`](../macro.assert_ready.html) and
+//! [`assert_pending!`](../macro.assert_pending.html) check the results of single polls.
+//!
+//! ```
+//! use fumio::testing::TestRuntime;
+//! use std::time::Duration;
+//!
+//! let mut rt = TestRuntime::new().unwrap();
+//! let mut delay = rt.handle().timer().delay(rt.now() + Duration::from_secs(60));
+//! fumio::assert_pending!(rt.poll(std::pin::Pin::new(&mut delay)));
+//! rt.advance(Duration::from_secs(60));
+//! assert!(rt.is_woken());
+//! fumio::assert_ready!(rt.poll(std::pin::Pin::new(&mut delay)));
+//! ```
+
+pub use crate::runtime::TestRuntime;
+
+/// Assert a `Poll` is `Ready` and return the value
+///
+/// Panics (with an optional message like `panic!`) if the poll is `Pending`.
+#[macro_export]
+macro_rules! assert_ready {
+	($e:expr) => {
+		match $e {
+			::std::task::Poll::Ready(v) => v,
+			::std::task::Poll::Pending => panic!("pending"),
+		}
+	};
+	($e:expr, $($msg:tt)+) => {
+		match $e {
+			::std::task::Poll::Ready(v) => v,
+			::std::task::Poll::Pending => panic!("pending; {}", format_args!($($msg)+)),
+		}
+	};
+}
+
+/// Assert a `Poll` is `Pending`
+///
+/// Panics (with an optional message like `panic!`) if the poll is `Ready`; the value needs to
+/// implement `Debug`.
+#[macro_export]
+macro_rules! assert_pending {
+	($e:expr) => {
+		match $e {
+			::std::task::Poll::Pending => {},
+			::std::task::Poll::Ready(v) => panic!("ready; value = {:?}", v),
+		}
+	};
+	($e:expr, $($msg:tt)+) => {
+		match $e {
+			::std::task::Poll::Pending => {},
+			::std::task::Poll::Ready(v) => panic!("ready; value = {:?}; {}", v, format_args!($($msg)+)),
+		}
+	};
+}
@@ -0,0 +1,16 @@
+//! Time based events
+//!
+//! Based on the pre-release `tokio-timer` 0.3.0-alpha.5; the re-exported types are its types
+//! and will change when the timer gets replaced.
+
+mod rate_limit;
+mod stream;
+
+pub use self::rate_limit::{throttle, RateLimiter, RateLimiterAcquire, Throttle};
+pub use self::stream::{DelayQueue, Interval, MissedTickBehavior};
+pub use tokio_timer::delay_queue::{Expired, Key};
+pub use tokio_timer::{
+	Delay,
+	Error,
+	Timeout,
+};
@@ -0,0 +1,190 @@
+use futures_core::stream::Stream;
+use std::convert::TryFrom;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio_timer::{clock, Delay};
+
+/// Token bucket rate limiter
+///
+/// The bucket holds up to `burst` tokens and starts full; a new token is added every `interval`.
+/// Each acquire takes one token, waiting for the next refill if the bucket is empty.
+///
+/// Waiting needs the timer of the current runtime.
+#[derive(Debug)]
+pub struct RateLimiter {
+	interval: Duration,
+	burst: u32,
+	tokens: u32,
+	// time the last token was added (or the bucket was full)
+	refilled: Instant,
+	delay: Option<Delay>,
+}
+
+impl RateLimiter {
+	/// Create new rate limiter allowing one acquire per `interval` with bursts of up to `burst`
+	///
+	/// # Panics
+	///
+	/// Panics if `interval` is zero or `burst` is zero.
+	pub fn new(interval: Duration, burst: u32) -> Self {
+		assert!(interval != Duration::from_secs(0), "rate limiter interval must not be zero");
+		assert!(burst != 0, "rate limiter burst must not be zero");
+		Self {
+			interval,
+			burst,
+			tokens: burst,
+			refilled: clock::now(),
+			delay: None,
+		}
+	}
+
+	fn refill(&mut self) {
+		let now = clock::now();
+		if self.tokens == self.burst || now <= self.refilled {
+			if self.tokens == self.burst {
+				self.refilled = now;
+			}
+			return;
+		}
+		let added = (now - self.refilled).as_nanos() / self.interval.as_nanos();
+		match u32::try_from(added) {
+			Ok(added) if added < self.burst - self.tokens => {
+				self.tokens += added;
+				self.refilled += self.interval * added;
+			},
+			_ => {
+				self.tokens = self.burst;
+				self.refilled = now;
+			},
+		}
+	}
+
+	// wait until at least one token is available (without taking it)
+	pub(crate) fn poll_available(&mut self, cx: &mut Context<'_>) -> Poll<u32> {
+		loop {
+			self.refill();
+			if self.tokens != 0 {
+				return Poll::Ready(self.tokens);
+			}
+			let next = self.refilled + self.interval;
+			let delay = match &mut self.delay {
+				Some(delay) => {
+					delay.reset(next);
+					delay
+				},
+				delay @ None => delay.get_or_insert_with(|| tokio_timer::delay(next)),
+			};
+			if Pin::new(delay).poll(cx).is_pending() {
+				return Poll::Pending;
+			}
+		}
+	}
+
+	// take tokens previously reported by `poll_available`
+	pub(crate) fn take(&mut self, tokens: u32) {
+		debug_assert!(tokens <= self.tokens);
+		self.tokens -= tokens;
+	}
+
+	/// Number of tokens currently available
+	pub fn available(&mut self) -> u32 {
+		self.refill();
+		self.tokens
+	}
+
+	/// Take a token if one is available
+	pub fn try_acquire(&mut self) -> bool {
+		self.refill();
+		if self.tokens == 0 {
+			return false;
+		}
+		self.tokens -= 1;
+		true
+	}
+
+	/// Take a token, registering the current task for wakeup if none is available yet
+	pub fn poll_acquire(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+		futures_util::ready!(self.poll_available(cx));
+		self.take(1);
+		Poll::Ready(())
+	}
+
+	/// Wait for and take a token
+	pub fn acquire(&mut self) -> RateLimiterAcquire<'_> {
+		RateLimiterAcquire { limiter: self }
+	}
+}
+
+/// Pending [`RateLimiter::acquire`](struct.RateLimiter.html#method.acquire) operation
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+#[derive(Debug)]
+pub struct RateLimiterAcquire<'a> {
+	limiter: &'a mut RateLimiter,
+}
+
+impl Future for RateLimiterAcquire<'_> {
+	type Output = ();
+
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+		self.get_mut().limiter.poll_acquire(cx)
+	}
+}
+
+/// Limit the rate at which items are taken from `stream`
+///
+/// Each item takes a token from `rate`; the stream isn't polled while no token is available.
+pub fn throttle<S>(rate: RateLimiter, stream: S) -> Throttle<S>
+where
+	S: Stream,
+{
+	Throttle {
+		limiter: rate,
+		stream,
+	}
+}
+
+/// Stream returned by [`throttle`](fn.throttle.html)
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct Throttle<S> {
+	limiter: RateLimiter,
+	stream: S,
+}
+
+impl<S> Throttle<S> {
+	/// Access the rate limiter
+	pub fn limiter(&mut self) -> &mut RateLimiter {
+		&mut self.limiter
+	}
+
+	/// Access the inner stream
+	pub const fn get_ref(&self) -> &S {
+		&self.stream
+	}
+
+	/// Extract the inner stream
+	pub fn into_inner(self) -> S {
+		self.stream
+	}
+}
+
+impl<S> Stream for Throttle<S>
+where
+	S: Stream,
+{
+	type Item = S::Item;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+		// only `stream` is structurally pinned
+		let this = unsafe { self.get_unchecked_mut() };
+		futures_util::ready!(this.limiter.poll_available(cx));
+		let stream = unsafe { Pin::new_unchecked(&mut this.stream) };
+		let item = futures_util::ready!(stream.poll_next(cx));
+		if item.is_some() {
+			this.limiter.take(1);
+		}
+		Poll::Ready(item)
+	}
+}
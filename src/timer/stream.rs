@@ -0,0 +1,172 @@
+use futures_core::stream::Stream;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tokio_timer::delay_queue::Expired;
+
+/// What [`Interval`](struct.Interval.html) does when ticks were missed (e.g. because the
+/// runtime was blocked)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MissedTickBehavior {
+	/// Yield all missed ticks immediately, then continue with the original schedule
+	Burst,
+	/// Yield one tick now, then continue `period` from now (shifts the schedule)
+	Delay,
+	/// Yield one tick now, then continue with the next tick of the original schedule that is in
+	/// the future (default)
+	#[default]
+	Skip,
+}
+
+impl MissedTickBehavior {
+	// next deadline after the tick scheduled for `deadline` fired at `now`
+	fn next(self, deadline: Instant, now: Instant, period: Duration) -> Instant {
+		let next = deadline + period;
+		if next > now {
+			return next;
+		}
+		match self {
+			Self::Burst => next,
+			Self::Delay => now + period,
+			Self::Skip => {
+				let period_nanos = period.as_nanos();
+				let late = now.duration_since(deadline).as_nanos() % period_nanos;
+				// at most `period`
+				#[allow(clippy::cast_possible_truncation)]
+				let wait = (period_nanos - late) as u64;
+				now + Duration::from_nanos(wait)
+			},
+		}
+	}
+}
+
+/// Stream yielding at fixed time intervals
+///
+/// Yields the instant each tick was scheduled for; missed ticks are handled according to
+/// [`MissedTickBehavior`](enum.MissedTickBehavior.html).
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct Interval {
+	delay: tokio_timer::Delay,
+	period: Duration,
+	missed_tick_behavior: MissedTickBehavior,
+}
+
+impl Interval {
+	/// Yield first at `at`, then every `period`
+	///
+	/// # Panics
+	///
+	/// Panics if `period` is zero.
+	pub fn new(at: Instant, period: Duration) -> Self {
+		assert!(period > Duration::new(0, 0), "interval period must be non-zero");
+		Self {
+			delay: tokio_timer::delay(at),
+			period,
+			missed_tick_behavior: MissedTickBehavior::default(),
+		}
+	}
+
+	/// Yield every `period`, starting `period` from now
+	///
+	/// # Panics
+	///
+	/// Panics if `period` is zero.
+	pub fn new_interval(period: Duration) -> Self {
+		Self::new(Instant::now() + period, period)
+	}
+
+	/// Time between ticks
+	pub const fn period(&self) -> Duration {
+		self.period
+	}
+
+	/// Instant the next tick is scheduled for
+	pub fn deadline(&self) -> Instant {
+		self.delay.deadline()
+	}
+
+	/// How missed ticks are handled
+	pub const fn missed_tick_behavior(&self) -> MissedTickBehavior {
+		self.missed_tick_behavior
+	}
+
+	/// Set how missed ticks are handled
+	pub fn set_missed_tick_behavior(&mut self, behavior: MissedTickBehavior) {
+		self.missed_tick_behavior = behavior;
+	}
+
+	/// Yield next at `at`, then every `period`
+	pub fn reset(&mut self, at: Instant) {
+		self.delay.reset(at);
+	}
+}
+
+impl Stream for Interval {
+	type Item = Instant;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Instant>> {
+		let this = self.get_mut();
+		futures_util::ready!(Pin::new(&mut this.delay).poll(cx));
+		let deadline = this.delay.deadline();
+		let next = this.missed_tick_behavior.next(deadline, Instant::now(), this.period);
+		this.delay.reset(next);
+		Poll::Ready(Some(deadline))
+	}
+}
+
+/// Queue of values yielded once their delay expired
+///
+/// Wraps `tokio_timer::DelayQueue` to implement `Stream` from `futures` 0.3; the methods of the
+/// inner queue are available through `Deref`.
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct DelayQueue<T>(tokio_timer::DelayQueue<T>);
+
+impl<T> DelayQueue<T> {
+	/// New empty queue
+	pub fn new() -> Self {
+		Self(tokio_timer::DelayQueue::new())
+	}
+
+	/// New empty queue with space for `capacity` values
+	pub fn with_capacity(capacity: usize) -> Self {
+		Self(tokio_timer::DelayQueue::with_capacity(capacity))
+	}
+}
+
+impl<T> Default for DelayQueue<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T> From<tokio_timer::DelayQueue<T>> for DelayQueue<T> {
+	fn from(queue: tokio_timer::DelayQueue<T>) -> Self {
+		Self(queue)
+	}
+}
+
+impl<T> Deref for DelayQueue<T> {
+	type Target = tokio_timer::DelayQueue<T>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.0
+	}
+}
+
+impl<T> DerefMut for DelayQueue<T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		&mut self.0
+	}
+}
+
+impl<T> Stream for DelayQueue<T> {
+	type Item = Result<Expired<T>, tokio_timer::Error>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		self.get_mut().0.poll_next(cx)
+	}
+}
@@ -1,42 +1,149 @@
 use crate::reactor;
 use tokio_timer::Timer;
+use fumio_utils::enter::Enter;
 use fumio_utils::park::Park;
-use futures_executor::Enter;
+use std::fmt;
 use std::io;
 use std::ptr::NonNull;
 use std::task::Waker;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+// called before blocking without timers, IO registrations or pending wakeups
+struct StallHook(Box<dyn FnMut()>);
+
+impl fmt::Debug for StallHook {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str("StallHook")
+	}
+}
+
+// file descriptor owned by someone else
+#[cfg(unix)]
 #[derive(Debug)]
-struct ParkReactor(reactor::Reactor, Option<NonNull<Enter>>);
+struct BorrowedFd(std::os::unix::io::RawFd);
+
+#[cfg(unix)]
+impl std::os::unix::io::AsRawFd for BorrowedFd {
+	fn as_raw_fd(&self) -> std::os::unix::io::RawFd {
+		self.0
+	}
+}
+
+// additional reactor, polled without blocking after the main reactor
+#[derive(Debug)]
+struct ExtraReactor {
+	name: String,
+	// wakes up the main reactor when `reactor` has events; must be dropped before `reactor`
+	#[cfg(unix)]
+	_wakeup: reactor::AsyncFd<BorrowedFd>,
+	reactor: reactor::Reactor,
+}
+
+#[derive(Debug)]
+struct ParkReactor {
+	reactor: reactor::Reactor,
+	enter: Option<NonNull<Enter>>,
+	stall_hook: Option<StallHook>,
+	extra: Vec<ExtraReactor>,
+	#[cfg(all(unix, feature = "task-dump"))]
+	task_dump: Option<crate::runtime::task_dump::TaskDump>,
+	// don't block: `park` and `park_timeout` only dispatch pending events and record how long
+	// they were supposed to wait in `next_timeout`
+	nonblocking: bool,
+	next_timeout: Option<Duration>,
+	// wakeups for timers are aligned to multiples of `slack` since `origin` (zero: disabled)
+	slack: Duration,
+	origin: Instant,
+}
+
+impl ParkReactor {
+	fn poll(reactor: &mut reactor::Reactor, timeout: Duration) {
+		if let Err(e) = reactor.poll(Some(timeout)) {
+			panic!("reactor poll failed: {}", e);
+		}
+	}
+
+	// extend `timeout` so it ends on the next slack boundary; timers expiring close together then
+	// fire in a single wakeup
+	fn align_timeout(&self, timeout: Duration) -> Duration {
+		if self.slack == Duration::new(0, 0) {
+			return timeout;
+		}
+		let since_origin = (Instant::now() + timeout).duration_since(self.origin);
+		let slack = self.slack.as_nanos();
+		let rem = since_origin.as_nanos() % slack;
+		if rem == 0 {
+			timeout
+		} else {
+			// at most `slack`
+			#[allow(clippy::cast_possible_truncation)]
+			let extend = (slack - rem) as u64;
+			timeout + Duration::from_nanos(extend)
+		}
+	}
+
+	// dispatch events of the extra reactors (in the order they were added)
+	fn poll_extra(&mut self) {
+		for extra in &mut self.extra {
+			Self::poll(&mut extra.reactor, Duration::new(0, 0));
+		}
+		#[cfg(all(unix, feature = "task-dump"))]
+		if let Some(task_dump) = &self.task_dump {
+			task_dump.poll();
+		}
+	}
+}
 
 #[derive(Debug)]
 struct Unpark(Waker);
 
 impl tokio_executor::park::Park for ParkReactor {
 	type Unpark = Unpark;
-	type Error = futures_core::Never;
+	type Error = std::convert::Infallible;
 
 	fn unpark(&self) -> Self::Unpark {
-		Unpark(self.0.waker())
+		Unpark(self.reactor.waker())
 	}
 
 	fn park(&mut self) -> Result<(), Self::Error> {
-		let enter = unsafe { self.1.as_mut().expect("not entered").as_mut() };
-		self.0.park(enter, None);
+		if self.nonblocking {
+			Self::poll(&mut self.reactor, Duration::new(0, 0));
+			self.next_timeout = None;
+			self.poll_extra();
+			return Ok(());
+		}
+		// the timer only parks without timeout if there are no timers
+		if let Some(hook) = &mut self.stall_hook {
+			if self.reactor.is_stalled() {
+				(hook.0)();
+			}
+		}
+		let enter = unsafe { self.enter.as_mut().expect("not entered").as_mut() };
+		self.reactor.park(enter, None);
+		self.poll_extra();
 		Ok(())
 	}
 
 	fn park_timeout(&mut self, timeout: Duration) -> Result<(), Self::Error> {
-		let enter = unsafe { self.1.as_mut().expect("not entered").as_mut() };
-		self.0.park(enter, Some(timeout));
+		if self.nonblocking {
+			self.next_timeout = Some(timeout);
+		}
+		if self.nonblocking || timeout == Duration::new(0, 0) {
+			// not blocking, doesn't need to be entered
+			Self::poll(&mut self.reactor, timeout);
+		} else {
+			let timeout = self.align_timeout(timeout);
+			let enter = unsafe { self.enter.as_mut().expect("not entered").as_mut() };
+			self.reactor.park(enter, Some(timeout));
+		}
+		self.poll_extra();
 		Ok(())
 	}
 }
 
 impl tokio_executor::park::Unpark for Unpark {
 	fn unpark(&self) {
-		self.0.wake_by_ref()
+		self.0.wake_by_ref();
 	}
 }
 
@@ -47,7 +154,18 @@ pub(crate) struct TimerReactor {
 
 impl TimerReactor {
 	pub(crate) fn new() -> io::Result<Self> {
-		let reactor = ParkReactor(reactor::Reactor::new()?, None);
+		let reactor = ParkReactor {
+			reactor: reactor::Reactor::new()?,
+			enter: None,
+			stall_hook: None,
+			extra: Vec::new(),
+			nonblocking: false,
+			next_timeout: None,
+			#[cfg(all(unix, feature = "task-dump"))]
+			task_dump: None,
+			slack: Duration::new(0, 0),
+			origin: Instant::now(),
+		};
 		Ok(Self {
 			timer: Timer::new(reactor),
 		})
@@ -58,19 +176,92 @@ impl TimerReactor {
 	}
 
 	pub(crate) fn reactor_handle(&self) -> reactor::Handle {
-		self.timer.get_park().0.handle()
+		self.timer.get_park().reactor.handle()
+	}
+
+	pub(crate) fn reactor_metrics(&self) -> reactor::ReactorMetrics {
+		self.timer.get_park().reactor.metrics()
+	}
+
+	// add reactor polled after the main reactor; the main reactor needs to wake up for its events
+	#[cfg(unix)]
+	pub(crate) fn add_extra_reactor(&mut self, name: &str) -> io::Result<()> {
+		use std::os::unix::io::AsRawFd;
+
+		let park = self.timer.get_park_mut();
+		let reactor = reactor::Reactor::new()?;
+		let wakeup = reactor::AsyncFd::with_handle(BorrowedFd(reactor.as_raw_fd()), park.reactor.handle().into())?;
+		park.extra.push(ExtraReactor {
+			name: name.to_string(),
+			_wakeup: wakeup,
+			reactor,
+		});
+		Ok(())
+	}
+
+	#[cfg(not(unix))]
+	pub(crate) fn add_extra_reactor(&mut self, _name: &str) -> io::Result<()> {
+		Err(io::Error::other("extra reactors not supported on this platform"))
+	}
+
+	// handles of extra reactors by name
+	pub(crate) fn extra_reactor_handles(&self) -> Vec<(String, reactor::Handle)> {
+		self.timer.get_park().extra.iter().map(|extra| (extra.name.clone(), extra.reactor.handle())).collect()
+	}
+
+	// `hook` is called when parking would block forever unless woken from another thread
+	pub(crate) fn set_stall_hook<F>(&mut self, hook: F)
+	where
+		F: FnMut() + 'static,
+	{
+		self.timer.get_park_mut().stall_hook = Some(StallHook(Box::new(hook)));
+	}
+
+	// dump tasks on `SIGUSR1` (checked after each wakeup)
+	#[cfg(all(unix, feature = "task-dump"))]
+	pub(crate) fn set_task_dump(&mut self, task_dump: crate::runtime::task_dump::TaskDump) {
+		self.timer.get_park_mut().task_dump = Some(task_dump);
+	}
+
+	// align timer wakeups to multiples of `slack` (zero disables it)
+	pub(crate) fn set_timer_slack(&mut self, slack: Duration) {
+		self.timer.get_park_mut().slack = slack;
+	}
+
+	// fire expired timers and dispatch pending IO events without blocking; returns when the next
+	// timer expires (`None`: no timers)
+	pub(crate) fn turn_nonblocking(&mut self) -> Option<Duration> {
+		self.timer.get_park_mut().nonblocking = true;
+		let r = self.timer.turn(None);
+		let park = self.timer.get_park_mut();
+		park.nonblocking = false;
+		if let Err(never) = r {
+			match never {}
+		}
+		park.next_timeout.take()
+	}
+
+	// file descriptor of the main reactor; readable when it has events
+	#[cfg(unix)]
+	pub(crate) fn reactor_fd(&self) -> std::os::unix::io::RawFd {
+		use std::os::unix::io::AsRawFd;
+
+		self.timer.get_park().reactor.as_raw_fd()
 	}
 }
 
 impl Park for TimerReactor {
 	fn waker(&self) -> std::task::Waker {
-		self.timer.get_park().0.waker()
+		self.timer.get_park().reactor.waker()
 	}
 
 	fn park(&mut self, enter: &mut Enter, duration: Option<Duration>) {
-		self.timer.get_park_mut().1 = Some(NonNull::from(enter));
+		self.timer.get_park_mut().enter = Some(NonNull::from(enter));
 		let r = self.timer.turn(duration);
-		self.timer.get_park_mut().1 = None;
-		r.unwrap();
+		self.timer.get_park_mut().enter = None;
+		// parking the reactor can't fail (it panics instead)
+		if let Err(never) = r {
+			match never {}
+		}
 	}
 }
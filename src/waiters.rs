@@ -0,0 +1,55 @@
+use std::task::Waker;
+
+// wakers of pending futures that can't be queued intrusively (e.g. receivers polled through
+// `&mut self`, which may move between polls); each future owns a slot while registered.
+//
+// Not synchronized itself: local primitives keep it in a `RefCell`, thread-safe ones in a
+// `Mutex`.
+#[derive(Debug, Default)]
+pub(crate) struct Waiters {
+	// (in use, waker)
+	slots: Vec<(bool, Option<Waker>)>,
+	// slots in use
+	used: usize,
+}
+
+impl Waiters {
+	pub(crate) fn register(&mut self, slot: &mut Option<usize>, waker: &Waker) {
+		if slot.is_none() {
+			let index = if self.used < self.slots.len() {
+				self.slots.iter().position(|(used, _)| !used).unwrap()
+			} else {
+				self.slots.push((false, None));
+				self.slots.len() - 1
+			};
+			self.used += 1;
+			*slot = Some(index);
+		}
+		self.slots[slot.unwrap()] = (true, Some(waker.clone()));
+	}
+
+	pub(crate) fn remove(&mut self, slot: &mut Option<usize>) {
+		// the registry might have been reset (with `mem::take`) since
+		match slot.take().and_then(|index| self.slots.get_mut(index)) {
+			Some(entry) if entry.0 => *entry = (false, None),
+			_ => return,
+		}
+		self.used -= 1;
+		// release trailing free slots, so the registry shrinks with the number of waiters
+		while let Some((false, _)) = self.slots.last() {
+			self.slots.pop();
+		}
+		if self.slots.len() <= self.slots.capacity() / 4 {
+			self.slots.shrink_to_fit();
+		}
+	}
+
+	// slots stay owned by their futures
+	pub(crate) fn wake_all(&mut self) {
+		for (_, waker) in &mut self.slots {
+			if let Some(waker) = waker.take() {
+				waker.wake();
+			}
+		}
+	}
+}
@@ -0,0 +1,53 @@
+#![cfg(unix)]
+
+use fumio::net::{is_accept_resource_error, TcpListener};
+use futures::future;
+use std::cell::RefCell;
+use std::time::Duration;
+
+fn set_nofile_limit(limit: libc::rlim_t) -> libc::rlim_t {
+	let mut rlim = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+	assert_eq!(0, unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut rlim) });
+	let old = rlim.rlim_cur;
+	rlim.rlim_cur = limit;
+	assert_eq!(0, unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &rlim) });
+	old
+}
+
+#[test]
+fn classify() {
+	assert!(is_accept_resource_error(&std::io::Error::from_raw_os_error(libc::EMFILE)));
+	assert!(is_accept_resource_error(&std::io::Error::from_raw_os_error(libc::ENFILE)));
+	assert!(!is_accept_resource_error(&std::io::Error::from_raw_os_error(libc::ECONNABORTED)));
+	assert!(!is_accept_resource_error(&std::io::ErrorKind::Other.into()));
+}
+
+// the fd limit is process-wide: other tests in this file must not open file descriptors
+#[test]
+fn backoff_on_emfile() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_ipv4_port(0).unwrap();
+		let server_addr = l.local_addr().unwrap();
+		let _client = std::net::TcpStream::connect(server_addr).unwrap();
+
+		// the accepted connection would get the lowest free fd: forbid it
+		let next_fd = std::fs::File::open("/dev/null").map(|f| std::os::unix::io::AsRawFd::as_raw_fd(&f)).unwrap();
+		let old_limit = set_nofile_limit(next_fd as libc::rlim_t);
+
+		let sleeps = RefCell::new(Vec::new());
+		let (_stream, _addr) = l.incoming().with_backoff(Duration::from_millis(10), Duration::from_millis(15), |d| {
+			let mut sleeps = sleeps.borrow_mut();
+			sleeps.push(d);
+			if sleeps.len() == 3 {
+				set_nofile_limit(old_limit);
+			}
+			future::ready(())
+		}).await.unwrap();
+
+		assert_eq!(*sleeps.borrow(), vec![
+			Duration::from_millis(10),
+			Duration::from_millis(15),
+			Duration::from_millis(15),
+		]);
+	});
+}
@@ -0,0 +1,36 @@
+#![cfg(target_os = "linux")]
+
+use std::thread;
+
+fn current_cores() -> Vec<usize> {
+	unsafe {
+		let mut set: libc::cpu_set_t = std::mem::zeroed();
+		assert_eq!(libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set), 0);
+		(0..libc::CPU_SETSIZE as usize).filter(|&core| libc::CPU_ISSET(core, &set)).collect()
+	}
+}
+
+#[test]
+fn pin_to_core() {
+	let core = current_cores()[0];
+	thread::spawn(move || {
+		let mut runtime = fumio::Runtime::builder().pin_to_core(core).build().unwrap();
+		runtime.run_until(async {});
+		assert_eq!(current_cores(), [core]);
+	}).join().unwrap();
+}
+
+#[test]
+fn invalid_core() {
+	assert!(fumio::Runtime::builder().pin_to_core(1 << 20).build().is_err());
+}
+
+#[test]
+fn spread_pinned() {
+	let core = current_cores()[0];
+	let spread = fumio::spread_pinned(&[core, core], move |_| async move {
+		assert_eq!(current_cores(), [core]);
+	}).unwrap();
+	assert_eq!(spread.len(), 2);
+	spread.join().unwrap();
+}
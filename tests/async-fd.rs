@@ -0,0 +1,54 @@
+#![cfg(unix)]
+
+use fumio::reactor::AsyncFd;
+use futures::future::poll_fn;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::task::Poll;
+
+#[test]
+fn read_write_ready() {
+	fumio::run(async {
+		let (a, b) = UnixStream::pair().unwrap();
+		a.set_nonblocking(true).unwrap();
+		b.set_nonblocking(true).unwrap();
+		let a = AsyncFd::new(a).unwrap();
+		let b = AsyncFd::new(b).unwrap();
+
+		poll_fn(|cx| {
+			let mut guard = futures::ready!(a.poll_write_ready(cx)).unwrap();
+			assert!(guard.ready().is_writable());
+			guard.try_io(|mut s| s.write(b"hello"))
+		}).await.unwrap();
+
+		let mut buf = [0u8; 16];
+		let n = poll_fn(|cx| loop {
+			let mut guard = futures::ready!(b.poll_read_ready(cx)).unwrap();
+			if let Poll::Ready(r) = guard.try_io(|mut s| s.read(&mut buf)) {
+				return Poll::Ready(r);
+			}
+		}).await.unwrap();
+		assert_eq!(&buf[..n], b"hello");
+
+		// nothing left: read blocks, clears the events and waits for new ones
+		poll_fn(|cx| {
+			let mut guard = futures::ready!(b.poll_read_ready(cx)).unwrap();
+			assert!(guard.try_io(|mut s| s.read(&mut buf)).is_pending());
+			assert!(guard.ready().is_empty());
+			drop(guard);
+			assert!(b.poll_read_ready(cx).is_pending());
+			Poll::Ready(())
+		}).await;
+
+		// dropping the guard without clearing retains the events
+		poll_fn(|cx| {
+			let guard = futures::ready!(a.poll_write_ready(cx)).unwrap();
+			drop(guard);
+			assert!(a.poll_write_ready(cx).is_ready());
+			Poll::Ready(())
+		}).await;
+
+		let inner = a.into_inner();
+		drop(inner);
+	});
+}
@@ -0,0 +1,29 @@
+use fumio::io::BandwidthLimited;
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use std::time::{Duration, Instant};
+
+#[test]
+fn limit_write() {
+	fumio::run(async {
+		let data = vec![0x55u8; 2000];
+		let mut limited = BandwidthLimited::new(Vec::new(), 10_000);
+		let start = Instant::now();
+		limited.write_all(&data).await.unwrap();
+		// 1000 bytes burst, then 1000 bytes at 10000 bytes per second
+		assert!(start.elapsed() >= Duration::from_millis(90));
+		assert_eq!(limited.into_inner(), data);
+	});
+}
+
+#[test]
+fn limit_read() {
+	fumio::run(async {
+		let data = vec![0xaau8; 1500];
+		let mut limited = BandwidthLimited::new(&data[..], 10_000);
+		let mut buf = Vec::new();
+		let start = Instant::now();
+		limited.read_to_end(&mut buf).await.unwrap();
+		assert!(start.elapsed() >= Duration::from_millis(45));
+		assert_eq!(buf, data);
+	});
+}
@@ -0,0 +1,52 @@
+use fumio::sync::broadcast;
+
+#[test]
+fn all_receivers_get_values() {
+	fumio::run(async {
+		let (sender, mut first) = broadcast::channel(4);
+		let mut second = sender.subscribe();
+		assert_eq!(sender.send(1), Ok(2));
+		let mut third = sender.subscribe();
+		sender.send(2).unwrap();
+		drop(sender);
+
+		for receiver in &mut [&mut first, &mut second] {
+			assert_eq!(receiver.recv().await, Ok(1));
+			assert_eq!(receiver.recv().await, Ok(2));
+			assert_eq!(receiver.recv().await, Err(broadcast::RecvError::Closed));
+		}
+		assert_eq!(third.recv().await, Ok(2));
+		assert_eq!(third.recv().await, Err(broadcast::RecvError::Closed));
+	});
+}
+
+#[test]
+fn wakes_receivers() {
+	fumio::run(async {
+		let (sender, mut receiver) = broadcast::channel(1);
+		let (task, task_handle) = futures::future::FutureExt::remote_handle(async move {
+			receiver.recv().await
+		});
+		fumio::current().unwrap().spawn(task).unwrap();
+		// let the task wait
+		fumio::current().unwrap().timer().delay(std::time::Instant::now()).await;
+		sender.send("shutdown").unwrap();
+		assert_eq!(task_handle.await, Ok("shutdown"));
+	});
+}
+
+#[test]
+fn lagged() {
+	let (sender, mut receiver) = broadcast::channel(2);
+	for i in 0..5 {
+		sender.send(i).unwrap();
+	}
+	fumio::run(async move {
+		assert_eq!(receiver.recv().await, Err(broadcast::RecvError::Lagged(3)));
+		assert_eq!(receiver.recv().await, Ok(3));
+		assert_eq!(receiver.recv().await, Ok(4));
+	});
+	let (sender, receiver) = broadcast::channel(2);
+	drop(receiver);
+	assert_eq!(sender.send(1), Err(broadcast::SendError(1)));
+}
@@ -0,0 +1,57 @@
+use fumio::io::BufStream;
+use fumio::net::{TcpListener, TcpStream};
+use futures::future;
+use futures::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt};
+use std::io;
+
+#[test]
+fn lines() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_port(0)?;
+		let server_addr = l.local_addr()?;
+
+		let server = async {
+			let (s, _) = l.incoming().await?;
+			let mut s = BufStream::with_capacity(16, 16, s);
+			let mut line = String::new();
+			loop {
+				line.clear();
+				if s.read_line(&mut line).await? == 0 {
+					break;
+				}
+				// echo in upper case; buffered until flushed
+				s.write_all(line.to_uppercase().as_bytes()).await?;
+			}
+			s.close().await?;
+			Ok::<_, io::Error>(())
+		};
+
+		let client = async {
+			let s = TcpStream::connect(server_addr).await?;
+			let mut s = BufStream::new(s);
+			s.write_all(b"hello\nworld, this line is longer than the server buffers\n").await?;
+			assert_eq!(s.write_buffer().len(), 57);
+			s.close().await?;
+			let mut response = String::new();
+			s.read_to_string(&mut response).await?;
+			assert_eq!(response, "HELLO\nWORLD, THIS LINE IS LONGER THAN THE SERVER BUFFERS\n");
+			Ok::<_, io::Error>(())
+		};
+
+		future::try_join(server, client).await?;
+		Ok::<_, io::Error>(())
+	}).unwrap();
+}
+
+#[test]
+fn large_write_bypasses_buffer() {
+	fumio::run(async {
+		let data = vec![0x42u8; 100];
+		let mut s = BufStream::with_capacity(16, 16, Vec::new());
+		s.write_all(b"head").await.unwrap();
+		s.write_all(&data).await.unwrap();
+		// the buffered "head" was written first
+		assert!(s.write_buffer().is_empty());
+		assert_eq!(s.get_ref().len(), 104);
+	});
+}
@@ -0,0 +1,37 @@
+use fumio::net::{TcpListener, TcpStream};
+use fumio::reactor::CloseOffloader;
+use futures::prelude::*;
+use std::sync::mpsc;
+
+#[test]
+fn close_in_helper_thread() {
+	struct ReportDrop(mpsc::Sender<Option<String>>);
+	impl Drop for ReportDrop {
+		fn drop(&mut self) {
+			let _ = self.0.send(std::thread::current().name().map(String::from));
+		}
+	}
+
+	let offloader = CloseOffloader::new().unwrap();
+	let (tx, rx) = mpsc::channel();
+	offloader.clone().close(ReportDrop(tx));
+	assert_eq!(rx.recv().unwrap().as_deref(), Some("fumio-close"));
+}
+
+#[test]
+fn offload_tcp_close() {
+	fumio::run(async {
+		let offloader = CloseOffloader::new()?;
+		let mut l = TcpListener::bind_ipv4_port(0)?;
+		let mut client = TcpStream::connect(l.local_addr()?).await?;
+		let (mut conn, _) = l.incoming().await?;
+		conn.set_close_offloader(Some(offloader));
+		conn.write_all(b"bye").await?;
+		drop(conn);
+
+		let mut buf = Vec::new();
+		client.read_to_end(&mut buf).await?;
+		assert_eq!(buf, b"bye");
+		Ok::<_, std::io::Error>(())
+	}).unwrap();
+}
@@ -0,0 +1,28 @@
+use fumio::net::{TcpListener, TcpStream};
+use futures::future;
+use std::time::{Duration, Instant};
+
+#[test]
+fn expired_deadline() {
+	fumio::run(async {
+		let l = TcpListener::bind_ipv4_port(0).unwrap();
+		let server_addr = l.local_addr().unwrap();
+
+		// the reactor didn't run yet, so the connection can't be established already
+		let err = TcpStream::connect(server_addr).with_deadline(future::ready(())).await.unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+	});
+}
+
+#[test]
+fn connect_before_deadline() {
+	fumio::run(async {
+		let l = TcpListener::bind_ipv4_port(0).unwrap();
+		let server_addr = l.local_addr().unwrap();
+
+		let timer = fumio::current().unwrap().timer();
+		let deadline = timer.delay(Instant::now() + Duration::from_secs(10));
+		let stream = TcpStream::connect(server_addr).with_deadline(deadline).await.unwrap();
+		assert_eq!(stream.peer_addr().unwrap().port(), server_addr.port());
+	});
+}
@@ -0,0 +1,49 @@
+use fumio::net::TcpStream;
+use futures::prelude::*;
+use std::cell::Cell;
+use std::io::Write;
+use std::rc::Rc;
+
+const DATA_LEN: usize = 4096;
+
+// connection with `DATA_LEN` bytes ready to read
+fn ready_connection() -> TcpStream {
+	let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+	let mut client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+	let (server, _) = listener.accept().unwrap();
+	client.write_all(&[0u8; DATA_LEN]).unwrap();
+	drop(client);
+	TcpStream::from_std(server, Default::default()).unwrap()
+}
+
+fn read_bytewise(budget: Option<u32>) -> usize {
+	let mut runtime = fumio::Runtime::builder().coop_budget(budget).build().unwrap();
+	let read = Rc::new(Cell::new(0));
+	let read_when_observed = Rc::new(Cell::new(None));
+
+	let reader_read = read.clone();
+	let mut conn = ready_connection();
+	runtime.spawn(async move {
+		let mut buf = [0u8; 1];
+		while conn.read(&mut buf).await.unwrap() > 0 {
+			reader_read.set(reader_read.get() + 1);
+		}
+	}).unwrap();
+	let observer_read = read_when_observed.clone();
+	runtime.spawn(async move {
+		observer_read.set(Some(read.get()));
+	}).unwrap();
+
+	runtime.enter_run(&mut fumio::enter().unwrap());
+	read_when_observed.get().unwrap()
+}
+
+#[test]
+fn budget_forces_yield() {
+	assert_eq!(read_bytewise(Some(16)), 16);
+}
+
+#[test]
+fn unconstrained_runs_to_completion() {
+	assert_eq!(read_bytewise(None), DATA_LEN);
+}
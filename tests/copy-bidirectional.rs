@@ -0,0 +1,53 @@
+use fumio::net::{TcpListener, TcpStream};
+use futures::future;
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use std::io;
+
+#[test]
+fn proxy() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_port(0)?;
+		let server_addr = l.local_addr()?;
+		let request = vec![0x42u8; 100_000];
+		let response = vec![0x17u8; 200_000];
+
+		// accepts the client first, then the backend, and forwards between them
+		let proxy = async {
+			let (mut client, _) = l.incoming().await?;
+			let (mut backend, _) = l.incoming().await?;
+			let amounts = fumio::io::copy_bidirectional(&mut client, &mut backend).await?;
+			assert_eq!(amounts, (100_000, 200_000));
+			Ok::<_, io::Error>(())
+		};
+
+		let peers = async {
+			let mut client = TcpStream::connect(server_addr).await?;
+			let mut backend = TcpStream::connect(server_addr).await?;
+
+			let client = async {
+				client.write_all(&request).await?;
+				// half-close; the response still arrives
+				client.close().await?;
+				let mut buf = Vec::new();
+				client.read_to_end(&mut buf).await?;
+				assert!(buf == response);
+				Ok::<_, io::Error>(())
+			};
+
+			let backend = async {
+				let mut buf = Vec::new();
+				backend.read_to_end(&mut buf).await?;
+				assert!(buf == request);
+				backend.write_all(&response).await?;
+				backend.close().await?;
+				Ok::<_, io::Error>(())
+			};
+
+			future::try_join(client, backend).await?;
+			Ok::<_, io::Error>(())
+		};
+
+		future::try_join(proxy, peers).await?;
+		Ok::<_, io::Error>(())
+	}).unwrap();
+}
@@ -0,0 +1,41 @@
+use std::cell::RefCell;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+#[test]
+#[should_panic(expected = "runtime deadlocked")]
+fn panic_on_deadlock() {
+	let mut runtime = fumio::Runtime::builder().panic_on_deadlock().build().unwrap();
+	let (_sender, receiver) = futures::channel::oneshot::channel::<()>();
+	runtime.run_until(receiver).unwrap();
+}
+
+#[test]
+fn hook_lists_stuck_tasks() {
+	let stuck = Rc::new(RefCell::new(None));
+	let mut runtime = {
+		let stuck = stuck.clone();
+		fumio::Runtime::builder().deadlock_hook(move |tasks| {
+			*stuck.borrow_mut() = Some(tasks.len());
+			panic!("stuck");
+		}).build().unwrap()
+	};
+	let (_sender, receiver) = futures::channel::oneshot::channel::<()>();
+	runtime.spawn(async move {
+		let _ = receiver.await;
+	}).unwrap();
+	runtime.spawn(async {}).unwrap();
+	let result = panic::catch_unwind(AssertUnwindSafe(|| runtime.run_until(futures::future::pending::<()>())));
+	assert!(result.is_err());
+	assert_eq!(*stuck.borrow(), Some(1));
+}
+
+#[test]
+fn timers_are_no_deadlock() {
+	let mut runtime = fumio::Runtime::builder().panic_on_deadlock().build().unwrap();
+	runtime.run_until(async {
+		let timer = fumio::current().unwrap().timer();
+		timer.delay(Instant::now() + Duration::from_millis(10)).await;
+	});
+}
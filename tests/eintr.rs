@@ -0,0 +1,36 @@
+#![cfg(unix)]
+
+use fumio::reactor::Reactor;
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+extern "C" fn ignore_signal(_: libc::c_int) {}
+
+#[test]
+fn poll_interrupted_by_signal() {
+	unsafe {
+		let mut action: libc::sigaction = std::mem::zeroed();
+		action.sa_sigaction = ignore_signal as extern "C" fn(libc::c_int) as usize;
+		assert_eq!(libc::sigaction(libc::SIGUSR1, &action, std::ptr::null_mut()), 0);
+	}
+
+	let (tx, rx) = mpsc::channel();
+	let poller = thread::spawn(move || {
+		let mut reactor = Reactor::new().unwrap();
+		tx.send(unsafe { libc::pthread_self() }).unwrap();
+		let start = Instant::now();
+		let stats = reactor.poll_stats(Some(Duration::from_secs(10))).unwrap();
+		(stats, start.elapsed())
+	});
+	let thread = rx.recv().unwrap();
+	thread::sleep(Duration::from_millis(100));
+	unsafe {
+		libc::pthread_kill(thread, libc::SIGUSR1);
+	}
+
+	let (stats, elapsed) = poller.join().unwrap();
+	assert_eq!(stats.events, 0);
+	assert!(!stats.woken);
+	assert!(elapsed < Duration::from_secs(5));
+}
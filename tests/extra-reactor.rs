@@ -0,0 +1,36 @@
+#![cfg(unix)]
+
+use fumio::net::{TcpListener, TcpStream};
+use futures::prelude::*;
+
+#[test]
+fn socket_on_extra_reactor() {
+	let mut runtime = fumio::Runtime::builder().extra_reactor("storage").build().unwrap();
+	let storage = runtime.extra_reactor("storage").unwrap();
+	assert!(runtime.extra_reactor("other").is_none());
+	assert!(runtime.handle().extra_reactor("storage").is_some());
+
+	let client_handle = storage.clone();
+	runtime.run_until(async move {
+		let mut l = TcpListener::bind_port(0).unwrap();
+		let addr = l.local_addr().unwrap();
+		let connect = TcpStream::connect_with(addr, client_handle.into());
+		let (client, server) = futures::join!(connect, l.incoming());
+		let mut client = client.unwrap();
+		let (mut server, _) = server.unwrap();
+
+		// client waits for data through the extra reactor
+		let read = async move {
+			let mut buf = [0u8; 4];
+			client.read_exact(&mut buf).await.unwrap();
+			buf
+		};
+		let write = async move {
+			server.write_all(b"ping").await.unwrap();
+		};
+		let (buf, ()) = futures::join!(read, write);
+		assert_eq!(&buf, b"ping");
+	});
+
+	assert!(storage.metrics().unwrap().events > 0);
+}
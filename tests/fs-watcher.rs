@@ -0,0 +1,85 @@
+#![cfg(any(
+	target_os = "linux",
+	target_os = "macos",
+	target_os = "ios",
+	target_os = "freebsd",
+	target_os = "dragonfly",
+	target_os = "openbsd",
+))]
+
+use fumio::fs::{EventKind, Watcher};
+use futures::prelude::*;
+
+#[cfg(target_os = "linux")]
+#[test]
+fn directory_changes() {
+	let dir = std::env::temp_dir().join(format!("fumio-watcher-{}", std::process::id()));
+	std::fs::create_dir(&dir).unwrap();
+
+	fumio::run(async {
+		let mut watcher = Watcher::new().unwrap();
+		let watch = watcher.watch(&dir).unwrap();
+		assert_eq!(watcher.path(watch), Some(dir.as_path()));
+
+		let file = dir.join("test.txt");
+		let renamed = dir.join("renamed.txt");
+		std::fs::write(&file, b"content").unwrap();
+		std::fs::rename(&file, &renamed).unwrap();
+		std::fs::remove_file(&renamed).unwrap();
+
+		let mut events = Vec::new();
+		while events.last().map(|&(ref path, kind)| (path == &renamed, kind)) != Some((true, EventKind::Remove)) {
+			let event = watcher.next().await.unwrap().unwrap();
+			assert_eq!(event.watch, Some(watch));
+			events.push((event.path, event.kind));
+		}
+		assert_eq!(events, vec![
+			(file.clone(), EventKind::Create),
+			(file.clone(), EventKind::Modify),
+			(file, EventKind::MovedFrom),
+			(renamed.clone(), EventKind::MovedTo),
+			(renamed, EventKind::Remove),
+		]);
+
+		watcher.unwatch(watch).unwrap();
+		assert_eq!(watcher.path(watch), None);
+	});
+
+	std::fs::remove_dir(&dir).unwrap();
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn next(watcher: &mut Watcher) -> (std::path::PathBuf, EventKind) {
+	let event = watcher.next().await.unwrap().unwrap();
+	assert!(event.watch.is_some());
+	(event.path, event.kind)
+}
+
+// kqueue only sees the difference between directory listings: wait for each change
+#[cfg(not(target_os = "linux"))]
+#[test]
+fn directory_changes() {
+	let dir = std::env::temp_dir().join(format!("fumio-watcher-{}", std::process::id()));
+	std::fs::create_dir(&dir).unwrap();
+
+	fumio::run(async {
+		let mut watcher = Watcher::new().unwrap();
+		let watch = watcher.watch(&dir).unwrap();
+		assert_eq!(watcher.path(watch), Some(dir.as_path()));
+
+		let file = dir.join("test.txt");
+		let renamed = dir.join("renamed.txt");
+		std::fs::write(&file, b"content").unwrap();
+		assert_eq!(next(&mut watcher).await, (file.clone(), EventKind::Create));
+		std::fs::rename(&file, &renamed).unwrap();
+		assert_eq!(next(&mut watcher).await, (file, EventKind::MovedFrom));
+		assert_eq!(next(&mut watcher).await, (renamed.clone(), EventKind::MovedTo));
+		std::fs::remove_file(&renamed).unwrap();
+		assert_eq!(next(&mut watcher).await, (renamed, EventKind::Remove));
+
+		watcher.unwatch(watch).unwrap();
+		assert_eq!(watcher.path(watch), None);
+	});
+
+	std::fs::remove_dir(&dir).unwrap();
+}
@@ -0,0 +1,26 @@
+use fumio::reactor::LazyHandle;
+
+#[test]
+fn compare_handles() {
+	let first = fumio::Runtime::new().unwrap();
+	let second = fumio::Runtime::new().unwrap();
+	let (a, b) = (first.handle(), second.handle());
+	assert!(a.ptr_eq(&first.handle()));
+	assert!(!a.ptr_eq(&b));
+	assert!(a.reactor().same_reactor(&first.handle().reactor()));
+	assert!(!a.reactor().same_reactor(&b.reactor()));
+	assert!(a.spawner().same_pool(&first.handle().spawner()));
+	assert!(!a.spawner().same_pool(&b.spawner()));
+}
+
+#[test]
+fn lazy_handle_binds_current() {
+	let other = fumio::Runtime::new().unwrap();
+	fumio::run(async move {
+		let runtime = fumio::current().unwrap();
+		let reactor = LazyHandle::new().bind().unwrap();
+		assert!(reactor.same_reactor(&runtime.reactor()));
+		assert!(!reactor.same_reactor(&other.handle().reactor()));
+		assert!(!runtime.ptr_eq(&other.handle()));
+	});
+}
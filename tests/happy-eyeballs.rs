@@ -0,0 +1,31 @@
+use fumio::net::{connect_happy, TcpListener};
+use std::net::SocketAddr;
+
+fn refused_addr() -> SocketAddr {
+	// nothing listens on the port after the listener is gone
+	let l = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+	l.local_addr().unwrap()
+}
+
+#[test]
+fn skips_failing_addresses() {
+	fumio::run(async {
+		let mut l = TcpListener::bind(([127, 0, 0, 1], 0).into()).unwrap();
+		let server_addr = l.local_addr().unwrap();
+
+		let stream = connect_happy(vec![refused_addr(), server_addr]).await.unwrap();
+		assert_eq!(stream.peer_addr().unwrap(), server_addr);
+		let _conn = l.incoming().await.unwrap();
+	});
+}
+
+#[test]
+fn all_failing() {
+	fumio::run(async {
+		let err = connect_happy(vec![refused_addr(), refused_addr()]).await.unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+
+		let err = connect_happy(Vec::new()).await.unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+	});
+}
@@ -0,0 +1,49 @@
+#![cfg(feature = "hyper-compat")]
+
+use fumio::hyper::{HyperAccept, HyperExecutor};
+use fumio::net::{TcpListener, TcpStream};
+use futures::future;
+use futures::prelude::*;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Response};
+use std::convert::Infallible;
+use std::rc::Rc;
+
+#[test]
+fn serve_local() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_port(0)?;
+		let server_addr = l.local_addr()?;
+		// not `Send`: must run on the local pool
+		let greeting = Rc::new("hello world");
+
+		let server = hyper::Server::builder(HyperAccept::new(l.incoming()))
+			.executor(HyperExecutor::current())
+			.serve(make_service_fn(move |_| {
+				let greeting = greeting.clone();
+				async move {
+					Ok::<_, Infallible>(service_fn(move |_req| {
+						let greeting = greeting.clone();
+						async move { Ok::<_, Infallible>(Response::new(Body::from(*greeting))) }
+					}))
+				}
+			}));
+
+		let client = async {
+			let mut s = TcpStream::connect(server_addr).await?;
+			s.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").await?;
+			let mut buf = String::new();
+			s.read_to_string(&mut buf).await?;
+			assert!(buf.starts_with("HTTP/1.1 200 OK\r\n"));
+			assert!(buf.ends_with("\r\n\r\nhello world"));
+			Ok::<_, std::io::Error>(())
+		};
+
+		futures::pin_mut!(client);
+		let result = match future::select(client, server).await {
+			future::Either::Left((result, _)) => result,
+			future::Either::Right(_) => panic!("server stopped"),
+		};
+		result
+	}).unwrap();
+}
@@ -0,0 +1,26 @@
+use fumio::net::TcpListener;
+use futures::future::{self, FutureExt};
+use futures::task::Poll;
+
+#[test]
+fn limit_outstanding() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_ipv4_port(0).unwrap();
+		let server_addr = l.local_addr().unwrap();
+		let _c1 = std::net::TcpStream::connect(server_addr).unwrap();
+		let _c2 = std::net::TcpStream::connect(server_addr).unwrap();
+
+		let mut incoming = l.incoming_limited(1);
+		let (_conn1, _, permit) = (&mut incoming).await.unwrap();
+		assert_eq!(incoming.outstanding(), 1);
+
+		// second connection is pending in the backlog, but limit is reached
+		let blocked = future::poll_fn(|cx| Poll::Ready(incoming.poll_unpin(cx).is_pending())).await;
+		assert!(blocked);
+
+		drop(permit);
+		assert_eq!(incoming.outstanding(), 0);
+		let (_conn2, _, _permit) = (&mut incoming).await.unwrap();
+		assert_eq!(incoming.outstanding(), 1);
+	});
+}
@@ -0,0 +1,44 @@
+use fumio::net::{TcpListener, TcpStream, UdpSocket};
+use futures::prelude::*;
+use std::io::{Read, Write};
+
+#[test]
+fn tcp_into_std() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_ipv4_port(0).unwrap();
+		let server_addr = l.local_addr().unwrap();
+		let mut client = TcpStream::connect(server_addr).await.unwrap();
+		let (mut conn, _) = l.incoming().await.unwrap();
+
+		client.write_all(b"async").await.unwrap();
+		let mut buf = [0u8; 5];
+		conn.read_exact(&mut buf).await.unwrap();
+		assert_eq!(&buf, b"async");
+
+		// blocking from here on
+		let mut client = client.into_std(false).unwrap();
+		let mut conn = conn.into_std(false).unwrap();
+		client.write_all(b"block").unwrap();
+		Read::read_exact(&mut conn, &mut buf).unwrap();
+		assert_eq!(&buf, b"block");
+
+		let l = l.into_std(true).unwrap();
+		assert_eq!(l.accept().unwrap_err().kind(), std::io::ErrorKind::WouldBlock);
+	});
+}
+
+#[test]
+fn udp_into_std() {
+	fumio::run(async {
+		let mut a = UdpSocket::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+		let b = UdpSocket::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+		let b_addr = b.local_addr().unwrap();
+		a.send_to(b"x", &b_addr).await.unwrap();
+
+		let b = b.into_std(false).unwrap();
+		let mut buf = [0u8; 4];
+		let (len, from) = b.recv_from(&mut buf).unwrap();
+		assert_eq!(&buf[..len], b"x");
+		assert_eq!(from, a.local_addr().unwrap());
+	});
+}
@@ -0,0 +1,54 @@
+use fumio::io::{TimeoutReader, TimeoutWriter};
+use fumio::net::{TcpListener, TcpStream};
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use std::io;
+use std::time::{Duration, Instant};
+
+#[test]
+fn read_timeout() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_port(0)?;
+		let mut client = TcpStream::connect(l.local_addr()?).await?;
+		let (conn, _) = l.incoming().await?;
+		let mut conn = TimeoutReader::new(conn, Some(Duration::from_millis(20)));
+
+		client.write_all(b"ping").await?;
+		let mut buf = [0u8; 4];
+		conn.read_exact(&mut buf).await?;
+		assert_eq!(&buf, b"ping");
+
+		// the peer stays silent
+		let start = Instant::now();
+		let err = conn.read(&mut buf).await.unwrap_err();
+		assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+		assert!(start.elapsed() >= Duration::from_millis(20));
+
+		// still usable after a timeout
+		conn.set_timeout(None);
+		assert_eq!(conn.timeout(), None);
+		client.write_all(b"pong").await?;
+		conn.read_exact(&mut buf).await?;
+		assert_eq!(&buf, b"pong");
+		Ok::<_, io::Error>(())
+	}).unwrap();
+}
+
+#[test]
+fn write_timeout() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_port(0)?;
+		let client = TcpStream::connect(l.local_addr()?).await?;
+		let (_conn, _) = l.incoming().await?;
+		let mut client = TimeoutWriter::new(client, Some(Duration::from_millis(20)));
+
+		// the peer doesn't read: the socket buffers fill up eventually
+		let data = vec![0u8; 64 * 1024];
+		let err = loop {
+			if let Err(e) = client.write_all(&data).await {
+				break e;
+			}
+		};
+		assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+		Ok::<_, io::Error>(())
+	}).unwrap();
+}
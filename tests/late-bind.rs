@@ -0,0 +1,26 @@
+use fumio::net::UdpSocket;
+use fumio::reactor::{Error, LazyHandle};
+use futures::future::poll_fn;
+use futures::task::Poll;
+
+#[test]
+fn bind_on_first_use_in_runtime() {
+	let std_socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+	let mut socket = UdpSocket::from_std(std_socket, LazyHandle::new()).unwrap();
+	let local = socket.local_addr().unwrap();
+
+	// used outside a runtime first: fails, but doesn't give up for good
+	let mut buf = [0u8; 16];
+	let err = futures::executor::block_on(socket.recv_from(&mut buf)).unwrap_err();
+	assert_eq!(Error::from_io(&err), Some(Error::NoReactor));
+
+	fumio::run(async move {
+		let mut sender = UdpSocket::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+		// registers (and waits) now
+		let pending = poll_fn(|cx| Poll::Ready(socket.poll_recv_from(cx, &mut buf).is_pending())).await;
+		assert!(pending);
+		sender.send_to(b"late", &local).await.unwrap();
+		let (len, _) = socket.recv_from(&mut buf).await.unwrap();
+		assert_eq!(&buf[..len], b"late");
+	});
+}
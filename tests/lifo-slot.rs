@@ -0,0 +1,37 @@
+use futures::channel::oneshot;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn wake_order(lifo_slot: bool) -> Vec<&'static str> {
+	let mut runtime = fumio::Runtime::builder().lifo_slot(lifo_slot).build().unwrap();
+	let log = Rc::new(RefCell::new(Vec::new()));
+	let (tx, rx) = oneshot::channel();
+
+	let receiver_log = log.clone();
+	runtime.spawn(async move {
+		rx.await.unwrap();
+		receiver_log.borrow_mut().push("receiver");
+	}).unwrap();
+	let sender_log = log.clone();
+	runtime.spawn(async move {
+		tx.send(()).unwrap();
+		sender_log.borrow_mut().push("sender");
+	}).unwrap();
+	let other_log = log.clone();
+	runtime.spawn(async move {
+		other_log.borrow_mut().push("other");
+	}).unwrap();
+
+	runtime.enter_run(&mut fumio::enter().unwrap());
+	log.replace(Vec::new())
+}
+
+#[test]
+fn woken_task_runs_next() {
+	assert_eq!(wake_order(true), ["sender", "receiver", "other"]);
+}
+
+#[test]
+fn disabled_lifo_slot_is_fifo() {
+	assert_eq!(wake_order(false), ["sender", "other", "receiver"]);
+}
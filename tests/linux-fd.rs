@@ -0,0 +1,43 @@
+#![cfg(target_os = "linux")]
+
+use fumio::reactor::{EventFd, TimerFd};
+use std::io::Write;
+use std::mem::ManuallyDrop;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::time::{Duration, Instant};
+
+#[test]
+fn eventfd_counter() {
+	fumio::run(async {
+		let mut event_fd = EventFd::new(0).unwrap();
+		event_fd.notify(2).unwrap();
+		event_fd.notify(3).unwrap();
+		assert_eq!(event_fd.read().await.unwrap(), 5);
+
+		// signal through the raw fd from another thread
+		let raw = event_fd.as_raw_fd();
+		let signal = std::thread::spawn(move || {
+			std::thread::sleep(Duration::from_millis(10));
+			let mut file = ManuallyDrop::new(unsafe { std::fs::File::from_raw_fd(raw) });
+			file.write_all(&7u64.to_ne_bytes()).unwrap();
+		});
+		assert_eq!(event_fd.read().await.unwrap(), 7);
+		signal.join().unwrap();
+	});
+}
+
+#[test]
+fn timerfd_expires() {
+	fumio::run(async {
+		let mut timer_fd = TimerFd::new().unwrap();
+		let start = Instant::now();
+		timer_fd.set(Duration::from_millis(20), None).unwrap();
+		assert_eq!(timer_fd.expired().await.unwrap(), 1);
+		assert!(start.elapsed() >= Duration::from_millis(20));
+
+		timer_fd.set(Duration::from_millis(1), Some(Duration::from_millis(1))).unwrap();
+		std::thread::sleep(Duration::from_millis(10));
+		assert!(timer_fd.expired().await.unwrap() >= 5);
+		timer_fd.disarm().unwrap();
+	});
+}
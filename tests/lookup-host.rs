@@ -0,0 +1,46 @@
+use fumio::net::{lookup_host, TcpListener, TcpStream};
+
+#[test]
+fn resolve_and_connect() {
+	fumio::run(async {
+		let addrs: Vec<_> = lookup_host("127.0.0.1:80").await.unwrap().collect();
+		assert_eq!(addrs, vec!["127.0.0.1:80".parse().unwrap()]);
+
+		let err = lookup_host("no port").await.unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+		let mut l = TcpListener::bind(([127, 0, 0, 1], 0).into()).unwrap();
+		let port = l.local_addr().unwrap().port();
+		let stream = TcpStream::connect(("localhost", port)).await.unwrap();
+		let (conn, _) = l.incoming().await.unwrap();
+		assert_eq!(stream.local_addr().unwrap(), conn.peer_addr().unwrap());
+	});
+}
+
+#[test]
+fn many_lookups() {
+	fumio::run(async {
+		// more lookups than resolver threads: the rest is queued
+		let lookups = (0..16).map(|port| lookup_host(("localhost", port)));
+		for (port, result) in futures::future::join_all(lookups).await.into_iter().enumerate() {
+			assert!(result.unwrap().all(|addr| usize::from(addr.port()) == port));
+		}
+	});
+}
+
+#[test]
+fn borrowed_targets() {
+	fumio::run(async {
+		let mut l = TcpListener::bind(([127, 0, 0, 1], 0).into()).unwrap();
+		let target = format!("localhost:{}", l.local_addr().unwrap().port());
+		let addrs: Vec<_> = lookup_host(&target).await.unwrap().collect();
+		assert!(addrs.contains(&l.local_addr().unwrap()));
+		let stream = TcpStream::connect(&target).await.unwrap();
+		let (conn, _) = l.incoming().await.unwrap();
+		assert_eq!(stream.local_addr().unwrap(), conn.peer_addr().unwrap());
+
+		let addrs = [l.local_addr().unwrap()];
+		let resolved: Vec<_> = lookup_host(&addrs[..]).await.unwrap().collect();
+		assert_eq!(resolved, addrs);
+	});
+}
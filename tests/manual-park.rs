@@ -0,0 +1,35 @@
+use fumio::pool::LocalPool;
+use fumio_utils::park::ManualPark;
+use futures::channel::oneshot;
+
+#[test]
+fn run_until_step_by_step() {
+	let mut pool = LocalPool::new();
+	let mut park = ManualPark::new();
+	park.set_max_parks(Some(10));
+
+	let (tx, rx) = oneshot::channel();
+	let mut tx = Some(tx);
+	// the "event" happens while the pool is parked
+	park.set_hook(move |_| {
+		if let Some(tx) = tx.take() {
+			tx.send(42).unwrap();
+		}
+	});
+
+	let mut enter = fumio::enter().unwrap();
+	let value = pool.run_until(&mut park, &mut enter, rx).unwrap();
+	assert_eq!(value, 42);
+	assert_eq!(park.parks(), [None]);
+	assert_eq!(park.wakeups(), 1);
+}
+
+#[test]
+#[should_panic(expected = "parked more than 3 times")]
+fn max_parks() {
+	let mut pool = LocalPool::new();
+	let mut park = ManualPark::new();
+	park.set_max_parks(Some(3));
+	let mut enter = fumio::enter().unwrap();
+	pool.run_until(&mut park, &mut enter, futures::future::pending::<()>());
+}
@@ -0,0 +1,28 @@
+use fumio::pool::LocalPool;
+use futures::task::{noop_waker_ref, LocalSpawnExt};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+#[test]
+fn limit_tasks_per_poll() {
+	let mut pool = LocalPool::new();
+	pool.set_max_tasks_per_tick(Some(2));
+	let log = Rc::new(RefCell::new(Vec::new()));
+
+	let spawner = pool.spawner();
+	for i in 0..5 {
+		let log = log.clone();
+		spawner.spawn_local(async move {
+			log.borrow_mut().push(i);
+		}).unwrap();
+	}
+
+	let mut cx = Context::from_waker(noop_waker_ref());
+	assert_eq!(pool.poll_pool(&mut cx), Poll::Pending);
+	assert_eq!(*log.borrow(), [0, 1]);
+	assert_eq!(pool.poll_pool(&mut cx), Poll::Pending);
+	assert_eq!(*log.borrow(), [0, 1, 2, 3]);
+	assert_eq!(pool.poll_pool(&mut cx), Poll::Ready(()));
+	assert_eq!(*log.borrow(), [0, 1, 2, 3, 4]);
+}
@@ -0,0 +1,80 @@
+use fumio::net::{TcpListener, TcpStream};
+use futures::prelude::*;
+
+#[test]
+fn count_tasks_and_events() {
+	let mut runtime = fumio::Runtime::new().unwrap();
+	let handle = runtime.handle();
+
+	runtime.spawn(async {
+		let mut l = TcpListener::bind_port(0).unwrap();
+		let addr = l.local_addr().unwrap();
+		let (client, server) = futures::join!(TcpStream::connect(addr), l.incoming());
+		let mut client = client.unwrap();
+		let (mut server, _) = server.unwrap();
+		client.write_all(b"ping").await.unwrap();
+		let mut buf = [0u8; 4];
+		server.read_exact(&mut buf).await.unwrap();
+	}).unwrap();
+	runtime.spawn(async {}).unwrap();
+	runtime.enter_run(&mut fumio::enter().unwrap());
+
+	let metrics = handle.metrics().unwrap();
+	assert_eq!(metrics, runtime.metrics());
+	assert_eq!(metrics.pool.tasks_spawned, 2);
+	assert_eq!(metrics.pool.tasks_completed, 2);
+	assert!(metrics.pool.task_polls > 2);
+	assert!(metrics.pool.local_wakeups > 0);
+	assert!(metrics.reactor.polls > 0);
+	assert!(metrics.reactor.events > 0);
+
+	drop(runtime);
+	assert!(handle.metrics().is_none());
+}
+
+#[test]
+fn reactor_poll_stats() {
+	use fumio::reactor::{Reactor, UserEvent};
+	use std::time::Duration;
+
+	let mut reactor = Reactor::new().unwrap();
+	let stats = reactor.poll_stats(Some(Duration::from_millis(0))).unwrap();
+	assert_eq!(stats.events, 0);
+	assert!(!stats.woken);
+
+	let event = UserEvent::with_handle(reactor.handle().into()).unwrap();
+	event.notifier().notify();
+	let stats = reactor.poll_stats(Some(Duration::from_secs(10))).unwrap();
+	assert_eq!(stats.events, 1);
+	assert!(stats.woken);
+	assert!(stats.blocked < Duration::from_secs(10));
+}
+
+#[test]
+fn coalesce_wakeups() {
+	use fumio::reactor::Reactor;
+	use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
+	use std::time::Duration;
+
+	let mut reactor = Reactor::new().unwrap();
+	let waker = reactor.waker();
+	let done = Arc::new(AtomicBool::new(false));
+	let thread = {
+		let done = done.clone();
+		std::thread::spawn(move || {
+			for _ in 0..1000 {
+				waker.wake_by_ref();
+			}
+			done.store(true, Ordering::SeqCst);
+		})
+	};
+	while !done.load(Ordering::SeqCst) {
+		reactor.poll(Some(Duration::from_millis(1))).unwrap();
+	}
+	thread.join().unwrap();
+
+	let metrics = reactor.metrics();
+	assert_eq!(metrics.wakeups, 1000);
+	assert!(metrics.wakeup_syscalls <= metrics.polls);
+	assert!(metrics.wakeup_syscalls < metrics.wakeups);
+}
@@ -0,0 +1,63 @@
+use fumio::sync::Notify;
+use futures::task::{noop_waker_ref, Context};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::Poll;
+
+fn poll_once<F: Future<Output = ()>>(future: Pin<&mut F>) -> bool {
+	let mut cx = Context::from_waker(noop_waker_ref());
+	match future.poll(&mut cx) {
+		Poll::Ready(()) => true,
+		Poll::Pending => false,
+	}
+}
+
+#[test]
+fn notify_one() {
+	let notify = Notify::new();
+	// stored notification
+	notify.notify_one();
+	notify.notify_one();
+	assert!(poll_once(Box::pin(notify.notified()).as_mut()));
+	assert!(!poll_once(Box::pin(notify.notified()).as_mut()));
+
+	let mut first = Box::pin(notify.notified());
+	let mut second = Box::pin(notify.notified());
+	assert!(!poll_once(first.as_mut()));
+	assert!(!poll_once(second.as_mut()));
+	notify.notify_one();
+	assert!(!poll_once(second.as_mut()));
+	// notification passed on when dropped without consuming it
+	drop(first);
+	assert!(poll_once(second.as_mut()));
+}
+
+#[test]
+fn notify_waiters() {
+	let notify = Notify::new();
+	let mut first = Box::pin(notify.notified());
+	let mut second = Box::pin(notify.notified());
+	let mut late = Box::pin(notify.notified());
+	assert!(!poll_once(first.as_mut()));
+	assert!(!poll_once(second.as_mut()));
+	notify.notify_waiters();
+	assert!(poll_once(first.as_mut()));
+	assert!(poll_once(second.as_mut()));
+	// not stored
+	assert!(!poll_once(late.as_mut()));
+}
+
+#[test]
+fn wakes_tasks() {
+	fumio::run(async {
+		let notify = std::rc::Rc::new(Notify::new());
+		let (task, done) = futures::future::FutureExt::remote_handle({
+			let notify = notify.clone();
+			async move { notify.notified().await }
+		});
+		fumio::current().unwrap().spawn(task).unwrap();
+		fumio::current().unwrap().timer().delay(std::time::Instant::now()).await;
+		notify.notify_waiters();
+		done.await;
+	});
+}
@@ -0,0 +1,47 @@
+use fumio::pool::PanicPolicy;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::task::Poll;
+
+// completes on second poll, giving spawned tasks a chance to run
+fn yield_once<T>(value: T) -> impl std::future::Future<Output = T> + Unpin {
+	let mut value = Some(value);
+	let mut yielded = false;
+	futures::future::poll_fn(move |cx| {
+		if yielded {
+			Poll::Ready(value.take().unwrap())
+		} else {
+			yielded = true;
+			cx.waker().wake_by_ref();
+			Poll::Pending
+		}
+	})
+}
+
+#[test]
+fn drop_task_continues_with_hook() {
+	let panics = Rc::new(RefCell::new(Vec::new()));
+	let hook_panics = panics.clone();
+	let mut runtime = fumio::Runtime::builder()
+		.panic_policy(PanicPolicy::DropTask)
+		.panic_hook(move |p| hook_panics.borrow_mut().push(p.message().unwrap().to_string()))
+		.build()
+		.unwrap();
+
+	runtime.spawn(async { panic!("task failed") }).unwrap();
+	let result = runtime.run_until(yield_once(42));
+	assert_eq!(result, 42);
+	assert_eq!(*panics.borrow(), vec!["task failed".to_string()]);
+}
+
+#[test]
+fn propagate_by_default() {
+	let mut runtime = fumio::Runtime::new().unwrap();
+	runtime.spawn(async { panic!("task failed") }).unwrap();
+	let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+		runtime.run_until(futures::future::pending::<()>())
+	}));
+	assert!(result.is_err());
+	// the panicking task was dropped, the pool is still usable
+	assert_eq!(runtime.run_until(async { 1 }), 1);
+}
@@ -0,0 +1,75 @@
+use fumio::pool::LocalPool;
+use futures::channel::oneshot;
+use futures::task::{noop_waker_ref, LocalSpawnExt};
+use std::task::Context;
+
+#[test]
+fn task_counts() {
+	let mut pool = LocalPool::new();
+	assert!(pool.is_empty());
+	assert!(pool.is_idle());
+
+	let (tx, rx) = oneshot::channel::<()>();
+	let spawner = pool.spawner();
+	spawner.spawn_local(async move {
+		rx.await.unwrap();
+	}).unwrap();
+	spawner.spawn_local(async {}).unwrap();
+	assert_eq!(pool.len(), 2);
+	assert_eq!(pool.pending_len(), 2);
+
+	let mut cx = Context::from_waker(noop_waker_ref());
+	let _ = pool.poll_pool(&mut cx);
+	assert_eq!(pool.len(), 1);
+	assert_eq!(pool.pending_len(), 0);
+	assert!(pool.is_idle());
+
+	// wake from another thread
+	std::thread::spawn(move || tx.send(()).unwrap()).join().unwrap();
+	assert!(!pool.is_idle());
+	assert_eq!(pool.pending_len(), 1);
+	let _ = pool.poll_pool(&mut cx);
+	assert!(pool.is_empty());
+}
+
+#[test]
+fn counts_after_lifo_and_panics() {
+	let mut pool = LocalPool::new();
+	pool.set_panic_policy(fumio::pool::PanicPolicy::DropTask);
+	let spawner = pool.spawner();
+
+	// woken through the LIFO slot
+	let (tx, rx) = oneshot::channel::<()>();
+	spawner.spawn_local(async move {
+		rx.await.unwrap();
+	}).unwrap();
+	spawner.spawn_local(async move {
+		tx.send(()).unwrap();
+	}).unwrap();
+	// wakes itself before completing
+	spawner.spawn_local(async {
+		fumio::task::yield_now().await;
+	}).unwrap();
+	// wakes itself before panicking
+	spawner.spawn_local(async {
+		futures::future::poll_fn(|cx| {
+			cx.waker().wake_by_ref();
+			std::task::Poll::<()>::Ready(())
+		}).await;
+		panic!("task failed");
+	}).unwrap();
+	spawner.spawn_local(futures::future::pending()).unwrap();
+	assert_eq!(pool.len(), 5);
+	assert_eq!(pool.pending_len(), 5);
+
+	let mut cx = Context::from_waker(noop_waker_ref());
+	let _ = pool.poll_pool(&mut cx);
+	assert_eq!(pool.len(), 2);
+	assert_eq!(pool.pending_len(), 1);
+	let _ = pool.poll_pool(&mut cx);
+	assert_eq!(pool.len(), 1);
+	assert_eq!(pool.pending_len(), 0);
+	pool.clear();
+	assert_eq!(pool.len(), 0);
+	assert!(pool.is_empty());
+}
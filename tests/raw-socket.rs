@@ -0,0 +1,49 @@
+#![cfg(all(unix, feature = "raw-socket"))]
+
+use fumio::net::RawSocket;
+use fumio::net::socket2::{Domain, Protocol, Type};
+use std::net::SocketAddr;
+
+// ICMP echo request with identifier 0x1234 and sequence 1
+fn echo_request(payload: &[u8]) -> Vec<u8> {
+	let mut packet = vec![8, 0, 0, 0, 0x12, 0x34, 0, 1];
+	packet.extend_from_slice(payload);
+	let mut sum = 0u32;
+	for chunk in packet.chunks(2) {
+		sum += u32::from(u16::from_be_bytes([chunk[0], *chunk.get(1).unwrap_or(&0)]));
+	}
+	while sum > 0xffff {
+		sum = (sum & 0xffff) + (sum >> 16);
+	}
+	packet[2..4].copy_from_slice(&(!(sum as u16)).to_be_bytes());
+	packet
+}
+
+#[test]
+fn ping_localhost() {
+	fumio::run(async {
+		let socket = RawSocket::icmp_v4().or_else(|_| RawSocket::new(Domain::IPV4, Type::RAW, Some(Protocol::ICMPV4)));
+		let mut socket = match socket {
+			Ok(socket) => socket,
+			Err(_) => return, // neither unprivileged ICMP sockets nor raw sockets allowed
+		};
+		let target: SocketAddr = "127.0.0.1:0".parse().unwrap();
+		let request = echo_request(b"fumio ping");
+		assert_eq!(socket.send_to(&request, &target).await.unwrap(), request.len());
+
+		let mut buf = [0u8; 1500];
+		loop {
+			let (n, from) = socket.recv_from(&mut buf).await.unwrap();
+			assert_eq!(from.ip(), target.ip());
+			let packet = &buf[..n];
+			// raw sockets include the IPv4 header
+			let packet = if packet[0] >> 4 == 4 { &packet[usize::from(packet[0] & 0xf) * 4..] } else { packet };
+			if packet[0] == 0 {
+				// echo reply
+				assert!(packet.ends_with(b"fumio ping"));
+				break;
+			}
+			// raw sockets see the request too
+		}
+	});
+}
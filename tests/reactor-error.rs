@@ -0,0 +1,47 @@
+use fumio::reactor::{Error, Reactor, UserEvent};
+
+#[test]
+fn no_reactor() {
+	let err = UserEvent::new().unwrap_err();
+	assert_eq!(Error::from_io(&err), Some(Error::NoReactor));
+}
+
+#[test]
+fn reactor_gone() {
+	let reactor = Reactor::new().unwrap();
+	let handle = reactor.handle();
+	drop(reactor);
+	let err = UserEvent::with_handle(handle.into()).unwrap_err();
+	assert_eq!(Error::from_io(&err), Some(Error::ReactorGone));
+
+	// genuine IO errors aren't reactor errors
+	assert_eq!(Error::from_io(&std::io::ErrorKind::NotFound.into()), None);
+}
+
+#[test]
+fn wake_on_reactor_drop() {
+	use fumio::net::UdpSocket;
+
+	let reactor = Reactor::new().unwrap();
+	let mut socket = UdpSocket::bind_with("127.0.0.1:0".parse().unwrap(), reactor.handle().into()).unwrap();
+	let (tx, rx) = std::sync::mpsc::channel();
+	let waiting = std::thread::spawn(move || {
+		futures::executor::block_on(async move {
+			let mut buf = [0u8; 16];
+			// registers with the reactor; the first poll is pending
+			let recv = socket.recv_from(&mut buf);
+			futures::pin_mut!(recv);
+			futures::future::poll_fn(|cx| {
+				let r = std::future::Future::poll(recv.as_mut(), cx);
+				if r.is_pending() {
+					let _ = tx.send(());
+				}
+				r
+			}).await
+		})
+	});
+	rx.recv().unwrap();
+	drop(reactor);
+	let err = waiting.join().unwrap().unwrap_err();
+	assert_eq!(Error::from_io(&err), Some(Error::ReactorGone));
+}
@@ -0,0 +1,18 @@
+use fumio::reactor::Reactor;
+
+#[test]
+fn on_shutdown() {
+	let reactor = Reactor::new().unwrap();
+	let handle = reactor.handle();
+	assert!(handle.is_alive());
+
+	let shutdown = handle.on_shutdown();
+	let waiting = std::thread::spawn(move || futures::executor::block_on(shutdown));
+	std::thread::sleep(std::time::Duration::from_millis(20));
+	drop(reactor);
+	waiting.join().unwrap();
+
+	assert!(!handle.is_alive());
+	// already gone: completes right away
+	futures::executor::block_on(handle.on_shutdown());
+}
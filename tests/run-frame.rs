@@ -0,0 +1,33 @@
+use std::time::{Duration, Instant};
+
+#[test]
+fn frame_budget() {
+	let mut runtime = fumio::Runtime::new().unwrap();
+	// busy task never completes, but yields
+	runtime.spawn(async {
+		loop {
+			fumio::task::yield_now().await;
+		}
+	}).unwrap();
+
+	let start = Instant::now();
+	assert!(!runtime.run_frame(Duration::from_millis(20)));
+	let elapsed = start.elapsed();
+	assert!(elapsed >= Duration::from_millis(20));
+	assert!(elapsed < Duration::from_secs(1));
+	assert!(runtime.metrics().pool.task_polls > 1);
+}
+
+#[test]
+fn frame_completes_early() {
+	let mut runtime = fumio::Runtime::new().unwrap();
+	runtime.spawn(async {
+		fumio::current().unwrap().timer().delay(Instant::now() + Duration::from_millis(10)).await;
+	}).unwrap();
+
+	let start = Instant::now();
+	assert!(runtime.run_frame(Duration::from_secs(5)));
+	assert!(start.elapsed() < Duration::from_secs(1));
+	// nothing left to run
+	assert!(runtime.run_frame(Duration::from_secs(5)));
+}
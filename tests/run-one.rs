@@ -0,0 +1,26 @@
+use fumio::pool::LocalPool;
+use futures::task::{noop_waker_ref, LocalSpawnExt};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::task::Context;
+
+#[test]
+fn try_run_one_polls_single_task() {
+	let mut pool = LocalPool::new();
+	let log = Rc::new(RefCell::new(Vec::new()));
+
+	let spawner = pool.spawner();
+	for i in 0..2 {
+		let log = log.clone();
+		spawner.spawn_local(async move {
+			log.borrow_mut().push(i);
+		}).unwrap();
+	}
+
+	let mut cx = Context::from_waker(noop_waker_ref());
+	assert!(pool.try_run_one(&mut cx));
+	assert_eq!(*log.borrow(), [0]);
+	assert!(pool.try_run_one(&mut cx));
+	assert_eq!(*log.borrow(), [0, 1]);
+	assert!(!pool.try_run_one(&mut cx));
+}
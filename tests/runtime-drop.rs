@@ -0,0 +1,52 @@
+use fumio::net::UdpSocket;
+use std::cell::Cell;
+use std::rc::Rc;
+
+struct DropFlag(Rc<Cell<bool>>);
+
+impl Drop for DropFlag {
+	fn drop(&mut self) {
+		self.0.set(true);
+	}
+}
+
+struct PanicOnDrop;
+
+impl Drop for PanicOnDrop {
+	fn drop(&mut self) {
+		panic!("dropping task");
+	}
+}
+
+#[test]
+fn drop_with_live_tasks() {
+	let mut runtime = fumio::Runtime::new().unwrap();
+	let token = runtime.shutdown_token();
+	let dropped = Rc::new(Cell::new(false));
+	let hook_dropped = Rc::new(Cell::new(false));
+
+	let flag = DropFlag(dropped.clone());
+	runtime.spawn(async move {
+		let _flag = flag;
+		let mut socket = UdpSocket::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+		let mut buf = [0u8; 16];
+		let _ = socket.recv_from(&mut buf).await;
+	}).unwrap();
+	runtime.spawn(async {
+		let _guard = PanicOnDrop;
+		futures::future::pending::<()>().await;
+	}).unwrap();
+	let flag = DropFlag(hook_dropped.clone());
+	runtime.on_shutdown(async move {
+		let _flag = flag;
+	});
+	// start tasks: they wait on IO forever
+	let mut drive = runtime.drive();
+	futures::executor::block_on(futures::StreamExt::next(&mut drive)).unwrap();
+	drop(drive);
+
+	drop(runtime);
+	assert!(token.is_cancelled());
+	assert!(dropped.get());
+	assert!(hook_dropped.get());
+}
@@ -0,0 +1,29 @@
+#![cfg(all(unix, feature = "serial"))]
+
+use fumio::io::serialport::Parity;
+use fumio::io::SerialPort;
+use futures::future;
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use std::io;
+
+#[test]
+fn pty_pair() {
+	fumio::run(async {
+		let (mut master, mut slave) = SerialPort::pair()?;
+		slave.set_baud_rate(115_200)?;
+		assert_eq!(slave.baud_rate()?, 115_200);
+		slave.set_parity(Parity::None)?;
+		assert_eq!(slave.parity()?, Parity::None);
+
+		// more than fits into the pty buffers at once
+		let data: Vec<u8> = (0..64 * 1024u32).map(|i| (i % 251) as u8).collect();
+		let send = async {
+			master.write_all(&data).await?;
+			master.flush().await
+		};
+		let mut buf = vec![0u8; data.len()];
+		future::try_join(send, slave.read_exact(&mut buf)).await?;
+		assert!(buf == data);
+		Ok::<_, io::Error>(())
+	}).unwrap();
+}
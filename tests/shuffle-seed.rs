@@ -0,0 +1,97 @@
+use futures::future;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn poll_order(seed: Option<u64>) -> Vec<usize> {
+	let mut runtime = fumio::Runtime::builder().shuffle_seed(seed).build().unwrap();
+	let log = Rc::new(RefCell::new(Vec::new()));
+	for i in 0..8 {
+		let log = log.clone();
+		runtime.spawn(async move {
+			log.borrow_mut().push(i);
+			fumio::task::yield_now().await;
+			log.borrow_mut().push(i + 10);
+		}).unwrap();
+	}
+	runtime.enter_run(&mut fumio::enter().unwrap());
+	log.replace(Vec::new())
+}
+
+#[test]
+fn spawn_order_without_seed() {
+	assert_eq!(poll_order(None), [0, 1, 2, 3, 4, 5, 6, 7, 10, 11, 12, 13, 14, 15, 16, 17]);
+}
+
+#[test]
+fn same_seed_same_order() {
+	for seed in 0..16 {
+		let order = poll_order(Some(seed));
+		assert_eq!(order, poll_order(Some(seed)), "seed {}", seed);
+		// a round still polls each pending task once
+		let (first, second) = order.split_at(8);
+		let mut first = first.to_vec();
+		first.sort_unstable();
+		assert_eq!(first, [0, 1, 2, 3, 4, 5, 6, 7]);
+		assert!(second.iter().all(|&i| i >= 10));
+	}
+}
+
+#[test]
+fn seeds_change_order() {
+	let unshuffled = poll_order(None);
+	let orders: Vec<_> = (0..16).map(|seed| poll_order(Some(seed))).collect();
+	assert!(orders.iter().any(|order| *order != unshuffled));
+	assert!(orders.iter().any(|order| *order != orders[0]));
+}
+
+#[test]
+fn run_one_shuffled() {
+	let mut pool = fumio::pool::LocalPool::new();
+	pool.set_shuffle_seed(Some(3));
+	assert_eq!(pool.shuffle_seed(), Some(3));
+	let mut park = fumio_utils::park::ManualPark::new();
+	let mut enter = fumio::enter().unwrap();
+
+	let log = Rc::new(RefCell::new(Vec::new()));
+	for i in 0..8 {
+		let log = log.clone();
+		pool.spawn(Box::pin(async move { log.borrow_mut().push(i) }).into());
+	}
+	while pool.run_one(&mut park, &mut enter) {}
+	let mut order = log.replace(Vec::new());
+	assert_ne!(order, [0, 1, 2, 3, 4, 5, 6, 7]);
+	order.sort_unstable();
+	assert_eq!(order, [0, 1, 2, 3, 4, 5, 6, 7]);
+}
+
+#[test]
+fn panic_hook_gets_seed() {
+	let mut pool = fumio::pool::LocalPool::new();
+	pool.set_shuffle_seed(Some(5));
+	pool.set_panic_policy(fumio::pool::PanicPolicy::DropTask);
+	let seeds = Rc::new(RefCell::new(Vec::new()));
+	let hook_seeds = seeds.clone();
+	pool.set_panic_hook(move |p| hook_seeds.borrow_mut().push(p.shuffle_seed()));
+	let mut park = fumio_utils::park::ManualPark::new();
+	let mut enter = fumio::enter().unwrap();
+
+	pool.spawn(Box::pin(async { panic!("task failed") }).into());
+	while pool.run_one(&mut park, &mut enter) {}
+	assert_eq!(*seeds.borrow(), [Some(5)]);
+}
+
+#[test]
+fn clear_after_run_one_shuffled() {
+	let mut pool = fumio::pool::LocalPool::new();
+	pool.set_shuffle_seed(Some(1));
+	for _ in 0..4 {
+		pool.spawn_with_priority(fumio::pool::Priority::High, Box::pin(future::pending()).into());
+		pool.spawn(Box::pin(future::pending()).into());
+	}
+	let waker = futures::task::noop_waker();
+	let mut cx = std::task::Context::from_waker(&waker);
+	assert!(pool.try_run_one(&mut cx));
+	assert_eq!(pool.pending_len(), 7);
+	pool.clear();
+	assert!(pool.is_empty());
+}
@@ -0,0 +1,74 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+#[test]
+fn shutdown_drains_tasks() {
+	let mut runtime = fumio::Runtime::new().unwrap();
+	let finished = Rc::new(Cell::new(0));
+
+	for _ in 0..3 {
+		let finished = finished.clone();
+		runtime.spawn(async move {
+			let token = fumio::shutdown::current().unwrap().child_token();
+			token.cancelled().await;
+			finished.set(finished.get() + 1);
+		}).unwrap();
+	}
+
+	runtime.run_until(async {});
+	assert_eq!(finished.get(), 0);
+	runtime.shutdown();
+	assert_eq!(finished.get(), 3);
+	assert!(runtime.shutdown_token().is_cancelled());
+}
+
+#[test]
+fn child_cancel_doesnt_cancel_parent() {
+	let parent = fumio::shutdown::CancellationToken::new();
+	let child = parent.child_token();
+	child.cancel();
+	assert!(!parent.is_cancelled());
+	parent.cancel();
+	assert!(parent.child_token().is_cancelled());
+}
+
+#[test]
+fn shutdown_timeout_drops_stuck_tasks() {
+	let mut runtime = fumio::Runtime::new().unwrap();
+	let graceful = Rc::new(Cell::new(false));
+
+	let task_graceful = graceful.clone();
+	runtime.spawn(async move {
+		fumio::shutdown::current().unwrap().cancelled().await;
+		task_graceful.set(true);
+	}).unwrap();
+	runtime.spawn(futures::future::pending()).unwrap();
+
+	assert!(!runtime.shutdown_timeout(std::time::Duration::from_millis(50)));
+	assert!(graceful.get());
+	// nothing left to run
+	runtime.run_until(async {});
+	assert!(runtime.shutdown_timeout(std::time::Duration::from_millis(0)));
+}
+
+#[test]
+fn shutdown_hooks_run_after_main_future() {
+	let flushed = Rc::new(Cell::new(false));
+
+	let hook_flushed = flushed.clone();
+	fumio::run(async move {
+		fumio::current().unwrap().on_shutdown(async move {
+			// hooks can still use IO
+			let mut l = fumio::net::TcpListener::bind_port(0).unwrap();
+			let addr = l.local_addr().unwrap();
+			let (client, server) = futures::join!(
+				fumio::net::TcpStream::connect(addr),
+				l.incoming(),
+			);
+			let _client = client.unwrap();
+			let _server = server.unwrap();
+			hook_flushed.set(true);
+		}).unwrap();
+	});
+	assert!(flushed.get());
+}
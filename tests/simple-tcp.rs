@@ -1,5 +1,3 @@
-#![feature(async_await)]
-
 use fumio::net::{TcpListener, TcpStream};
 use futures::prelude::*;
 
@@ -11,7 +9,7 @@ fn main() {
 		println!("Serving on {}", server_addr);
 
 		let client_task = async {
-			let mut s = TcpStream::connect(server_addr)?.await?;
+			let mut s = TcpStream::connect(server_addr).await?;
 			s.write_all(b"hello server\n").await?;
 			s.close().await?;
 			let mut buf = String::new();
@@ -0,0 +1,22 @@
+use fumio::pool::LocalPool;
+use futures::task::noop_waker_ref;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::task::Context;
+
+#[test]
+fn spawn_future() {
+	let mut pool = LocalPool::new();
+	let spawner = pool.spawner();
+	let done = Rc::new(Cell::new(false));
+	let flag = done.clone();
+	spawner.spawn(async move {
+		flag.set(true);
+	}).unwrap();
+	let mut cx = Context::from_waker(noop_waker_ref());
+	let _ = pool.poll_pool(&mut cx);
+	assert!(done.get());
+
+	drop(pool);
+	assert!(spawner.spawn(async {}).is_err());
+}
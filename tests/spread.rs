@@ -0,0 +1,47 @@
+#![cfg(unix)]
+
+use fumio::net::{TcpListener, TcpStream};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn spread_reuse_port() {
+	let first = TcpListener::bind_reuse_port("127.0.0.1:0".parse().unwrap(), fumio::reactor::LazyHandle::new()).unwrap();
+	let addr: SocketAddr = first.local_addr().unwrap();
+	let bound = Arc::new(AtomicUsize::new(0));
+	let stopped = Arc::new(AtomicUsize::new(0));
+
+	let spread = {
+		let bound = bound.clone();
+		let stopped = stopped.clone();
+		fumio::spread(3, move |index| {
+			let bound = bound.clone();
+			let stopped = stopped.clone();
+			async move {
+				assert!(thread::current().name().unwrap().ends_with(&index.to_string()));
+				let _listener = TcpListener::bind_reuse_port(addr, fumio::reactor::LazyHandle::new()).unwrap();
+				bound.fetch_add(1, Ordering::SeqCst);
+				fumio::shutdown::current().unwrap().cancelled().await;
+				let stopped = stopped.clone();
+				fumio::current().unwrap().on_shutdown(async move {
+					stopped.fetch_add(1, Ordering::SeqCst);
+				}).unwrap();
+			}
+		}).unwrap()
+	};
+	assert_eq!(spread.len(), 3);
+
+	while bound.load(Ordering::SeqCst) < 3 {
+		thread::sleep(Duration::from_millis(10));
+	}
+	drop(first);
+	fumio::run(async move {
+		// some listener accepts the connection (queued in its backlog)
+		let _stream = TcpStream::connect(addr).await.unwrap();
+	});
+	spread.shutdown().unwrap();
+	assert_eq!(stopped.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,43 @@
+use fumio::pool::Supervisor;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+#[test]
+fn restart_until_limit() {
+	fumio::run(async {
+		let runs = Rc::new(Cell::new(0));
+		let start = Instant::now();
+		let mut supervisor = Supervisor::new();
+		supervisor.backoff(Duration::from_millis(10), Duration::from_millis(100)).max_restarts(Some(3));
+		let counter = runs.clone();
+		supervisor.supervise(move || {
+			let counter = counter.clone();
+			async move {
+				counter.set(counter.get() + 1);
+				if counter.get() % 2 == 0 {
+					panic!("supervised task failed");
+				}
+			}
+		}).await;
+		assert_eq!(runs.get(), 4);
+		// backoff 10ms, 20ms, 40ms
+		assert!(start.elapsed() >= Duration::from_millis(65));
+	});
+}
+
+#[test]
+fn spawn_supervised() {
+	fumio::run(async {
+		let runs = Rc::new(Cell::new(0));
+		let mut supervisor = Supervisor::new();
+		supervisor.backoff(Duration::from_millis(1), Duration::from_millis(1));
+		let counter = runs.clone();
+		supervisor.spawn(move || {
+			counter.set(counter.get() + 1);
+			async {}
+		}).unwrap();
+		fumio::current().unwrap().timer().delay(Instant::now() + Duration::from_millis(50)).await;
+		assert!(runs.get() >= 5);
+	});
+}
@@ -0,0 +1,157 @@
+use fumio::sync::{Mutex, RwLock, Semaphore};
+use futures::task::{noop_waker_ref, ArcWake, Context};
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+fn poll_once<F: Future>(future: Pin<&mut F>) -> Option<F::Output> {
+	let mut cx = Context::from_waker(noop_waker_ref());
+	match future.poll(&mut cx) {
+		std::task::Poll::Ready(v) => Some(v),
+		std::task::Poll::Pending => None,
+	}
+}
+
+#[test]
+fn mutex_fifo() {
+	let mutex = Rc::new(Mutex::new(Vec::new()));
+	fumio::run({
+		let mutex = mutex.clone();
+		async move {
+			let guard = mutex.lock().await;
+			let handle = fumio::current().unwrap();
+			for i in 0..3 {
+				let mutex = mutex.clone();
+				handle.spawn(async move {
+					mutex.lock().await.push(i);
+				}).unwrap();
+			}
+			// let the tasks queue up
+			handle.timer().delay(std::time::Instant::now()).await;
+			assert!(mutex.try_lock().is_none());
+			drop(guard);
+			// queued after the tasks
+			drop(mutex.lock().await);
+		}
+	});
+	assert_eq!(*mutex.try_lock().unwrap(), [0, 1, 2]);
+}
+
+#[test]
+fn mutex_dropped_waiters() {
+	let mutex = Mutex::new(0);
+	let guard = mutex.try_lock().unwrap();
+	let mut first = Box::pin(mutex.lock());
+	let mut second = Box::pin(mutex.lock());
+	let mut third = Box::pin(mutex.lock());
+	assert!(poll_once(first.as_mut()).is_none());
+	assert!(poll_once(second.as_mut()).is_none());
+	assert!(poll_once(third.as_mut()).is_none());
+	// queued waiter is removed
+	drop(second);
+	drop(guard);
+	// lock handed to `first`, but it never takes it: passed on to `third`
+	drop(first);
+	let mut guard = poll_once(third.as_mut()).unwrap();
+	*guard += 1;
+	drop(guard);
+	drop(third);
+	assert_eq!(*mutex.try_lock().unwrap(), 1);
+}
+
+#[test]
+fn rwlock() {
+	let lock = RwLock::new(0);
+	let read1 = lock.try_read().unwrap();
+	let read2 = lock.try_read().unwrap();
+	assert!(lock.try_write().is_none());
+
+	let mut write = Box::pin(lock.write());
+	assert!(poll_once(write.as_mut()).is_none());
+	// readers queue behind the waiting writer
+	assert!(lock.try_read().is_none());
+	let mut read3 = Box::pin(lock.read());
+	let mut read4 = Box::pin(lock.read());
+	assert!(poll_once(read3.as_mut()).is_none());
+	assert!(poll_once(read4.as_mut()).is_none());
+
+	drop(read1);
+	assert!(poll_once(write.as_mut()).is_none());
+	drop(read2);
+	let mut guard = poll_once(write.as_mut()).unwrap();
+	*guard = 5;
+	drop(guard);
+
+	// both waiting readers get the lock together
+	let guard3 = poll_once(read3.as_mut()).unwrap();
+	let guard4 = poll_once(read4.as_mut()).unwrap();
+	assert_eq!((*guard3, *guard4), (5, 5));
+}
+
+#[test]
+fn semaphore() {
+	let semaphore = Semaphore::new(3);
+	let two = semaphore.try_acquire_many(2).unwrap();
+	assert_eq!(semaphore.available_permits(), 1);
+
+	let mut big = Box::pin(semaphore.acquire_many(2));
+	assert!(poll_once(big.as_mut()).is_none());
+	// doesn't overtake the queued request
+	assert!(semaphore.try_acquire().is_none());
+	let mut small = Box::pin(semaphore.acquire());
+	assert!(poll_once(small.as_mut()).is_none());
+
+	drop(two);
+	let big_permit = poll_once(big.as_mut()).unwrap();
+	assert_eq!(big_permit.permits(), 2);
+	let small_permit = poll_once(small.as_mut()).unwrap();
+	assert_eq!(semaphore.available_permits(), 0);
+
+	small_permit.forget();
+	drop(big_permit);
+	assert_eq!(semaphore.available_permits(), 2);
+	semaphore.add_permits(1);
+	assert_eq!(semaphore.available_permits(), 3);
+}
+
+struct WakeFlag(AtomicBool);
+
+impl ArcWake for WakeFlag {
+	fn wake_by_ref(arc_self: &Arc<Self>) {
+		arc_self.0.store(true, Ordering::SeqCst);
+	}
+}
+
+#[test]
+fn semaphore_cancelled_waiter() {
+	let semaphore = Semaphore::new(2);
+	let mut big = Box::pin(semaphore.acquire_many(3));
+	assert!(poll_once(big.as_mut()).is_none());
+	let woken = Arc::new(WakeFlag(AtomicBool::new(false)));
+	let waker = futures::task::waker(woken.clone());
+	let mut small = Box::pin(semaphore.acquire());
+	assert!(small.as_mut().poll(&mut Context::from_waker(&waker)).is_pending());
+
+	drop(big);
+	assert!(woken.0.load(Ordering::SeqCst));
+	let permit = poll_once(small.as_mut()).unwrap();
+	assert_eq!(permit.permits(), 1);
+	assert_eq!(semaphore.available_permits(), 1);
+}
+
+#[test]
+fn rwlock_cancelled_writer() {
+	let lock = RwLock::new(0);
+	let read1 = lock.try_read().unwrap();
+	let mut write = Box::pin(lock.write());
+	assert!(poll_once(write.as_mut()).is_none());
+	let mut read2 = Box::pin(lock.read());
+	assert!(poll_once(read2.as_mut()).is_none());
+
+	// the reader doesn't wait for the cancelled writer
+	drop(write);
+	let guard2 = poll_once(read2.as_mut()).unwrap();
+	assert_eq!((*read1, *guard2), (0, 0));
+}
@@ -0,0 +1,60 @@
+use fumio::pool::{LocalPool, Priority};
+use futures::task::noop_waker_ref;
+use std::task::Context;
+
+#[test]
+fn task_info() {
+	let mut pool = LocalPool::new();
+	let spawner = pool.spawner();
+	spawner.spawn_named("control", futures::future::pending()).unwrap();
+	spawner.spawn_with_priority(Priority::Low, futures::future::pending()).unwrap();
+
+	let tasks = pool.tasks();
+	assert_eq!(tasks.len(), 2);
+	assert_eq!(tasks[0].name.as_deref(), Some("control"));
+	assert_eq!(tasks[0].priority, Priority::Normal);
+	assert!(tasks[0].pending);
+	#[cfg(feature = "task-dump")]
+	assert!(tasks[0].last_poll.is_none());
+	assert_eq!(tasks[1].name, None);
+	assert_eq!(tasks[1].priority, Priority::Low);
+	assert!(tasks[0].to_string().starts_with("task#0 \"control\" Normal pending"));
+	#[cfg(feature = "task-dump")]
+	assert!(tasks[0].to_string().ends_with(", never polled"));
+
+	let mut cx = Context::from_waker(noop_waker_ref());
+	let _ = pool.poll_pool(&mut cx);
+	let tasks = spawner.tasks().unwrap();
+	assert!(!tasks[0].pending);
+	#[cfg(feature = "task-dump")]
+	assert!(tasks[0].last_poll.is_some());
+	pool.clear();
+}
+
+#[cfg(all(unix, feature = "task-dump"))]
+#[test]
+fn dump_on_signal() {
+	use std::time::{Duration, Instant};
+
+	let mut runtime = fumio::Runtime::new().unwrap();
+	runtime.enable_task_dump().unwrap();
+	runtime.spawn_named("sleeper", async {
+		let timer = fumio::current().unwrap().timer();
+		timer.delay(Instant::now() + Duration::from_millis(10)).await;
+		unsafe { libc::raise(libc::SIGUSR1); }
+		timer.delay(Instant::now() + Duration::from_millis(10)).await;
+	}).unwrap();
+	// the dump doesn't keep the runtime busy
+	runtime.enter_run(&mut fumio::enter().unwrap());
+	assert!(runtime.handle().spawner().tasks().unwrap().is_empty());
+}
+
+#[cfg(all(unix, feature = "task-dump"))]
+#[test]
+#[should_panic(expected = "runtime deadlocked")]
+fn dump_keeps_deadlock_detection() {
+	let mut runtime = fumio::Runtime::builder().panic_on_deadlock().build().unwrap();
+	runtime.enable_task_dump().unwrap();
+	let (_sender, receiver) = futures::channel::oneshot::channel::<()>();
+	runtime.run_until(receiver).unwrap();
+}
@@ -0,0 +1,41 @@
+use fumio::pool::{LocalPool, Priority};
+use futures::task::noop_waker_ref;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::task::Context;
+
+fn spawn_all(pool: &LocalPool, order: &Rc<RefCell<Vec<char>>>) {
+	let spawner = pool.spawner();
+	for &(priority, label) in &[(Priority::Low, 'L'), (Priority::Normal, 'N'), (Priority::High, 'H')] {
+		for _ in 0..3 {
+			let order = order.clone();
+			spawner.spawn_with_priority(priority, async move {
+				order.borrow_mut().push(label);
+			}).unwrap();
+		}
+	}
+}
+
+#[test]
+fn weighted_order() {
+	let mut pool = LocalPool::new();
+	let order = Rc::new(RefCell::new(Vec::new()));
+	spawn_all(&pool, &order);
+
+	let mut cx = Context::from_waker(noop_waker_ref());
+	let _ = pool.poll_pool(&mut cx);
+	let order: String = order.borrow().iter().collect();
+	assert_eq!(order, "HHHNNLNLL");
+}
+
+#[test]
+fn run_one_highest_first() {
+	let mut pool = LocalPool::new();
+	let order = Rc::new(RefCell::new(Vec::new()));
+	spawn_all(&pool, &order);
+
+	let mut cx = Context::from_waker(noop_waker_ref());
+	while pool.try_run_one(&mut cx) {}
+	let order: String = order.borrow().iter().collect();
+	assert_eq!(order, "HHHNNNLLL");
+}
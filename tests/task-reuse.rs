@@ -0,0 +1,43 @@
+use fumio::pool::LocalPool;
+use futures::task::{noop_waker_ref, LocalSpawnExt};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::task::{Context, Poll, Waker};
+
+#[test]
+fn reuse_finished_tasks() {
+	let mut pool = LocalPool::new();
+	let spawner = pool.spawner();
+	let mut cx = Context::from_waker(noop_waker_ref());
+
+	for _ in 0..10 {
+		spawner.spawn_local(async {}).unwrap();
+		assert_eq!(pool.poll_pool(&mut cx), Poll::Ready(()));
+	}
+	let metrics = pool.metrics();
+	assert_eq!(metrics.tasks_spawned, 10);
+	assert_eq!(metrics.tasks_reused, 9);
+}
+
+#[test]
+fn keep_tasks_with_wakers() {
+	let mut pool = LocalPool::new();
+	let spawner = pool.spawner();
+	let mut cx = Context::from_waker(noop_waker_ref());
+
+	// a waker outliving its task must not wake a new task on the same allocation
+	let waker: Rc<RefCell<Option<Waker>>> = Rc::default();
+	let save = waker.clone();
+	spawner.spawn_local(futures::future::poll_fn(move |cx| {
+		*save.borrow_mut() = Some(cx.waker().clone());
+		Poll::Ready(())
+	})).unwrap();
+	assert_eq!(pool.poll_pool(&mut cx), Poll::Ready(()));
+
+	spawner.spawn_local(futures::future::pending()).unwrap();
+	assert_eq!(pool.metrics().tasks_reused, 0);
+	let _ = pool.poll_pool(&mut cx);
+	waker.borrow_mut().take().unwrap().wake();
+	assert!(pool.is_idle());
+	pool.clear();
+}
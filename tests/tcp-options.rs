@@ -0,0 +1,160 @@
+use fumio::net::{KeepAlive, TcpListener, TcpStream};
+use futures::prelude::*;
+use std::net::Shutdown;
+use std::time::Duration;
+
+#[test]
+fn accessors() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_ipv4_port(0)?;
+		let server_addr = l.local_addr()?;
+
+		let client = TcpStream::connect(server_addr).await?;
+		let (mut conn, client_addr) = l.incoming().await?;
+		assert_eq!(client.peer_addr()?.port(), server_addr.port());
+		assert_eq!(client.local_addr()?, client_addr);
+		assert_eq!(conn.peer_addr()?, client_addr);
+
+		client.set_nodelay(true)?;
+		assert!(client.nodelay()?);
+		client.set_ttl(42)?;
+		assert_eq!(client.ttl()?, 42);
+
+		client.shutdown(Shutdown::Write)?;
+		let mut buf = Vec::new();
+		conn.read_to_end(&mut buf).await?;
+		assert!(buf.is_empty());
+		Ok::<_, std::io::Error>(())
+	}).unwrap();
+}
+
+#[test]
+fn keepalive() {
+	fumio::run(async {
+		let local = "127.0.0.1:0".parse().unwrap();
+		let keepalive = KeepAlive {
+			time: Some(Duration::from_secs(60)),
+			interval: Some(Duration::from_secs(10)),
+			retries: Some(3),
+		};
+		let builder = TcpListener::default_builder_with_keepalive(&local, Some(keepalive))?;
+		builder.bind(&local.into())?;
+		builder.listen(16)?;
+		let mut l = TcpListener::from_std(builder.into(), Default::default())?;
+		let server_addr = l.local_addr()?;
+
+		let client = TcpStream::connect(server_addr).await?;
+		let (_conn, _) = l.incoming().await?;
+		client.set_keepalive(Some(KeepAlive::default()))?;
+		client.set_keepalive(Some(keepalive))?;
+		client.set_keepalive(None)?;
+		Ok::<_, std::io::Error>(())
+	}).unwrap();
+}
+
+#[test]
+fn peek() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_ipv4_port(0)?;
+		let server_addr = l.local_addr()?;
+
+		let mut client = TcpStream::connect(server_addr).await?;
+		let (mut conn, _) = l.incoming().await?;
+		client.write_all(b"GET / HTTP/1.0\r\n").await?;
+		client.close().await?;
+
+		let mut method = [0u8; 3];
+		assert_eq!(conn.peek(&mut method).await?, 3);
+		assert_eq!(&method, b"GET");
+		let mut buf = Vec::new();
+		conn.read_to_end(&mut buf).await?;
+		assert_eq!(buf, b"GET / HTTP/1.0\r\n");
+		Ok::<_, std::io::Error>(())
+	}).unwrap();
+}
+
+#[test]
+fn listen_backlog() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_with_backlog(([127, 0, 0, 1], 0).into(), 4, Default::default())?;
+		let server_addr = l.local_addr()?;
+		let client = TcpStream::connect(server_addr).await?;
+		let (conn, _) = l.incoming().await?;
+		assert_eq!(conn.peer_addr()?, client.local_addr()?);
+		Ok::<_, std::io::Error>(())
+	}).unwrap();
+}
+
+#[test]
+fn socket_options() {
+	fumio::run(async {
+		let l = TcpListener::bind_ipv4_port(0).unwrap();
+		l.set_recv_buffer_size(32 * 1024).unwrap();
+		assert!(l.recv_buffer_size().unwrap() >= 32 * 1024);
+		#[cfg(unix)]
+		{
+			l.set_reuse_port(true).unwrap();
+			assert!(l.reuse_port().unwrap());
+		}
+
+		let c = TcpStream::connect(l.local_addr().unwrap()).await.unwrap();
+		c.set_send_buffer_size(32 * 1024).unwrap();
+		assert!(c.send_buffer_size().unwrap() >= 32 * 1024);
+		c.set_tos(0x10).unwrap();
+		assert_eq!(c.tos().unwrap(), 0x10);
+
+		if let Ok(l6) = TcpListener::bind("[::1]:0".parse().unwrap()) {
+			let c6 = TcpStream::connect(l6.local_addr().unwrap()).await.unwrap();
+			c6.set_tclass_v6(0xb8).unwrap();
+			assert_eq!(c6.tclass_v6().unwrap(), 0xb8);
+		}
+	});
+}
+
+#[test]
+fn try_clone() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_ipv4_port(0).unwrap();
+		let mut client = TcpStream::connect(l.local_addr().unwrap()).await.unwrap();
+		let (conn, _) = l.incoming().await.unwrap();
+
+		// write through the clone, read through the original
+		let mut writer = conn.try_clone().unwrap();
+		let mut reader = conn;
+		writer.write_all(b"ping").await.unwrap();
+		let mut buf = [0u8; 4];
+		client.read_exact(&mut buf).await.unwrap();
+		client.write_all(&buf).await.unwrap();
+		reader.read_exact(&mut buf).await.unwrap();
+		assert_eq!(&buf, b"ping");
+		assert_eq!(writer.peer_addr().unwrap(), reader.peer_addr().unwrap());
+	});
+}
+
+#[test]
+fn linger() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_ipv4_port(0)?;
+		l.set_linger(Some(Duration::from_secs(0)))?;
+		assert_eq!(l.linger()?, Some(Duration::from_secs(0)));
+
+		let mut client = TcpStream::connect(l.local_addr()?).await?;
+		let (conn, _) = l.incoming().await?;
+		assert_eq!(client.linger()?, None);
+		client.set_linger(Some(Duration::from_secs(5)))?;
+		assert_eq!(client.linger()?, Some(Duration::from_secs(5)));
+		client.set_linger(None)?;
+		assert_eq!(client.linger()?, None);
+
+		// accepted connection inherited zero linger: dropping it resets the connection
+		#[cfg(target_os = "linux")]
+		assert_eq!(conn.linger()?, Some(Duration::from_secs(0)));
+		drop(conn);
+		let mut buf = Vec::new();
+		#[cfg(target_os = "linux")]
+		assert_eq!(client.read_to_end(&mut buf).await.unwrap_err().kind(), std::io::ErrorKind::ConnectionReset);
+		#[cfg(not(target_os = "linux"))]
+		let _ = client.read_to_end(&mut buf).await;
+		Ok::<_, std::io::Error>(())
+	}).unwrap();
+}
@@ -0,0 +1,64 @@
+use fumio::net::{TcpListener, TcpStream};
+use futures::prelude::*;
+
+#[test]
+fn borrowed_halves() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_port(0)?;
+		let server_addr = l.local_addr()?;
+
+		let client_task = async {
+			let mut s = TcpStream::connect(server_addr).await?;
+			s.write_all(b"ping").await?;
+			s.close().await?;
+			let mut buf = Vec::new();
+			s.read_to_end(&mut buf).await?;
+			assert_eq!(buf, b"ping");
+			Ok::<_, std::io::Error>(())
+		};
+
+		let serv_task = async {
+			let (mut conn, _) = l.incoming().await?;
+			// `AsyncReadExt::split` would shadow the inherent method
+			let (reader, mut writer) = TcpStream::split(&mut conn);
+			futures::io::copy(reader, &mut writer).await?;
+			writer.close().await?;
+			Ok::<_, std::io::Error>(())
+		};
+
+		futures::try_join!(client_task, serv_task)
+	}).unwrap();
+}
+
+#[test]
+fn owned_halves_in_separate_tasks() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_port(0)?;
+		let server_addr = l.local_addr()?;
+
+		let client = TcpStream::connect(server_addr).await?;
+		let (mut conn, _) = l.incoming().await?;
+		let (mut reader, mut writer) = client.into_split();
+
+		let (tx, rx) = futures::channel::oneshot::channel();
+		fumio::current().unwrap().spawn(async move {
+			let mut buf = [0u8; 5];
+			reader.read_exact(&mut buf).await.unwrap();
+			tx.send((reader, buf)).unwrap();
+		}).unwrap();
+
+		conn.write_all(b"hello").await?;
+		writer.write_all(b"world").await?;
+		writer.close().await?;
+
+		let (reader, buf) = rx.await.unwrap();
+		assert_eq!(&buf, b"hello");
+		let mut buf = Vec::new();
+		conn.read_to_end(&mut buf).await?;
+		assert_eq!(buf, b"world");
+
+		let stream = reader.reunite(writer).unwrap();
+		assert_eq!(stream.peer_addr()?.port(), server_addr.port());
+		Ok::<_, std::io::Error>(())
+	}).unwrap();
+}
@@ -0,0 +1,98 @@
+use fumio::testing::TestRuntime;
+use fumio::{assert_pending, assert_ready};
+use std::cell::Cell;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::Poll;
+use std::time::Duration;
+
+#[test]
+fn assert_macros() {
+	assert_eq!(assert_ready!(Poll::Ready(5)), 5);
+	assert_pending!(Poll::<u32>::Pending);
+	assert_pending!(Poll::<u32>::Pending, "with {}", "message");
+}
+
+#[test]
+#[should_panic(expected = "pending; no value")]
+fn assert_ready_fails() {
+	assert_ready!(Poll::<u32>::Pending, "no {}", "value");
+}
+
+#[test]
+#[should_panic(expected = "ready; value = 3")]
+fn assert_pending_fails() {
+	assert_pending!(Poll::Ready(3));
+}
+
+#[test]
+fn manual_time() {
+	let mut rt = TestRuntime::new().unwrap();
+	let start = rt.now();
+	let fired = Rc::new(Cell::new(false));
+
+	let timer = rt.handle().timer();
+	let task_fired = fired.clone();
+	rt.spawn(async move {
+		timer.delay(start + Duration::from_secs(3600)).await;
+		task_fired.set(true);
+	});
+
+	assert!(!rt.run_until_stalled());
+	rt.advance(Duration::from_secs(3599));
+	assert!(!fired.get());
+	rt.advance(Duration::from_secs(1));
+	assert!(fired.get());
+	assert!(rt.is_empty());
+	assert_eq!(rt.now(), start + Duration::from_secs(3600));
+}
+
+#[test]
+fn poll_fn_context() {
+	let mut rt = TestRuntime::new().unwrap();
+	let now = rt.now();
+	let handle = rt.handle();
+	let same_runtime = assert_ready!(rt.poll_fn(|_cx| {
+		// runtime, reactor and the manual clock are available
+		assert_eq!(tokio_timer::clock::now(), now);
+		assert!(fumio::reactor::current().is_some());
+		Poll::Ready(fumio::current().unwrap().ptr_eq(&handle))
+	}));
+	assert!(same_runtime);
+}
+
+#[test]
+fn poll_wakeup() {
+	let mut rt = TestRuntime::new().unwrap();
+	let (tx, mut rx) = futures::channel::oneshot::channel::<u32>();
+
+	assert_pending!(rt.poll(Pin::new(&mut rx)));
+	assert!(!rt.is_woken());
+
+	rt.spawn(async move {
+		tx.send(7).unwrap();
+	});
+	rt.run_until_stalled();
+	assert!(rt.is_woken());
+	assert_eq!(assert_ready!(rt.poll(Pin::new(&mut rx))), Ok(7));
+}
+
+#[test]
+fn block_on_spawned() {
+	let mut rt = TestRuntime::new().unwrap();
+	let (tx, rx) = futures::channel::oneshot::channel::<u32>();
+	rt.spawn(async move {
+		fumio::task::yield_now().await;
+		tx.send(9).unwrap();
+	});
+	assert_eq!(rt.block_on(rx), Ok(9));
+}
+
+#[test]
+#[should_panic(expected = "future stalled")]
+fn block_on_stalled() {
+	let mut rt = TestRuntime::new().unwrap();
+	let timer = rt.handle().timer();
+	let deadline = rt.now() + Duration::from_secs(1);
+	rt.block_on(timer.delay(deadline));
+}
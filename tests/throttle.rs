@@ -0,0 +1,29 @@
+use fumio::timer::{throttle, RateLimiter};
+use futures::StreamExt;
+use std::time::{Duration, Instant};
+
+#[test]
+fn rate_limiter_burst() {
+	fumio::run(async {
+		let mut limiter = RateLimiter::new(Duration::from_millis(50), 2);
+		assert!(limiter.try_acquire());
+		assert!(limiter.try_acquire());
+		assert!(!limiter.try_acquire());
+		let start = Instant::now();
+		limiter.acquire().await;
+		assert!(start.elapsed() >= Duration::from_millis(40));
+		assert_eq!(limiter.available(), 0);
+	});
+}
+
+#[test]
+fn throttle_stream() {
+	fumio::run(async {
+		let start = Instant::now();
+		let rate = RateLimiter::new(Duration::from_millis(20), 1);
+		let items: Vec<u32> = throttle(rate, futures::stream::iter(0..4)).collect().await;
+		assert_eq!(items, [0, 1, 2, 3]);
+		// first item from the initial burst, then one every 20ms
+		assert!(start.elapsed() >= Duration::from_millis(55));
+	});
+}
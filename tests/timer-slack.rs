@@ -0,0 +1,36 @@
+use std::time::{Duration, Instant};
+
+// 50 timers spread over 20ms; returns the number of reactor polls needed
+fn run_timers(runtime: &mut fumio::Runtime) -> u64 {
+	let polls_before = runtime.metrics().reactor.polls;
+	let start = Instant::now();
+	runtime.run_until(async {
+		let timer = fumio::current().unwrap().timer();
+		let delays = (0..50u32).map(|i| {
+			let deadline = start + Duration::from_micros(u64::from(i) * 400);
+			let delay = timer.delay(deadline);
+			async move {
+				delay.await;
+				assert!(Instant::now() >= deadline);
+			}
+		});
+		futures::future::join_all(delays).await;
+	});
+	runtime.metrics().reactor.polls - polls_before
+}
+
+#[test]
+fn coalesce_timers() {
+	let mut runtime = fumio::Runtime::builder().timer_slack(Duration::from_millis(50)).build().unwrap();
+	let polls = run_timers(&mut runtime);
+	// all timers fit into one or two slack buckets
+	assert!(polls <= 4, "too many polls: {}", polls);
+}
+
+#[test]
+fn no_slack() {
+	let mut runtime = fumio::Runtime::new().unwrap();
+	let polls = run_timers(&mut runtime);
+	// timers have millisecond resolution
+	assert!(polls > 4, "too few polls: {}", polls);
+}
@@ -0,0 +1,67 @@
+use fumio::timer::{DelayQueue, Interval, MissedTickBehavior};
+use futures::StreamExt;
+use std::time::{Duration, Instant};
+
+#[test]
+fn interval_stream() {
+	fumio::run(async {
+		let start = Instant::now();
+		let ticks: Vec<Instant> = Interval::new(start, Duration::from_millis(10)).take(3).collect().await;
+		assert_eq!(ticks.len(), 3);
+		assert!(start.elapsed() >= Duration::from_millis(20));
+	});
+}
+
+#[test]
+fn delay_queue_stream() {
+	fumio::run(async {
+		let mut queue = DelayQueue::new();
+		queue.insert(2, Duration::from_millis(20));
+		queue.insert(1, Duration::from_millis(10));
+		let values: Vec<u32> = queue.map(|expired| expired.unwrap().into_inner()).collect().await;
+		assert_eq!(values, [1, 2]);
+	});
+}
+
+// blocks the runtime for 35ms after the first tick of a 10ms interval
+fn missed_ticks(behavior: MissedTickBehavior) -> (Instant, Vec<Instant>) {
+	fumio::run(async {
+		let start = Instant::now();
+		let mut interval = Interval::new(start, Duration::from_millis(10));
+		interval.set_missed_tick_behavior(behavior);
+		assert_eq!(interval.missed_tick_behavior(), behavior);
+		let mut ticks = vec![interval.next().await.unwrap()];
+		std::thread::sleep(Duration::from_millis(35));
+		for _ in 0..3 {
+			ticks.push(interval.next().await.unwrap());
+		}
+		(start, ticks)
+	})
+}
+
+#[test]
+fn missed_tick_burst() {
+	let (start, ticks) = missed_ticks(MissedTickBehavior::Burst);
+	let expected: Vec<Instant> = (0..4).map(|i| start + Duration::from_millis(i * 10)).collect();
+	assert_eq!(ticks, expected);
+}
+
+#[test]
+fn missed_tick_skip() {
+	let (start, ticks) = missed_ticks(MissedTickBehavior::Skip);
+	assert_eq!(MissedTickBehavior::default(), MissedTickBehavior::Skip);
+	// one tick for the missed ones, then back on the 10ms grid
+	assert_eq!(ticks[1], start + Duration::from_millis(10));
+	assert!(ticks[2] >= start + Duration::from_millis(40));
+	assert_eq!((ticks[2] - start).as_nanos() % Duration::from_millis(10).as_nanos(), 0);
+	assert_eq!(ticks[3], ticks[2] + Duration::from_millis(10));
+}
+
+#[test]
+fn missed_tick_delay() {
+	let (start, ticks) = missed_ticks(MissedTickBehavior::Delay);
+	assert_eq!(ticks[1], start + Duration::from_millis(10));
+	// next tick `period` after the late one was yielded
+	assert!(ticks[2] >= start + Duration::from_millis(45));
+	assert_eq!(ticks[3], ticks[2] + Duration::from_millis(10));
+}
@@ -0,0 +1,92 @@
+#![cfg(feature = "tls")]
+
+use fumio::net::tls::rustls::{Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig};
+use fumio::net::tls::{TlsAcceptor, TlsConnector};
+use fumio::net::{TcpListener, TcpStream};
+use futures::future;
+use futures::prelude::*;
+use std::io;
+use std::sync::Arc;
+
+fn connector() -> TlsConnector {
+	let mut roots = RootCertStore::empty();
+	roots.add(&Certificate(include_bytes!("tls/ca.der").to_vec())).unwrap();
+	let config = ClientConfig::builder()
+		.with_safe_defaults()
+		.with_root_certificates(roots)
+		.with_no_client_auth();
+	TlsConnector::from(Arc::new(config))
+}
+
+fn acceptor() -> TlsAcceptor {
+	let config = ServerConfig::builder()
+		.with_safe_defaults()
+		.with_no_client_auth()
+		.with_single_cert(
+			vec![Certificate(include_bytes!("tls/localhost.der").to_vec())],
+			PrivateKey(include_bytes!("tls/localhost.key.der").to_vec()),
+		)
+		.unwrap();
+	TlsAcceptor::from(Arc::new(config))
+}
+
+#[test]
+fn echo() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_port(0)?;
+		let server_addr = l.local_addr()?;
+		let acceptor = acceptor();
+
+		let server = async {
+			let (s, _) = l.incoming().await?;
+			let mut s = acceptor.accept(s)?.await?;
+			let mut buf = Vec::new();
+			s.read_to_end(&mut buf).await?;
+			s.write_all(&buf).await?;
+			s.close().await?;
+			Ok::<_, io::Error>(())
+		};
+
+		let client = async {
+			let s = TcpStream::connect(server_addr).await?;
+			let mut s = connector().connect("localhost", s)?.await?;
+			// larger than a single TLS record
+			let data = vec![0x42u8; 100_000];
+			s.write_all(&data).await?;
+			// `close_notify` ends the server's read, the TCP stream is half-closed
+			s.close().await?;
+			let mut buf = Vec::new();
+			s.read_to_end(&mut buf).await?;
+			assert!(buf == data);
+			Ok::<_, io::Error>(())
+		};
+
+		future::try_join(server, client).await?;
+		Ok::<_, io::Error>(())
+	}).unwrap();
+}
+
+#[test]
+fn wrong_name() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_port(0)?;
+		let server_addr = l.local_addr()?;
+		let acceptor = acceptor();
+
+		let server = async {
+			let (s, _) = l.incoming().await?;
+			assert!(acceptor.accept(s)?.await.is_err());
+			Ok::<_, io::Error>(())
+		};
+
+		let client = async {
+			let s = TcpStream::connect(server_addr).await?;
+			let err = connector().connect("example.com", s)?.await.unwrap_err();
+			assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+			Ok::<_, io::Error>(())
+		};
+
+		future::try_join(server, client).await?;
+		Ok::<_, io::Error>(())
+	}).unwrap();
+}
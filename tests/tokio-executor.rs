@@ -0,0 +1,30 @@
+use futures::channel::oneshot;
+use std::cell::Cell;
+use std::rc::Rc;
+use tokio_executor::TypedExecutor;
+
+#[test]
+fn default_executor() {
+	fumio::run(async {
+		let (tx, rx) = oneshot::channel();
+		tokio_executor::spawn(async move {
+			tx.send(42).unwrap();
+		});
+		assert_eq!(rx.await.unwrap(), 42);
+	});
+}
+
+#[test]
+fn typed_executor_local() {
+	fumio::run(async {
+		let done = Rc::new(Cell::new(false));
+		let (tx, rx) = oneshot::channel();
+		let flag = done.clone();
+		TypedExecutor::spawn(&mut fumio::current().unwrap(), async move {
+			flag.set(true);
+			tx.send(()).unwrap();
+		}).unwrap();
+		rx.await.unwrap();
+		assert!(done.get());
+	});
+}
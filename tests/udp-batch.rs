@@ -0,0 +1,32 @@
+use fumio::net::UdpSocket;
+use futures::future::poll_fn;
+
+#[test]
+fn send_recv_batch() {
+	fumio::run(async {
+		let mut a = UdpSocket::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+		let mut b = UdpSocket::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+		let a_addr = a.local_addr().unwrap();
+		let b_addr = b.local_addr().unwrap();
+
+		let datagrams: Vec<(&[u8], _)> = vec![(b"one", b_addr), (b"two", b_addr), (b"three", b_addr)];
+		let mut sent = 0;
+		while sent < datagrams.len() {
+			sent += poll_fn(|cx| a.poll_send_batch(cx, &datagrams[sent..])).await.unwrap();
+		}
+
+		let mut storage = [[0u8; 16]; 4];
+		let mut messages = Vec::new();
+		let mut received = Vec::new();
+		while messages.len() < 3 {
+			let mut bufs: Vec<&mut [u8]> = storage.iter_mut().map(|b| &mut b[..]).collect();
+			let n = poll_fn(|cx| b.poll_recv_batch(cx, &mut bufs, &mut received)).await.unwrap();
+			assert_eq!(n, received.len());
+			for (buf, &(len, from)) in bufs.iter().zip(&received) {
+				assert_eq!(from, a_addr);
+				messages.push(buf[..len].to_vec());
+			}
+		}
+		assert_eq!(messages, vec![b"one".to_vec(), b"two".to_vec(), b"three".to_vec()]);
+	});
+}
@@ -0,0 +1,48 @@
+use fumio::net::{UdpCodec, UdpFramed, UdpSocket};
+use futures::{SinkExt, StreamExt};
+use std::io;
+use std::net::SocketAddr;
+
+// line based text messages
+struct Lines;
+
+impl UdpCodec for Lines {
+	type In = String;
+	type Out = String;
+	type Error = io::Error;
+
+	fn decode(&mut self, buf: &[u8], _from: &SocketAddr) -> io::Result<String> {
+		String::from_utf8(buf.to_vec()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+	}
+
+	fn encode(&mut self, msg: String, buf: &mut Vec<u8>) -> io::Result<()> {
+		buf.extend_from_slice(msg.as_bytes());
+		Ok(())
+	}
+}
+
+#[test]
+fn ping_pong() {
+	fumio::run(async {
+		let mut a = UdpFramed::new(UdpSocket::bind("127.0.0.1:0".parse().unwrap()).unwrap(), Lines);
+		let mut b = UdpFramed::new(UdpSocket::bind("127.0.0.1:0".parse().unwrap()).unwrap(), Lines);
+		let a_addr = a.get_ref().local_addr().unwrap();
+		let b_addr = b.get_ref().local_addr().unwrap();
+
+		a.send(("ping".to_string(), b_addr)).await.unwrap();
+		let (msg, from) = b.next().await.unwrap().unwrap();
+		assert_eq!(msg, "ping");
+		assert_eq!(from, a_addr);
+
+		b.send(("pong".to_string(), from)).await.unwrap();
+		let (msg, from) = a.next().await.unwrap().unwrap();
+		assert_eq!(msg, "pong");
+		assert_eq!(from, b_addr);
+
+		// decode errors don't end the stream
+		a.get_mut().send_to(b"\xff", &b_addr).await.unwrap();
+		a.send(("ok".to_string(), b_addr)).await.unwrap();
+		assert_eq!(b.next().await.unwrap().unwrap_err().kind(), io::ErrorKind::InvalidData);
+		assert_eq!(b.next().await.unwrap().unwrap().0, "ok");
+	});
+}
@@ -0,0 +1,59 @@
+use fumio::net::UdpSocket;
+
+#[test]
+fn ipv6_options() {
+	fumio::run(async {
+		let s = match UdpSocket::bind("[::1]:0".parse().unwrap()) {
+			Ok(s) => s,
+			Err(_) => return, // no IPv6 support
+		};
+
+		s.set_unicast_hops_v6(17).unwrap();
+		assert_eq!(s.unicast_hops_v6().unwrap(), 17);
+
+		s.set_multicast_hops_v6(5).unwrap();
+		assert_eq!(s.multicast_hops_v6().unwrap(), 5);
+
+		s.set_multicast_if_v6(0).unwrap();
+		assert_eq!(s.multicast_if_v6().unwrap(), 0);
+
+		// DSCP EF (46), ECN ECT(0)
+		s.set_tclass_v6(0xba).unwrap();
+		assert_eq!(s.tclass_v6().unwrap(), 0xba);
+
+		// `default_builder_for` disables only_v6 only for unspecified addresses
+		s.only_v6().unwrap();
+		let any = UdpSocket::bind_port(0).unwrap();
+		assert!(!any.only_v6().unwrap());
+	});
+}
+
+#[test]
+fn ssm_v4() {
+	fumio::run(async {
+		let s = UdpSocket::bind_ipv4_port(0).unwrap();
+		let group = "232.1.2.3".parse().unwrap();
+		let source = "192.0.2.1".parse().unwrap();
+		let interface = std::net::Ipv4Addr::UNSPECIFIED;
+		if s.join_ssm_v4(group, source, interface).is_err() {
+			return; // no multicast capable interface / route present
+		}
+		// joining the same source twice fails
+		assert!(s.join_ssm_v4(group, source, interface).is_err());
+		s.leave_ssm_v4(group, source, interface).unwrap();
+		assert!(s.leave_ssm_v4(group, source, interface).is_err());
+	});
+}
+
+#[test]
+fn socket_options() {
+	fumio::run(async {
+		let s = UdpSocket::bind_ipv4_port(0).unwrap();
+		s.set_recv_buffer_size(64 * 1024).unwrap();
+		assert!(s.recv_buffer_size().unwrap() >= 64 * 1024);
+		s.set_send_buffer_size(64 * 1024).unwrap();
+		assert!(s.send_buffer_size().unwrap() >= 64 * 1024);
+		s.set_tos(0x10).unwrap();
+		assert_eq!(s.tos().unwrap(), 0x10);
+	});
+}
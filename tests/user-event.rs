@@ -0,0 +1,32 @@
+use fumio::reactor::UserEvent;
+use std::time::Duration;
+
+#[test]
+fn notify_from_thread() {
+	fumio::run(async {
+		let mut event = UserEvent::new().unwrap();
+		let notifier = event.notifier();
+
+		// merged into a single notification
+		notifier.notify();
+		notifier.notify();
+		event.triggered().await.unwrap();
+
+		let thread = std::thread::spawn(move || {
+			std::thread::sleep(Duration::from_millis(10));
+			notifier.notify();
+			notifier
+		});
+		event.triggered().await.unwrap();
+		let notifier = thread.join().unwrap();
+
+		// notifications for dropped events are ignored
+		drop(event);
+		notifier.notify();
+		let mut other = UserEvent::new().unwrap();
+		let other_notifier = other.notifier();
+		notifier.notify();
+		other_notifier.notify();
+		other.triggered().await.unwrap();
+	});
+}
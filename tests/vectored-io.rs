@@ -0,0 +1,35 @@
+use fumio::net::{TcpListener, TcpStream};
+use futures::prelude::*;
+use std::io::{IoSlice, IoSliceMut};
+
+#[test]
+fn header_and_body() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_port(0).unwrap();
+		let server_addr = l.local_addr().unwrap();
+
+		let client_task = async {
+			let mut s = TcpStream::connect(server_addr).await?;
+			let n = s.write_vectored(&[IoSlice::new(b"head:"), IoSlice::new(b"body")]).await?;
+			assert_eq!(n, 9);
+			s.close().await?;
+			Ok::<_, std::io::Error>(())
+		};
+
+		let serv_task = async {
+			let (mut conn, _) = l.incoming().await?;
+			let mut head = [0u8; 5];
+			let mut body = [0u8; 4];
+			// a single small write on loopback isn't split
+			let n = conn.read_vectored(&mut [IoSliceMut::new(&mut head), IoSliceMut::new(&mut body)]).await?;
+			assert_eq!(n, 9);
+			assert_eq!(&head, b"head:");
+			assert_eq!(&body, b"body");
+			Ok::<_, std::io::Error>(())
+		};
+
+		let (c, s) = future::join(client_task, serv_task).await;
+		c.unwrap();
+		s.unwrap();
+	});
+}
@@ -0,0 +1,39 @@
+use fumio::sync::watch;
+
+#[test]
+fn latest_value() {
+	fumio::run(async {
+		let (sender, mut receiver) = watch::channel(0);
+		let mut second = sender.subscribe();
+		assert_eq!(sender.receiver_count(), 2);
+		assert!(!receiver.has_changed());
+
+		let task = async move {
+			let mut seen = Vec::new();
+			while receiver.changed().await.is_ok() {
+				seen.push(*receiver.borrow());
+			}
+			seen
+		};
+		let handle = fumio::current().unwrap();
+		let (task, task_handle) = futures::future::FutureExt::remote_handle(task);
+		handle.spawn(task).unwrap();
+
+		sender.send(1).unwrap();
+		sender.send(2).unwrap();
+		// second receiver only sees the latest value
+		second.changed().await.unwrap();
+		assert_eq!(*second.borrow(), 2);
+		drop(sender);
+		assert_eq!(second.changed().await, Err(watch::RecvError));
+		assert_eq!(task_handle.await, vec![2]);
+	});
+}
+
+#[test]
+fn no_receivers() {
+	let (sender, receiver) = watch::channel(0);
+	drop(receiver);
+	assert_eq!(sender.send(1), Err(watch::SendError(1)));
+	assert_eq!(*sender.borrow(), 0);
+}
@@ -0,0 +1,19 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+#[test]
+fn yield_interleaves_tasks() {
+	let order = Rc::new(RefCell::new(Vec::new()));
+	let mut runtime = fumio::Runtime::new().unwrap();
+	for id in 0..2 {
+		let order = order.clone();
+		runtime.spawn(async move {
+			for _ in 0..3 {
+				order.borrow_mut().push(id);
+				fumio::task::yield_now().await;
+			}
+		}).unwrap();
+	}
+	runtime.enter_run(&mut fumio::enter().unwrap());
+	assert_eq!(*order.borrow(), [0, 1, 0, 1, 0, 1]);
+}
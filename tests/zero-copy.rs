@@ -0,0 +1,80 @@
+#![cfg(target_os = "linux")]
+
+use fumio::net::{TcpListener, TcpStream};
+use futures::future;
+use futures::prelude::*;
+use std::io::{self, Write};
+
+fn temp_file(data: &[u8]) -> std::fs::File {
+	let path = std::env::temp_dir().join(format!("fumio-zero-copy-{}", std::process::id()));
+	let mut file = std::fs::OpenOptions::new().read(true).write(true).create(true).truncate(true).open(&path).unwrap();
+	std::fs::remove_file(&path).unwrap();
+	file.write_all(data).unwrap();
+	file
+}
+
+#[test]
+fn send_file() {
+	let data: Vec<u8> = (0..300_000u32).map(|i| (i % 251) as u8).collect();
+	let file = temp_file(&data);
+
+	fumio::run(async {
+		let mut l = TcpListener::bind_port(0)?;
+		let server_addr = l.local_addr()?;
+
+		let server = async {
+			let (mut s, _) = l.incoming().await?;
+			assert_eq!(fumio::io::send_file(&mut s, &file, 1000..200_000).await?, 199_000);
+			// stops at the end of the file
+			assert_eq!(fumio::io::send_file(&mut s, &file, 299_000..400_000).await?, 1000);
+			s.close().await?;
+			Ok::<_, io::Error>(())
+		};
+
+		let client = async {
+			let mut s = TcpStream::connect(server_addr).await?;
+			let mut buf = Vec::new();
+			s.read_to_end(&mut buf).await?;
+			assert!(buf[..199_000] == data[1000..200_000]);
+			assert!(buf[199_000..] == data[299_000..]);
+			Ok::<_, io::Error>(())
+		};
+
+		future::try_join(server, client).await?;
+		Ok::<_, io::Error>(())
+	}).unwrap();
+}
+
+#[test]
+fn splice() {
+	fumio::run(async {
+		let mut l = TcpListener::bind_port(0)?;
+		let server_addr = l.local_addr()?;
+		let data = vec![0x42u8; 500_000];
+
+		// proxy: accepts two connections and forwards the first one to the second one
+		let proxy = async {
+			let (mut a, _) = l.incoming().await?;
+			let (mut b, _) = l.incoming().await?;
+			assert_eq!(fumio::io::splice(&mut a, &mut b).await?, 500_000);
+			b.close().await?;
+			Ok::<_, io::Error>(())
+		};
+
+		let clients = async {
+			let mut a = TcpStream::connect(server_addr).await?;
+			let mut b = TcpStream::connect(server_addr).await?;
+			let send = async {
+				a.write_all(&data).await?;
+				a.close().await
+			};
+			let mut buf = Vec::new();
+			future::try_join(send, b.read_to_end(&mut buf)).await?;
+			assert!(buf == data);
+			Ok::<_, io::Error>(())
+		};
+
+		future::try_join(proxy, clients).await?;
+		Ok::<_, io::Error>(())
+	}).unwrap();
+}